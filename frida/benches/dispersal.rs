@@ -0,0 +1,180 @@
+use std::time::Instant;
+
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use csv::Writer;
+use frida::{
+    plan::{DispersalInputs, DispersalPlan},
+    security::COMMON_BLOB_SIZES,
+    FridaConfig, ProveOptions, Prover, SecurityLevel, SoundnessRegime, Verifier,
+};
+use rand::{thread_rng, Rng};
+
+// What a node sends back once its shares check: the commitment it signs and
+// an Ed25519-sized signature. The certificate is a quorum of them.
+const ATTESTATION_BYTES: usize = 32 + 64;
+const FIELD_BYTES: usize = 32;
+
+// Disperse, verify every node's shares, sample and certify for a range of
+// committee sizes, reporting bytes on the wire: what each node receives and
+// sends, the disperser's total egress, one light-client sample and the
+// certificate. The replication columns are the baseline of sending every
+// node the whole blob, which it checks against a published hash.
+fn main() {
+    let mut rng = thread_rng();
+    let mut wtr = Writer::from_path("dispersal.csv").unwrap();
+    wtr.write_record([
+        "node_num",
+        "blob_size",
+        "code_rate",
+        "poly_num",
+        "log_degree",
+        "shares_per_node",
+        "disperse_time",
+        "node_verify_time",
+        "proof_bytes",
+        "node_ingress",
+        "node_egress",
+        "disperser_egress",
+        "sample_bytes",
+        "sample_num",
+        "certificate_bytes",
+        "node_storage",
+        "replication_node_ingress",
+        "replication_disperser_egress",
+        "replication_node_storage",
+        "replication_verify_time",
+    ])
+    .unwrap();
+    let blob_size = COMMON_BLOB_SIZES[1];
+    for node_num in [128, 512, 1024] {
+        let plan = DispersalPlan::pick(&DispersalInputs {
+            blob_size,
+            node_num,
+            adversary_fraction: 0.33,
+            failure_probability: 1e-9,
+        })
+        .unwrap();
+        let regime = SoundnessRegime::Proven;
+        let config = FridaConfig {
+            regime,
+            ..FridaConfig::new(
+                b"dispersal",
+                plan.poly_num,
+                plan.log_degree,
+                plan.code_rate,
+                SecurityLevel::Bits100.query_num(plan.code_rate, regime),
+            )
+        };
+        let polies = (0..plan.poly_num)
+            .map(|_| {
+                (0..(1 << plan.log_degree))
+                    .map(|_| Fr::rand(&mut rng))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // disperse: commit, prove and cut every node's shares
+        let start = Instant::now();
+        let prover = Prover::new(&polies, &config.groups()[0], &ProveOptions::default()).unwrap();
+        let proof = prover.prove(&config);
+        let shares = (0..node_num)
+            .map(|node| {
+                (node..plan.leave_number)
+                    .step_by(node_num)
+                    .map(|i| (i, prover.open_chunk(i)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let disperse_time = start.elapsed().as_micros() as usize;
+        let proof_bytes = proof.to_bytes().len();
+        let share_bytes =
+            |symbols: &[Fr], path: &[u8]| 8 + symbols.len() * FIELD_BYTES + path.len();
+
+        // verify-share: every node checks the proof once and each of its shares
+        let root = prover.commit();
+        let commitment = config.commitment(&root);
+        let start = Instant::now();
+        let attested = shares
+            .iter()
+            .filter(|node| {
+                Verifier::verify_proof(&config, &commitment, &proof).accepted()
+                    && node.iter().all(|(i, (symbols, path))| {
+                        Verifier::verify_chunk(
+                            &root,
+                            plan.leave_number,
+                            config.leaf_size(0),
+                            *i,
+                            symbols,
+                            path,
+                        )
+                    })
+            })
+            .count();
+        let node_verify_time = start.elapsed().as_micros() as usize / node_num;
+        assert_eq!(attested, node_num);
+        let node_ingress = shares
+            .iter()
+            .map(|node| {
+                proof_bytes
+                    + node
+                        .iter()
+                        .map(|(_, (symbols, path))| share_bytes(symbols, path))
+                        .sum::<usize>()
+            })
+            .collect::<Vec<_>>();
+
+        // sample: a light client opens one leaf per sample
+        let (symbols, path) = prover.open_chunk(0);
+        let sample_bytes = share_bytes(&symbols, &path);
+
+        // certify: enough attesting nodes that their shares reconstruct
+        let quorum = plan.required_leaves().div_ceil(plan.shares_per_node);
+        let node_storage = shares
+            .iter()
+            .map(|node| {
+                node.iter()
+                    .map(|(_, (symbols, path))| share_bytes(symbols, path))
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap();
+
+        // replication: every node downloads the blob and hashes it
+        let blob = (0..blob_size).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let hash = blake3::hash(&blob);
+        let start = Instant::now();
+        for _ in 0..node_num {
+            assert_eq!(blake3::hash(&blob), hash);
+        }
+        let replication_verify_time = start.elapsed().as_micros() as usize / node_num;
+
+        wtr.write_record(
+            [
+                node_num,
+                blob_size,
+                plan.code_rate,
+                plan.poly_num,
+                plan.log_degree,
+                plan.shares_per_node,
+                disperse_time,
+                node_verify_time,
+                proof_bytes,
+                node_ingress.iter().max().copied().unwrap(),
+                ATTESTATION_BYTES,
+                node_ingress.iter().sum(),
+                sample_bytes,
+                plan.sample_num,
+                quorum * ATTESTATION_BYTES,
+                node_storage,
+                blob_size + 32,
+                node_num * (blob_size + 32),
+                blob_size,
+                replication_verify_time,
+            ]
+            .map(|x| x.to_string()),
+        )
+        .unwrap();
+        wtr.flush().unwrap();
+    }
+}
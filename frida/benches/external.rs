@@ -0,0 +1,159 @@
+use std::time::Instant;
+
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use csv::Writer;
+use frida::{FridaConfig, ProveOptions, Prover};
+use p3_baby_bear::BabyBear;
+use p3_blake3::Blake3;
+use p3_challenger::{CanObserve, FieldChallenger, HashChallenger, SerializingChallenger32};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use rand::{thread_rng, Rng};
+use util::mul_group::Radix2Group;
+use winter_crypto::hashers::Blake3_256;
+use winter_fri::{DefaultProverChannel, FriOptions, FriProver};
+use winter_math::{fft, fields::f128::BaseElement, FieldElement};
+
+// All provers commit to one polynomial of degree 2^log_size, fold by two
+// and answer the same number of queries at the same code rate.
+fn frida(log_size: usize, coderate: usize, query_num: usize) -> (usize, usize) {
+    let mut rng = thread_rng();
+    let polies = vec![(0..(1 << log_size))
+        .map(|_| <Fr as UniformRand>::rand(&mut rng))
+        .collect::<Vec<_>>()];
+    let groups = (0..log_size)
+        .rev()
+        .map(|x| Radix2Group::new(x + 1 + coderate))
+        .collect::<Vec<_>>();
+    let challenges = (
+        <Fr as UniformRand>::rand(&mut rng),
+        (0..log_size)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>(),
+    );
+    let config = FridaConfig::new(b"bench", 1, log_size, coderate, query_num);
+    let now = Instant::now();
+    let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+    let (prover_state, iopp_commits) = prover
+        .commit_phase(&groups, &challenges, &ProveOptions::default())
+        .unwrap();
+    let query_results = prover.sample(&prover_state, &config);
+    let prover_time = now.elapsed().as_micros() as usize;
+    let proof_size =
+        iopp_commits.proof_size() + query_results.iter().map(|x| x.proof_size()).sum::<usize>();
+    (prover_time, proof_size)
+}
+
+fn winterfell(log_size: usize, coderate: usize, query_num: usize) -> (usize, usize) {
+    let mut rng = thread_rng();
+    let blowup = 1 << coderate;
+    let domain_size = blowup << log_size;
+    let mut evaluations = (0..(1 << log_size))
+        .map(|_| BaseElement::new(rng.gen::<u128>()))
+        .collect::<Vec<_>>();
+    let now = Instant::now();
+    evaluations.resize(domain_size, BaseElement::ZERO);
+    let twiddles = fft::get_twiddles::<BaseElement>(domain_size);
+    fft::evaluate_poly(&mut evaluations, &twiddles);
+    let mut channel =
+        DefaultProverChannel::<BaseElement, BaseElement, Blake3_256<BaseElement>>::new(
+            domain_size,
+            query_num,
+        );
+    let mut prover = FriProver::new(FriOptions::new(blowup, 2, 7));
+    prover.build_layers(&mut channel, evaluations);
+    let positions = channel.draw_query_positions();
+    let proof = prover.build_proof(&positions);
+    let prover_time = now.elapsed().as_micros() as usize;
+    (prover_time, proof.size())
+}
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ValMmcs = MerkleTreeMmcs<
+    Val,
+    u8,
+    SerializingHasher32<Blake3>,
+    CompressionFunctionFromHasher<Blake3, 2, 32>,
+    32,
+>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, Blake3, 32>>;
+type P3Pcs = TwoAdicFriPcs<Val, Radix2DitParallel<Val>, ValMmcs, ChallengeMmcs>;
+
+// Plonky3 commits to evaluations on the trace domain and extends them by the
+// blowup itself, so the timer covers its LDE as it covers FRIDA's FFT.
+fn plonky3(log_size: usize, coderate: usize, query_num: usize) -> (usize, usize) {
+    let mut rng = thread_rng();
+    let evaluations = RowMajorMatrix::new(
+        (0..(1 << log_size))
+            .map(|_| rng.gen::<Val>())
+            .collect::<Vec<_>>(),
+        1,
+    );
+    let val_mmcs = ValMmcs::new(
+        SerializingHasher32::new(Blake3),
+        CompressionFunctionFromHasher::new(Blake3),
+    );
+    let fri_config = FriConfig {
+        log_blowup: coderate,
+        log_final_poly_len: 0,
+        num_queries: query_num,
+        proof_of_work_bits: 0,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let pcs = P3Pcs::new(Radix2DitParallel::default(), val_mmcs, fri_config);
+    let mut challenger = Challenger::from_hasher(vec![], Blake3);
+    let now = Instant::now();
+    let domain =
+        <P3Pcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_size);
+    let (commit, data) =
+        <P3Pcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evaluations)]);
+    challenger.observe(commit);
+    let zeta: Challenge = challenger.sample_ext_element();
+    let (_, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger);
+    let prover_time = now.elapsed().as_micros() as usize;
+    (prover_time, bincode::serialize(&proof).unwrap().len())
+}
+
+fn main() {
+    let mut wtr = Writer::from_path("fri_comparison.csv").unwrap();
+    wtr.write_record([
+        "log_blob_size",
+        "frida_prover_time",
+        "frida_proof_size",
+        "winterfell_prover_time",
+        "winterfell_proof_size",
+        "plonky3_prover_time",
+        "plonky3_proof_size",
+        "prover_time_ratio",
+        "proof_size_ratio",
+    ])
+    .unwrap();
+    let coderate = 2;
+    let query_num =
+        (100.0 / (2.0 / (1.0 + 0.5_f32.powi(coderate as i32))).log2()).ceil() as usize - 20;
+    for log_size in 12..21 {
+        let (frida_time, frida_size) = frida(log_size, coderate, query_num);
+        let (winter_time, winter_size) = winterfell(log_size, coderate, query_num);
+        let (p3_time, p3_size) = plonky3(log_size, coderate, query_num);
+        wtr.write_record([
+            log_size.to_string(),
+            frida_time.to_string(),
+            frida_size.to_string(),
+            winter_time.to_string(),
+            winter_size.to_string(),
+            p3_time.to_string(),
+            p3_size.to_string(),
+            format!("{:.3}", frida_time as f64 / winter_time as f64),
+            format!("{:.3}", frida_size as f64 / winter_size as f64),
+        ])
+        .unwrap();
+    }
+}
@@ -3,17 +3,35 @@ use std::time::Instant;
 use ark_bn254::Fr;
 use ark_ff::UniformRand;
 use csv::Writer;
-use frida::{Prover, Verifier};
-use rand::{thread_rng, RngCore};
-use util::mul_group::Radix2Group;
+use frida::{FridaConfig, ProveOptions, Prover, Verifier};
+use rand::thread_rng;
+use util::{
+    memory::{peak_rss, reset_peak_rss, TrackingAllocator},
+    mul_group::Radix2Group,
+};
+
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
 
 fn frida() {
     let mut rng = thread_rng();
     let mut wtr = Writer::from_path("frida.csv").unwrap();
-    wtr.write_record(&["log_blob_size", "prover_time", "proof_size"])
-        .unwrap();
+    wtr.write_record([
+        "log_blob_size",
+        "prover_time",
+        "proof_size",
+        "open_time",
+        "verify_time",
+        "sample_bandwidth",
+        "peak_heap",
+        "allocations",
+        "peak_rss",
+    ])
+    .unwrap();
     let poly_num = 32;
     for log_degree in 11..18 {
+        TrackingAllocator::reset();
+        reset_peak_rss();
         let polies = (0..poly_num)
             .map(|_| {
                 (0..(1 << log_degree))
@@ -34,43 +52,82 @@ fn frida() {
                     .collect::<Vec<_>>(),
             )
         };
-        let leaf_indices = (0..((100.0 / (2.0 / (1.0 + 0.5_f32.powi(coderate as i32))).log2())
-            .ceil() as usize)
-            - 20)
-            .map(|_| rng.next_u32() as usize)
-            .collect::<Vec<_>>();
+        let config = FridaConfig::new(
+            b"bench",
+            poly_num,
+            log_degree,
+            coderate,
+            ((100.0 / (2.0 / (1.0 + 0.5_f32.powi(coderate as i32))).log2()).ceil() as usize) - 20,
+        );
         let now = Instant::now();
         for _ in 0..9 {
-            let prover = Prover::new(&polies, &groups[0]);
-            let (prover_state, _) = prover.commit_phase(&groups, &challenges);
-            let _ = prover.sample(
-                &prover_state,
-                leaf_indices.clone(),
-                1 << (log_degree + coderate),
-            );
+            let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+            let (prover_state, _) = prover
+                .commit_phase(&groups, &challenges, &ProveOptions::default())
+                .unwrap();
+            let _ = prover.sample(&prover_state, &config);
         }
-        let prover = Prover::new(&polies, &groups[0]);
-        let (prover_state, iopp_commits) = prover.commit_phase(&groups, &challenges);
-        let query_results = prover.sample(
-            &prover_state,
-            leaf_indices.clone(),
-            1 << (log_degree + coderate),
-        );
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        let (prover_state, iopp_commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        let query_results = prover.sample(&prover_state, &config);
         let prover_time = now.elapsed().as_micros() as usize / 10;
         let proof_size =
             iopp_commits.proof_size() + query_results.iter().map(|x| x.proof_size()).sum::<usize>();
-        wtr.write_record(&[log_degree + 5, prover_time, proof_size].map(|x| x.to_string()))
-            .unwrap();
+        // The folding and sampling share of the prover time, for one proof.
+        let now = Instant::now();
+        for _ in 0..10 {
+            let (prover_state, _) = prover
+                .commit_phase(&groups, &challenges, &ProveOptions::default())
+                .unwrap();
+            let _ = prover.sample(&prover_state, &config);
+        }
+        let open_time = now.elapsed().as_micros() as usize / 10;
+        let sample_bandwidth = iopp_commits.proof_size()
+            + prover
+                .sample(
+                    &prover_state,
+                    &FridaConfig {
+                        query_num: 1,
+                        ..config.clone()
+                    },
+                )
+                .iter()
+                .map(|x| x.proof_size())
+                .sum::<usize>();
 
         let commit = prover.commit();
         let verifier = Verifier::new(commit, poly_num, 1 << (log_degree + coderate - 1));
-        verifier.verify(
-            &groups,
-            &challenges,
-            leaf_indices,
-            iopp_commits,
-            query_results,
-        );
+        let now = Instant::now();
+        for _ in 0..10 {
+            verifier
+                .verify(
+                    &config,
+                    &groups,
+                    &challenges,
+                    iopp_commits.clone(),
+                    query_results.clone(),
+                )
+                .into_result()
+                .unwrap();
+        }
+        let verify_time = now.elapsed().as_micros() as usize / 10;
+        wtr.write_record(
+            [
+                log_degree + 5,
+                prover_time,
+                proof_size,
+                open_time,
+                verify_time,
+                sample_bandwidth,
+                TrackingAllocator::peak(),
+                TrackingAllocator::allocations(),
+                peak_rss().unwrap_or(0),
+            ]
+            .map(|x| x.to_string()),
+        )
+        .unwrap();
     }
 }
 
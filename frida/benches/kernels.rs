@@ -0,0 +1,196 @@
+use ark_bn254::Fr;
+use ark_ff::{Field, UniformRand};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use frida::{leaf_bytes, leaf_bytes_dynamic, FridaConfig, Layout, ProveOptions, Prover};
+use rand::thread_rng;
+use util::{
+    merkle_tree::{Blake32, MerkleTreeProver, Serialize},
+    mul_group::Radix2Group,
+    placement::Placement,
+};
+
+fn random_fields(len: usize) -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..len).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn fft(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fft");
+    for log_d in [10, 14, 18] {
+        let coeff = random_fields(1 << log_d);
+        let mul_group = Radix2Group::new(log_d + 1);
+        group.bench_with_input(BenchmarkId::from_parameter(log_d), &coeff, |b, coeff| {
+            b.iter_batched(
+                || coeff.clone(),
+                |coeff| mul_group.fft(coeff),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn merkle_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_tree");
+    for log_leaves in [10, 14, 18] {
+        let leaf_values = random_fields(2 << log_leaves)
+            .chunks(2)
+            .map(Serialize::serialize_fields)
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(log_leaves),
+            &leaf_values,
+            |b, leaf_values| b.iter(|| MerkleTreeProver::<Blake32>::new(leaf_values)),
+        );
+    }
+    group.finish();
+}
+
+fn leaf_serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("leaf_bytes");
+    for leaf_size in [2, 32] {
+        let value = random_fields(leaf_size << 14);
+        group.bench_with_input(BenchmarkId::new("fixed", leaf_size), &value, |b, value| {
+            b.iter(|| leaf_bytes(black_box(value), leaf_size))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("dynamic", leaf_size),
+            &value,
+            |b, value| b.iter(|| leaf_bytes_dynamic(black_box(value), leaf_size)),
+        );
+    }
+    group.finish();
+}
+
+fn folding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluate_next_domain");
+    let inv_2 = Fr::from(2).inverse().unwrap();
+    for log_n in [10, 14, 18] {
+        let evaluations = random_fields(1 << log_n);
+        let mul_group = Radix2Group::new(log_n);
+        let challenge = Fr::rand(&mut thread_rng());
+        group.bench_with_input(
+            BenchmarkId::from_parameter(log_n),
+            &evaluations,
+            |b, evaluations| {
+                b.iter(|| {
+                    Prover::evaluate_next_domain(
+                        black_box(evaluations),
+                        &mul_group,
+                        inv_2,
+                        challenge,
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn sample(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sample");
+    let mut rng = thread_rng();
+    let poly_num = 16;
+    let coderate = 1;
+    for log_degree in [8, 10, 12] {
+        let polies = (0..poly_num)
+            .map(|_| random_fields(1 << log_degree))
+            .collect::<Vec<_>>();
+        let groups = (0..log_degree)
+            .rev()
+            .map(|x| Radix2Group::new(x + 1 + coderate))
+            .collect::<Vec<_>>();
+        let challenges = (Fr::rand(&mut rng), random_fields(log_degree));
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        let (prover_state, _) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        let config = FridaConfig::new(b"bench", poly_num, log_degree, coderate, 60);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(log_degree),
+            &config,
+            |b, config| b.iter(|| prover.sample(&prover_state, config)),
+        );
+    }
+    group.finish();
+}
+
+// Commitment favours contiguous leaves, folding contiguous codewords; the
+// end-to-end prover decides which layout is the default.
+fn layout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout");
+    let mut rng = thread_rng();
+    let poly_num = 16;
+    let coderate = 1;
+    for log_degree in [8, 10, 12] {
+        let polies = (0..poly_num)
+            .map(|_| random_fields(1 << log_degree))
+            .collect::<Vec<_>>();
+        let groups = (0..log_degree)
+            .rev()
+            .map(|x| Radix2Group::new(x + 1 + coderate))
+            .collect::<Vec<_>>();
+        let challenges = (Fr::rand(&mut rng), random_fields(log_degree));
+        for layout in [Layout::Columnar, Layout::RowMajor] {
+            let name = format!("{:?}", layout);
+            group.bench_with_input(
+                BenchmarkId::new(format!("commit/{}", name), log_degree),
+                &polies,
+                |b, polies| b.iter(|| Prover::with_layout(polies, &groups[0], layout)),
+            );
+            let prover = Prover::with_layout(&polies, &groups[0], layout);
+            group.bench_with_input(
+                BenchmarkId::new(format!("commit_phase/{}", name), log_degree),
+                &challenges,
+                |b, challenges| {
+                    b.iter(|| {
+                        prover
+                            .commit_phase(&groups, challenges, &ProveOptions::default())
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+// Encoding into a placed codeword, against the default arm. No results are
+// recorded yet: any gain needs a multi-socket host with transparent
+// hugepages in `madvise` mode, and on one node the numa arms do the same
+// work as the default.
+fn placement(c: &mut Criterion) {
+    let mut group = c.benchmark_group("placement");
+    group.sample_size(10);
+    let poly_num = 64;
+    for log_degree in [12, 16] {
+        let polies = (0..poly_num)
+            .map(|_| random_fields(1 << log_degree))
+            .collect::<Vec<_>>();
+        let domain = Radix2Group::new(log_degree + 1);
+        for (name, hugepages, numa) in [
+            ("default", false, false),
+            ("hugepages", true, false),
+            ("numa", false, true),
+            ("both", true, true),
+        ] {
+            let options = ProveOptions::default().with_placement(Placement { hugepages, numa });
+            group.bench_with_input(BenchmarkId::new(name, log_degree), &polies, |b, polies| {
+                b.iter(|| Prover::new(polies, &domain, &options).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    kernels,
+    fft,
+    merkle_tree,
+    leaf_serialization,
+    folding,
+    sample,
+    layout,
+    placement
+);
+criterion_main!(kernels);
@@ -0,0 +1,195 @@
+// Where a byte range of the original blob lives in the committed codewords.
+// The blob is packed BYTES_PER_ELEMENT bytes per element and element e is
+// entry e mod 2^log_degree of polynomial e / 2^log_degree. Systematic
+// encodings (`Prover::from_evaluations`) store entry t as the evaluation at
+// domain index t * 2^code_rate, so a range is read straight from the leaves
+// holding those indices; coefficient encodings (`Prover::new`) spread every
+// entry over the whole codeword and any `required_leaves` leaves are needed to
+// interpolate it.
+use std::ops::Range;
+
+use crate::{group_leaves, plan::BYTES_PER_ELEMENT, FridaConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Systematic,
+    Coefficients,
+}
+
+// A codeword position: polynomial and index in its evaluation domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub poly: usize,
+    pub domain_index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retrieval {
+    // Blob elements the range touches.
+    pub elements: Range<usize>,
+    // Where those elements sit, for systematic encodings; empty otherwise.
+    pub positions: Vec<Position>,
+    // First-oracle leaves to fetch, ascending.
+    pub leaves: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressMap {
+    poly_num: usize,
+    log_degree: usize,
+    code_rate: usize,
+    // First-oracle leaves per position, `FridaConfig::leaf_groups`.
+    groups: usize,
+    encoding: Encoding,
+}
+
+impl AddressMap {
+    pub fn new(config: &FridaConfig, encoding: Encoding) -> Self {
+        AddressMap {
+            poly_num: config.poly_num,
+            log_degree: config.log_degree,
+            code_rate: config.code_rate,
+            groups: config.leaf_groups(),
+            encoding,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        (self.poly_num << self.log_degree) * BYTES_PER_ELEMENT
+    }
+
+    fn positions(&self) -> usize {
+        1 << (self.log_degree + self.code_rate - 1)
+    }
+
+    pub fn leave_number(&self) -> usize {
+        self.positions() * self.groups
+    }
+
+    // Each position holds two evaluations of every polynomial, spread over
+    // its leaf in every group.
+    pub fn required_leaves(&self) -> usize {
+        self.groups << (self.log_degree - 1)
+    }
+
+    pub fn position(&self, element: usize) -> Position {
+        assert!(element < self.poly_num << self.log_degree);
+        let degree = 1 << self.log_degree;
+        Position {
+            poly: element / degree,
+            domain_index: (element % degree) << self.code_rate,
+        }
+    }
+
+    // The leaf holding `position` and the index of its value among the
+    // leaf's symbols, as `Prover::open_chunk` lays them out.
+    pub fn leaf_of(&self, position: Position) -> (usize, usize) {
+        let len = self.positions();
+        let half = 2 * position.poly + position.domain_index / len;
+        (
+            (half % self.groups) * len + position.domain_index % len,
+            half / self.groups,
+        )
+    }
+
+    // The first-oracle key of `position`, as query results are keyed.
+    pub fn key(&self, position: Position) -> usize {
+        (position.poly << (self.log_degree + self.code_rate)) + position.domain_index
+    }
+
+    // None for an empty range or one past the end of the blob.
+    pub fn retrieval(&self, offset: usize, length: usize) -> Option<Retrieval> {
+        if length == 0 || offset.checked_add(length)? > self.capacity() {
+            return None;
+        }
+        let elements = offset / BYTES_PER_ELEMENT..(offset + length).div_ceil(BYTES_PER_ELEMENT);
+        Some(match self.encoding {
+            Encoding::Systematic => {
+                let positions = elements
+                    .clone()
+                    .map(|e| self.position(e))
+                    .collect::<Vec<_>>();
+                let mut leaves = positions
+                    .iter()
+                    .map(|x| self.leaf_of(*x).0)
+                    .collect::<Vec<_>>();
+                leaves.sort_unstable();
+                leaves.dedup();
+                Retrieval {
+                    elements,
+                    positions,
+                    leaves,
+                }
+            }
+            Encoding::Coefficients => Retrieval {
+                elements,
+                positions: vec![],
+                leaves: group_leaves(
+                    &(0..1 << (self.log_degree - 1)).collect::<Vec<_>>(),
+                    self.positions(),
+                    self.groups,
+                ),
+            },
+        })
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+    use util::vrs::VrsScheme;
+
+    use super::*;
+    use crate::{scheme::FridaScheme, Interleave, Prover};
+
+    #[test]
+    fn systematic_ranges() {
+        let mut rng = thread_rng();
+        // Two polynomials to a leaf spread each position over two leaves.
+        for interleave in [Interleave::Full, Interleave::Rows(2)] {
+            let config = FridaConfig {
+                interleave,
+                ..FridaConfig::new(b"address", 4, 4, 2, 8)
+            };
+            let evals = (0..4)
+                .map(|_| (0..16).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let prover = Prover::from_evaluations(&evals, &config);
+            let map = AddressMap::new(&config, Encoding::Systematic);
+            assert!(map.retrieval(map.capacity() - 1, 2).is_none());
+            assert!(map.retrieval(0, 0).is_none());
+
+            // Bytes 20..100 span elements 0..4, crossing no polynomial boundary;
+            // 480..500 cross from polynomial 0 into polynomial 1.
+            for (offset, length) in [(20, 80), (480, 20), (map.capacity() - 1, 1)] {
+                let retrieval = map.retrieval(offset, length).unwrap();
+                assert_eq!(retrieval.positions.len(), retrieval.elements.len());
+                for (e, position) in retrieval.elements.clone().zip(&retrieval.positions) {
+                    let (leaf, symbol) = map.leaf_of(*position);
+                    assert!(retrieval.leaves.binary_search(&leaf).is_ok());
+                    let (symbols, _) = prover.open_chunk(leaf);
+                    assert_eq!(symbols[symbol], evals[e / 16][e % 16]);
+                    assert_eq!(
+                        prover.interpolation.get(map.key(*position)),
+                        symbols[symbol]
+                    );
+                }
+            }
+
+            let map = AddressMap::new(&config, Encoding::Coefficients);
+            let retrieval = map.retrieval(100, 10).unwrap();
+            assert_eq!(retrieval.elements, 3..4);
+            let scheme = FridaScheme { config };
+            let data = evals.concat();
+            let (_, prover) = scheme.commit(&data);
+            let shares = retrieval
+                .leaves
+                .iter()
+                .map(|&i| scheme.sample(&prover, i))
+                .collect::<Vec<_>>();
+            assert_eq!(scheme.reconstruct(&shares).unwrap(), data);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+// Many audit rounds checked at once. Every issued round is hashed with its
+// outcome, and with its response when there is one, into a digest; the
+// digests, in issue order, are the leaves of an accumulator tree, and the
+// published root binds the tree to how many rounds it covers, so a node can
+// neither drop a late, missed or failed round nor pass off a shorter history.
+// The auditor keeps the tree as rounds close and the node keeps it beside its
+// responses. An aggregate opens `count` rounds drawn from the root, and
+// fails on any opened round that did not pass, so a node that failed or
+// could not have answered a fraction f of them is caught with probability
+// 1 - (1 - f)^count. This is a sampled aggregate, not a succinct one: it is
+// linear in `count` and logarithmic in the rounds covered, where folding the
+// openings through a further FRI instance would make it constant size. The
+// root only has to be published on time, by the auditor or on chain; whoever
+// checks the aggregate later needs that root, the config and nothing from
+// the rounds in between.
+use std::fmt;
+
+use util::merkle_tree::{Blake32, MerkleTreeProver, MerkleTreeVerifier};
+
+use crate::{
+    audit::{audit_leaves, AuditOutcome},
+    service::SampleResponse,
+    slashing::{trace, Misbehavior},
+    FridaConfig,
+};
+
+const DIGEST_DOMAIN: &[u8] = b"frida-audit-digest";
+const ROOT_DOMAIN: &[u8] = b"frida-audit-root";
+const SAMPLE_DOMAIN: &[u8] = b"frida-audit-aggregate";
+
+// A failed round commits to its response, not to the misbehavior traced
+// from it, so anyone can trace it again.
+fn outcome_tag(outcome: &AuditOutcome) -> u8 {
+    match outcome {
+        AuditOutcome::Passed => 0,
+        AuditOutcome::Late => 1,
+        AuditOutcome::Missed => 2,
+        AuditOutcome::Failed(_) => 3,
+    }
+}
+
+pub fn round_digest(
+    commitment: &[u8; 32],
+    nonce: &[u8; 32],
+    outcome: &AuditOutcome,
+    response: Option<&SampleResponse>,
+) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher
+        .update(DIGEST_DOMAIN)
+        .update(commitment)
+        .update(nonce)
+        .update(&[outcome_tag(outcome)]);
+    if let Some(response) = response {
+        hasher.update(&response.to_bytes());
+    }
+    *hasher.finalize().as_bytes()
+}
+
+fn accumulator(digests: &[[u8; 32]]) -> MerkleTreeProver<Blake32> {
+    MerkleTreeProver::from_leaf_iter(digests.iter().map(|x| x.to_vec()))
+}
+
+fn bind_rounds(tree_root: &[u8; 32], rounds: usize) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher
+        .update(ROOT_DOMAIN)
+        .update(&(rounds as u64).to_le_bytes())
+        .update(tree_root);
+    *hasher.finalize().as_bytes()
+}
+
+// The root over `digests`, bound to their number; None for no rounds.
+pub fn accumulator_root(digests: &[[u8; 32]]) -> Option<[u8; 32]> {
+    (!digests.is_empty()).then(|| bind_rounds(&accumulator(digests).commit(), digests.len()))
+}
+
+// The `count` rounds, ascending, an aggregate of `rounds` rounds opens.
+pub fn aggregate_rounds(root: &[u8; 32], rounds: usize, count: usize) -> Vec<usize> {
+    audit_leaves(root, blake3::hash(SAMPLE_DOMAIN).as_bytes(), rounds, count)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregatedRound {
+    pub round: usize,
+    pub nonce: [u8; 32],
+    pub outcome: AuditOutcome,
+    // None for a missed round.
+    pub response: Option<SampleResponse>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateAudit {
+    pub rounds: usize,
+    // The accumulator tree's root, before it is bound to `rounds`.
+    pub tree_root: [u8; 32],
+    pub opened: Vec<AggregatedRound>,
+    // Multiproof of the opened digests against `tree_root`.
+    pub path: Vec<u8>,
+}
+
+impl AggregateAudit {
+    pub fn proof_size(&self) -> usize {
+        32 + self.path.len()
+            + self
+                .opened
+                .iter()
+                .map(|x| 33 + x.response.as_ref().map_or(0, |x| x.to_bytes().len()))
+                .sum::<usize>()
+    }
+}
+
+// The node's side: every round it was issued, oldest first, with the
+// outcome the auditor recorded.
+#[derive(Clone)]
+pub struct AuditAccumulator {
+    commitment: [u8; 32],
+    digests: Vec<[u8; 32]>,
+    rounds: Vec<([u8; 32], AuditOutcome, Option<SampleResponse>)>,
+}
+
+impl AuditAccumulator {
+    pub fn new(commitment: [u8; 32]) -> Self {
+        AuditAccumulator {
+            commitment,
+            digests: vec![],
+            rounds: vec![],
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        nonce: [u8; 32],
+        outcome: AuditOutcome,
+        response: Option<SampleResponse>,
+    ) {
+        self.digests.push(round_digest(
+            &self.commitment,
+            &nonce,
+            &outcome,
+            response.as_ref(),
+        ));
+        self.rounds.push((nonce, outcome, response));
+    }
+
+    pub fn root(&self) -> Option<[u8; 32]> {
+        accumulator_root(&self.digests)
+    }
+
+    // None before the first round.
+    pub fn aggregate(&self, count: usize) -> Option<AggregateAudit> {
+        let tree = (!self.digests.is_empty()).then(|| accumulator(&self.digests))?;
+        let rounds = self.digests.len();
+        let picked = aggregate_rounds(&bind_rounds(&tree.commit(), rounds), rounds, count);
+        Some(AggregateAudit {
+            rounds,
+            tree_root: tree.commit(),
+            opened: picked
+                .iter()
+                .map(|&round| {
+                    let (nonce, outcome, response) = &self.rounds[round];
+                    AggregatedRound {
+                        round,
+                        nonce: *nonce,
+                        outcome: outcome.clone(),
+                        response: response.clone(),
+                    }
+                })
+                .collect(),
+            path: tree.open(&picked),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AggregateError {
+    // The aggregate does not cover the rounds the root was published for.
+    RoundCount {
+        expected: usize,
+        rounds: usize,
+    },
+    WrongRounds,
+    // An opened round that is committed as not passed.
+    Faulted {
+        round: usize,
+        outcome: AuditOutcome,
+    },
+    // An opened round committed as passed whose response does not verify.
+    Failed {
+        round: usize,
+        misbehavior: Misbehavior,
+    },
+    // The opened rounds are not in the accumulator.
+    PathMismatch,
+}
+
+impl fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateError::RoundCount { expected, rounds } => {
+                write!(f, "aggregate covers {} rounds, not {}", rounds, expected)
+            }
+            AggregateError::WrongRounds => write!(f, "opened rounds are not the sampled ones"),
+            AggregateError::Faulted { round, outcome } => {
+                write!(f, "round {} did not pass: {:?}", round, outcome)
+            }
+            AggregateError::Failed { round, misbehavior } => {
+                write!(f, "round {} fails: {:?}", round, misbehavior)
+            }
+            AggregateError::PathMismatch => write!(f, "opened rounds do not match the root"),
+        }
+    }
+}
+
+impl std::error::Error for AggregateError {}
+
+// Checks `proof` opens `count` of the `rounds` rounds accumulated under
+// `root`, each committed as passed and answering its nonce's `samples`
+// leaves of the blob under `commitment`. The path is checked first, so a
+// fault it reports is one the node committed to.
+pub fn verify_aggregate(
+    config: &FridaConfig,
+    commitment: &[u8; 32],
+    samples: usize,
+    root: &[u8; 32],
+    rounds: usize,
+    count: usize,
+    proof: &AggregateAudit,
+) -> Result<(), AggregateError> {
+    if proof.rounds != rounds {
+        return Err(AggregateError::RoundCount {
+            expected: rounds,
+            rounds: proof.rounds,
+        });
+    }
+    if bind_rounds(&proof.tree_root, rounds) != *root {
+        return Err(AggregateError::PathMismatch);
+    }
+    let picked = aggregate_rounds(root, rounds, count);
+    if !proof
+        .opened
+        .iter()
+        .map(|x| x.round)
+        .eq(picked.iter().copied())
+    {
+        return Err(AggregateError::WrongRounds);
+    }
+    let digests = proof
+        .opened
+        .iter()
+        .map(|x| round_digest(commitment, &x.nonce, &x.outcome, x.response.as_ref()).to_vec())
+        .collect::<Vec<_>>();
+    if !MerkleTreeVerifier::<Blake32>::new(rounds, &proof.tree_root).verify(
+        proof.path.clone(),
+        &picked,
+        &digests,
+    ) {
+        return Err(AggregateError::PathMismatch);
+    }
+    for opened in &proof.opened {
+        if opened.outcome != AuditOutcome::Passed {
+            return Err(AggregateError::Faulted {
+                round: opened.round,
+                outcome: opened.outcome.clone(),
+            });
+        }
+        let leaves = audit_leaves(
+            commitment,
+            &opened.nonce,
+            config.first_oracle_leaves(),
+            samples,
+        );
+        let misbehavior = match &opened.response {
+            Some(response) => trace(config, commitment, &leaves, response),
+            None => Some(Misbehavior::WrongLeaves {
+                requested: leaves,
+                served: vec![],
+            }),
+        };
+        if let Some(misbehavior) = misbehavior {
+            return Err(AggregateError::Failed {
+                round: opened.round,
+                misbehavior,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::{audit::AuditLog, fixtures::random_prover};
+
+    #[test]
+    fn aggregate_audits() {
+        let config = FridaConfig::new(b"aggregate", 2, 5, 1, 4);
+        let (_, prover) = random_prover(&config);
+        let commitment = config.commitment(&prover.commit());
+        let mut log = AuditLog::new(config.clone(), commitment, 3, 10);
+        let mut node = AuditAccumulator::new(commitment);
+        let mut lazy = AuditAccumulator::new(commitment);
+        for epoch in 0..40u8 {
+            let challenge = log.challenge([epoch; 32], 100 * epoch as u64).unwrap();
+            let response = prover.answer_audit(&challenge).unwrap();
+            let outcome = log
+                .answer(&[epoch; 32], &response, 100 * epoch as u64 + 1)
+                .unwrap();
+            node.push([epoch; 32], outcome, Some(response));
+            // Keeps one leaf and answers every round with it.
+            let response = prover.open_leaves(&[0]).unwrap();
+            lazy.push([epoch; 32], AuditOutcome::Passed, Some(response));
+        }
+        let (root, rounds) = log.accumulator().unwrap();
+        assert_eq!(node.root(), Some(root));
+        assert_eq!(rounds, 40);
+
+        let proof = node.aggregate(8).unwrap();
+        assert_eq!(proof.opened.len(), 8);
+        assert_eq!(
+            verify_aggregate(&config, &commitment, 3, &root, 40, 8, &proof),
+            Ok(())
+        );
+        assert_eq!(
+            verify_aggregate(&config, &commitment, 3, &root, 41, 8, &proof),
+            Err(AggregateError::RoundCount {
+                expected: 41,
+                rounds: 40
+            })
+        );
+        // The root binds the count, so a shorter history does not open.
+        let mut short = proof.clone();
+        short.rounds = 39;
+        assert_eq!(
+            verify_aggregate(&config, &commitment, 3, &root, 39, 8, &short),
+            Err(AggregateError::PathMismatch)
+        );
+        let mut swapped = proof.clone();
+        swapped.opened[0].response = Some(prover.open_leaves(&[0]).unwrap());
+        assert_eq!(
+            verify_aggregate(&config, &commitment, 3, &root, 40, 8, &swapped),
+            Err(AggregateError::PathMismatch)
+        );
+        // The lazy node's own root opens, but its rounds fail.
+        let lazy_root = lazy.root().unwrap();
+        assert!(matches!(
+            verify_aggregate(
+                &config,
+                &commitment,
+                3,
+                &lazy_root,
+                40,
+                8,
+                &lazy.aggregate(8).unwrap()
+            ),
+            Err(AggregateError::Failed { .. })
+        ));
+        // And it cannot present the honest rounds under the auditor's root.
+        assert_eq!(
+            verify_aggregate(&config, &commitment, 3, &lazy_root, 40, 8, &proof),
+            Err(AggregateError::PathMismatch)
+        );
+
+        // A missed and a late round enter the root like passed ones.
+        let challenge = log.challenge([40; 32], 4000).unwrap();
+        log.challenge([41; 32], 4100).unwrap();
+        let late = prover.answer_audit(&challenge).unwrap();
+        assert_eq!(log.answer(&[40; 32], &late, 4050), Ok(AuditOutcome::Late));
+        assert_eq!(log.accumulator().unwrap().1, 41);
+        assert_eq!(log.expire(4200), 1);
+        let (root, rounds) = log.accumulator().unwrap();
+        assert_eq!(rounds, 42);
+        let mut hiding = node.clone();
+        node.push([40; 32], AuditOutcome::Late, Some(late.clone()));
+        node.push([41; 32], AuditOutcome::Missed, None);
+        assert_eq!(node.root(), Some(root));
+        assert_eq!(
+            verify_aggregate(
+                &config,
+                &commitment,
+                3,
+                &root,
+                42,
+                42,
+                &node.aggregate(42).unwrap()
+            ),
+            Err(AggregateError::Faulted {
+                round: 40,
+                outcome: AuditOutcome::Late
+            })
+        );
+        // Recording them as passed changes the root.
+        hiding.push([40; 32], AuditOutcome::Passed, Some(late));
+        hiding.push([41; 32], AuditOutcome::Passed, None);
+        assert_eq!(
+            verify_aggregate(
+                &config,
+                &commitment,
+                3,
+                &root,
+                42,
+                8,
+                &hiding.aggregate(8).unwrap()
+            ),
+            Err(AggregateError::PathMismatch)
+        );
+    }
+}
@@ -0,0 +1,98 @@
+// Verifying a round's folding equations as one random linear combination
+// sum_j r^j (2 v'_j - fold_j) = 0 instead of one comparison per query. r is
+// hashed from the roots and final value, which fix every opened value once
+// the merkle paths check, so a wrong round survives with probability at most
+// q / |F| over q queries. In round 0 the batched sums are formed before the
+// challenge is applied, saving a product per polynomial and query. Failures
+// no longer name the query.
+use ark_bn254::Fr;
+
+use crate::{Blake3Transcript, IoppCommits, Transcript, Verifier};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FoldCheck {
+    #[default]
+    PerIndex,
+    Aggregated,
+}
+
+// One combination weight per round.
+pub(crate) fn fold_weights(
+    merkle_root: &[u8; 32],
+    iopp_commits: &IoppCommits,
+    rounds: usize,
+) -> Vec<Fr> {
+    let mut transcript = Blake3Transcript::new(b"frida_aggregated_folding");
+    transcript.absorb(b"merkle_root", merkle_root);
+    for root in iopp_commits.merkle_roots.iter() {
+        transcript.absorb(b"round_root", root);
+    }
+    transcript.absorb_field(b"final_value", &iopp_commits.final_value);
+    (0..rounds)
+        .map(|_| transcript.challenge_field(b"fold_weight"))
+        .collect()
+}
+
+impl Verifier {
+    pub fn with_fold_check(mut self, fold_check: FoldCheck) -> Self {
+        self.fold_check = fold_check;
+        self
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{replay, Batching, FailedCheck, FridaConfig, ProveOptions, Prover};
+
+    #[test]
+    fn aggregated_rounds() {
+        let mut rng = thread_rng();
+        let config = FridaConfig {
+            batching: Batching::Independent,
+            ..FridaConfig::new(b"amortized", 4, 6, 1, 40)
+        };
+        let groups = config.groups();
+        let bounds = vec![64, 40, 64, 33];
+        let run = |polies: &[Vec<Fr>], fold_check: FoldCheck| {
+            let prover = Prover::new(polies, &groups[0], &ProveOptions::default())
+                .unwrap()
+                .with_degree_bounds(bounds.clone());
+            let proof = prover.prove(&config);
+            let (challenges, _) =
+                replay::<Blake3Transcript>(&config, &proof.merkle_root, &proof.iopp_commits);
+            Verifier::for_config(&config, &proof.merkle_root)
+                .with_degree_bounds(bounds.clone())
+                .with_fold_check(fold_check)
+                .verify(
+                    &config,
+                    &groups,
+                    &challenges,
+                    proof.iopp_commits,
+                    proof.query_results,
+                )
+        };
+        let mut random = |len: usize| {
+            let mut x = (0..len).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+            x.resize(64, Fr::from(0u64));
+            x
+        };
+        let honest = vec![random(64), random(40), random(64), random(33)];
+        assert!(run(&honest, FoldCheck::Aggregated).accepted());
+        assert!(run(&honest, FoldCheck::PerIndex).accepted());
+
+        // The last polynomial breaks its bound of 33; folding stays honest, so
+        // the excess degree surfaces against the final value.
+        let cheating = vec![random(64), random(40), random(64), random(34)];
+        assert_eq!(
+            run(&cheating, FoldCheck::Aggregated).failure,
+            Some(FailedCheck::AggregatedFolding {
+                round: config.log_degree - 1
+            })
+        );
+        assert!(!run(&cheating, FoldCheck::PerIndex).accepted());
+    }
+}
@@ -0,0 +1,267 @@
+// Appending symbols to a committed blob. Each polynomial p of degree < d
+// grows to p' = p + X^d q, where q holds the appended coefficients, and is
+// re-encoded on the doubled domain at the same rate. The old domain H is the
+// even half of the new one, so new leaf 2i holds p' at the same x_i, -x_i as
+// old leaf i. The consistency proof commits q on H, proves it low-degree and
+// opens old, new and delta trees at transcript-chosen leaves, where a verifier
+// checks p'(x) = p(x) + x^d q(x).
+//
+// p' - p - X^d q has degree < 2d, so a nonzero one vanishes on at most
+// 2d / |H| = 2^(1 - code_rate) of H; appends therefore need code_rate >= 2.
+use ark_bn254::Fr;
+use ark_ff::Field;
+use util::mul_group::Radix2Group;
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::{Blake3Transcript, FridaConfig, FridaProof, Transcript, Verifier};
+
+#[derive(Clone)]
+pub struct AppendOpening {
+    pub index: usize,
+    pub old_symbols: Vec<Fr>,
+    pub old_path: Vec<u8>,
+    pub new_symbols: Vec<Fr>,
+    pub new_path: Vec<u8>,
+    pub delta_symbols: Vec<Fr>,
+    pub delta_path: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct AppendProof {
+    pub delta_root: [u8; 32],
+    pub delta_proof: FridaProof,
+    pub openings: Vec<AppendOpening>,
+}
+
+// The config of the grown blob: same polynomials and rate, twice the degree.
+pub fn appended_config(config: &FridaConfig) -> FridaConfig {
+    FridaConfig {
+        log_degree: config.log_degree + 1,
+        ..config.clone()
+    }
+}
+
+fn append_indices(
+    config: &FridaConfig,
+    old_root: &[u8; 32],
+    new_root: &[u8; 32],
+    delta_root: &[u8; 32],
+) -> Vec<usize> {
+    let mut transcript = config.transcript_with::<Blake3Transcript>();
+    transcript.absorb(b"append_old_root", old_root);
+    transcript.absorb(b"append_new_root", new_root);
+    transcript.absorb(b"append_delta_root", delta_root);
+    (0..config.query_num)
+        .map(|_| transcript.challenge_index(b"append_query", config.first_oracle_leaves()))
+        .collect()
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // Returns the prover of the grown blob and the proof linking its root to
+    // this one. `appended[k]` extends polynomial k by d coefficients.
+    pub fn append(&self, config: &FridaConfig, appended: &[Vec<Fr>]) -> (Prover, AppendProof) {
+        let degree = 1 << config.log_degree;
+        assert!(config.code_rate >= 2 && config.log_degree >= 1);
+        assert_eq!(appended.len(), config.poly_num);
+        assert!(appended.iter().all(|x| x.len() == degree));
+        assert_eq!(self.domain_size(), config.domain_size());
+        assert_eq!(self.leaf_rows, config.leaf_rows());
+
+        let group = Radix2Group::new(config.log_degree + config.code_rate);
+        let codeword = self.interpolation.codeword();
+        let domain = config.domain_size();
+        let polies = appended
+            .iter()
+            .enumerate()
+            .map(|(k, q)| {
+                let mut coeff = group.ifft(codeword[k * domain..(k + 1) * domain].to_vec());
+                coeff.truncate(degree);
+                coeff.extend_from_slice(q);
+                coeff
+            })
+            .collect::<Vec<_>>();
+        let rows = config.leaf_rows();
+        let extended = Prover::interleaved(&polies, &appended_config(config).groups()[0], rows);
+        let delta = Prover::interleaved(appended, &config.groups()[0], rows);
+        let delta_root = delta.commit();
+        let openings = append_indices(config, &self.commit(), &extended.commit(), &delta_root)
+            .into_iter()
+            .map(|index| {
+                let (old_symbols, old_path) = self.open_chunk(index);
+                let (new_symbols, new_path) = extended.open_chunk(2 * index);
+                let (delta_symbols, delta_path) = delta.open_chunk(index);
+                AppendOpening {
+                    index,
+                    old_symbols,
+                    old_path,
+                    new_symbols,
+                    new_path,
+                    delta_symbols,
+                    delta_path,
+                }
+            })
+            .collect();
+        let proof = AppendProof {
+            delta_root,
+            delta_proof: delta.prove(config),
+            openings,
+        };
+        (extended, proof)
+    }
+}
+
+impl Verifier {
+    // Checks that `new_root`, under `appended_config(config)`, commits the
+    // blob behind `old_root` with d coefficients appended to every polynomial.
+    // Low-degreeness of the new blob itself is its own proof's business.
+    pub fn verify_append(
+        config: &FridaConfig,
+        old_root: &[u8; 32],
+        new_root: &[u8; 32],
+        proof: &AppendProof,
+    ) -> bool {
+        if config.code_rate < 2
+            || config.log_degree < 1
+            || proof.delta_proof.merkle_root != proof.delta_root
+            || proof.openings.len() != config.query_num
+        {
+            return false;
+        }
+        if !Verifier::verify_proof(
+            config,
+            &config.commitment(&proof.delta_root),
+            &proof.delta_proof,
+        )
+        .accepted()
+        {
+            return false;
+        }
+        let leave_number = config.first_oracle_leaves();
+        let leaf_size = config.leaf_size(0);
+        let group = Radix2Group::new(config.log_degree + config.code_rate);
+        let indices = append_indices(config, old_root, new_root, &proof.delta_root);
+        proof.openings.iter().zip(indices).all(|(x, index)| {
+            // d is even, so x^d is shared by x_i and -x_i.
+            let shift = group
+                .element_at(index % config.leave_number())
+                .pow([1u64 << config.log_degree]);
+            x.index == index
+                && Verifier::verify_chunk(
+                    old_root,
+                    leave_number,
+                    leaf_size,
+                    index,
+                    &x.old_symbols,
+                    &x.old_path,
+                )
+                && Verifier::verify_chunk(
+                    new_root,
+                    leave_number * 2,
+                    leaf_size,
+                    2 * index,
+                    &x.new_symbols,
+                    &x.new_path,
+                )
+                && Verifier::verify_chunk(
+                    &proof.delta_root,
+                    leave_number,
+                    leaf_size,
+                    index,
+                    &x.delta_symbols,
+                    &x.delta_path,
+                )
+                && (0..leaf_size)
+                    .all(|j| x.new_symbols[j] == x.old_symbols[j] + shift * x.delta_symbols[j])
+        })
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{Interleave, ProveOptions};
+
+    #[test]
+    fn append_extend() {
+        let mut rng = thread_rng();
+        let config = FridaConfig::new(b"append", 2, 4, 2, 16);
+        let mut random = |n: usize| {
+            (0..config.poly_num)
+                .map(|_| (0..n).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let polies = random(16);
+        let appended = random(16);
+        let prover = Prover::new(&polies, &config.groups()[0], &ProveOptions::default()).unwrap();
+        let (extended, proof) = prover.append(&config, &appended);
+
+        let grown = appended_config(&config);
+        let concatenated = polies
+            .iter()
+            .zip(appended.iter())
+            .map(|(p, q)| [p.clone(), q.clone()].concat())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            extended.commit(),
+            Prover::new(&concatenated, &grown.groups()[0], &ProveOptions::default())
+                .unwrap()
+                .commit()
+        );
+        assert!(Verifier::verify_proof(
+            &grown,
+            &grown.commitment(&extended.commit()),
+            &extended.prove(&grown)
+        )
+        .accepted());
+        assert!(Verifier::verify_append(
+            &config,
+            &prover.commit(),
+            &extended.commit(),
+            &proof
+        ));
+
+        // A new root that does not extend the old blob.
+        let other = random(16);
+        let (unrelated, _) = Prover::new(&other, &config.groups()[0], &ProveOptions::default())
+            .unwrap()
+            .append(&config, &appended);
+        assert!(!Verifier::verify_append(
+            &config,
+            &prover.commit(),
+            &unrelated.commit(),
+            &proof
+        ));
+        let mut forged = proof.clone();
+        forged.openings[0].delta_symbols[0] += Fr::from(1u64);
+        assert!(!Verifier::verify_append(
+            &config,
+            &prover.commit(),
+            &extended.commit(),
+            &forged
+        ));
+
+        // One polynomial to a leaf: leaf a * len + i still grows into leaf
+        // 2 * (a * len + i).
+        let rows = FridaConfig {
+            interleave: Interleave::Rows(1),
+            ..config.clone()
+        };
+        let prover = Prover::interleaved(&polies, &rows.groups()[0], 1);
+        let (extended, proof) = prover.append(&rows, &appended);
+        assert_eq!(
+            extended.commit(),
+            Prover::interleaved(&concatenated, &grown.groups()[0], 1).commit()
+        );
+        assert!(Verifier::verify_append(
+            &rows,
+            &prover.commit(),
+            &extended.commit(),
+            &proof
+        ));
+    }
+}
@@ -0,0 +1,302 @@
+// Proof rounds in three flat buffers, reused across proofs. `sample_into`
+// writes every round's keys, values and path into the arena rather than into a
+// `QueryResult` and its map each, and `parse` decodes a proof's rounds into
+// it; `clear` keeps the capacity, so a server answering queries at a steady
+// rate stops allocating per proof once the buffers have grown to its largest
+// one. Keys within a round are ascending, which is the canonical encoding's
+// order and what lookups search.
+use ark_bn254::Fr;
+
+use crate::{
+    encoding::{push_field, push_len, Reader},
+    Blake3Transcript, FridaConfig, IoppCommits, Openings, ProtocolVersion, VerificationReport,
+    Verifier,
+};
+#[cfg(feature = "prover")]
+use crate::{replay, InterpolateValue, IoppProverState, Prover};
+
+#[derive(Default)]
+pub struct ProofArena {
+    keys: Vec<usize>,
+    values: Vec<Fr>,
+    paths: Vec<u8>,
+    // Where each round's values and path end.
+    ends: Vec<(usize, usize)>,
+    #[cfg(feature = "prover")]
+    scratch: Vec<usize>,
+    #[cfg(feature = "prover")]
+    merkle_scratch: Vec<usize>,
+}
+
+// One round's openings, borrowed from the arena.
+#[derive(Clone, Copy)]
+pub struct ArenaQuery<'a> {
+    keys: &'a [usize],
+    values: &'a [Fr],
+    path: &'a [u8],
+}
+
+impl Openings for ArenaQuery<'_> {
+    fn value(&self, key: usize) -> Option<Fr> {
+        self.keys.binary_search(&key).ok().map(|i| self.values[i])
+    }
+
+    fn contains(&self, key: usize) -> bool {
+        self.keys.binary_search(&key).is_ok()
+    }
+
+    fn value_num(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn path(&self) -> &[u8] {
+        self.path
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (usize, Fr)> + '_ {
+        self.keys.iter().copied().zip(self.values.iter().copied())
+    }
+}
+
+pub struct ArenaProof<'a> {
+    pub version: ProtocolVersion,
+    pub merkle_root: [u8; 32],
+    pub iopp_commits: IoppCommits,
+    query_results: Vec<ArenaQuery<'a>>,
+}
+
+impl ArenaProof<'_> {
+    pub fn query_results(&self) -> &[ArenaQuery<'_>] {
+        &self.query_results
+    }
+}
+
+impl ProofArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+        self.paths.clear();
+        self.ends.clear();
+    }
+
+    pub fn round_num(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn round(&self, i: usize) -> ArenaQuery<'_> {
+        let (start, path_start) = if i == 0 { (0, 0) } else { self.ends[i - 1] };
+        let (end, path_end) = self.ends[i];
+        ArenaQuery {
+            keys: &self.keys[start..end],
+            values: &self.values[start..end],
+            path: &self.paths[path_start..path_end],
+        }
+    }
+
+    fn end_round(&mut self) {
+        self.ends.push((self.values.len(), self.paths.len()));
+    }
+
+    // The proof holding the arena's rounds, in `FridaProof::to_bytes`'s
+    // encoding, appended to `out`.
+    pub fn write_proof(
+        &self,
+        version: ProtocolVersion,
+        merkle_root: &[u8; 32],
+        iopp_commits: &IoppCommits,
+        out: &mut Vec<u8>,
+    ) {
+        out.extend(version.to_bytes());
+        out.extend(merkle_root);
+        push_len(out, iopp_commits.merkle_roots.len());
+        for root in iopp_commits.merkle_roots.iter() {
+            out.extend(root);
+        }
+        push_field(out, &iopp_commits.final_value);
+        push_len(out, self.round_num());
+        for i in 0..self.round_num() {
+            let round = self.round(i);
+            push_len(out, round.keys.len());
+            push_len(out, round.path.len());
+            for k in round.keys {
+                out.extend((*k as u64).to_le_bytes());
+            }
+            for v in round.values {
+                push_field(out, v);
+            }
+            out.extend(round.path);
+        }
+    }
+
+    // `FridaProof::from_bytes` into the arena, replacing what it held, with
+    // the same checks.
+    pub fn parse(&mut self, bytes: &[u8]) -> Option<ArenaProof<'_>> {
+        self.clear();
+        let mut r = Reader { bytes };
+        let version = ProtocolVersion::from_bytes(r.take(4)?.try_into().ok()?);
+        let merkle_root = r.take(32)?.try_into().ok()?;
+        let round_num = r.len()?;
+        let merkle_roots = r
+            .take(round_num.checked_mul(32)?)?
+            .chunks(32)
+            .map(|x| x.try_into().unwrap())
+            .collect();
+        let final_value = r.field()?;
+        let result_num = r.len()?;
+        for _ in 0..result_num {
+            let value_num = r.len()?;
+            let path_len = r.len()?;
+            let start = self.keys.len();
+            let keys = r.take(value_num.checked_mul(8)?)?;
+            self.keys.extend(
+                keys.chunks(8)
+                    .map(|x| u64::from_le_bytes(x.try_into().unwrap()) as usize),
+            );
+            if self.keys[start..].windows(2).any(|x| x[0] >= x[1]) {
+                return None;
+            }
+            for _ in 0..value_num {
+                self.values.push(r.field()?);
+            }
+            self.paths.extend_from_slice(r.take(path_len)?);
+            self.end_round();
+        }
+        if !r.bytes.is_empty() {
+            return None;
+        }
+        Some(ArenaProof {
+            version,
+            merkle_root,
+            iopp_commits: IoppCommits::new(merkle_roots, final_value),
+            query_results: (0..self.round_num()).map(|i| self.round(i)).collect(),
+        })
+    }
+}
+
+#[cfg(feature = "prover")]
+impl InterpolateValue {
+    // `query` into the arena as one more round; `leaf_indices` sorted and
+    // distinct.
+    fn query_into(&self, leaf_indices: &[usize], arena: &mut ProofArena) {
+        let len = self.merkle_tree.leave_num();
+        for i in 0..self.leaf_size {
+            for j in leaf_indices {
+                arena.keys.push(j + i * len);
+                arena.values.push(self.get(j + i * len));
+            }
+        }
+        self.merkle_tree
+            .open_into(leaf_indices, &mut arena.merkle_scratch, &mut arena.paths);
+        arena.end_round();
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // `sample`, with the rounds written into `arena` after what it holds.
+    pub fn sample_into(
+        &self,
+        prover_state: &IoppProverState,
+        config: &FridaConfig,
+        arena: &mut ProofArena,
+    ) {
+        assert_eq!(config.poly_num, self.poly_num);
+        assert_eq!(config.log_degree, self.log_degree);
+        let (_, positions) =
+            replay::<Blake3Transcript>(config, &self.commit(), &prover_state.commits);
+        let mut indices = std::mem::take(&mut arena.scratch);
+        for i in 0..self.log_degree {
+            let len = self.positions() >> i;
+            indices.clear();
+            indices.extend(positions.iter().map(|x| x % len));
+            indices.sort_unstable();
+            indices.dedup();
+            if i == 0 {
+                // As `group_leaves`, in place.
+                let n = indices.len();
+                for a in 1..self.leaf_groups() {
+                    for k in 0..n {
+                        indices.push(a * len + indices[k]);
+                    }
+                }
+                self.interpolation.query_into(&indices, arena);
+            } else {
+                let oracle = &prover_state.interpolations[i - 1];
+                // As `folded_leaves`, in place.
+                let leaves = oracle.leave_num();
+                indices.iter_mut().for_each(|x| *x %= leaves);
+                indices.sort_unstable();
+                indices.dedup();
+                oracle.query_into(&indices, arena);
+            }
+        }
+        arena.scratch = indices;
+    }
+}
+
+impl Verifier {
+    pub fn verify_arena(
+        config: &FridaConfig,
+        commitment: &[u8; 32],
+        proof: &ArenaProof,
+    ) -> VerificationReport {
+        if let Some(report) = Self::check_version(config, proof.version) {
+            return report;
+        }
+        Self::verify_slots::<Blake3Transcript, _>(
+            config,
+            commitment,
+            &proof.merkle_root,
+            &proof.iopp_commits,
+            &(0..config.query_num).collect::<Vec<_>>(),
+            &proof.query_results,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_prover, FridaProof};
+
+    #[test]
+    fn arena_proofs() {
+        let config = FridaConfig::new(b"arena", 4, 5, 2, 12);
+        let (_, prover) = random_prover(&config);
+        let proof = prover.prove(&config);
+        let commitment = config.commitment(&proof.merkle_root);
+        let state = prover.fold_transcript::<Blake3Transcript>(&config);
+
+        let mut arena = ProofArena::new();
+        let mut bytes = vec![];
+        for _ in 0..2 {
+            arena.clear();
+            bytes.clear();
+            prover.sample_into(&state, &config, &mut arena);
+            assert_eq!(arena.round_num(), config.log_degree);
+            arena.write_proof(
+                proof.version,
+                &proof.merkle_root,
+                &proof.iopp_commits,
+                &mut bytes,
+            );
+            assert_eq!(bytes, proof.to_bytes());
+        }
+
+        let mut parsed = ProofArena::new();
+        let view = parsed.parse(&bytes).unwrap();
+        assert!(Verifier::verify_arena(&config, &commitment, &view).accepted());
+        assert_eq!(
+            FridaProof::from_bytes(&bytes).map(|x| x.query_results.len()),
+            Some(view.query_results().len())
+        );
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(parsed.parse(&extended).is_none());
+        assert!(parsed.parse(&bytes[..bytes.len() - 1]).is_none());
+    }
+}
@@ -0,0 +1,292 @@
+// Periodic audits of a stored blob. The auditor picks a fresh nonce, the node
+// has until the deadline to open the leaves the nonce selects, and the log
+// records how each round went. Positions depend on the nonce alone, so a node
+// can only answer by holding the leaves, or by fetching them before the
+// deadline. Times are whatever clock the caller keeps, in any unit, as long
+// as it is the same one throughout.
+use std::fmt;
+
+use crate::{
+    aggregate::{accumulator_root, round_digest},
+    cache::normalize,
+    service::SampleResponse,
+    slashing::{trace, Misbehavior},
+    FridaConfig,
+};
+#[cfg(feature = "prover")]
+use crate::{service::ServiceError, Prover};
+
+const AUDIT_DOMAIN: &[u8] = b"frida-audit";
+
+// The `count` distinct leaves, ascending, nonce selects on a blob of
+// `leave_number` leaves; all of them if fewer.
+pub fn audit_leaves(
+    commitment: &[u8; 32],
+    nonce: &[u8; 32],
+    leave_number: usize,
+    count: usize,
+) -> Vec<usize> {
+    let count = count.min(leave_number);
+    let mut leaves = vec![];
+    let mut counter = 0u64;
+    while leaves.len() < count {
+        let mut hasher = blake3::Hasher::new();
+        hasher
+            .update(AUDIT_DOMAIN)
+            .update(commitment)
+            .update(nonce)
+            .update(&counter.to_le_bytes());
+        let x = u64::from_le_bytes(hasher.finalize().as_bytes()[..8].try_into().unwrap());
+        leaves.push((x % leave_number as u64) as usize);
+        leaves = normalize(&leaves);
+        counter += 1;
+    }
+    leaves
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditChallenge {
+    pub commitment: [u8; 32],
+    pub nonce: [u8; 32],
+    pub leaves: Vec<usize>,
+    pub deadline: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Passed,
+    // A valid answer after the deadline.
+    Late,
+    // No answer by the deadline.
+    Missed,
+    Failed(Misbehavior),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub challenge: AuditChallenge,
+    pub issued_at: u64,
+    pub answered_at: Option<u64>,
+    // None while the round is open.
+    pub outcome: Option<AuditOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditError {
+    // Nonces are single use, or answers could be replayed.
+    ReusedNonce,
+    UnknownNonce,
+    Closed,
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::ReusedNonce => write!(f, "nonce was already used for this blob"),
+            AuditError::UnknownNonce => write!(f, "no audit was issued with this nonce"),
+            AuditError::Closed => write!(f, "audit round is already closed"),
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+// Every audit of one blob on one node, oldest first.
+pub struct AuditLog {
+    config: FridaConfig,
+    commitment: [u8; 32],
+    samples: usize,
+    window: u64,
+    entries: Vec<AuditEntry>,
+    // Of every round, in issue order, once it closes; see `aggregate`.
+    digests: Vec<Option<[u8; 32]>>,
+}
+
+impl AuditLog {
+    // Each audit opens `samples` leaves and is due `window` after issue.
+    pub fn new(config: FridaConfig, commitment: [u8; 32], samples: usize, window: u64) -> Self {
+        assert!(samples > 0);
+        AuditLog {
+            config,
+            commitment,
+            samples,
+            window,
+            entries: vec![],
+            digests: vec![],
+        }
+    }
+
+    pub fn challenge(&mut self, nonce: [u8; 32], now: u64) -> Result<AuditChallenge, AuditError> {
+        if self.entries.iter().any(|x| x.challenge.nonce == nonce) {
+            return Err(AuditError::ReusedNonce);
+        }
+        let challenge = AuditChallenge {
+            commitment: self.commitment,
+            nonce,
+            leaves: audit_leaves(
+                &self.commitment,
+                &nonce,
+                self.config.first_oracle_leaves(),
+                self.samples,
+            ),
+            deadline: now.saturating_add(self.window),
+        };
+        self.entries.push(AuditEntry {
+            challenge: challenge.clone(),
+            issued_at: now,
+            answered_at: None,
+            outcome: None,
+        });
+        self.digests.push(None);
+        Ok(challenge)
+    }
+
+    pub fn answer(
+        &mut self,
+        nonce: &[u8; 32],
+        response: &SampleResponse,
+        now: u64,
+    ) -> Result<AuditOutcome, AuditError> {
+        let round = self
+            .entries
+            .iter()
+            .position(|x| x.challenge.nonce == *nonce)
+            .ok_or(AuditError::UnknownNonce)?;
+        let entry = &mut self.entries[round];
+        if entry.outcome.is_some() {
+            return Err(AuditError::Closed);
+        }
+        let outcome = match trace(
+            &self.config,
+            &self.commitment,
+            &entry.challenge.leaves,
+            response,
+        ) {
+            Some(misbehavior) => AuditOutcome::Failed(misbehavior),
+            None if now > entry.challenge.deadline => AuditOutcome::Late,
+            None => AuditOutcome::Passed,
+        };
+        self.digests[round] = Some(round_digest(
+            &self.commitment,
+            nonce,
+            &outcome,
+            Some(response),
+        ));
+        entry.answered_at = Some(now);
+        entry.outcome = Some(outcome.clone());
+        Ok(outcome)
+    }
+
+    // Closes open rounds past their deadline as missed; how many were.
+    pub fn expire(&mut self, now: u64) -> usize {
+        let mut missed = 0;
+        for (entry, digest) in self.entries.iter_mut().zip(self.digests.iter_mut()) {
+            if entry.outcome.is_none() && now > entry.challenge.deadline {
+                entry.outcome = Some(AuditOutcome::Missed);
+                *digest = Some(round_digest(
+                    &self.commitment,
+                    &entry.challenge.nonce,
+                    &AuditOutcome::Missed,
+                    None,
+                ));
+                missed += 1;
+            }
+        }
+        missed
+    }
+
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    // When the node last answered an audit on time.
+    pub fn last_passed(&self) -> Option<u64> {
+        self.entries
+            .iter()
+            .filter(|x| x.outcome == Some(AuditOutcome::Passed))
+            .filter_map(|x| x.answered_at)
+            .max()
+    }
+
+    // The root over the closed rounds up to the first open one, whatever
+    // their outcome, and how many there are, to publish for
+    // `verify_aggregate`; None while the first round is open.
+    pub fn accumulator(&self) -> Option<([u8; 32], usize)> {
+        let closed = self.digests.iter().map_while(|x| *x).collect::<Vec<_>>();
+        accumulator_root(&closed).map(|x| (x, closed.len()))
+    }
+
+    // Closed rounds that did not pass.
+    pub fn faults(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|x| {
+                x.outcome
+                    .as_ref()
+                    .is_some_and(|x| *x != AuditOutcome::Passed)
+            })
+            .count()
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    pub fn answer_audit(&self, challenge: &AuditChallenge) -> Result<SampleResponse, ServiceError> {
+        self.open_leaves(&challenge.leaves)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::random_prover;
+
+    #[test]
+    fn audit_rounds() {
+        let config = FridaConfig::new(b"audit", 2, 5, 1, 4);
+        let (_, prover) = random_prover(&config);
+        let commitment = config.commitment(&prover.commit());
+        let mut log = AuditLog::new(config, commitment, 4, 10);
+
+        let first = log.challenge([1; 32], 100).unwrap();
+        assert_eq!(first.leaves.len(), 4);
+        assert_eq!(first.leaves, audit_leaves(&commitment, &[1; 32], 32, 4));
+        let response = prover.answer_audit(&first).unwrap();
+        assert_eq!(
+            log.answer(&[1; 32], &response, 105),
+            Ok(AuditOutcome::Passed)
+        );
+        assert_eq!(
+            log.answer(&[1; 32], &response, 106),
+            Err(AuditError::Closed)
+        );
+        assert_eq!(log.challenge([1; 32], 200), Err(AuditError::ReusedNonce));
+
+        // Leaves other than the nonce's fail the round.
+        log.challenge([2; 32], 200).unwrap();
+        let stale = prover.open_leaves(&[0]).unwrap();
+        assert!(matches!(
+            log.answer(&[2; 32], &stale, 201),
+            Ok(AuditOutcome::Failed(Misbehavior::WrongLeaves { .. }))
+        ));
+        let third = log.challenge([3; 32], 300).unwrap();
+        let late = prover.answer_audit(&third).unwrap();
+        assert_eq!(log.answer(&[3; 32], &late, 311), Ok(AuditOutcome::Late));
+        log.challenge([4; 32], 400).unwrap();
+        assert_eq!(log.expire(405), 0);
+        assert_eq!(log.expire(411), 1);
+        assert_eq!(
+            log.answer(&[5; 32], &late, 412),
+            Err(AuditError::UnknownNonce)
+        );
+
+        assert_eq!(log.last_passed(), Some(105));
+        assert_eq!(log.faults(), 3);
+        assert_eq!(log.entries()[3].outcome, Some(AuditOutcome::Missed));
+
+        // A clock near its end saturates the deadline rather than wrapping.
+        let edge = log.challenge([6; 32], u64::MAX - 1).unwrap();
+        assert_eq!(edge.deadline, u64::MAX);
+        assert_eq!(log.expire(u64::MAX), 0);
+    }
+}
@@ -0,0 +1,131 @@
+// How the round-0 oracles are combined into one. `Powers` weights term t of n
+// by alpha^(n-1-t), so a far batch slips through for at most n - 1 values of
+// alpha; `Independent` weights every term by its own coefficient and the
+// batching error no longer grows with the number of polynomials. The
+// coefficients are expanded from alpha through a hash, so they follow from the
+// transcript and an interactive verifier still sends one challenge.
+use ark_bn254::Fr;
+use ark_ff::One;
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::{Blake3Transcript, Transcript, Verifier};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Batching {
+    #[default]
+    Powers,
+    Independent,
+}
+
+impl Batching {
+    pub(crate) fn id(self) -> u8 {
+        match self {
+            Batching::Powers => 0,
+            Batching::Independent => 1,
+        }
+    }
+
+    // The weight of each of `terms` batched oracles, in batching order.
+    pub fn weights(self, alpha: Fr, terms: usize) -> Vec<Fr> {
+        match self {
+            Batching::Powers => {
+                let mut power = Fr::one();
+                let mut weights = (0..terms)
+                    .map(|_| {
+                        let w = power;
+                        power *= alpha;
+                        w
+                    })
+                    .collect::<Vec<_>>();
+                weights.reverse();
+                weights
+            }
+            Batching::Independent => {
+                let mut transcript = Blake3Transcript::new(b"frida_batching");
+                transcript.absorb_field(b"alpha", &alpha);
+                (0..terms)
+                    .map(|_| transcript.challenge_field(b"coefficient"))
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // Used by the explicit-challenge commit phase; `prove` follows the
+    // config's batching instead.
+    pub fn with_batching(mut self, batching: Batching) -> Self {
+        self.batching = batching;
+        self
+    }
+}
+
+impl Verifier {
+    pub fn with_batching(mut self, batching: Batching) -> Self {
+        self.batching = batching;
+        self
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::{Field, UniformRand};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{fixtures::random_polies, replay, FridaConfig, ProveOptions};
+
+    #[test]
+    fn independent_coefficients() {
+        let mut rng = thread_rng();
+        let alpha = Fr::rand(&mut rng);
+        let powers = Batching::Powers.weights(alpha, 3);
+        assert_eq!(powers, vec![alpha.square(), alpha, Fr::one()]);
+        let independent = Batching::Independent.weights(alpha, 3);
+        assert_eq!(independent, Batching::Independent.weights(alpha, 3));
+        assert_ne!(independent[1], independent[0].square());
+
+        let config = FridaConfig {
+            batching: Batching::Independent,
+            ..FridaConfig::new(b"batching", 4, 5, 2, 20)
+        };
+        let polies = random_polies(4, 32);
+        let groups = config.groups();
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        let proof = prover.prove(&config);
+        let commitment = config.commitment(&proof.merkle_root);
+        assert!(Verifier::verify_proof(&config, &commitment, &proof).accepted());
+        let powers = FridaConfig {
+            batching: Batching::Powers,
+            ..config.clone()
+        };
+        assert_ne!(powers.commitment(&proof.merkle_root), commitment);
+
+        // The explicit-challenge API takes the batching from the builders.
+        let (challenges, _) =
+            replay::<Blake3Transcript>(&config, &proof.merkle_root, &proof.iopp_commits);
+        let verify = |batching: Batching| {
+            Verifier::new(proof.merkle_root, config.poly_num, config.leave_number())
+                .with_batching(batching)
+                .verify(
+                    &config,
+                    &groups,
+                    &challenges,
+                    proof.iopp_commits.clone(),
+                    proof.query_results.clone(),
+                )
+                .accepted()
+        };
+        assert!(verify(Batching::Independent));
+        assert!(!verify(Batching::Powers));
+        let (_, commits) = prover
+            .with_batching(Batching::Independent)
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        assert_eq!(commits.merkle_roots, proof.iopp_commits.merkle_roots);
+        assert_eq!(commits.final_value, proof.iopp_commits.final_value);
+    }
+}
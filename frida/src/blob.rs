@@ -0,0 +1,269 @@
+// Byte blobs end to end, for blobs too large to hold more than once. Packing
+// feeds `Prover::from_stream` one polynomial at a time and decoding writes
+// each recovered polynomial's bytes out before starting the next, so beyond
+// the codeword only the polynomial in flight is ever resident. Blob lengths
+// and byte offsets are u64, as a 1 GiB blob's would overflow a 32-bit usize
+// once multiplied out. That is as far as it goes: the codeword is still one
+// contiguous allocation per oracle, and encoding, FFT, hashing and sampling
+// index it with usize, so large blobs need a 64-bit host with memory for the
+// whole codeword.
+use std::{collections::BTreeMap, fmt};
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use util::mul_group::Radix2Group;
+
+use crate::{plan::BYTES_PER_ELEMENT, scheme::FridaShare, FridaConfig, Prover};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobError {
+    TooLarge { blob_len: u64, capacity: u64 },
+    // Some polynomial could not be recovered from the shares.
+    TooFewShares,
+}
+
+impl fmt::Display for BlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlobError::TooLarge { blob_len, capacity } => {
+                write!(f, "{} bytes exceed the config's {}", blob_len, capacity)
+            }
+            BlobError::TooFewShares => write!(f, "too few shares to recover the blob"),
+        }
+    }
+}
+
+impl std::error::Error for BlobError {}
+
+fn poly_bytes(config: &FridaConfig) -> u64 {
+    (BYTES_PER_ELEMENT as u64) << config.log_degree
+}
+
+// The most bytes a blob committed under `config` can hold.
+pub fn blob_capacity(config: &FridaConfig) -> u64 {
+    config.poly_num as u64 * poly_bytes(config)
+}
+
+// `blob` as `config.poly_num` coefficient vectors, BYTES_PER_ELEMENT bytes per
+// element, zero padded; each is built only when the stream reaches it.
+pub fn pack_polies<'a>(
+    config: &FridaConfig,
+    blob: &'a [u8],
+) -> impl ExactSizeIterator<Item = Vec<Fr>> + 'a {
+    let degree = 1 << config.log_degree;
+    let per_poly = poly_bytes(config);
+    let len = blob.len() as u64;
+    (0..config.poly_num).map(move |k| {
+        let k = k as u64;
+        let start = (k * per_poly).min(len) as usize;
+        let end = ((k + 1) * per_poly).min(len) as usize;
+        let mut poly = blob[start..end]
+            .chunks(BYTES_PER_ELEMENT)
+            .map(Fr::from_le_bytes_mod_order)
+            .collect::<Vec<_>>();
+        poly.resize(degree, Fr::zero());
+        poly
+    })
+}
+
+// Panics if `blob` exceeds the capacity of `config`, which
+// `DispersalPlan::pick` sized it for.
+pub fn commit_blob(config: &FridaConfig, blob: &[u8]) -> Prover {
+    assert!(blob.len() as u64 <= blob_capacity(config));
+    Prover::from_stream_interleaved(
+        pack_polies(config, blob),
+        &config.groups()[0],
+        config.leaf_rows(),
+    )
+}
+
+// Polynomial `k` from first-oracle shares; `group` is the first oracle's
+// domain. Slot j of leaf a * len + i holds entry i of half-codeword
+// a + j * groups, as `group_leaves` lays them out. Shares off the first
+// oracle or of the wrong width are skipped rather than trusted, and a leaf
+// given twice counts once, since interpolation needs distinct points.
+pub(crate) fn decode_poly(
+    config: &FridaConfig,
+    group: &Radix2Group,
+    shares: &[FridaShare],
+    k: usize,
+) -> Option<Vec<Fr>> {
+    let len = config.leave_number();
+    let groups = config.leaf_groups();
+    let (leaves, width) = (config.first_oracle_leaves(), config.leaf_size(0));
+    let evals = shares
+        .iter()
+        .filter(|x| x.index < leaves && x.symbols.len() == width)
+        .flat_map(|x| {
+            let (a, i) = (x.index / len, x.index % len);
+            x.symbols.iter().enumerate().filter_map(move |(j, v)| {
+                let half = a + j * groups;
+                (half / 2 == k).then_some((i + (half % 2) * len, *v))
+            })
+        })
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    group.recover(&evals, 1 << config.log_degree)
+}
+
+// The first `blob_len` bytes of the blob behind `shares`, which are assumed
+// verified. Recovery is n log n per polynomial when the shares include every
+// leaf of one residue class mod 2^code_rate, and quadratic otherwise.
+pub fn reconstruct_blob(
+    config: &FridaConfig,
+    shares: &[FridaShare],
+    blob_len: u64,
+) -> Result<Vec<u8>, BlobError> {
+    // The blob is returned whole, so it must also fit a usize.
+    let capacity = blob_capacity(config).min(usize::MAX as u64);
+    if blob_len > capacity {
+        return Err(BlobError::TooLarge { blob_len, capacity });
+    }
+    let group = Radix2Group::new(config.log_degree + config.code_rate);
+    let mut blob = Vec::with_capacity(blob_len as usize);
+    let poly_num = blob_len.div_ceil(poly_bytes(config)) as usize;
+    for k in 0..poly_num {
+        let poly = decode_poly(config, &group, shares, k).ok_or(BlobError::TooFewShares)?;
+        for x in poly {
+            blob.extend_from_slice(&x.into_bigint().to_bytes_le()[..BYTES_PER_ELEMENT]);
+        }
+    }
+    blob.truncate(blob_len as usize);
+    Ok(blob)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::Verifier;
+
+    fn shares(prover: &Prover, leaves: impl Iterator<Item = usize>) -> Vec<FridaShare> {
+        leaves
+            .map(|index| {
+                let (symbols, path) = prover.open_chunk(index);
+                FridaShare {
+                    index,
+                    symbols,
+                    path,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blob_roundtrip() {
+        let mut rng = thread_rng();
+        let config = FridaConfig::new(b"blob", 4, 4, 2, 4);
+        assert_eq!(blob_capacity(&config), 64 * 31);
+        // Spans three polynomials; the fourth is padding.
+        let blob = (0..1200).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let prover = commit_blob(&config, &blob);
+        let root = prover.commit();
+        let proof = prover.prove(&config);
+        assert!(Verifier::verify_proof(&config, &config.commitment(&root), &proof).accepted());
+
+        // Leaves 1 mod 4 hold the coset w * <w^4> of every polynomial.
+        let coset = shares(&prover, (1..32).step_by(4));
+        let scattered = shares(&prover, [0, 2, 3, 5, 6, 10, 19, 30].into_iter());
+        let group = Radix2Group::new(6);
+        for (k, poly) in pack_polies(&config, &blob).enumerate() {
+            assert_eq!(
+                decode_poly(&config, &group, &coset, k).as_ref(),
+                Some(&poly)
+            );
+            assert_eq!(decode_poly(&config, &group, &scattered, k), Some(poly));
+        }
+        let recovered = reconstruct_blob(&config, &coset, 1200).unwrap();
+        assert_eq!(recovered.len(), 1200);
+        assert_eq!(
+            reconstruct_blob(&config, &scattered, 1200),
+            Ok(recovered.clone())
+        );
+        assert_eq!(
+            reconstruct_blob(&config, &scattered[..7], 1200),
+            Err(BlobError::TooFewShares)
+        );
+        // A repeated share adds no point, and does not break recovery.
+        let mut repeated = scattered.clone();
+        repeated.insert(3, scattered[0].clone());
+        assert_eq!(reconstruct_blob(&config, &repeated, 1200), Ok(recovered));
+        let mut short = scattered[..7].to_vec();
+        short.push(scattered[6].clone());
+        assert_eq!(
+            reconstruct_blob(&config, &short, 1200),
+            Err(BlobError::TooFewShares)
+        );
+        let mut truncated = scattered.clone();
+        truncated[7].symbols.pop();
+        assert_eq!(
+            reconstruct_blob(&config, &truncated, 1200),
+            Err(BlobError::TooFewShares)
+        );
+        truncated[7] = shares(&prover, [31].into_iter()).remove(0);
+        truncated[7].index = 64;
+        assert_eq!(
+            reconstruct_blob(&config, &truncated, 1200),
+            Err(BlobError::TooFewShares)
+        );
+        assert_eq!(
+            reconstruct_blob(&config, &coset, 2000),
+            Err(BlobError::TooLarge {
+                blob_len: 2000,
+                capacity: 1984
+            })
+        );
+    }
+
+    // cargo test --release --features expensive-tests -p frida gigabyte_blob
+    #[cfg(feature = "expensive-tests")]
+    #[test]
+    fn gigabyte_blob() {
+        use crate::{
+            plan::{DispersalInputs, DispersalPlan},
+            SecurityLevel, SoundnessRegime,
+        };
+
+        let mut rng = thread_rng();
+        let blob_len = 1 << 30;
+        let plan = DispersalPlan::pick(&DispersalInputs {
+            blob_size: blob_len,
+            node_num: 1024,
+            adversary_fraction: 0.33,
+            failure_probability: 1e-9,
+        })
+        .unwrap();
+        let config = FridaConfig::new(
+            b"gigabyte",
+            plan.poly_num,
+            plan.log_degree,
+            plan.code_rate,
+            SecurityLevel::Bits100.query_num(plan.code_rate, SoundnessRegime::Proven),
+        );
+        let mut blob = vec![0u8; blob_len];
+        rng.fill(&mut blob[..]);
+
+        let prover = commit_blob(&config, &blob);
+        let root = prover.commit();
+        let proof = prover.prove(&config);
+        assert!(Verifier::verify_proof(&config, &config.commitment(&root), &proof).accepted());
+        let leave_number = config.leave_number();
+        for _ in 0..plan.sample_num {
+            let index = rng.gen_range(0..leave_number);
+            let (symbols, path) = prover.open_chunk(index);
+            assert!(Verifier::verify_chunk(
+                &root,
+                leave_number,
+                config.leaf_size(0),
+                index,
+                &symbols,
+                &path
+            ));
+        }
+        let held = shares(&prover, (0..leave_number).step_by(1 << config.code_rate));
+        drop(prover);
+        assert_eq!(reconstruct_blob(&config, &held, blob_len as u64), Ok(blob));
+    }
+}
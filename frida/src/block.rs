@@ -0,0 +1,269 @@
+// Several blobs committed under one root. Each blob keeps its own config and
+// first oracle; the block tree's leaf i is blob i's published commitment, so
+// the 32-byte block root binds every blob's root, context and parameters. A
+// sample carries the blob's merkle root and its path in the block tree next to
+// the usual leaf opening, and checks against the block root alone. A
+// `BlockMultiSample` answers many positions in many blobs at once: one
+// multiproof over the block tree for all sampled blobs and one per blob over
+// its sampled leaves, so paths shared near either root are sent once.
+use ark_bn254::Fr;
+use util::merkle_tree::{Blake32, Blake3Merkle, MerkleTreeProver, MerkleTreeVerifier, Serialize};
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::{FridaConfig, Verifier};
+
+pub struct BlockTree {
+    commitments: Vec<[u8; 32]>,
+    tree: MerkleTreeProver<Blake3Merkle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSample {
+    pub blob: usize,
+    pub merkle_root: [u8; 32],
+    pub blob_path: Vec<u8>,
+    pub leaf: usize,
+    pub symbols: Vec<Fr>,
+    pub path: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobOpening {
+    pub blob: usize,
+    pub merkle_root: [u8; 32],
+    // Ascending and distinct.
+    pub leaves: Vec<usize>,
+    pub symbols: Vec<Vec<Fr>>,
+    pub path: Vec<u8>,
+}
+
+// `openings` is ascending by blob, one per sampled blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockMultiSample {
+    pub blob_path: Vec<u8>,
+    pub openings: Vec<BlobOpening>,
+}
+
+impl BlockTree {
+    // `commitments[i]` is `configs[i].commitment` of blob i's root.
+    pub fn new(commitments: Vec<[u8; 32]>) -> Self {
+        assert!(!commitments.is_empty());
+        let tree = MerkleTreeProver::new(&commitments.iter().map(|x| x.to_vec()).collect());
+        BlockTree { commitments, tree }
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn from_blobs(blobs: &[(&FridaConfig, &Prover)]) -> Self {
+        Self::new(
+            blobs
+                .iter()
+                .map(|(config, prover)| config.commitment(&prover.commit()))
+                .collect(),
+        )
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.tree.commit()
+    }
+
+    pub fn blob_num(&self) -> usize {
+        self.commitments.len()
+    }
+
+    pub fn commitment(&self, blob: usize) -> [u8; 32] {
+        self.commitments[blob]
+    }
+
+    // The path binding blob `blob`'s commitment to the block root.
+    pub fn open_blob(&self, blob: usize) -> Vec<u8> {
+        self.tree.open(&[blob])
+    }
+
+    // `prover` must be the one blob `blob` was committed with.
+    #[cfg(feature = "prover")]
+    pub fn sample(&self, blob: usize, prover: &Prover, leaf: usize) -> BlockSample {
+        let (symbols, path) = prover.open_chunk(leaf);
+        BlockSample {
+            blob,
+            merkle_root: prover.commit(),
+            blob_path: self.open_blob(blob),
+            leaf,
+            symbols,
+            path,
+        }
+    }
+
+    // One response for `requests`, each a blob, the prover it was committed
+    // with and the leaves wanted from it.
+    #[cfg(feature = "prover")]
+    pub fn sample_many(&self, requests: &[(usize, &Prover, Vec<usize>)]) -> BlockMultiSample {
+        let mut requests = requests.iter().collect::<Vec<_>>();
+        requests.sort_by_key(|x| x.0);
+        assert!(requests.windows(2).all(|x| x[0].0 < x[1].0));
+        let openings = requests
+            .iter()
+            .map(|(blob, prover, leaves)| {
+                let mut leaves = leaves.clone();
+                leaves.sort_unstable();
+                leaves.dedup();
+                BlobOpening {
+                    blob: *blob,
+                    merkle_root: prover.commit(),
+                    symbols: leaves
+                        .iter()
+                        .map(|&i| prover.interpolation.leaf(i))
+                        .collect(),
+                    path: prover.interpolation.merkle_tree.open(&leaves),
+                    leaves,
+                }
+            })
+            .collect::<Vec<_>>();
+        BlockMultiSample {
+            blob_path: self
+                .tree
+                .open(&openings.iter().map(|x| x.blob).collect::<Vec<_>>()),
+            openings,
+        }
+    }
+}
+
+impl BlockSample {
+    // `config` is the sampled blob's; `blob_num` the block's blob count.
+    pub fn verify(&self, block_root: &[u8; 32], blob_num: usize, config: &FridaConfig) -> bool {
+        self.blob < blob_num
+            && self.leaf < config.first_oracle_leaves()
+            && MerkleTreeVerifier::<Blake3Merkle>::new(blob_num, block_root).verify(
+                self.blob_path.clone(),
+                &[self.blob],
+                &[config.commitment(&self.merkle_root).to_vec()],
+            )
+            && Verifier::verify_chunk(
+                &self.merkle_root,
+                config.first_oracle_leaves(),
+                config.leaf_size(0),
+                self.leaf,
+                &self.symbols,
+                &self.path,
+            )
+    }
+}
+
+impl BlockMultiSample {
+    // `configs[i]` is blob i's config, for every blob of the block.
+    pub fn verify(&self, block_root: &[u8; 32], configs: &[FridaConfig]) -> bool {
+        let blobs = self.openings.iter().map(|x| x.blob).collect::<Vec<_>>();
+        if blobs.is_empty()
+            || blobs.windows(2).any(|x| x[0] >= x[1])
+            || *blobs.last().unwrap() >= configs.len()
+        {
+            return false;
+        }
+        let commitments = self
+            .openings
+            .iter()
+            .map(|x| configs[x.blob].commitment(&x.merkle_root).to_vec())
+            .collect::<Vec<_>>();
+        MerkleTreeVerifier::<Blake3Merkle>::new(configs.len(), block_root).verify(
+            self.blob_path.clone(),
+            &blobs,
+            &commitments,
+        ) && self.openings.iter().all(|x| {
+            let leave_number = configs[x.blob].first_oracle_leaves();
+            !x.leaves.is_empty()
+                && x.leaves.windows(2).all(|y| y[0] < y[1])
+                && *x.leaves.last().unwrap() < leave_number
+                && MerkleTreeVerifier::<Blake32>::new(leave_number, &x.merkle_root).verify(
+                    x.path.clone(),
+                    &x.leaves,
+                    &x.symbols
+                        .iter()
+                        .map(|y| Serialize::serialize_fields(y))
+                        .collect::<Vec<_>>(),
+                )
+        })
+    }
+
+    // Hashes in every path of the response.
+    pub fn hash_count(&self) -> usize {
+        (self.blob_path.len() + self.openings.iter().map(|x| x.path.len()).sum::<usize>()) / 32
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::random_prover;
+
+    #[test]
+    fn block_samples() {
+        let configs = [
+            (b"rollup-a", 2, 4),
+            (b"rollup-b", 4, 5),
+            (b"rollup-c", 1, 3),
+        ]
+        .map(|(context, poly_num, log_degree)| {
+            FridaConfig::new(context, poly_num, log_degree, 2, 8)
+        });
+        let provers = configs
+            .iter()
+            .map(|config| random_prover(config).1)
+            .collect::<Vec<_>>();
+        let block = BlockTree::from_blobs(&configs.iter().zip(&provers).collect::<Vec<_>>());
+        let root = block.root();
+        for (blob, (config, prover)) in configs.iter().zip(&provers).enumerate() {
+            assert_eq!(block.commitment(blob), config.commitment(&prover.commit()));
+            for leaf in [0, config.leave_number() - 1] {
+                let sample = block.sample(blob, prover, leaf);
+                assert!(sample.verify(&root, 3, config));
+                // A sample binds to its own blob: neither another blob's
+                // config nor another slot in the block accepts it.
+                assert!(!sample.verify(&root, 3, &configs[(blob + 1) % 3]));
+                let mut moved = sample.clone();
+                moved.blob = (blob + 1) % 3;
+                assert!(!moved.verify(&root, 3, config));
+                let mut forged = sample;
+                forged.symbols[0] += Fr::from(1u64);
+                assert!(!forged.verify(&root, 3, config));
+            }
+        }
+    }
+
+    #[test]
+    fn cross_blob_samples() {
+        let configs = (0..5)
+            .map(|i| FridaConfig::new(&[b'b', i as u8], 2, 5, 1, 8))
+            .collect::<Vec<_>>();
+        let provers = configs
+            .iter()
+            .map(|config| random_prover(config).1)
+            .collect::<Vec<_>>();
+        let block = BlockTree::from_blobs(&configs.iter().zip(&provers).collect::<Vec<_>>());
+        let root = block.root();
+        let requests = [0, 1, 3, 4]
+            .map(|blob| (blob, &provers[blob], vec![2, 3, 17, 30]))
+            .to_vec();
+        let sample = block.sample_many(&requests);
+        assert!(sample.verify(&root, &configs));
+
+        // Fewer hashes than answering each position on its own.
+        let separate = requests
+            .iter()
+            .flat_map(|(blob, prover, leaves)| {
+                leaves.iter().map(|&leaf| block.sample(*blob, prover, leaf))
+            })
+            .map(|x| (x.blob_path.len() + x.path.len()) / 32)
+            .sum::<usize>();
+        assert!(sample.hash_count() * 2 < separate);
+
+        let mut forged = sample.clone();
+        forged.openings[2].symbols[1][0] += Fr::from(1u64);
+        assert!(!forged.verify(&root, &configs));
+        let mut dropped = sample.clone();
+        dropped.openings.remove(1);
+        assert!(!dropped.verify(&root, &configs));
+        let mut moved = sample;
+        moved.openings[3].blob = 2;
+        assert!(!moved.verify(&root, &configs));
+    }
+}
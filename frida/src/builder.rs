@@ -0,0 +1,139 @@
+// The proving pipeline as a chain of typestates, Configured -> Committed ->
+// Folded -> Ready, so a stage can only be reached from the one before it and
+// every stage works from the one config it was started with.
+use ark_bn254::Fr;
+
+use crate::{
+    Blake3Transcript, FridaConfig, FridaProof, IoppProverState, ProtocolVersion, Prover,
+    VerificationReport, Verifier,
+};
+
+pub struct Configured;
+
+pub struct Committed {
+    prover: Prover,
+}
+
+pub struct Folded {
+    prover: Prover,
+    prover_state: IoppProverState,
+}
+
+pub struct Ready {
+    proof: FridaProof,
+}
+
+pub struct FridaBuilder<S> {
+    config: FridaConfig,
+    stage: S,
+}
+
+impl<S> FridaBuilder<S> {
+    pub fn config(&self) -> &FridaConfig {
+        &self.config
+    }
+}
+
+impl FridaBuilder<Configured> {
+    pub fn new(config: FridaConfig) -> Self {
+        FridaBuilder {
+            config,
+            stage: Configured,
+        }
+    }
+
+    pub fn commit(self, polies: &[Vec<Fr>]) -> FridaBuilder<Committed> {
+        assert_eq!(polies.len(), self.config.poly_num);
+        assert_eq!(polies[0].len(), 1 << self.config.log_degree);
+        let prover = Prover::interleaved(polies, &self.config.groups()[0], self.config.leaf_rows());
+        FridaBuilder {
+            config: self.config,
+            stage: Committed { prover },
+        }
+    }
+}
+
+impl FridaBuilder<Committed> {
+    pub fn prover(&self) -> &Prover {
+        &self.stage.prover
+    }
+
+    // What gets published: the root bound to the config's parameters.
+    pub fn commitment(&self) -> [u8; 32] {
+        self.config.commitment(&self.stage.prover.commit())
+    }
+
+    pub fn commit_phase(self) -> FridaBuilder<Folded> {
+        let prover_state = self
+            .stage
+            .prover
+            .fold_transcript::<Blake3Transcript>(&self.config);
+        FridaBuilder {
+            config: self.config,
+            stage: Folded {
+                prover: self.stage.prover,
+                prover_state,
+            },
+        }
+    }
+}
+
+impl FridaBuilder<Folded> {
+    pub fn sample(self) -> FridaBuilder<Ready> {
+        let Folded {
+            prover,
+            prover_state,
+        } = self.stage;
+        let query_results = prover.sample(&prover_state, &self.config);
+        FridaBuilder {
+            config: self.config,
+            stage: Ready {
+                proof: FridaProof {
+                    merkle_root: prover.commit(),
+                    iopp_commits: prover_state.commits,
+                    query_results,
+                    version: ProtocolVersion::CURRENT,
+                },
+            },
+        }
+    }
+}
+
+impl FridaBuilder<Ready> {
+    pub fn proof(&self) -> &FridaProof {
+        &self.stage.proof
+    }
+
+    pub fn into_proof(self) -> FridaProof {
+        self.stage.proof
+    }
+
+    // Checks the proof against the same config it was produced under.
+    pub fn verify(&self) -> VerificationReport {
+        let commitment = self.config.commitment(&self.stage.proof.merkle_root);
+        Verifier::verify_proof(&self.config, &commitment, &self.stage.proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_polies, ProveOptions};
+
+    #[test]
+    fn pipeline() {
+        let config = FridaConfig::new(b"builder", 4, 5, 2, 16);
+        let polies = random_polies(config.poly_num, 1 << config.log_degree);
+        let committed = FridaBuilder::new(config.clone()).commit(&polies);
+        let commitment = committed.commitment();
+        let ready = committed.commit_phase().sample();
+        assert!(ready.verify().accepted());
+
+        let proof = ready.into_proof();
+        let direct = Prover::new(&polies, &config.groups()[0], &ProveOptions::default())
+            .unwrap()
+            .prove(&config);
+        assert_eq!(proof.iopp_commits.digest(), direct.iopp_commits.digest());
+        assert!(Verifier::verify_proof(&config, &commitment, &proof).accepted());
+    }
+}
@@ -0,0 +1,177 @@
+// A least-recently-used cache of serialized sample responses for a serving
+// node. Light clients of a popular blob draw overlapping index sets, so
+// responses are keyed by the root and the index set sorted and deduplicated,
+// and two clients asking for the same leaves in another order share an entry.
+// The budget is in response bytes; the oldest entries go first once it is
+// exceeded, and a response larger than the whole budget is never kept.
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 / (self.hits + self.misses).max(1) as f64
+    }
+}
+
+type Key = ([u8; 32], Vec<usize>);
+
+#[derive(Default)]
+struct Entries {
+    // Response and the tick it was last used at.
+    map: HashMap<Key, (Arc<Vec<u8>>, u64)>,
+    recency: BTreeMap<u64, Key>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+pub struct ProofCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+pub fn normalize(indices: &[usize]) -> Vec<usize> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+impl ProofCache {
+    pub fn new(capacity: usize) -> Self {
+        ProofCache {
+            capacity,
+            entries: Default::default(),
+        }
+    }
+
+    // The cached response for `indices` under `root`, or the one `respond`
+    // builds from the normalized indices. `respond` runs outside the lock,
+    // so concurrent misses on one key may each build it.
+    pub fn get_or_insert_with<F: FnOnce(&[usize]) -> Vec<u8>>(
+        &self,
+        root: &[u8; 32],
+        indices: &[usize],
+        respond: F,
+    ) -> Arc<Vec<u8>> {
+        let key = (*root, normalize(indices));
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let entries = &mut *entries;
+            if let Some((response, used)) = entries.map.get_mut(&key) {
+                entries.tick += 1;
+                let key = entries.recency.remove(used).unwrap();
+                *used = entries.tick;
+                entries.recency.insert(entries.tick, key);
+                entries.stats.hits += 1;
+                return response.clone();
+            }
+            entries.stats.misses += 1;
+        }
+        let response = Arc::new(respond(&key.1));
+        self.insert(key, response.clone());
+        response
+    }
+
+    fn insert(&self, key: Key, response: Arc<Vec<u8>>) {
+        if response.len() > self.capacity {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let entries = &mut *entries;
+        if entries.map.contains_key(&key) {
+            return;
+        }
+        while entries.stats.bytes + response.len() > self.capacity {
+            let (_, oldest) = entries.recency.pop_first().unwrap();
+            let (evicted, _) = entries.map.remove(&oldest).unwrap();
+            entries.stats.bytes -= evicted.len();
+            entries.stats.evictions += 1;
+        }
+        entries.tick += 1;
+        entries.stats.bytes += response.len();
+        entries.recency.insert(entries.tick, key.clone());
+        entries.map.insert(key, (response, entries.tick));
+        entries.stats.entries = entries.map.len();
+    }
+
+    // Drops every response for `root`, e.g. once the blob expires.
+    pub fn invalidate(&self, root: &[u8; 32]) {
+        let mut entries = self.entries.lock().unwrap();
+        let entries = &mut *entries;
+        let map = &mut entries.map;
+        let stats = &mut entries.stats;
+        entries.recency.retain(|_, key| {
+            if key.0 != *root {
+                return true;
+            }
+            stats.bytes -= map.remove(key).unwrap().0.len();
+            false
+        });
+        stats.entries = map.len();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.entries.lock().unwrap().stats
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use util::merkle_tree::Serialize;
+
+    use super::*;
+    use crate::{fixtures::random_prover, FridaConfig};
+
+    #[test]
+    fn cached_responses() {
+        let config = FridaConfig::new(b"cache", 2, 5, 1, 8);
+        let (_, prover) = random_prover(&config);
+        let root = prover.commit();
+        let respond = |leaves: &[usize]| {
+            let mut out = vec![];
+            for &leaf in leaves {
+                let (symbols, path) = prover.open_chunk(leaf);
+                out.extend(Serialize::serialize_fields(&symbols));
+                out.extend(path);
+            }
+            out
+        };
+        let leaf_bytes = respond(&[0]).len();
+        let cache = ProofCache::new(4 * leaf_bytes);
+
+        let first = cache.get_or_insert_with(&root, &[3, 1, 3], respond);
+        let second = cache.get_or_insert_with(&root, &[1, 3], |_| unreachable!());
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*first, respond(&[1, 3]));
+
+        // [1, 3] is touched after [5] goes in, so [7] evicts [5] and bringing
+        // [5] back evicts [6].
+        cache.get_or_insert_with(&root, &[5], respond);
+        cache.get_or_insert_with(&root, &[3, 1], |_| unreachable!());
+        cache.get_or_insert_with(&root, &[6], respond);
+        cache.get_or_insert_with(&root, &[7], respond);
+        cache.get_or_insert_with(&root, &[1, 3], |_| unreachable!());
+        cache.get_or_insert_with(&root, &[5], respond);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (3, 5));
+        assert_eq!(stats.evictions, 2);
+        assert!(stats.bytes <= 4 * leaf_bytes);
+        assert!((stats.hit_rate() - 3.0 / 8.0).abs() < 1e-9);
+
+        cache.get_or_insert_with(&root, &(0..16).collect::<Vec<_>>(), respond);
+        assert_eq!(cache.stats().entries, stats.entries);
+        cache.invalidate(&root);
+        assert_eq!((cache.stats().entries, cache.stats().bytes), (0, 0));
+    }
+}
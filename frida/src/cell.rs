@@ -0,0 +1,132 @@
+use ark_bn254::Fr;
+use ark_ff::{FftField, Field};
+
+use crate::{replay, Blake3Transcript, FridaConfig, IoppCommits, QueryResult, Verifier};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellMode {
+    // Only the first-oracle leaf is checked against the root.
+    Membership,
+    // The position is followed through every folding round down to the
+    // final value.
+    FullPath,
+}
+
+// Checks a single sampled cell without materializing any evaluation domain;
+// twiddles are computed on demand, so memory stays at a few field elements
+// per round.
+pub struct CellVerifier {
+    verifier: Verifier,
+    log_domain: usize,
+    rounds: usize,
+    iopp_commits: Option<(IoppCommits, (Fr, Vec<Fr>))>,
+}
+
+impl CellVerifier {
+    pub fn membership(config: &FridaConfig, merkle_root: [u8; 32]) -> Self {
+        CellVerifier {
+            verifier: Verifier::for_config(config, &merkle_root),
+            log_domain: config.log_degree + config.code_rate,
+            rounds: config.log_degree,
+            iopp_commits: None,
+        }
+    }
+
+    pub fn full_path(
+        config: &FridaConfig,
+        merkle_root: [u8; 32],
+        iopp_commits: IoppCommits,
+    ) -> Self {
+        let (challenges, _) = replay::<Blake3Transcript>(config, &merkle_root, &iopp_commits);
+        CellVerifier {
+            iopp_commits: Some((iopp_commits, challenges)),
+            ..Self::membership(config, merkle_root)
+        }
+    }
+
+    pub fn mode(&self) -> CellMode {
+        match self.iopp_commits {
+            None => CellMode::Membership,
+            Some(_) => CellMode::FullPath,
+        }
+    }
+
+    pub fn verify(&self, position: usize, cell: &[QueryResult]) -> bool {
+        let index = vec![position];
+        if position >= self.verifier.mt_verifier.leave_number || cell.is_empty() {
+            return false;
+        }
+        match &self.iopp_commits {
+            None => self.verifier.verify_positions(&index, &cell[0]),
+            Some((iopp_commits, challenges)) => {
+                if self.verifier.validate_rounds(self.rounds).is_err()
+                    || self
+                        .verifier
+                        .validate_queries(self.rounds, &index, iopp_commits, cell)
+                        .is_err()
+                {
+                    return false;
+                }
+                let omega_inv = (0..self.rounds)
+                    .map(|i| {
+                        Fr::get_root_of_unity(1 << (self.log_domain - i))
+                            .unwrap()
+                            .inverse()
+                            .unwrap()
+                    })
+                    .collect::<Vec<_>>();
+                self.verifier
+                    .check_rounds(
+                        |i, j| omega_inv[i].pow([j as u64]),
+                        challenges,
+                        &index,
+                        iopp_commits,
+                        cell,
+                    )
+                    .is_ok()
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_polies, ProveOptions, Prover};
+
+    #[test]
+    fn single_cell() {
+        let config = FridaConfig::new(b"cell", 2, 6, 2, 1);
+        let polies = random_polies(config.poly_num, 1 << config.log_degree);
+        let groups = config.groups();
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        let root = prover.commit();
+        // Rebuild the folded layers under the transcript's challenges so cells
+        // can be opened at positions the proof itself never queried.
+        let proof = prover.prove(&config);
+        let (challenges, _) = replay::<Blake3Transcript>(&config, &root, &proof.iopp_commits);
+        let (prover_state, iopp_commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        assert_eq!(iopp_commits.merkle_roots, proof.iopp_commits.merkle_roots);
+
+        let membership = CellVerifier::membership(&config, root);
+        let full = CellVerifier::full_path(&config, root, iopp_commits);
+        assert_eq!(full.mode(), CellMode::FullPath);
+        for position in [0, 9, config.leave_number() - 1] {
+            let cell = prover.open_cell(&prover_state, position);
+            assert_eq!(cell.len(), config.log_degree);
+            assert!(membership.verify(position, &cell[..1]));
+            assert!(full.verify(position, &cell));
+            assert!(!full.verify(position ^ 1, &cell));
+            assert!(!full.verify(position, &cell[..2]));
+
+            let mut bad = cell.clone();
+            let v = bad[3].values.values_mut().next().unwrap();
+            *v += Fr::from(1);
+            assert!(membership.verify(position, &bad[..1]));
+            assert!(!full.verify(position, &bad));
+        }
+        assert!(!membership.verify(config.leave_number(), &prover.open_cell(&prover_state, 0)));
+    }
+}
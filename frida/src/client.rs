@@ -0,0 +1,366 @@
+// A light client checking that a committed blob is available. Its leaves are
+// split into batches, each asked of the best-scoring peer not yet tried for
+// it, and retried elsewhere on a refusal, a timeout or a response that fails
+// verification, up to `max_attempts` peers. Peers are scored by how often
+// they failed, invalid responses weighing most, and ties go to the least
+// used, which spreads batches across healthy peers. A `Scoreboard` carries
+// reputation across sessions: banned peers are never asked and lower scores
+// come later among peers with the same record this session. The blob is
+// available once every leaf has a verified response.
+use std::{
+    cmp::Reverse,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "prover")]
+use crate::service::ProofService;
+use crate::{
+    cache::normalize,
+    scoring::{Offense, Scoreboard},
+    service::{PeerId, SampleResponse},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchError {
+    Timeout,
+    Refused,
+}
+
+pub trait SamplePeer {
+    // Stable across sessions, as scores are kept by it.
+    fn id(&self) -> PeerId;
+
+    fn fetch(
+        &self,
+        root: &[u8; 32],
+        leaves: &[usize],
+        timeout: Duration,
+    ) -> Result<SampleResponse, FetchError>;
+}
+
+// A `ProofService` in the same process, asked as peer `id`.
+#[cfg(feature = "prover")]
+pub struct LocalPeer<'a> {
+    pub service: &'a ProofService,
+    pub id: PeerId,
+}
+
+#[cfg(feature = "prover")]
+impl SamplePeer for LocalPeer<'_> {
+    fn id(&self) -> PeerId {
+        self.id
+    }
+
+    fn fetch(
+        &self,
+        root: &[u8; 32],
+        leaves: &[usize],
+        timeout: Duration,
+    ) -> Result<SampleResponse, FetchError> {
+        let handle = self
+            .service
+            .submit(self.id, root, leaves)
+            .map_err(|_| FetchError::Refused)?;
+        match handle.wait_timeout(timeout) {
+            Some(outcome) => outcome.map_err(|_| FetchError::Refused),
+            None => Err(FetchError::Timeout),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientConfig {
+    pub timeout: Duration,
+    pub max_attempts: usize,
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerStats {
+    pub requests: usize,
+    pub verified: usize,
+    pub refused: usize,
+    pub timeouts: usize,
+    pub invalid: usize,
+    pub latency: Duration,
+}
+
+impl PeerStats {
+    fn penalty(&self) -> usize {
+        self.refused + self.timeouts + 4 * self.invalid
+    }
+
+    pub fn mean_latency(&self) -> Duration {
+        self.latency / self.requests.max(1) as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Available,
+    Unavailable { missing: Vec<usize> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamplingReport {
+    pub verdict: Verdict,
+    pub verified: Vec<usize>,
+    pub attempts: usize,
+}
+
+pub struct SamplingClient<P> {
+    root: [u8; 32],
+    leave_number: usize,
+    peers: Vec<P>,
+    stats: Vec<PeerStats>,
+    scoreboard: Scoreboard,
+    config: ClientConfig,
+}
+
+impl<P: SamplePeer> SamplingClient<P> {
+    pub fn new(root: [u8; 32], leave_number: usize, peers: Vec<P>, config: ClientConfig) -> Self {
+        assert!(!peers.is_empty() && config.max_attempts > 0 && config.batch_size > 0);
+        SamplingClient {
+            root,
+            leave_number,
+            stats: vec![PeerStats::default(); peers.len()],
+            peers,
+            scoreboard: Scoreboard::default(),
+            config,
+        }
+    }
+
+    pub fn with_scoreboard(mut self, scoreboard: Scoreboard) -> Self {
+        self.scoreboard = scoreboard;
+        self
+    }
+
+    pub fn scoreboard(&self) -> &Scoreboard {
+        &self.scoreboard
+    }
+
+    pub fn into_scoreboard(self) -> Scoreboard {
+        self.scoreboard
+    }
+
+    pub fn stats(&self) -> &[PeerStats] {
+        &self.stats
+    }
+
+    fn pick(&self, tried: &[usize]) -> Option<usize> {
+        (0..self.peers.len())
+            .filter(|x| !tried.contains(x) && !self.scoreboard.banned(self.peers[*x].id()))
+            .min_by_key(|&x| {
+                (
+                    self.stats[x].penalty(),
+                    Reverse(self.scoreboard.score(self.peers[x].id())),
+                    self.stats[x].requests,
+                )
+            })
+    }
+
+    pub fn sample(&mut self, leaves: &[usize]) -> SamplingReport {
+        let leaves = normalize(leaves);
+        let mut verified = vec![];
+        let mut missing = leaves
+            .iter()
+            .copied()
+            .filter(|x| *x >= self.leave_number)
+            .collect::<Vec<_>>();
+        let mut attempts = 0;
+        let wanted = leaves
+            .into_iter()
+            .filter(|x| *x < self.leave_number)
+            .collect::<Vec<_>>();
+        for batch in wanted.chunks(self.config.batch_size) {
+            let mut tried = vec![];
+            let done = loop {
+                if tried.len() == self.config.max_attempts {
+                    break false;
+                }
+                let Some(peer) = self.pick(&tried) else {
+                    break false;
+                };
+                tried.push(peer);
+                attempts += 1;
+                let start = Instant::now();
+                let outcome = self.peers[peer].fetch(&self.root, batch, self.config.timeout);
+                let elapsed = start.elapsed();
+                let id = self.peers[peer].id();
+                let stats = &mut self.stats[peer];
+                stats.requests += 1;
+                stats.latency += elapsed;
+                match outcome {
+                    _ if elapsed > self.config.timeout => {
+                        stats.timeouts += 1;
+                        self.scoreboard.record_timeout(id);
+                    }
+                    Err(FetchError::Timeout) => {
+                        stats.timeouts += 1;
+                        self.scoreboard.record_timeout(id);
+                    }
+                    Err(FetchError::Refused) => {
+                        stats.refused += 1;
+                        self.scoreboard.record_refused(id);
+                    }
+                    Ok(response)
+                        if response.leaves == batch
+                            && response.verify(&self.root, self.leave_number) =>
+                    {
+                        stats.verified += batch.len();
+                        self.scoreboard.record_served(id, elapsed);
+                        break true;
+                    }
+                    Ok(response) => {
+                        stats.invalid += 1;
+                        self.scoreboard.record_invalid(
+                            id,
+                            Offense {
+                                root: self.root,
+                                leave_number: self.leave_number,
+                                requested: batch.to_vec(),
+                                response,
+                            },
+                        );
+                    }
+                }
+            };
+            if done {
+                verified.extend_from_slice(batch);
+            } else {
+                missing.extend_from_slice(batch);
+            }
+        }
+        missing.sort_unstable();
+        SamplingReport {
+            verdict: if missing.is_empty() {
+                Verdict::Available
+            } else {
+                Verdict::Unavailable { missing }
+            },
+            verified,
+            attempts,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use std::sync::Arc;
+
+    use ark_bn254::Fr;
+
+    use super::*;
+    use crate::{fixtures::random_prover, service::ServiceConfig, FridaConfig, Prover};
+
+    enum Mock<'a> {
+        Honest(&'a Prover),
+        Dead,
+        Liar(&'a Prover),
+        Slow(&'a Prover),
+    }
+
+    impl SamplePeer for Mock<'_> {
+        fn id(&self) -> PeerId {
+            match self {
+                Mock::Honest(_) => 3,
+                Mock::Dead => 0,
+                Mock::Liar(_) => 1,
+                Mock::Slow(_) => 2,
+            }
+        }
+
+        fn fetch(
+            &self,
+            _: &[u8; 32],
+            leaves: &[usize],
+            timeout: Duration,
+        ) -> Result<SampleResponse, FetchError> {
+            match self {
+                Mock::Honest(prover) => Ok(prover.open_leaves(leaves).unwrap()),
+                Mock::Dead => Err(FetchError::Refused),
+                Mock::Liar(prover) => {
+                    let mut response = prover.open_leaves(leaves).unwrap();
+                    response.symbols[0][0] += Fr::from(1u64);
+                    Ok(response)
+                }
+                Mock::Slow(prover) => {
+                    std::thread::sleep(timeout * 2);
+                    Ok(prover.open_leaves(leaves).unwrap())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn retries_and_verdicts() {
+        let config = FridaConfig::new(b"client", 2, 5, 1, 8);
+        let (_, prover) = random_prover(&config);
+        let (root, leave_number) = (prover.commit(), config.leave_number());
+        let client_config = ClientConfig {
+            timeout: Duration::from_millis(20),
+            max_attempts: 4,
+            batch_size: 3,
+        };
+        let peers = vec![
+            Mock::Dead,
+            Mock::Liar(&prover),
+            Mock::Slow(&prover),
+            Mock::Honest(&prover),
+        ];
+        let mut client = SamplingClient::new(root, leave_number, peers, client_config);
+        let leaves = (0..30).step_by(2).collect::<Vec<_>>();
+        let report = client.sample(&leaves);
+        assert_eq!(report.verdict, Verdict::Available);
+        assert_eq!(report.verified, leaves);
+        // The first batch tries every faulty peer once; afterwards the honest
+        // one scores best and answers the rest first time.
+        assert_eq!(report.attempts, 4 + 4);
+        let stats = client.stats();
+        assert_eq!(
+            (stats[0].refused, stats[1].invalid, stats[2].timeouts),
+            (1, 1, 1)
+        );
+        assert_eq!(stats[3].verified, leaves.len());
+
+        // The next session starts from these scores: the liar is banned and
+        // the honest peer, scoring highest, answers every batch first time.
+        let scoreboard = client.into_scoreboard();
+        assert!(scoreboard.banned(1) && scoreboard.get(1).unwrap().offense.is_some());
+        let peers = vec![
+            Mock::Dead,
+            Mock::Liar(&prover),
+            Mock::Slow(&prover),
+            Mock::Honest(&prover),
+        ];
+        let mut client = SamplingClient::new(root, leave_number, peers, client_config)
+            .with_scoreboard(scoreboard);
+        assert_eq!(client.sample(&leaves).attempts, 5);
+        assert_eq!(client.stats()[1].requests, 0);
+
+        let peers = vec![Mock::Dead, Mock::Liar(&prover)];
+        let mut client = SamplingClient::new(root, leave_number, peers, client_config);
+        let report = client.sample(&[1, 2, leave_number]);
+        assert_eq!(
+            report.verdict,
+            Verdict::Unavailable {
+                missing: vec![1, 2, leave_number]
+            }
+        );
+
+        let service = ProofService::new(ServiceConfig {
+            workers: 2,
+            queue_capacity: 16,
+            per_peer_capacity: 4,
+        });
+        service.register(Arc::new(prover));
+        let peers = (0..2)
+            .map(|id| LocalPeer {
+                service: &service,
+                id,
+            })
+            .collect();
+        let mut client = SamplingClient::new(root, leave_number, peers, client_config);
+        assert_eq!(client.sample(&leaves).verdict, Verdict::Available);
+    }
+}
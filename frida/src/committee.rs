@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::{
+    Blake3Transcript, FailedCheck, FridaConfig, IoppCommits, QueryResult, VerificationReport,
+    Verifier,
+};
+
+// Query slots handed to each of `members`, round-robin.
+pub fn partition(query_num: usize, members: usize) -> Vec<Vec<usize>> {
+    assert!(members > 0);
+    (0..members)
+        .map(|m| (m..query_num).step_by(members).collect())
+        .collect()
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubProof {
+    pub member: usize,
+    pub slots: Vec<usize>,
+    pub query_results: Vec<QueryResult>,
+}
+
+// A proof whose queries are split across a committee. Every member receives
+// the shared commits and its own part.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionedProof {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
+    pub merkle_root: [u8; 32],
+    pub iopp_commits: IoppCommits,
+    pub parts: Vec<SubProof>,
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    pub fn prove_partitioned(&self, config: &FridaConfig, members: usize) -> PartitionedProof {
+        let (prover_state, proof) = self.prove_with_state::<Blake3Transcript>(config);
+        let (_, leaf_indices) =
+            crate::replay::<Blake3Transcript>(config, &proof.merkle_root, &proof.iopp_commits);
+        let parts = partition(config.query_num, members)
+            .into_iter()
+            .enumerate()
+            .map(|(member, slots)| {
+                let positions = slots.iter().map(|&x| leaf_indices[x]).collect::<Vec<_>>();
+                SubProof {
+                    member,
+                    query_results: self.query_positions(&prover_state, &positions),
+                    slots,
+                }
+            })
+            .collect();
+        PartitionedProof {
+            merkle_root: proof.merkle_root,
+            iopp_commits: proof.iopp_commits,
+            parts,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberVerdict {
+    pub member: usize,
+    pub slots: Vec<usize>,
+    // Identifies the commits the member checked against, so the combiner can
+    // tell whether everyone saw the same proof.
+    pub commits_digest: [u8; 32],
+    pub report: VerificationReport,
+}
+
+pub fn verify_part(
+    config: &FridaConfig,
+    commitment: &[u8; 32],
+    merkle_root: &[u8; 32],
+    iopp_commits: &IoppCommits,
+    part: &SubProof,
+) -> MemberVerdict {
+    MemberVerdict {
+        member: part.member,
+        slots: part.slots.clone(),
+        commits_digest: iopp_commits.digest(),
+        report: Verifier::verify_slots::<Blake3Transcript, _>(
+            config,
+            commitment,
+            merkle_root,
+            iopp_commits,
+            &part.slots,
+            &part.query_results,
+        ),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JointVerdict {
+    pub missing_slots: Vec<usize>,
+    pub duplicated_slots: Vec<usize>,
+    pub inconsistent_members: Vec<usize>,
+    pub failures: Vec<(usize, FailedCheck)>,
+}
+
+impl JointVerdict {
+    pub fn accepted(&self) -> bool {
+        self.missing_slots.is_empty()
+            && self.duplicated_slots.is_empty()
+            && self.inconsistent_members.is_empty()
+            && self.failures.is_empty()
+    }
+}
+
+// Accepts only if the accepted members together cover every query slot
+// exactly once and all checked the same commits.
+pub fn combine(config: &FridaConfig, verdicts: &[MemberVerdict]) -> JointVerdict {
+    let mut coverage = BTreeMap::new();
+    for verdict in verdicts.iter() {
+        for &slot in verdict.slots.iter() {
+            *coverage.entry(slot).or_insert(0usize) += 1;
+        }
+    }
+    let reference = verdicts.first().map(|x| x.commits_digest);
+    JointVerdict {
+        missing_slots: (0..config.query_num)
+            .filter(|x| !coverage.contains_key(x))
+            .collect(),
+        duplicated_slots: coverage.iter().filter(|x| *x.1 > 1).map(|x| *x.0).collect(),
+        inconsistent_members: verdicts
+            .iter()
+            .filter(|x| Some(x.commits_digest) != reference)
+            .map(|x| x.member)
+            .collect(),
+        failures: verdicts
+            .iter()
+            .filter_map(|x| x.report.failure.clone().map(|e| (x.member, e)))
+            .collect(),
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::random_prover;
+
+    #[test]
+    fn committee() {
+        let config = FridaConfig::new(b"committee", 2, 6, 1, 10);
+        let (_, prover) = random_prover(&config);
+        let commitment = config.commitment(&prover.commit());
+        let proof = prover.prove_partitioned(&config, 3);
+        assert_eq!(partition(10, 3)[2], vec![2, 5, 8]);
+
+        let verdicts = proof
+            .parts
+            .iter()
+            .map(|part| {
+                verify_part(
+                    &config,
+                    &commitment,
+                    &proof.merkle_root,
+                    &proof.iopp_commits,
+                    part,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(verdicts.iter().all(|x| x.report.accepted()));
+        assert!(combine(&config, &verdicts).accepted());
+
+        let joint = combine(&config, &verdicts[1..]);
+        assert_eq!(joint.missing_slots, vec![0, 3, 6, 9]);
+        assert!(!joint.accepted());
+
+        let mut twice = verdicts.clone();
+        twice.push(verdicts[0].clone());
+        assert_eq!(combine(&config, &twice).duplicated_slots, vec![0, 3, 6, 9]);
+
+        // A member handed another member's answers rejects its part.
+        let mut swapped = proof.parts[0].clone();
+        swapped.query_results = proof.parts[1].query_results.clone();
+        let mut bad = verdicts.clone();
+        bad[0] = verify_part(
+            &config,
+            &commitment,
+            &proof.merkle_root,
+            &proof.iopp_commits,
+            &swapped,
+        );
+        let joint = combine(&config, &bad);
+        assert_eq!(joint.failures.len(), 1);
+        assert_eq!(joint.failures[0].0, 0);
+
+        let mut forked = verdicts.clone();
+        forked[2].commits_digest = [0; 32];
+        assert_eq!(combine(&config, &forked).inconsistent_members, vec![2]);
+    }
+}
@@ -0,0 +1,212 @@
+// Complaints against a dealer. The dealer signs every package it hands out
+// under the dealing's commitment; a node whose package fails
+// `verify_my_share` publishes it with the signature and the failing check,
+// and anyone holding the public transcript and the dealer's key reruns the
+// check. The signature binds the package to its node index, so a node
+// showing another's package frames nobody. A complaint that holds
+// disqualifies the dealer in the DKG's complaint round; one that does not
+// only shows the complainer lying.
+use std::fmt;
+
+use crate::{
+    dealer::{DealingTranscript, NodePackage},
+    share::{verify_my_share, ShareError},
+    slashing::SignatureScheme,
+    vss::SharingConfig,
+};
+
+const PACKAGE_DOMAIN: &[u8] = b"frida-dealt-package";
+
+// What the dealer signs for each package.
+pub fn package_message<K>(commitment: &[u8; 32], package: &NodePackage<K>) -> Vec<u8> {
+    let mut message = PACKAGE_DOMAIN.to_vec();
+    message.extend(commitment);
+    message.extend(package.to_bytes());
+    message
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Complaint<K, S: SignatureScheme> {
+    pub package: NodePackage<K>,
+    pub signature: S::Signature,
+    pub claim: ShareError,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComplaintError {
+    BadSignature,
+    // The key is not signed, so it must be the transcript's for the index.
+    Readdressed,
+    // The package verifies; the complaint is false.
+    NoFault,
+    ClaimMismatch { actual: ShareError },
+}
+
+impl fmt::Display for ComplaintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComplaintError::BadSignature => write!(f, "package is not signed by the dealer"),
+            ComplaintError::Readdressed => write!(f, "package names another key"),
+            ComplaintError::NoFault => write!(f, "package verifies"),
+            ComplaintError::ClaimMismatch { actual } => {
+                write!(f, "package fails otherwise than claimed: {}", actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ComplaintError {}
+
+impl<K: PartialEq, S: SignatureScheme> Complaint<K, S> {
+    // None if the package verifies under the agreed `expected` sharing and
+    // `threshold`. The signature is not checked here.
+    pub fn build(
+        transcript: &DealingTranscript<K>,
+        expected: &SharingConfig,
+        threshold: usize,
+        package: NodePackage<K>,
+        signature: S::Signature,
+    ) -> Option<Self> {
+        let claim =
+            verify_my_share(&package, transcript, expected, threshold, package.index).err()?;
+        Some(Complaint {
+            package,
+            signature,
+            claim,
+        })
+    }
+}
+
+// The failing check `complaint` proves against the dealer holding `dealer`.
+pub fn verify_complaint<K: PartialEq, S: SignatureScheme>(
+    transcript: &DealingTranscript<K>,
+    expected: &SharingConfig,
+    threshold: usize,
+    dealer: &S::PublicKey,
+    complaint: &Complaint<K, S>,
+) -> Result<ShareError, ComplaintError> {
+    let message = package_message(&transcript.commitment, &complaint.package);
+    if !S::verify(dealer, &message, &complaint.signature) {
+        return Err(ComplaintError::BadSignature);
+    }
+    let package = &complaint.package;
+    if transcript
+        .nodes
+        .get(package.index)
+        .is_some_and(|x| *x != package.node)
+    {
+        return Err(ComplaintError::Readdressed);
+    }
+    match verify_my_share(package, transcript, expected, threshold, package.index) {
+        Ok(_) => Err(ComplaintError::NoFault),
+        Err(actual) if actual != complaint.claim => Err(ComplaintError::ClaimMismatch { actual }),
+        Err(actual) => Ok(actual),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{dealer::Dealer, session::Session, SoundnessRegime};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Keyed;
+
+    impl SignatureScheme for Keyed {
+        type PublicKey = [u8; 32];
+        type Signature = [u8; 32];
+
+        fn verify(key: &[u8; 32], message: &[u8], signature: &[u8; 32]) -> bool {
+            blake3::keyed_hash(key, message).as_bytes() == signature
+        }
+    }
+
+    #[test]
+    fn complaint_round() {
+        let mut rng = thread_rng();
+        let dealer = Dealer {
+            context: b"complaint".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let dealing = dealer
+            .deal(
+                &[Fr::rand(&mut rng)],
+                &(0..8u64).collect::<Vec<_>>(),
+                4,
+                &mut rng,
+            )
+            .unwrap();
+        let transcript = &dealing.transcript;
+        let expected = dealer.sharing(1, 8, 4).unwrap();
+        let key = [9u8; 32];
+        let sign = |package: &NodePackage<u64>| {
+            *blake3::keyed_hash(&key, &package_message(&transcript.commitment, package)).as_bytes()
+        };
+
+        let honest = dealing.packages[2].clone();
+        assert!(Complaint::<u64, Keyed>::build(
+            transcript,
+            &expected,
+            4,
+            honest.clone(),
+            sign(&honest)
+        )
+        .is_none());
+        let framed = Complaint::<u64, Keyed> {
+            signature: sign(&honest),
+            package: honest,
+            claim: ShareError::PathMismatch,
+        };
+        assert_eq!(
+            verify_complaint(transcript, &expected, 4, &key, &framed),
+            Err(ComplaintError::NoFault)
+        );
+
+        // A dealer signing a share off its root is caught by anyone.
+        let mut bad = dealing.packages[6].clone();
+        bad.symbols[1] += Fr::from(1u64);
+        let complaint =
+            Complaint::<u64, Keyed>::build(transcript, &expected, 4, bad.clone(), sign(&bad))
+                .unwrap();
+        assert_eq!(
+            verify_complaint(transcript, &expected, 4, &key, &complaint),
+            Ok(ShareError::PathMismatch)
+        );
+        assert_eq!(
+            verify_complaint(transcript, &expected, 4, &[1u8; 32], &complaint),
+            Err(ComplaintError::BadSignature)
+        );
+        let mut misclaimed = complaint;
+        misclaimed.claim = ShareError::WrongNode;
+        assert_eq!(
+            verify_complaint(transcript, &expected, 4, &key, &misclaimed),
+            Err(ComplaintError::ClaimMismatch {
+                actual: ShareError::PathMismatch
+            })
+        );
+
+        let mut readdressed = misclaimed;
+        readdressed.package.node = 7;
+        assert_eq!(
+            verify_complaint(transcript, &expected, 4, &key, &readdressed),
+            Err(ComplaintError::Readdressed)
+        );
+
+        // Node 5 showing the package node 4 got cannot frame the dealer.
+        let swapped = dealing.packages[4].clone();
+        assert!(Complaint::<u64, Keyed>::build(
+            transcript,
+            &expected,
+            4,
+            swapped.clone(),
+            sign(&swapped)
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,103 @@
+// Light-client probability math. While the data is unrecoverable fewer than
+// leave_number >> code_rate leaves are served, so a uniformly sampled leaf is
+// answered with probability below 2^-code_rate; confidence is one minus the
+// chance that every successful sample still landed on a served leaf.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdversaryModel {
+    // Independent samples, possibly repeating a leaf.
+    WithReplacement,
+    // Distinct leaves out of `leave_number`, which tightens the bound for
+    // large sample counts.
+    WithoutReplacement { leave_number: usize },
+}
+
+// Probability that `samples` successful samples are all answered although the
+// data is unrecoverable.
+fn false_accept(samples: usize, code_rate: usize, model: AdversaryModel) -> f64 {
+    match model {
+        AdversaryModel::WithReplacement => 0.5_f64.powi((samples * code_rate) as i32),
+        AdversaryModel::WithoutReplacement { leave_number } => {
+            let served = (leave_number >> code_rate).saturating_sub(1);
+            if samples > served {
+                return 0.0;
+            }
+            (0..samples).fold(1.0, |acc, i| {
+                acc * (served - i) as f64 / (leave_number - i) as f64
+            })
+        }
+    }
+}
+
+// A failed sample is a withheld or invalid leaf, so it drops confidence to
+// zero; clients that retry transient failures should report them as ok.
+pub fn confidence(
+    samples_ok: usize,
+    samples_total: usize,
+    code_rate: usize,
+    model: AdversaryModel,
+) -> f64 {
+    assert!(samples_ok <= samples_total);
+    if samples_ok < samples_total {
+        return 0.0;
+    }
+    1.0 - false_accept(samples_ok, code_rate, model)
+}
+
+// Fewest samples reaching `target` confidence, or None if no count does.
+pub fn samples_needed(target: f64, code_rate: usize, model: AdversaryModel) -> Option<usize> {
+    if !(0.0..1.0).contains(&target) {
+        return None;
+    }
+    samples_for_failure(1.0 - target, code_rate, model)
+}
+
+// Same inverse phrased as a failure probability, which keeps precision for
+// targets too close to one to represent as a confidence.
+pub(crate) fn samples_for_failure(
+    failure: f64,
+    code_rate: usize,
+    model: AdversaryModel,
+) -> Option<usize> {
+    match model {
+        AdversaryModel::WithReplacement => {
+            let n = (failure.ln() / 0.5_f64.powi(code_rate as i32).ln()).ceil() as usize;
+            // Nudge past rounding in the logarithms.
+            (n.saturating_sub(1)..=n + 1).find(|&n| false_accept(n, code_rate, model) <= failure)
+        }
+        AdversaryModel::WithoutReplacement { leave_number } => {
+            (0..=leave_number).find(|&n| false_accept(n, code_rate, model) <= failure)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confidence_bounds() {
+        let model = AdversaryModel::WithReplacement;
+        assert_eq!(confidence(10, 10, 1, model), 1.0 - 0.5_f64.powi(10));
+        assert_eq!(confidence(9, 10, 1, model), 0.0);
+        assert_eq!(confidence(0, 0, 2, model), 0.0);
+        assert_eq!(samples_needed(1.0 - 1e-9, 1, model), Some(30));
+        assert_eq!(samples_needed(1.0, 1, model), None);
+        for target in [0.5, 0.9, 0.999, 1.0 - 1e-12] {
+            for code_rate in 1..4 {
+                let n = samples_needed(target, code_rate, model).unwrap();
+                assert!(confidence(n, n, code_rate, model) >= target);
+                assert!(n == 0 || confidence(n - 1, n - 1, code_rate, model) < target);
+            }
+        }
+
+        let model = AdversaryModel::WithoutReplacement { leave_number: 64 };
+        for n in 1..40 {
+            assert!(
+                confidence(n, n, 1, model) >= confidence(n, n, 1, AdversaryModel::WithReplacement)
+            );
+        }
+        assert_eq!(confidence(32, 32, 1, model), 1.0);
+        let n = samples_needed(0.999, 1, model).unwrap();
+        assert!(n < samples_needed(0.999, 1, AdversaryModel::WithReplacement).unwrap());
+    }
+}
@@ -0,0 +1,251 @@
+use util::mul_group::Radix2Group;
+
+use crate::{
+    transcript::{Blake3Transcript, Transcript},
+    Batching,
+};
+
+const PARAMS_VERSION: u8 = 5;
+pub const HASH_ID: &[u8] = b"blake3-256";
+
+// Parameters shared by prover and verifier. `context` names the deployment
+// (chain id, blob namespace, protocol version) and is absorbed before anything
+// else, so proofs never transfer between deployments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FridaConfig {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
+    pub context: Vec<u8>,
+    pub poly_num: usize,
+    pub log_degree: usize,
+    pub code_rate: usize,
+    pub query_num: usize,
+    pub regime: SoundnessRegime,
+    pub batching: Batching,
+    pub interleave: Interleave,
+    // Values per leaf of the folded oracles, a power of two from 2; a leaf of
+    // 2^k holds a coset of 2^k points, so one path covers what k rounds of
+    // pairs would open separately.
+    pub folded_leaf_size: usize,
+}
+
+// How many polynomials share a first-oracle leaf. `Full` puts every one in
+// each leaf, so a leaf is one position of the whole blob; `Rows(r)` splits
+// the polynomials into poly_num / r groups with a leaf per group and
+// position, so leaves are smaller and sampled separately, and a query opens
+// one leaf of each group under a longer path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interleave {
+    #[default]
+    Full,
+    Rows(usize),
+}
+
+// Which analysis query counts are sized by. A query misses a word far from
+// the code with probability sqrt(rate) up to the Johnson bound, which is
+// proven, or rate up to list-decoding capacity, which is only conjectured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SoundnessRegime {
+    Proven,
+    Conjectured,
+}
+
+impl SoundnessRegime {
+    pub fn query_error(self, code_rate: usize) -> f64 {
+        let rate = 0.5_f64.powi(code_rate as i32);
+        match self {
+            SoundnessRegime::Proven => rate.sqrt(),
+            SoundnessRegime::Conjectured => rate,
+        }
+    }
+
+    fn id(self) -> u8 {
+        match self {
+            SoundnessRegime::Proven => 0,
+            SoundnessRegime::Conjectured => 1,
+        }
+    }
+}
+
+impl FridaConfig {
+    // The proven regime, powers batching, every polynomial in each leaf and
+    // pairs in the folded oracles; set anything else by struct update.
+    pub fn new(
+        context: &[u8],
+        poly_num: usize,
+        log_degree: usize,
+        code_rate: usize,
+        query_num: usize,
+    ) -> Self {
+        FridaConfig {
+            context: context.to_vec(),
+            poly_num,
+            log_degree,
+            code_rate,
+            query_num,
+            regime: SoundnessRegime::Proven,
+            batching: Batching::Powers,
+            interleave: Interleave::Full,
+            folded_leaf_size: 2,
+        }
+    }
+
+    pub fn domain_size(&self) -> usize {
+        1 << (self.log_degree + self.code_rate)
+    }
+
+    // Positions of the first oracle; each has `leaf_groups` leaves.
+    pub fn leave_number(&self) -> usize {
+        self.domain_size() / 2
+    }
+
+    pub fn leaf_rows(&self) -> usize {
+        match self.interleave {
+            Interleave::Full => self.poly_num,
+            Interleave::Rows(rows) => rows,
+        }
+    }
+
+    pub fn leaf_groups(&self) -> usize {
+        self.poly_num / self.leaf_rows()
+    }
+
+    // Leaves of the first oracle's tree.
+    pub fn first_oracle_leaves(&self) -> usize {
+        self.leave_number() * self.leaf_groups()
+    }
+
+    // Values per leaf of round `round`'s oracle, the first one included. A
+    // folded oracle smaller than `folded_leaf_size` is a single leaf.
+    pub fn leaf_size(&self, round: usize) -> usize {
+        match round {
+            0 => self.leaf_rows() * 2,
+            _ => self.folded_leaf_size.min(self.domain_size() >> round),
+        }
+    }
+
+    pub fn groups(&self) -> Vec<Radix2Group> {
+        (0..self.log_degree)
+            .rev()
+            .map(|x| Radix2Group::new(x + 1 + self.code_rate))
+            .collect()
+    }
+
+    // Canonical encoding of everything that determines how the committed
+    // codeword was built; a root means nothing without it.
+    pub fn params_header(&self) -> Vec<u8> {
+        let mut bytes = vec![PARAMS_VERSION];
+        bytes.extend((HASH_ID.len() as u64).to_le_bytes());
+        bytes.extend(HASH_ID);
+        for x in [
+            self.poly_num,
+            self.log_degree,
+            self.code_rate,
+            self.leaf_rows(),
+            self.folded_leaf_size,
+        ] {
+            bytes.extend((x as u64).to_le_bytes());
+        }
+        // Bound so a verifier holding a proven-regime config rejects
+        // commitments made under the conjecture.
+        bytes.push(self.regime.id());
+        bytes.push(self.batching.id());
+        bytes
+    }
+
+    pub fn params_hash(&self) -> [u8; 32] {
+        *blake3::hash(&self.params_header()).as_bytes()
+    }
+
+    // Bits of soundness from `queries` queries under the config's regime.
+    pub fn soundness_bits(&self, queries: usize) -> f64 {
+        -(queries as f64) * self.regime.query_error(self.code_rate).log2()
+    }
+
+    pub fn transcript(&self) -> Blake3Transcript {
+        self.transcript_with()
+    }
+
+    pub fn transcript_with<T: Transcript>(&self) -> T {
+        let mut transcript = T::new(&self.context);
+        transcript.absorb(b"params", &self.params_header());
+        transcript
+    }
+
+    // The published commitment: the first-oracle root bound to the context and
+    // encoding parameters.
+    pub fn commitment(&self, merkle_root: &[u8; 32]) -> [u8; 32] {
+        self.commitment_with::<Blake3Transcript>(merkle_root)
+    }
+
+    pub fn commitment_with<T: Transcript>(&self, merkle_root: &[u8; 32]) -> [u8; 32] {
+        let mut transcript = self.transcript_with::<T>();
+        transcript.absorb(b"merkle_root", merkle_root);
+        transcript.challenge_bytes(b"commitment")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_binding() {
+        let config = FridaConfig::new(b"test", 4, 8, 1, 20);
+        let root = [3u8; 32];
+        let commitment = config.commitment(&root);
+        for other in [
+            FridaConfig {
+                poly_num: 8,
+                log_degree: 7,
+                ..config.clone()
+            },
+            FridaConfig {
+                code_rate: 2,
+                ..config.clone()
+            },
+            FridaConfig {
+                regime: SoundnessRegime::Conjectured,
+                ..config.clone()
+            },
+            FridaConfig {
+                batching: Batching::Independent,
+                ..config.clone()
+            },
+            FridaConfig {
+                interleave: Interleave::Rows(2),
+                ..config.clone()
+            },
+            FridaConfig {
+                folded_leaf_size: 8,
+                ..config.clone()
+            },
+        ] {
+            assert_ne!(other.params_hash(), config.params_hash());
+            assert_ne!(other.commitment(&root), commitment);
+        }
+        let more_queries = FridaConfig {
+            query_num: 40,
+            ..config.clone()
+        };
+        assert_eq!(more_queries.commitment(&root), commitment);
+        let rows = FridaConfig {
+            interleave: Interleave::Rows(4),
+            ..config.clone()
+        };
+        assert_eq!(rows.commitment(&root), commitment);
+        assert_eq!(
+            (0..4).map(|i| config.leaf_size(i)).collect::<Vec<_>>(),
+            [8, 2, 2, 2]
+        );
+
+        let conjectured = FridaConfig {
+            regime: SoundnessRegime::Conjectured,
+            ..config.clone()
+        };
+        assert!((config.soundness_bits(20) - 10.0).abs() < 1e-9);
+        assert!((conjectured.soundness_bits(20) - 20.0).abs() < 1e-9);
+    }
+}
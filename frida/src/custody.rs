@@ -0,0 +1,301 @@
+// Proof of custody over the leaves a storage node holds. Each epoch the node
+// derives a key from its long-term secret, tags every assigned leaf with it
+// and publishes the hash of the tags with a commitment to the key. Only the
+// holder of the secret can compute the tags, and only from the leaves
+// themselves, so a node that dropped its data has to fetch all of it back
+// before every claim. A challenge names a few of the assigned leaves; the
+// node answers with the key, every tag and those leaves with their paths,
+// and anyone holding the config can check the answer. A revealed key opens
+// only the claim it was derived for.
+use std::fmt;
+
+use ark_bn254::Fr;
+use util::merkle_tree::Serialize;
+
+use crate::{scheme::FridaShare, FridaConfig, Verifier};
+
+const KEY_DOMAIN: &[u8] = b"frida-custody-key";
+const TAGS_DOMAIN: &[u8] = b"frida-custody-tags";
+const CHALLENGE_DOMAIN: &[u8] = b"frida-custody-challenge";
+
+pub struct CustodySecret([u8; 32]);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustodyClaim {
+    pub epoch: u64,
+    pub commitment: [u8; 32],
+    pub key_commitment: [u8; 32],
+    pub value: [u8; 32],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustodyChallenge {
+    pub epoch: u64,
+    pub commitment: [u8; 32],
+    // Ascending and distinct, among the node's assigned leaves.
+    pub leaves: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CustodyResponse {
+    pub key: [u8; 32],
+    pub root: [u8; 32],
+    // One per assigned leaf, in leaf order.
+    pub tags: Vec<[u8; 32]>,
+    pub opened: Vec<FridaShare>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustodyError {
+    WrongClaim,
+    WrongKey,
+    WrongRoot,
+    TagCount { assigned: usize, tags: usize },
+    ValueMismatch,
+    WrongLeaves,
+    // The leaf does not verify against the root or its tag.
+    BadLeaf { leaf: usize },
+}
+
+impl fmt::Display for CustodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustodyError::WrongClaim => write!(f, "challenge is for another claim"),
+            CustodyError::WrongKey => write!(f, "key does not match the claim"),
+            CustodyError::WrongRoot => write!(f, "root does not match the commitment"),
+            CustodyError::TagCount { assigned, tags } => {
+                write!(f, "{} tags for {} assigned leaves", tags, assigned)
+            }
+            CustodyError::ValueMismatch => write!(f, "tags do not hash to the custody value"),
+            CustodyError::WrongLeaves => write!(f, "opened leaves are not the challenged ones"),
+            CustodyError::BadLeaf { leaf } => write!(f, "leaf {} fails its path or tag", leaf),
+        }
+    }
+}
+
+impl std::error::Error for CustodyError {}
+
+fn key_commitment(key: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(KEY_DOMAIN).update(key);
+    *hasher.finalize().as_bytes()
+}
+
+fn leaf_tag(key: &[u8; 32], leaf: usize, symbols: &[Fr]) -> [u8; 32] {
+    let mut message = (leaf as u64).to_le_bytes().to_vec();
+    message.extend(Serialize::serialize_fields(symbols));
+    *blake3::keyed_hash(key, &message).as_bytes()
+}
+
+fn custody_value(tags: &[[u8; 32]]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(TAGS_DOMAIN);
+    tags.iter().for_each(|x| {
+        hasher.update(x);
+    });
+    *hasher.finalize().as_bytes()
+}
+
+impl CustodySecret {
+    pub fn new(secret: [u8; 32]) -> Self {
+        CustodySecret(secret)
+    }
+
+    fn key(&self, epoch: u64, commitment: &[u8; 32]) -> [u8; 32] {
+        let mut message = KEY_DOMAIN.to_vec();
+        message.extend(epoch.to_le_bytes());
+        message.extend(commitment);
+        *blake3::keyed_hash(&self.0, &message).as_bytes()
+    }
+
+    // `held` are the node's assigned leaves of the blob under `commitment`,
+    // ascending.
+    pub fn claim(&self, epoch: u64, commitment: &[u8; 32], held: &[FridaShare]) -> CustodyClaim {
+        let key = self.key(epoch, commitment);
+        let tags = held
+            .iter()
+            .map(|x| leaf_tag(&key, x.index, &x.symbols))
+            .collect::<Vec<_>>();
+        CustodyClaim {
+            epoch,
+            commitment: *commitment,
+            key_commitment: key_commitment(&key),
+            value: custody_value(&tags),
+        }
+    }
+
+    // None if a challenged leaf is not among `held`.
+    pub fn respond(
+        &self,
+        root: &[u8; 32],
+        held: &[FridaShare],
+        challenge: &CustodyChallenge,
+    ) -> Option<CustodyResponse> {
+        let key = self.key(challenge.epoch, &challenge.commitment);
+        let opened = challenge
+            .leaves
+            .iter()
+            .map(|leaf| held.iter().find(|x| x.index == *leaf).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        Some(CustodyResponse {
+            key,
+            root: *root,
+            tags: held
+                .iter()
+                .map(|x| leaf_tag(&key, x.index, &x.symbols))
+                .collect(),
+            opened,
+        })
+    }
+}
+
+impl CustodyChallenge {
+    // `count` of `assigned` drawn from `seed`, all of them if fewer. The seed
+    // should be fixed only after the claim is published.
+    pub fn sample(claim: &CustodyClaim, assigned: &[usize], seed: &[u8; 32], count: usize) -> Self {
+        let mut picked = vec![false; assigned.len()];
+        let mut left = count.min(assigned.len());
+        let mut counter = 0u64;
+        while left > 0 {
+            let mut hasher = blake3::Hasher::new();
+            hasher
+                .update(CHALLENGE_DOMAIN)
+                .update(seed)
+                .update(&claim.value)
+                .update(&counter.to_le_bytes());
+            let x = u64::from_le_bytes(hasher.finalize().as_bytes()[..8].try_into().unwrap());
+            let i = (x % assigned.len() as u64) as usize;
+            if !picked[i] {
+                picked[i] = true;
+                left -= 1;
+            }
+            counter += 1;
+        }
+        let mut leaves = assigned
+            .iter()
+            .zip(picked)
+            .filter_map(|(leaf, x)| x.then_some(*leaf))
+            .collect::<Vec<_>>();
+        leaves.sort_unstable();
+        CustodyChallenge {
+            epoch: claim.epoch,
+            commitment: claim.commitment,
+            leaves,
+        }
+    }
+}
+
+// Checks `response` answers `challenge` for `claim`; `assigned` are the
+// node's leaves, ascending.
+pub fn verify_custody(
+    config: &FridaConfig,
+    claim: &CustodyClaim,
+    assigned: &[usize],
+    challenge: &CustodyChallenge,
+    response: &CustodyResponse,
+) -> Result<(), CustodyError> {
+    if challenge.epoch != claim.epoch || challenge.commitment != claim.commitment {
+        return Err(CustodyError::WrongClaim);
+    }
+    if key_commitment(&response.key) != claim.key_commitment {
+        return Err(CustodyError::WrongKey);
+    }
+    if config.commitment(&response.root) != claim.commitment {
+        return Err(CustodyError::WrongRoot);
+    }
+    if response.tags.len() != assigned.len() {
+        return Err(CustodyError::TagCount {
+            assigned: assigned.len(),
+            tags: response.tags.len(),
+        });
+    }
+    if custody_value(&response.tags) != claim.value {
+        return Err(CustodyError::ValueMismatch);
+    }
+    if !response
+        .opened
+        .iter()
+        .map(|x| x.index)
+        .eq(challenge.leaves.iter().copied())
+    {
+        return Err(CustodyError::WrongLeaves);
+    }
+    for share in &response.opened {
+        let position = assigned.binary_search(&share.index);
+        let valid = position.is_ok_and(|i| {
+            leaf_tag(&response.key, share.index, &share.symbols) == response.tags[i]
+                && Verifier::verify_chunk(
+                    &response.root,
+                    config.first_oracle_leaves(),
+                    config.leaf_size(0),
+                    share.index,
+                    &share.symbols,
+                    &share.path,
+                )
+        });
+        if !valid {
+            return Err(CustodyError::BadLeaf { leaf: share.index });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::random_prover;
+
+    #[test]
+    fn custody_challenge() {
+        let config = FridaConfig::new(b"custody", 2, 5, 1, 4);
+        let (_, prover) = random_prover(&config);
+        let root = prover.commit();
+        let commitment = config.commitment(&root);
+        let assigned = (3..config.leave_number()).step_by(4).collect::<Vec<_>>();
+        let held = assigned
+            .iter()
+            .map(|&index| {
+                let (symbols, path) = prover.open_chunk(index);
+                FridaShare {
+                    index,
+                    symbols,
+                    path,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let secret = CustodySecret::new([7; 32]);
+        let claim = secret.claim(5, &commitment, &held);
+        let challenge = CustodyChallenge::sample(&claim, &assigned, &[1; 32], 3);
+        assert_eq!(challenge.leaves.len(), 3);
+        let response = secret.respond(&root, &held, &challenge).unwrap();
+        assert_eq!(
+            verify_custody(&config, &claim, &assigned, &challenge, &response),
+            Ok(())
+        );
+
+        // A node that kept the value but lost a leaf it is challenged on.
+        let mut forged = response.clone();
+        forged.opened[1].symbols[0] += Fr::from(1u64);
+        let leaf = forged.opened[1].index;
+        assert_eq!(
+            verify_custody(&config, &claim, &assigned, &challenge, &forged),
+            Err(CustodyError::BadLeaf { leaf })
+        );
+        // Tags under another node's key do not match the claim.
+        let other = CustodySecret::new([8; 32])
+            .respond(&root, &held, &challenge)
+            .unwrap();
+        assert_eq!(
+            verify_custody(&config, &claim, &assigned, &challenge, &other),
+            Err(CustodyError::WrongKey)
+        );
+        // The claim is bound to the epoch.
+        let next = secret.claim(6, &commitment, &held);
+        assert_ne!(next.value, claim.value);
+        assert_eq!(
+            verify_custody(&config, &next, &assigned, &challenge, &response),
+            Err(CustodyError::WrongClaim)
+        );
+    }
+}
@@ -0,0 +1,247 @@
+// A dealer for the secret-sharing mode. Node i of the committee is handed
+// leaf i of the first oracle, two evaluations of every polynomial. The
+// degree is the least power of two at which `threshold - 1` nodes learn
+// nothing even next to the public proof, and any `quorum` nodes hold the
+// `degree` evaluations that determine a sharing polynomial, so the quorum is
+// at least the threshold; the code rate is fixed by the committee size.
+// Everyone gets the public transcript, the root and a proof that the dealing
+// is a sharing, and each node its own package. Receivers check a transcript
+// against the config and threshold they expect, not the dealer's.
+use std::fmt;
+
+use ark_bn254::Fr;
+use rand::Rng;
+
+use crate::{
+    session::Session,
+    vss::{SharingConfig, SharingError},
+    FridaConfig, FridaProof, SoundnessRegime, Verifier,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DealError {
+    NoNodes,
+    // `threshold` must be positive and its quorum no larger than `nodes`.
+    Threshold { threshold: usize, nodes: usize },
+    Sharing(SharingError),
+}
+
+impl fmt::Display for DealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DealError::NoNodes => write!(f, "dealing to an empty committee"),
+            DealError::Threshold { threshold, nodes } => {
+                write!(f, "threshold {} does not fit {} nodes", threshold, nodes)
+            }
+            DealError::Sharing(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DealError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dealer {
+    pub context: Vec<u8>,
+    pub session: Session,
+    pub query_num: usize,
+    pub regime: SoundnessRegime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodePackage<K> {
+    pub index: usize,
+    pub node: K,
+    pub symbols: Vec<Fr>,
+    pub path: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct DealingTranscript<K> {
+    pub sharing: SharingConfig,
+    pub threshold: usize,
+    pub nodes: Vec<K>,
+    pub root: [u8; 32],
+    pub commitment: [u8; 32],
+    pub proof: FridaProof,
+}
+
+#[derive(Clone)]
+pub struct Dealing<K> {
+    pub transcript: DealingTranscript<K>,
+    pub packages: Vec<NodePackage<K>>,
+}
+
+impl Dealer {
+    // The sharing `deal` uses for `nodes` nodes and `threshold`.
+    pub fn sharing(
+        &self,
+        secret_len: usize,
+        nodes: usize,
+        threshold: usize,
+    ) -> Result<SharingConfig, DealError> {
+        if nodes == 0 {
+            return Err(DealError::NoNodes);
+        }
+        if threshold == 0 || threshold > nodes {
+            return Err(DealError::Threshold { threshold, nodes });
+        }
+        let log_leaves = nodes.next_power_of_two().ilog2() as usize;
+        let mut log_degree = (2 * threshold).next_power_of_two().ilog2() as usize;
+        while 1 << (log_degree - 1) <= nodes {
+            let config = FridaConfig {
+                regime: self.regime,
+                ..FridaConfig::new(
+                    &self.session_context(),
+                    2,
+                    log_degree,
+                    (log_leaves + 1).saturating_sub(log_degree).max(1),
+                    self.query_num,
+                )
+            };
+            match SharingConfig::hiding(config, secret_len, threshold - 1) {
+                Err(
+                    SharingError::SecretTooLong { .. }
+                    | SharingError::ProofExposes { .. }
+                    | SharingError::Coalition { .. },
+                ) => log_degree += 1,
+                sharing => return sharing.map_err(DealError::Sharing),
+            }
+        }
+        Err(DealError::Threshold { threshold, nodes })
+    }
+
+    pub fn deal<K: Clone, R: Rng>(
+        &self,
+        secret: &[Fr],
+        node_pubkeys: &[K],
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<Dealing<K>, DealError> {
+        let sharing = self.sharing(secret.len(), node_pubkeys.len(), threshold)?;
+        let prover = sharing.share(&[secret.to_vec()], rng);
+        let root = prover.commit();
+        let packages = node_pubkeys
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let (symbols, path) = prover.open_chunk(index);
+                NodePackage {
+                    index,
+                    node: node.clone(),
+                    symbols,
+                    path,
+                }
+            })
+            .collect();
+        let transcript = DealingTranscript {
+            threshold,
+            nodes: node_pubkeys.to_vec(),
+            root,
+            commitment: sharing.config.commitment(&root),
+            proof: prover.prove(&sharing.config),
+            sharing,
+        };
+        Ok(Dealing {
+            transcript,
+            packages,
+        })
+    }
+}
+
+impl<K> DealingTranscript<K> {
+    // Whether the committed codeword is a sharing for this committee under
+    // the receiver's `expected` config and `threshold`. The dealer may run
+    // more queries than expected but not fewer.
+    pub fn verify(&self, expected: &SharingConfig, threshold: usize) -> bool {
+        self.matches(expected, threshold) && self.verify_proof()
+    }
+
+    pub(crate) fn matches(&self, expected: &SharingConfig, threshold: usize) -> bool {
+        let config = &self.sharing.config;
+        let query_num = expected.config.query_num;
+        self.threshold == threshold
+            && self.sharing.secret_len == expected.secret_len
+            && config.query_num >= query_num
+            && FridaConfig {
+                query_num,
+                ..config.clone()
+            } == expected.config
+    }
+
+    // `verify` under whatever config the transcript carries.
+    pub(crate) fn verify_proof(&self) -> bool {
+        let config = &self.sharing.config;
+        self.nodes.len() <= config.leave_number()
+            && self.proof.merkle_root == self.root
+            && config.commitment(&self.root) == self.commitment
+            && Verifier::verify_proof(config, &self.commitment, &self.proof)
+                .failure
+                .is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn dealing_packages() {
+        let mut rng = thread_rng();
+        let dealer = Dealer {
+            context: b"dealer".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 2,
+            regime: SoundnessRegime::Proven,
+        };
+        let nodes = (0..40u64).collect::<Vec<_>>();
+        let secret = [Fr::rand(&mut rng)];
+        let dealing = dealer.deal(&secret, &nodes, 16, &mut rng).unwrap();
+        let transcript = &dealing.transcript;
+        let expected = dealer.sharing(1, 40, 16).unwrap();
+        assert!(transcript.verify(&expected, 16));
+        assert!(!transcript.verify(&expected, 8));
+        let config = &transcript.sharing.config;
+        assert_eq!((config.log_degree, config.code_rate), (6, 1));
+        assert_eq!(transcript.sharing.quorum(), 32);
+        assert!(transcript.sharing.coalition_bound() >= 15);
+        assert_eq!(dealing.packages.len(), 40);
+        for package in dealing.packages.iter() {
+            assert_eq!(package.node, nodes[package.index]);
+            assert!(Verifier::verify_chunk(
+                &transcript.root,
+                config.leave_number(),
+                config.leaf_size(0),
+                package.index,
+                &package.symbols,
+                &package.path
+            ));
+        }
+
+        let mut forged = transcript.clone();
+        forged.root[0] ^= 1;
+        assert!(!forged.verify(&expected, 16));
+        // Fewer queries than the receiver asks for prove too little.
+        let lax = Dealer {
+            query_num: 1,
+            ..dealer.clone()
+        };
+        let unproven = lax.deal(&secret, &nodes, 16, &mut rng).unwrap();
+        assert!(unproven.transcript.verify_proof());
+        assert!(!unproven.transcript.verify(&expected, 16));
+        assert_eq!(
+            dealer.deal(&secret, &nodes[..20], 16, &mut rng).err(),
+            Some(DealError::Threshold {
+                threshold: 16,
+                nodes: 20
+            })
+        );
+        assert_eq!(
+            dealer.deal(&secret, &nodes[..0], 1, &mut rng).err(),
+            Some(DealError::NoNodes)
+        );
+    }
+}
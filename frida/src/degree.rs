@@ -0,0 +1,97 @@
+// Degree bounds that are not a power of two. A polynomial f with bound d is
+// committed padded to N = 2^log_degree coefficients, and X^(N - d) * f is
+// batched alongside it as a virtual polynomial: its values are derived from
+// f's openings, and it stays below degree N only if f is below degree d.
+use ark_bn254::Fr;
+use ark_ff::Zero;
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::Verifier;
+
+// `coeffs` with zeros appended up to the power of two covering
+// `declared_degree`, ready for `Prover::new`.
+pub fn pad_to_domain(coeffs: &[Fr], declared_degree: usize) -> Vec<Fr> {
+    assert!(declared_degree > 0 && coeffs.len() <= declared_degree);
+    let mut padded = coeffs.to_vec();
+    padded.resize(declared_degree.next_power_of_two(), Fr::zero());
+    padded
+}
+
+// The polynomials that need a shifted copy, with the shift's exponent.
+pub(crate) fn shifts(bounds: &Option<Vec<usize>>, log_degree: usize) -> Vec<(usize, usize)> {
+    let n = 1 << log_degree;
+    bounds
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(k, &d)| {
+            assert!(d > 0 && d <= n);
+            (d < n).then_some((k, n - d))
+        })
+        .collect()
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // `bounds[k]` is the strict degree bound of polynomial k; the verifier
+    // must be given the same bounds.
+    pub fn with_degree_bounds(mut self, bounds: Vec<usize>) -> Self {
+        assert_eq!(bounds.len(), self.poly_num);
+        self.degree_bounds = Some(bounds);
+        self
+    }
+}
+
+impl Verifier {
+    pub fn with_degree_bounds(mut self, bounds: Vec<usize>) -> Self {
+        assert_eq!(bounds.len(), self.poly_num);
+        self.degree_bounds = Some(bounds);
+        self
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{replay, Blake3Transcript, FridaConfig, ProveOptions};
+
+    #[test]
+    fn strict_bounds() {
+        let config = FridaConfig::new(b"degree", 2, 6, 2, 30);
+        let random = |len: usize| {
+            (0..len)
+                .map(|_| <Fr as UniformRand>::rand(&mut thread_rng()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pad_to_domain(&random(40), 40).len(), 64);
+        let groups = config.groups();
+        let bounds = vec![40, 64];
+        let run = |polies: &[Vec<Fr>]| {
+            let prover = Prover::new(polies, &groups[0], &ProveOptions::default())
+                .unwrap()
+                .with_degree_bounds(bounds.clone());
+            let proof = prover.prove(&config);
+            let (challenges, _) =
+                replay::<Blake3Transcript>(&config, &proof.merkle_root, &proof.iopp_commits);
+            Verifier::new(proof.merkle_root, config.poly_num, config.leave_number())
+                .with_degree_bounds(bounds.clone())
+                .verify(
+                    &config,
+                    &groups,
+                    &challenges,
+                    proof.iopp_commits,
+                    proof.query_results,
+                )
+        };
+
+        let honest = vec![pad_to_domain(&random(40), 40), random(64)];
+        assert!(run(&honest).accepted());
+        // Below the power-of-two bound, but not below the declared one.
+        let cheating = vec![pad_to_domain(&random(50), 64), random(64)];
+        assert!(!run(&cheating).accepted());
+    }
+}
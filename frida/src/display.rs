@@ -0,0 +1,207 @@
+// Log-friendly formatting for proofs and commitments. Roots print as the first
+// four bytes in hex, field elements are cut after a dozen digits and query
+// results show their sizes and the first few values by key, so a failing
+// assertion names the proof without dumping every opening.
+use std::fmt;
+
+use ark_bn254::Fr;
+
+use crate::{CommitmentMeta, FridaProof, IoppCommits, QueryResult};
+
+const SAMPLES: usize = 3;
+const DIGITS: usize = 12;
+
+pub(crate) struct ShortHex<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Display for ShortHex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for x in self.0.iter().take(4) {
+            write!(f, "{:02x}", x)?;
+        }
+        if self.0.len() > 4 {
+            write!(f, "…")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ShortHex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+struct ShortField(Fr);
+
+impl fmt::Debug for ShortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.0.to_string();
+        if digits.len() > DIGITS {
+            write!(f, "{}…", &digits[..DIGITS])
+        } else {
+            write!(f, "{}", digits)
+        }
+    }
+}
+
+// At most `SAMPLES` entries, then how many were left out.
+struct Sample<I>(I, usize);
+
+impl<I: Iterator<Item = T> + Clone, T: fmt::Debug> fmt::Debug for Sample<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        list.entries(self.0.clone().take(SAMPLES));
+        if self.1 > SAMPLES {
+            list.entry(&format_args!("… {} more", self.1 - SAMPLES));
+        }
+        list.finish()
+    }
+}
+
+impl fmt::Debug for QueryResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut values = self.values.iter().collect::<Vec<_>>();
+        values.sort_unstable_by_key(|(k, _)| **k);
+        f.debug_struct("QueryResult")
+            .field("values", &values.len())
+            .field("path_bytes", &self.paths.len())
+            .field(
+                "sample",
+                &Sample(
+                    values.iter().map(|(k, v)| (**k, ShortField(**v))),
+                    values.len(),
+                ),
+            )
+            .finish()
+    }
+}
+
+impl fmt::Display for QueryResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} values, {}-byte path",
+            self.values.len(),
+            self.paths.len()
+        )
+    }
+}
+
+impl fmt::Debug for IoppCommits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IoppCommits")
+            .field("rounds", &self.merkle_roots.len())
+            .field(
+                "roots",
+                &Sample(
+                    self.merkle_roots.iter().map(|x| ShortHex(x)),
+                    self.merkle_roots.len(),
+                ),
+            )
+            .field("final_value", &ShortField(self.final_value))
+            .finish()
+    }
+}
+
+impl fmt::Display for IoppCommits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} rounds, final value {:?}",
+            self.merkle_roots.len(),
+            ShortField(self.final_value)
+        )
+    }
+}
+
+impl fmt::Debug for FridaProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FridaProof")
+            .field("merkle_root", &ShortHex(&self.merkle_root))
+            .field("iopp_commits", &self.iopp_commits)
+            .field(
+                "query_results",
+                &Sample(self.query_results.iter(), self.query_results.len()),
+            )
+            .field("proof_size", &self.proof_size())
+            .field("version", &format_args!("{}", self.version))
+            .finish()
+    }
+}
+
+impl fmt::Display for FridaProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "proof for {}: {}, {} query rounds, {} bytes",
+            ShortHex(&self.merkle_root),
+            self.iopp_commits,
+            self.query_results.len(),
+            self.proof_size()
+        )
+    }
+}
+
+impl fmt::Debug for CommitmentMeta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommitmentMeta")
+            .field("merkle_root", &ShortHex(&self.merkle_root))
+            .field("poly_num", &self.poly_num)
+            .field("log_degree", &self.log_degree)
+            .field("code_rate", &self.code_rate)
+            .field("leave_number", &self.leave_number)
+            .field("leaf_rows", &self.leaf_rows)
+            .field("version", &format_args!("{}", self.version))
+            .finish()
+    }
+}
+
+impl fmt::Display for CommitmentMeta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} polynomials of degree 2^{}, rate 1/2^{}, {} leaves",
+            ShortHex(&self.merkle_root),
+            self.poly_num,
+            self.log_degree,
+            self.code_rate,
+            self.leave_number
+        )
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use crate::{fixtures::random_polies, FridaConfig, ProveOptions, Prover};
+
+    use super::*;
+
+    #[test]
+    fn truncated_output() {
+        let config = FridaConfig::new(b"display", 4, 6, 2, 16);
+        let polies = random_polies(4, 64);
+        let proof = Prover::new(&polies, &config.groups()[0], &ProveOptions::default())
+            .unwrap()
+            .prove(&config);
+        let root = format!("{}", ShortHex(&proof.merkle_root));
+        assert_eq!(root.chars().count(), 9);
+
+        let debug = format!("{:?}", proof);
+        assert!(debug.contains(&root));
+        assert!(debug.contains(&format!("rounds: {}", config.log_degree - 1)));
+        assert!(debug.contains(&format!("{} more", config.log_degree - 1 - SAMPLES)));
+        assert!(debug.len() < 2000);
+        let display = proof.to_string();
+        assert!(display.contains(&format!("{} bytes", proof.proof_size())));
+        assert_eq!(
+            proof.query_results[0].to_string(),
+            format!(
+                "{} values, {}-byte path",
+                proof.query_results[0].values.len(),
+                proof.query_results[0].paths.len()
+            )
+        );
+        assert_eq!(format!("{:?}", ShortField(Fr::from(42u64))), "42");
+        assert!(format!("{:?}", ShortField(-Fr::from(1u64))).ends_with('…'));
+    }
+}
@@ -0,0 +1,147 @@
+// Batches of polynomials whose natural domains differ, like trace columns of
+// different lengths. `Prover::new` commits each on the largest domain with
+// zero coefficients appended, and `with_domains` makes its natural length a
+// strict degree bound so a short column cannot grow into the padding. A
+// natural domain is a subgroup of the largest one, so natural index i sits at
+// codeword position i * stride.
+#[cfg(feature = "prover")]
+use ark_bn254::Fr;
+#[cfg(feature = "prover")]
+use util::mul_group::Radix2Group;
+
+use crate::Verifier;
+#[cfg(feature = "prover")]
+use crate::{ProveOptions, Prover};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainMap {
+    log_degrees: Vec<usize>,
+    code_rate: usize,
+}
+
+impl DomainMap {
+    pub fn new(log_degrees: Vec<usize>, code_rate: usize) -> Self {
+        assert!(!log_degrees.is_empty());
+        DomainMap {
+            log_degrees,
+            code_rate,
+        }
+    }
+
+    // The shared domain is sized for the longest polynomial.
+    pub fn log_degree(&self) -> usize {
+        *self.log_degrees.iter().max().unwrap()
+    }
+
+    pub fn log_degrees(&self) -> &[usize] {
+        &self.log_degrees
+    }
+
+    pub fn degree_bounds(&self) -> Vec<usize> {
+        self.log_degrees.iter().map(|d| 1 << d).collect()
+    }
+
+    pub fn natural_size(&self, poly: usize) -> usize {
+        1 << (self.log_degrees[poly] + self.code_rate)
+    }
+
+    pub fn stride(&self, poly: usize) -> usize {
+        1 << (self.log_degree() - self.log_degrees[poly])
+    }
+
+    pub fn position(&self, poly: usize, natural_index: usize) -> usize {
+        assert!(natural_index < self.natural_size(poly));
+        natural_index * self.stride(poly)
+    }
+
+    pub fn natural_index(&self, poly: usize, position: usize) -> Option<usize> {
+        let stride = self.stride(poly);
+        position.is_multiple_of(stride).then_some(position / stride)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // `polies[k]` may have any power-of-two length; the returned map must be
+    // handed to the verifier.
+    pub fn with_domains(polies: &[Vec<Fr>], code_rate: usize) -> (Prover, DomainMap) {
+        let map = DomainMap::new(
+            polies
+                .iter()
+                .map(|x| {
+                    assert!(x.len().is_power_of_two());
+                    x.len().ilog2() as usize
+                })
+                .collect(),
+            code_rate,
+        );
+        let group = Radix2Group::new(map.log_degree() + code_rate);
+        let prover = Prover::new(polies, &group, &ProveOptions::default())
+            .unwrap()
+            .with_degree_bounds(map.degree_bounds());
+        (prover, map)
+    }
+
+    // Polynomial `poly` at point `natural_index` of its own domain.
+    pub fn natural_evaluation(&self, map: &DomainMap, poly: usize, natural_index: usize) -> Fr {
+        self.interpolation
+            .get(poly * self.domain_size() + map.position(poly, natural_index))
+    }
+}
+
+impl Verifier {
+    pub fn with_domains(self, map: &DomainMap) -> Self {
+        self.with_degree_bounds(map.degree_bounds())
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{replay, Blake3Transcript, FridaConfig};
+
+    #[test]
+    fn mixed_domains() {
+        let mut rng = thread_rng();
+        let mut random = |len: usize| (0..len).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let polies = vec![random(16), random(4), random(8)];
+        let (prover, map) = Prover::with_domains(&polies, 2);
+        assert_eq!(map.log_degree(), 4);
+        assert_eq!(map.stride(1), 4);
+        assert_eq!(map.natural_index(1, 8), Some(2));
+        assert_eq!(map.natural_index(1, 9), None);
+        for (k, poly) in polies.iter().enumerate() {
+            let natural = Radix2Group::new(map.log_degrees()[k] + 2).fft(poly.clone());
+            for (i, x) in natural.iter().enumerate() {
+                assert_eq!(prover.natural_evaluation(&map, k, i), *x);
+            }
+        }
+
+        let config = FridaConfig::new(b"domains", 3, map.log_degree(), 2, 30);
+        let groups = config.groups();
+        let run = |prover: &Prover| {
+            let proof = prover.prove(&config);
+            let (challenges, _) =
+                replay::<Blake3Transcript>(&config, &proof.merkle_root, &proof.iopp_commits);
+            Verifier::new(proof.merkle_root, config.poly_num, config.leave_number())
+                .with_domains(&map)
+                .verify(
+                    &config,
+                    &groups,
+                    &challenges,
+                    proof.iopp_commits,
+                    proof.query_results,
+                )
+        };
+        assert!(run(&prover).accepted());
+        // The short column committed with more coefficients than its domain.
+        let long = vec![polies[0].clone(), random(16), polies[2].clone()];
+        let cheating = Prover::new(&long, &groups[0], &ProveOptions::default())
+            .unwrap()
+            .with_degree_bounds(map.degree_bounds());
+        assert!(!run(&cheating).accepted());
+    }
+}
@@ -0,0 +1,407 @@
+// Canonical byte encoding of `FridaProof` for transports that carry proofs as
+// opaque bytes. Integers are little-endian u32 lengths or u64 keys and field
+// elements are compressed. Each round is laid out as a key table in ascending
+// order, the values in the same order and then the merkle path, so
+// `FridaProofView` verifies straight from the buffer without building maps or
+// copying values, and equal proofs always encode to equal bytes.
+use std::collections::HashMap;
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+#[cfg(feature = "prover")]
+use crate::dealer::NodePackage;
+use crate::{
+    service::SampleResponse, Blake3Transcript, FridaConfig, FridaProof, IoppCommits, Openings,
+    ProtocolVersion, QueryResult, VerificationReport, Verifier,
+};
+
+fn field_len() -> usize {
+    Fr::zero().compressed_size()
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < n {
+            return None;
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Some(head)
+    }
+
+    pub(crate) fn len(&mut self) -> Option<usize> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?) as usize)
+    }
+
+    pub(crate) fn field(&mut self) -> Option<Fr> {
+        Fr::deserialize_compressed(self.take(field_len())?).ok()
+    }
+}
+
+pub(crate) fn push_len(out: &mut Vec<u8>, len: usize) {
+    out.extend((len as u32).to_le_bytes());
+}
+
+pub(crate) fn push_field(out: &mut Vec<u8>, x: &Fr) {
+    x.serialize_compressed(&mut *out).unwrap();
+}
+
+// One round's openings, borrowed from the encoded proof.
+#[derive(Clone, Copy)]
+pub struct QueryView<'a> {
+    keys: &'a [u8],
+    values: &'a [u8],
+    path: &'a [u8],
+}
+
+impl QueryView<'_> {
+    fn key_at(&self, i: usize) -> usize {
+        u64::from_le_bytes(self.keys[i * 8..(i + 1) * 8].try_into().unwrap()) as usize
+    }
+
+    fn value_bytes(&self, i: usize) -> &[u8] {
+        let n = field_len();
+        &self.values[i * n..(i + 1) * n]
+    }
+
+    fn find(&self, key: usize) -> Option<usize> {
+        let (mut lo, mut hi) = (0, self.value_num());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.key_at(mid).cmp(&key) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    fn decode(&self, i: usize) -> Fr {
+        // Every value was checked canonical when the view was parsed.
+        Fr::deserialize_compressed(self.value_bytes(i)).unwrap()
+    }
+}
+
+impl Openings for QueryView<'_> {
+    fn value(&self, key: usize) -> Option<Fr> {
+        self.find(key).map(|i| self.decode(i))
+    }
+
+    fn contains(&self, key: usize) -> bool {
+        self.find(key).is_some()
+    }
+
+    fn value_num(&self) -> usize {
+        self.keys.len() / 8
+    }
+
+    fn path(&self) -> &[u8] {
+        self.path
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (usize, Fr)> + '_ {
+        (0..self.value_num()).map(|i| (self.key_at(i), self.decode(i)))
+    }
+
+    // Canonical values re-serialize to their own bytes, so the leaf is hashed
+    // from the buffer.
+    fn leaf_hash(&self, leaf: usize, leaf_size: usize, len: usize) -> Option<[u8; 32]> {
+        let mut hasher = blake3::Hasher::new();
+        for j in 0..leaf_size {
+            hasher.update(self.value_bytes(self.find(leaf + j * len)?));
+        }
+        Some(hasher.finalize().into())
+    }
+}
+
+pub struct FridaProofView<'a> {
+    pub version: ProtocolVersion,
+    pub merkle_root: [u8; 32],
+    merkle_roots: &'a [u8],
+    pub final_value: Fr,
+    query_results: Vec<QueryView<'a>>,
+}
+
+impl<'a> FridaProofView<'a> {
+    // Rejects truncated input, trailing bytes, unsorted or repeated keys and
+    // non-canonical field elements; the result still has to pass the
+    // verifier.
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let mut r = Reader { bytes };
+        let version = ProtocolVersion::from_bytes(r.take(4)?.try_into().ok()?);
+        let merkle_root = r.take(32)?.try_into().ok()?;
+        let round_num = r.len()?;
+        let merkle_roots = r.take(round_num.checked_mul(32)?)?;
+        let final_value = r.field()?;
+        let result_num = r.len()?;
+        let mut query_results = Vec::with_capacity(result_num.min(64));
+        for _ in 0..result_num {
+            let value_num = r.len()?;
+            let path_len = r.len()?;
+            let view = QueryView {
+                keys: r.take(value_num.checked_mul(8)?)?,
+                values: r.take(value_num.checked_mul(field_len())?)?,
+                path: r.take(path_len)?,
+            };
+            if (1..value_num).any(|i| view.key_at(i - 1) >= view.key_at(i))
+                || (0..value_num).any(|i| Fr::deserialize_compressed(view.value_bytes(i)).is_err())
+            {
+                return None;
+            }
+            query_results.push(view);
+        }
+        if !r.bytes.is_empty() {
+            return None;
+        }
+        Some(FridaProofView {
+            version,
+            merkle_root,
+            merkle_roots,
+            final_value,
+            query_results,
+        })
+    }
+
+    pub fn iopp_commits(&self) -> IoppCommits {
+        IoppCommits::new(
+            self.merkle_roots
+                .chunks(32)
+                .map(|x| x.try_into().unwrap())
+                .collect(),
+            self.final_value,
+        )
+    }
+
+    pub fn query_results(&self) -> &[QueryView<'a>] {
+        &self.query_results
+    }
+
+    pub fn to_proof(&self) -> FridaProof {
+        FridaProof {
+            merkle_root: self.merkle_root,
+            iopp_commits: self.iopp_commits(),
+            version: self.version,
+            query_results: self
+                .query_results
+                .iter()
+                .map(|x| QueryResult {
+                    paths: x.path.to_vec(),
+                    values: x.entries().collect::<HashMap<_, _>>(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FridaProof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.version.to_bytes().to_vec();
+        out.extend(self.merkle_root);
+        push_len(&mut out, self.iopp_commits.merkle_roots.len());
+        for root in self.iopp_commits.merkle_roots.iter() {
+            out.extend(root);
+        }
+        push_field(&mut out, &self.iopp_commits.final_value);
+        push_len(&mut out, self.query_results.len());
+        for result in self.query_results.iter() {
+            let mut values = result.values.iter().collect::<Vec<_>>();
+            values.sort_unstable_by_key(|(k, _)| **k);
+            push_len(&mut out, values.len());
+            push_len(&mut out, result.paths.len());
+            for (k, _) in values.iter() {
+                out.extend((**k as u64).to_le_bytes());
+            }
+            for (_, v) in values {
+                push_field(&mut out, v);
+            }
+            out.extend(&result.paths);
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<FridaProof> {
+        FridaProofView::parse(bytes).map(|x| x.to_proof())
+    }
+}
+
+// Root, the leaf indices as u64, the symbols per leaf, every leaf's symbols
+// and the multiproof.
+impl SampleResponse {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.root.to_vec();
+        push_len(&mut out, self.leaves.len());
+        for leaf in self.leaves.iter() {
+            out.extend((*leaf as u64).to_le_bytes());
+        }
+        push_len(&mut out, self.symbols.first().map_or(0, |x| x.len()));
+        for x in self.symbols.iter().flatten() {
+            push_field(&mut out, x);
+        }
+        push_len(&mut out, self.path.len());
+        out.extend(&self.path);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<SampleResponse> {
+        let mut r = Reader { bytes };
+        let root = r.take(32)?.try_into().ok()?;
+        let leaf_num = r.len()?;
+        let leaves = r
+            .take(leaf_num.checked_mul(8)?)?
+            .chunks(8)
+            .map(|x| u64::from_le_bytes(x.try_into().unwrap()) as usize)
+            .collect();
+        let width = r.len()?;
+        let symbols = (0..leaf_num)
+            .map(|_| (0..width).map(|_| r.field()).collect::<Option<Vec<_>>>())
+            .collect::<Option<Vec<_>>>()?;
+        let path_len = r.len()?;
+        let path = r.take(path_len)?.to_vec();
+        if !r.bytes.is_empty() {
+            return None;
+        }
+        Some(SampleResponse {
+            root,
+            leaves,
+            symbols,
+            path,
+        })
+    }
+}
+
+// The leaf index as u64, the symbols and the merkle path. The node's key
+// travels beside the bytes, as it is what they are sealed to.
+#[cfg(feature = "prover")]
+impl<K> NodePackage<K> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = (self.index as u64).to_le_bytes().to_vec();
+        push_len(&mut out, self.symbols.len());
+        for x in self.symbols.iter() {
+            push_field(&mut out, x);
+        }
+        push_len(&mut out, self.path.len());
+        out.extend(&self.path);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8], node: K) -> Option<NodePackage<K>> {
+        let mut r = Reader { bytes };
+        let index = u64::from_le_bytes(r.take(8)?.try_into().ok()?) as usize;
+        let width = r.len()?;
+        let symbols = (0..width).map(|_| r.field()).collect::<Option<Vec<_>>>()?;
+        let path_len = r.len()?;
+        let path = r.take(path_len)?.to_vec();
+        if !r.bytes.is_empty() {
+            return None;
+        }
+        Some(NodePackage {
+            index,
+            node,
+            symbols,
+            path,
+        })
+    }
+}
+
+impl Verifier {
+    // `verify_proof` over a parsed view; only the round roots are copied out.
+    pub fn verify_view(
+        config: &FridaConfig,
+        commitment: &[u8; 32],
+        proof: &FridaProofView,
+    ) -> VerificationReport {
+        if let Some(report) = Self::check_version(config, proof.version) {
+            return report;
+        }
+        Self::verify_slots::<Blake3Transcript, _>(
+            config,
+            commitment,
+            &proof.merkle_root,
+            &proof.iopp_commits(),
+            &(0..config.query_num).collect::<Vec<_>>(),
+            &proof.query_results,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use crate::{
+        fixtures::{random_polies, random_prover},
+        Batching, FridaConfig, ProveOptions, Prover, Verifier,
+    };
+
+    use super::*;
+
+    #[test]
+    fn proof_bytes() {
+        let config = FridaConfig::new(b"encoding", 4, 5, 2, 12);
+        let (_, prover) = random_prover(&config);
+        let proof = prover.prove(&config);
+        let bytes = proof.to_bytes();
+        let decoded = FridaProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        let commitment = config.commitment(&prover.commit());
+        assert!(Verifier::verify_proof(&config, &commitment, &decoded).accepted());
+
+        assert!(FridaProof::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(FridaProof::from_bytes(&extended).is_none());
+    }
+
+    #[test]
+    fn borrowed_view() {
+        let config = FridaConfig {
+            batching: Batching::Independent,
+            ..FridaConfig::new(b"view", 2, 6, 1, 16)
+        };
+        let polies = random_polies(2, 64);
+        let prover = Prover::new(&polies, &config.groups()[0], &ProveOptions::default()).unwrap();
+        let proof = prover.prove(&config);
+        let bytes = proof.to_bytes();
+        let commitment = config.commitment(&proof.merkle_root);
+
+        let view = FridaProofView::parse(&bytes).unwrap();
+        let report = Verifier::verify_view(&config, &commitment, &view);
+        assert!(report.accepted());
+        assert_eq!(
+            report.opened,
+            Verifier::verify_proof(&config, &commitment, &proof).opened
+        );
+
+        // Version, root, the folded roots, the final value, the round count,
+        // then the first round's two lengths before its keys.
+        let first_key = proof.version.to_bytes().len()
+            + 32
+            + 4
+            + 32 * proof.iopp_commits.merkle_roots.len()
+            + field_len()
+            + 4
+            + 8;
+        let value_num = proof.query_results[0].values.len();
+        let first_key_value = proof.query_results[0].values.keys().min().copied().unwrap() as u64;
+        assert_eq!(
+            bytes[first_key..first_key + 8],
+            first_key_value.to_le_bytes()
+        );
+        // Flip a byte of the first value: still well formed, but rejected.
+        let mut tampered = bytes.clone();
+        tampered[first_key + value_num * 8] ^= 1;
+        let view = FridaProofView::parse(&tampered).unwrap();
+        assert!(!Verifier::verify_view(&config, &commitment, &view).accepted());
+        // Keys must be strictly ascending.
+        let mut unsorted = bytes.clone();
+        let keys = first_key..first_key + 16;
+        let (a, b) = unsorted[keys].split_at_mut(8);
+        a.swap_with_slice(b);
+        assert!(FridaProofView::parse(&unsorted).is_none());
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+
+use ark_bn254::Fr;
+
+use crate::ProtocolVersion;
+
+// Structural problems with a proof, found before any hashing or field
+// arithmetic is attempted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShapeError {
+    NoRounds,
+    NoQueries,
+    LeaveNumber {
+        leave_number: usize,
+        rounds: usize,
+    },
+    GroupCount {
+        expected: usize,
+        actual: usize,
+    },
+    GroupSize {
+        round: usize,
+        expected: usize,
+        actual: usize,
+    },
+    RootCount {
+        expected: usize,
+        actual: usize,
+    },
+    QueryResultCount {
+        expected: usize,
+        actual: usize,
+    },
+    IndexOutOfRange {
+        index: usize,
+        domain_size: usize,
+    },
+    LeafSize {
+        round: usize,
+        expected: usize,
+        actual: usize,
+    },
+    MissingValue {
+        round: usize,
+        position: usize,
+    },
+    MalformedPath {
+        round: usize,
+    },
+    QuerySlot {
+        slot: usize,
+        query_num: usize,
+    },
+    // Leaf rows must divide the polynomial count.
+    Interleave {
+        poly_num: usize,
+        rows: usize,
+    },
+    FoldedLeafSize {
+        size: usize,
+    },
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::NoRounds => write!(f, "proof has no folding rounds"),
+            ShapeError::NoQueries => write!(f, "proof has no query indices"),
+            ShapeError::LeaveNumber {
+                leave_number,
+                rounds,
+            } => write!(
+                f,
+                "{} leaves cannot be folded over {} rounds",
+                leave_number, rounds
+            ),
+            ShapeError::GroupCount { expected, actual } => {
+                write!(f, "expected {} groups, got {}", expected, actual)
+            }
+            ShapeError::GroupSize {
+                round,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "round {} group has size {}, expected {}",
+                round, actual, expected
+            ),
+            ShapeError::RootCount { expected, actual } => {
+                write!(f, "expected {} round roots, got {}", expected, actual)
+            }
+            ShapeError::QueryResultCount { expected, actual } => {
+                write!(f, "expected {} query results, got {}", expected, actual)
+            }
+            ShapeError::IndexOutOfRange { index, domain_size } => write!(
+                f,
+                "query index {} outside domain of size {}",
+                index, domain_size
+            ),
+            ShapeError::LeafSize {
+                round,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "round {} opens {} values, expected {}",
+                round, actual, expected
+            ),
+            ShapeError::MissingValue { round, position } => {
+                write!(f, "round {} lacks a value at position {}", round, position)
+            }
+            ShapeError::MalformedPath { round } => {
+                write!(f, "round {} merkle path is malformed", round)
+            }
+            ShapeError::QuerySlot { slot, query_num } => {
+                write!(f, "query slot {} beyond {} queries", slot, query_num)
+            }
+            ShapeError::FoldedLeafSize { size } => {
+                write!(
+                    f,
+                    "folded leaves of {} values are not a power of two from 2",
+                    size
+                )
+            }
+            ShapeError::Interleave { poly_num, rows } => {
+                write!(
+                    f,
+                    "{} polynomials cannot be split into leaves of {} rows",
+                    poly_num, rows
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailedCheck {
+    Shape(ShapeError),
+    // The root, context or parameters do not match the published commitment.
+    Transcript,
+    MerklePath {
+        round: usize,
+    },
+    Folding {
+        round: usize,
+        index: usize,
+    },
+    FinalValue {
+        index: usize,
+    },
+    // Some folding equation of the round fails; aggregated checks cannot say
+    // which.
+    AggregatedFolding {
+        round: usize,
+    },
+    // The proof was written at a version this build does not read.
+    Version {
+        supported: ProtocolVersion,
+        actual: ProtocolVersion,
+    },
+}
+
+impl fmt::Display for FailedCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailedCheck::Shape(e) => write!(f, "malformed proof: {}", e),
+            FailedCheck::Transcript => write!(f, "commitment does not match transcript"),
+            FailedCheck::MerklePath { round } => {
+                write!(f, "merkle path rejected in round {}", round)
+            }
+            FailedCheck::Folding { round, index } => write!(
+                f,
+                "folding inconsistent in round {} at index {}",
+                round, index
+            ),
+            FailedCheck::FinalValue { index } => {
+                write!(f, "final value mismatch at index {}", index)
+            }
+            FailedCheck::AggregatedFolding { round } => {
+                write!(f, "aggregated folding check failed in round {}", round)
+            }
+            FailedCheck::Version { supported, actual } => write!(
+                f,
+                "proof version {} is not readable by version {}",
+                actual, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FailedCheck {}
+
+impl From<ShapeError> for FailedCheck {
+    fn from(e: ShapeError) -> Self {
+        FailedCheck::Shape(e)
+    }
+}
+
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub rounds: usize,
+    pub queries: usize,
+    pub failure: Option<FailedCheck>,
+    // On success, the verified first-oracle symbols of each polynomial as
+    // (position in the evaluation domain, value).
+    pub opened: Vec<Vec<(usize, Fr)>>,
+}
+
+impl VerificationReport {
+    pub fn accepted(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    pub fn into_result(self) -> Result<(), FailedCheck> {
+        match self.failure {
+            None => Ok(()),
+            Some(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+// Fixtures shared by the unit tests.
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use rand::thread_rng;
+
+use crate::{FridaConfig, ProveOptions, Prover};
+
+// `poly_num` random polynomials of `degree` coefficients each.
+pub(crate) fn random_polies(poly_num: usize, degree: usize) -> Vec<Vec<Fr>> {
+    let mut rng = thread_rng();
+    (0..poly_num)
+        .map(|_| (0..degree).map(|_| Fr::rand(&mut rng)).collect())
+        .collect()
+}
+
+// Random polynomials filling `config`, committed over its first domain by
+// `Prover::new`, so for whole-blob leaves and pairs in the folded oracles.
+pub(crate) fn random_prover(config: &FridaConfig) -> (Vec<Vec<Fr>>, Prover) {
+    let polies = random_polies(config.poly_num, 1 << config.log_degree);
+    let prover = Prover::new(&polies, &config.groups()[0], &ProveOptions::default()).unwrap();
+    (polies, prover)
+}
@@ -0,0 +1,70 @@
+// The FRI fold exactly as prover and verifier compute it, for code that has to
+// reproduce it bit for bit: fraud-proof builders, circuit gadgets, other
+// provers. Writing f(X) = f_e(X^2) + X f_o(X^2), a fold with challenge beta is
+// the polynomial (1 - beta) f_e + beta f_o on the squared domain, so the pair
+// f(x), f(-x) determines it at x^2.
+use ark_bn254::Fr;
+use ark_ff::Field;
+use util::mul_group::Radix2Group;
+
+// Twice the folded value at x^2; the verifier compares against doubled
+// openings and never needs 1/2. `x_inv` is the inverse of x.
+pub fn fold_point(x: Fr, nx: Fr, x_inv: Fr, beta: Fr) -> Fr {
+    let sum = x + nx;
+    sum + beta * ((x - nx) * x_inv - sum)
+}
+
+// The folded value at x^2 from f(x) and f(-x).
+pub fn fold_pair(x: Fr, nx: Fr, x_inv: Fr, inv_2: Fr, beta: Fr) -> Fr {
+    fold_point(x, nx, x_inv, beta) * inv_2
+}
+
+// Folds evaluations over `group`, where entry i + |group| / 2 is the
+// evaluation at minus the point of entry i, into evaluations over the squared
+// group.
+pub fn fold_domain(evals: &[Fr], group: &Radix2Group, beta: Fr) -> Vec<Fr> {
+    let half = group.size() / 2;
+    assert_eq!(evals.len(), group.size());
+    let inv_2 = Fr::from(2u64).inverse().unwrap();
+    (0..half)
+        .map(|i| {
+            fold_pair(
+                evals[i],
+                evals[i + half],
+                group.element_inv_at(i),
+                inv_2,
+                beta,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn fold_matches_split() {
+        let mut rng = thread_rng();
+        let coeff = (0..16).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let beta = Fr::rand(&mut rng);
+        let folded = coeff
+            .chunks(2)
+            .map(|c| (Fr::from(1u64) - beta) * c[0] + beta * c[1])
+            .collect::<Vec<_>>();
+        let group = Radix2Group::new(6);
+        let evals = fold_domain(&group.fft(coeff), &group, beta);
+        assert_eq!(evals, Radix2Group::new(5).fft(folded));
+
+        let (x, nx) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let x_inv = group.element_inv_at(3);
+        let inv_2 = Fr::from(2u64).inverse().unwrap();
+        assert_eq!(
+            fold_pair(x, nx, x_inv, inv_2, beta).double(),
+            fold_point(x, nx, x_inv, beta)
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// Secret hygiene for the sharing mode, behind the `zeroize` feature. Shares,
+// packages and reconstructed secrets are zeroized when dropped, as are
+// codewords, and the secret paths scrub what they leave behind: sharing
+// coefficients once committed, plaintexts once sealed or opened, and the
+// evaluations and coefficients of a reconstruction. Transforms run in place,
+// and `util` grows a buffer only after copying out of it, so no stale copy is
+// left in FFT scratch. Without the feature the scrubbing does nothing. A
+// clone is the caller's copy and is zeroized when it is dropped.
+use ark_bn254::Fr;
+
+#[cfg(feature = "zeroize")]
+pub(crate) fn scrub<'a, I: IntoIterator<Item = &'a mut Fr>>(values: I) {
+    values.into_iter().for_each(zeroize::Zeroize::zeroize);
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn scrub<'a, I: IntoIterator<Item = &'a mut Fr>>(_: I) {}
+
+#[cfg(feature = "zeroize")]
+pub(crate) fn scrub_bytes(bytes: &mut Vec<u8>) {
+    zeroize::Zeroize::zeroize(bytes);
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn scrub_bytes(_: &mut Vec<u8>) {}
+
+#[cfg(feature = "zeroize")]
+mod on_drop {
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+
+    use super::scrub;
+    use crate::{dealer::NodePackage, reconstruct::Reconstruction, share::Share, InterpolateValue};
+
+    impl Zeroize for Share {
+        fn zeroize(&mut self) {
+            scrub(&mut self.symbols);
+        }
+    }
+
+    impl Drop for Share {
+        fn drop(&mut self) {
+            self.zeroize();
+        }
+    }
+
+    impl ZeroizeOnDrop for Share {}
+
+    // The path is public; only the symbols are the node's.
+    impl<K> Zeroize for NodePackage<K> {
+        fn zeroize(&mut self) {
+            scrub(&mut self.symbols);
+        }
+    }
+
+    impl<K> Drop for NodePackage<K> {
+        fn drop(&mut self) {
+            self.zeroize();
+        }
+    }
+
+    impl<K> ZeroizeOnDrop for NodePackage<K> {}
+
+    impl Zeroize for Reconstruction {
+        fn zeroize(&mut self) {
+            scrub(&mut self.secret);
+        }
+    }
+
+    impl Drop for Reconstruction {
+        fn drop(&mut self) {
+            self.zeroize();
+        }
+    }
+
+    impl ZeroizeOnDrop for Reconstruction {}
+
+    // Every codeword, the sharing's first oracle among them.
+    impl Drop for InterpolateValue {
+        fn drop(&mut self) {
+            scrub(&mut self.value);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod tests {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    };
+
+    use ark_ff::{UniformRand, Zero};
+    use rand::thread_rng;
+    use util::mul_group::Radix2Group;
+    use zeroize::Zeroize;
+
+    use super::*;
+    use crate::{
+        dealer::Dealer,
+        reconstruct::{reconstruct, Faults},
+        session::Session,
+        share::verify_my_share,
+        InterpolateValue, SoundnessRegime,
+    };
+
+    // Notes whether the watched buffer holds only zeroes when it is freed,
+    // which is the only point a dropped value's memory can be read.
+    struct Watch;
+
+    thread_local! {
+        static WATCHED: Cell<usize> = const { Cell::new(0) };
+        static ZEROED: Cell<Option<bool>> = const { Cell::new(None) };
+    }
+
+    unsafe impl GlobalAlloc for Watch {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            if WATCHED.try_with(|x| x.get()) == Ok(ptr as usize) {
+                let bytes = std::slice::from_raw_parts(ptr, layout.size());
+                let _ = ZEROED.try_with(|x| x.set(Some(bytes.iter().all(|b| *b == 0))));
+            }
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOC: Watch = Watch;
+
+    // Whether the buffer at `ptr` was all zeroes when `run` freed it.
+    fn zeroed_on_free<F: FnOnce()>(ptr: *const Fr, run: F) -> bool {
+        WATCHED.with(|x| x.set(ptr as usize));
+        ZEROED.with(|x| x.set(None));
+        run();
+        WATCHED.with(|x| x.set(0));
+        ZEROED
+            .with(|x| x.take())
+            .expect("the watched buffer was not freed")
+    }
+
+    fn random_fields(len: usize) -> Vec<Fr> {
+        let mut rng = thread_rng();
+        (0..len).map(|_| Fr::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn secrets_zeroized() {
+        let mut rng = thread_rng();
+        let dealer = Dealer {
+            context: b"hygiene".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let mut dealing = dealer
+            .deal(
+                &[Fr::rand(&mut rng)],
+                &(0..8u64).collect::<Vec<_>>(),
+                4,
+                &mut rng,
+            )
+            .unwrap();
+        let expected = dealer.sharing(1, 8, 4).unwrap();
+        let mut share =
+            verify_my_share(&dealing.packages[1], &dealing.transcript, &expected, 4, 1).unwrap();
+        let shares = dealing
+            .packages
+            .iter()
+            .map(|x| verify_my_share(x, &dealing.transcript, &expected, 4, x.index).unwrap())
+            .collect::<Vec<_>>();
+        let mut recovered = reconstruct(&shares, 8, 1, Faults::Reject).unwrap();
+
+        share.zeroize();
+        dealing.packages[1].zeroize();
+        recovered.zeroize();
+        assert!(share.symbols.iter().all(|x| x.is_zero()));
+        assert!(dealing.packages[1].symbols.iter().all(|x| x.is_zero()));
+        assert!(recovered.secret.iter().all(|x| x.is_zero()));
+    }
+
+    #[test]
+    fn secrets_zeroized_on_drop() {
+        let mut rng = thread_rng();
+        // A plain vector is freed as it was, so the watch does see contents.
+        let plain = random_fields(8);
+        assert!(!zeroed_on_free(plain.as_ptr(), || drop(plain)));
+
+        let dealer = Dealer {
+            context: b"hygiene".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let mut dealing = dealer
+            .deal(
+                &[Fr::rand(&mut rng)],
+                &(0..8u64).collect::<Vec<_>>(),
+                4,
+                &mut rng,
+            )
+            .unwrap();
+        let expected = dealer.sharing(1, 8, 4).unwrap();
+        let shares = dealing
+            .packages
+            .iter()
+            .map(|x| verify_my_share(x, &dealing.transcript, &expected, 4, x.index).unwrap())
+            .collect::<Vec<_>>();
+        let recovered = reconstruct(&shares, 8, 1, Faults::Reject).unwrap();
+        assert!(zeroed_on_free(recovered.secret.as_ptr(), || {
+            drop(recovered)
+        }));
+        let mut shares = shares;
+        let share = shares.pop().unwrap();
+        assert!(zeroed_on_free(share.symbols.as_ptr(), || drop(share)));
+        let package = dealing.packages.pop().unwrap();
+        assert!(zeroed_on_free(package.symbols.as_ptr(), || drop(package)));
+
+        let codeword = InterpolateValue::new(random_fields(32), 4);
+        let ptr = codeword.codeword().as_ptr();
+        assert!(zeroed_on_free(ptr, || drop(codeword)));
+    }
+
+    #[test]
+    fn fft_scratch_zeroized() {
+        // Coefficients shorter than the domain are copied into a new buffer;
+        // the one the caller handed over is wiped before it is freed.
+        let coeff = random_fields(4);
+        assert!(coeff.capacity() < 16);
+        let group = Radix2Group::new(4);
+        let expected = {
+            let mut padded = coeff.clone();
+            padded.resize(16, Fr::zero());
+            group.fft(padded)
+        };
+        let mut evals = vec![];
+        assert!(zeroed_on_free(coeff.as_ptr(), || evals = group.fft(coeff)));
+        assert_eq!(evals, expected);
+    }
+}
@@ -0,0 +1,191 @@
+// What the vectors handed to a prover hold. Every form ends in the same
+// codewords: evaluations are interpolated to coefficients of degree below
+// 2^log_degree first, except over the subgroup, whose values
+// `Prover::from_evaluations` copies into the codeword as they are.
+use std::fmt;
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+use util::mul_group::Radix2Group;
+
+use crate::{FridaConfig, Layout, ProveOptions, Prover};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputForm {
+    // Lowest first, at most 2^log_degree of them; shorter ones are zero
+    // padded.
+    Coefficients,
+    // The values at the order 2^log_degree subgroup, in its natural order.
+    SubgroupEvaluations,
+    // The values at shift times that subgroup.
+    CosetEvaluations(Fr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    PolyCount {
+        expected: usize,
+        actual: usize,
+    },
+    // Polynomial `poly` does not fit the config's degree: more coefficients
+    // than 2^log_degree, or evaluations not exactly that many.
+    Degree {
+        poly: usize,
+        log_degree: usize,
+        len: usize,
+    },
+    ZeroShift,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::PolyCount { expected, actual } => {
+                write!(f, "expected {} polynomials, got {}", expected, actual)
+            }
+            InputError::Degree {
+                poly,
+                log_degree,
+                len,
+            } => write!(
+                f,
+                "polynomial {} has {} entries, not within degree 2^{}",
+                poly, len, log_degree
+            ),
+            InputError::ZeroShift => write!(f, "coset shift is zero"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl InputForm {
+    fn check(self, config: &FridaConfig, inputs: &[Vec<Fr>]) -> Result<(), InputError> {
+        if inputs.len() != config.poly_num {
+            return Err(InputError::PolyCount {
+                expected: config.poly_num,
+                actual: inputs.len(),
+            });
+        }
+        if self == InputForm::CosetEvaluations(Fr::zero()) {
+            return Err(InputError::ZeroShift);
+        }
+        let degree = 1 << config.log_degree;
+        let fits = |len: usize| match self {
+            InputForm::Coefficients => len <= degree,
+            _ => len == degree,
+        };
+        match inputs.iter().position(|x| !fits(x.len())) {
+            Some(poly) => Err(InputError::Degree {
+                poly,
+                log_degree: config.log_degree,
+                len: inputs[poly].len(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Prover {
+    // The prover for `inputs` in `form`, under `config`'s degree and leaf
+    // layout.
+    pub fn from_input(
+        inputs: &[Vec<Fr>],
+        form: InputForm,
+        config: &FridaConfig,
+    ) -> Result<Self, InputError> {
+        form.check(config, inputs)?;
+        let degree = 1 << config.log_degree;
+        let coeffs = match form {
+            InputForm::SubgroupEvaluations => return Ok(Self::from_evaluations(inputs, config)),
+            InputForm::Coefficients => inputs
+                .iter()
+                .map(|x| {
+                    let mut x = x.clone();
+                    x.resize(degree, Fr::zero());
+                    x
+                })
+                .collect::<Vec<_>>(),
+            InputForm::CosetEvaluations(shift) => {
+                let coset = Radix2Group::coset(config.log_degree, shift);
+                inputs.iter().map(|x| coset.ifft(x.clone())).collect()
+            }
+        };
+        Ok(Self::build(
+            &coeffs,
+            &config.groups()[0],
+            Layout::default(),
+            config.leaf_rows(),
+            &ProveOptions::default(),
+        )
+        .unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_polies, Interleave, Verifier};
+
+    #[test]
+    fn input_forms() {
+        let config = FridaConfig {
+            interleave: Interleave::Rows(1),
+            ..FridaConfig::new(b"input", 2, 4, 2, 8)
+        };
+        let polies = random_polies(2, 16);
+        let expected = Prover::interleaved(&polies, &config.groups()[0], 1).commit();
+        let shift = Fr::from(7u64);
+        let subgroup = Radix2Group::new(4);
+        let coset = Radix2Group::coset(4, shift);
+        for (form, inputs) in [
+            (InputForm::Coefficients, polies.clone()),
+            (
+                InputForm::SubgroupEvaluations,
+                polies.iter().map(|x| subgroup.fft(x.clone())).collect(),
+            ),
+            (
+                InputForm::CosetEvaluations(shift),
+                polies.iter().map(|x| coset.fft(x.clone())).collect(),
+            ),
+        ] {
+            let prover = Prover::from_input(&inputs, form, &config).unwrap();
+            assert_eq!(prover.commit(), expected);
+            let proof = prover.prove(&config);
+            assert!(
+                Verifier::verify_proof(&config, &config.commitment(&expected), &proof).accepted()
+            );
+        }
+
+        let short = vec![polies[0][..9].to_vec(), polies[1].clone()];
+        let mut padded = short.clone();
+        padded[0].resize(16, Fr::zero());
+        assert_eq!(
+            Prover::from_input(&short, InputForm::Coefficients, &config)
+                .unwrap()
+                .commit(),
+            Prover::interleaved(&padded, &config.groups()[0], 1).commit()
+        );
+        assert_eq!(
+            Prover::from_input(&short, InputForm::SubgroupEvaluations, &config).err(),
+            Some(InputError::Degree {
+                poly: 0,
+                log_degree: 4,
+                len: 9
+            })
+        );
+        let long = vec![vec![Fr::zero(); 17], polies[1].clone()];
+        assert!(Prover::from_input(&long, InputForm::Coefficients, &config).is_err());
+        assert_eq!(
+            Prover::from_input(&polies[..1], InputForm::Coefficients, &config).err(),
+            Some(InputError::PolyCount {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            Prover::from_input(&polies, InputForm::CosetEvaluations(Fr::zero()), &config).err(),
+            Some(InputError::ZeroShift)
+        );
+    }
+}
@@ -1,14 +1,166 @@
+#[cfg(feature = "prover")]
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 use std::{collections::HashMap, mem::size_of};
 
 use ark_bn254::Fr;
-use ark_ff::{Field, Zero};
+#[cfg(feature = "prover")]
+use ark_ff::FftField;
+use ark_ff::{Field, One, Zero};
+#[cfg(feature = "prover")]
+use util::merkle_tree::MerkleTreeProver;
 use util::{
-    merkle_tree::{Blake32, MerkleTreeProver, MerkleTreeVerifier, Serialize},
+    merkle_tree::{Blake32, MerkleTreeVerifier, Serialize},
     mul_group::Radix2Group,
 };
 
+// The modules `transparent-vrs` re-exports are the supported API; the rest
+// stay public for the sibling crates and benches but are hidden from the
+// docs, as internals that may change between releases.
+#[doc(hidden)]
+pub mod address;
+#[doc(hidden)]
+pub mod aggregate;
+#[doc(hidden)]
+pub mod amortized;
+#[doc(hidden)]
+pub mod append;
+#[cfg(feature = "arena")]
+#[doc(hidden)]
+pub mod arena;
+#[doc(hidden)]
+pub mod audit;
+#[doc(hidden)]
+pub mod batching;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod blob;
+#[doc(hidden)]
+pub mod block;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod builder;
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
+pub mod cell;
+pub mod client;
+#[doc(hidden)]
+pub mod committee;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod complaint;
+pub mod confidence;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod custody;
+#[cfg(feature = "prover")]
+pub mod dealer;
+#[doc(hidden)]
+pub mod degree;
+mod display;
+#[doc(hidden)]
+pub mod domains;
+#[doc(hidden)]
+pub mod encoding;
+#[doc(hidden)]
+pub mod error;
+#[cfg(all(test, feature = "prover"))]
+mod fixtures;
+#[doc(hidden)]
+pub mod folding;
+#[cfg(feature = "prover")]
+mod hygiene;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod input;
+#[doc(hidden)]
+pub mod mempool;
+#[doc(hidden)]
+pub mod options;
+#[doc(hidden)]
+pub mod partial;
+#[doc(hidden)]
+pub mod pedersen;
+#[doc(hidden)]
+pub mod peerdas;
+pub mod plan;
+#[doc(hidden)]
+pub mod poseidon;
+#[cfg(feature = "prover")]
+pub mod receive;
+#[cfg(feature = "prover")]
+pub mod reconstruct;
+#[doc(hidden)]
+pub mod recording;
+#[cfg(all(test, feature = "prover"))]
+mod reference;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod refresh;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod reshare;
+#[doc(hidden)]
+pub mod rollup;
+#[doc(hidden)]
+pub mod schedule;
+pub mod scheme;
+#[doc(hidden)]
+pub mod scoring;
+#[doc(hidden)]
+pub mod security;
+#[cfg(feature = "serde")]
+mod serde_hex;
+pub mod service;
+pub mod session;
+#[cfg(feature = "prover")]
+pub mod share;
+#[doc(hidden)]
+pub mod signed;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod sim;
+#[doc(hidden)]
+pub mod slashing;
+#[doc(hidden)]
+pub mod transcript;
+#[doc(hidden)]
+pub mod update;
+#[doc(hidden)]
+pub mod version;
+#[cfg(feature = "jellyfish")]
+#[doc(hidden)]
+pub mod vid;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod vss;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod weights;
+
+pub use amortized::FoldCheck;
+pub use batching::Batching;
+#[cfg(feature = "prover")]
+pub use builder::FridaBuilder;
+pub use config::{FridaConfig, Interleave, SoundnessRegime};
+pub use error::{FailedCheck, ShapeError, VerificationReport};
+pub use options::{CancellationToken, Cancelled, Phase, ProveOptions};
+pub use recording::RecordingTranscript;
+pub use schedule::{QueryIndices, QuerySchedule};
+pub use security::SecurityLevel;
+pub use transcript::{Blake3Transcript, Transcript};
+pub use version::ProtocolVersion;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QueryResult {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
     paths: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::values"))]
     values: HashMap<usize, Fr>,
 }
 
@@ -19,74 +171,267 @@ impl QueryResult {
 
     pub fn verify_merkle_tree(
         &self,
-        leaf_indices: &Vec<usize>,
+        leaf_indices: &[usize],
         leaf_size: usize,
         merkle_verifier: &MerkleTreeVerifier<Blake32>,
     ) -> bool {
-        let len = merkle_verifier.leave_number;
-        let leaves: Vec<Vec<u8>> = leaf_indices
-            .iter()
-            .map(|x| {
-                Serialize::serialize_fields(
-                    &(0..leaf_size)
-                        .map(|j| self.values.get(&(x.clone() + j * len)).unwrap().clone())
-                        .collect::<Vec<_>>(),
-                )
-            })
-            .collect();
-        let res = merkle_verifier.verify(self.paths.clone(), leaf_indices, &leaves);
-        assert!(res);
-        res
+        verify_openings(self, leaf_indices, leaf_size, merkle_verifier)
+    }
+}
+
+// One round's opened values and merkle path as the checks read them, owned as
+// a `QueryResult` or borrowed from a proof buffer.
+pub(crate) trait Openings {
+    fn value(&self, key: usize) -> Option<Fr>;
+    fn contains(&self, key: usize) -> bool;
+    fn value_num(&self) -> usize;
+    fn path(&self) -> &[u8];
+    fn entries(&self) -> impl Iterator<Item = (usize, Fr)> + '_;
+
+    // Hash of the serialized leaf holding keys `leaf + j * len`.
+    fn leaf_hash(&self, leaf: usize, leaf_size: usize, len: usize) -> Option<[u8; 32]> {
+        let v = (0..leaf_size)
+            .map(|j| self.value(leaf + j * len))
+            .collect::<Option<Vec<_>>>()?;
+        Some(blake3::hash(&Serialize::serialize_fields(&v)).into())
+    }
+}
+
+impl Openings for QueryResult {
+    fn value(&self, key: usize) -> Option<Fr> {
+        self.values.get(&key).copied()
+    }
+
+    fn contains(&self, key: usize) -> bool {
+        self.values.contains_key(&key)
+    }
+
+    fn value_num(&self) -> usize {
+        self.values.len()
+    }
+
+    fn path(&self) -> &[u8] {
+        &self.paths
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (usize, Fr)> + '_ {
+        self.values.iter().map(|(k, v)| (*k, *v))
+    }
+}
+
+fn verify_openings<O: Openings>(
+    openings: &O,
+    leaf_indices: &[usize],
+    leaf_size: usize,
+    merkle_verifier: &MerkleTreeVerifier<Blake32>,
+) -> bool {
+    let len = merkle_verifier.leave_number;
+    match leaf_indices
+        .iter()
+        .map(|x| openings.leaf_hash(*x, leaf_size, len))
+        .collect::<Option<Vec<_>>>()
+    {
+        Some(hashes) => merkle_verifier.verify_hashes(openings.path(), leaf_indices, &hashes),
+        None => false,
+    }
+}
+
+// The first oracle holds 2 * poly_num half-codewords of `len` entries, and
+// with `groups` leaves per position leaf a * len + x holds entry x of
+// half-codewords a, a + groups, a + 2 * groups and so on; these are the
+// leaves of `positions`, ascending.
+pub(crate) fn group_leaves(positions: &[usize], len: usize, groups: usize) -> Vec<usize> {
+    (0..groups)
+        .flat_map(|a| positions.iter().map(move |x| a * len + x))
+        .collect()
+}
+
+// The leaves of a folded oracle with `leaves` leaves holding `positions`: a
+// leaf of size s holds x + j * leaves for j < s, so x and -x share one.
+pub(crate) fn folded_leaves(positions: &[usize], leaves: usize) -> Vec<usize> {
+    let mut out = positions.iter().map(|x| x % leaves).collect::<Vec<_>>();
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+// Where entry `i + j * len` (slot j of leaf i) of an oracle is stored.
+// Columnar keeps every codeword contiguous, which the batching and folding
+// sweeps read sequentially; row-major keeps every leaf contiguous, which
+// serialization and openings read as one slice. Columnar is the default only
+// because it was the original layout: the `layout` group in benches/kernels
+// has not been recorded yet, and its end-to-end numbers should decide.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layout {
+    #[default]
+    Columnar,
+    RowMajor,
+}
+
+#[cfg(feature = "prover")]
+impl Layout {
+    fn offset(self, key: usize, len: usize, leaf_size: usize) -> usize {
+        match self {
+            Layout::Columnar => key,
+            Layout::RowMajor => (key % len) * leaf_size + key / len,
+        }
+    }
+
+    // `value` is given column by column and returned in this layout.
+    fn arrange(self, value: Vec<Fr>, leaf_size: usize) -> Vec<Fr> {
+        match self {
+            Layout::Columnar => value,
+            Layout::RowMajor => {
+                let len = value.len() / leaf_size;
+                (0..value.len())
+                    .map(|k| value[(k % leaf_size) * len + k / leaf_size])
+                    .collect()
+            }
+        }
     }
 }
 
+#[cfg(feature = "prover")]
 pub struct InterpolateValue {
-    pub value: Vec<Fr>,
+    value: Vec<Fr>,
     leaf_size: usize,
+    layout: Layout,
     merkle_tree: MerkleTreeProver<Blake32>,
 }
 
+// Leaf i of a codeword gathers value[len * j + i] for j < leaf_size. Folded
+// layers use width 2 and the first oracle 2 * poly_num, so the usual widths
+// get a fixed-size gather the compiler can unroll; others take the loop.
+fn gather<const L: usize>(value: &[Fr], len: usize, i: usize) -> [Fr; L] {
+    std::array::from_fn(|j| value[len * j + i])
+}
+
+fn leaf_iter_fixed<const L: usize>(value: &[Fr]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let len = value.len() / L;
+    (0..len).map(move |i| Serialize::serialize_fields(&gather::<L>(value, len, i)))
+}
+
+fn leaf_iter_dynamic(value: &[Fr], leaf_size: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let len = value.len() / leaf_size;
+    (0..len).map(move |i| {
+        Serialize::serialize_fields(
+            &(0..leaf_size)
+                .map(|j| value[len * j + i])
+                .collect::<Vec<_>>(),
+        )
+    })
+}
+
+fn leaf_iter(value: &[Fr], leaf_size: usize) -> Box<dyn Iterator<Item = Vec<u8>> + '_> {
+    match leaf_size {
+        2 => Box::new(leaf_iter_fixed::<2>(value)),
+        4 => Box::new(leaf_iter_fixed::<4>(value)),
+        8 => Box::new(leaf_iter_fixed::<8>(value)),
+        16 => Box::new(leaf_iter_fixed::<16>(value)),
+        32 => Box::new(leaf_iter_fixed::<32>(value)),
+        64 => Box::new(leaf_iter_fixed::<64>(value)),
+        _ => Box::new(leaf_iter_dynamic(value, leaf_size)),
+    }
+}
+
+pub fn leaf_bytes_dynamic(value: &[Fr], leaf_size: usize) -> Vec<Vec<u8>> {
+    leaf_iter_dynamic(value, leaf_size).collect()
+}
+
+pub fn leaf_bytes(value: &[Fr], leaf_size: usize) -> Vec<Vec<u8>> {
+    leaf_iter(value, leaf_size).collect()
+}
+
+#[cfg(feature = "prover")]
 impl InterpolateValue {
     pub fn new(value: Vec<Fr>, leaf_size: usize) -> Self {
-        let len = value.len() / leaf_size;
-        let mt = MerkleTreeProver::new(
-            &(0..len)
-                .map(|i| {
-                    Serialize::serialize_fields(
-                        &(0..leaf_size)
-                            .map(|j| value[len * j + i])
-                            .collect::<Vec<_>>(),
-                    )
-                })
-                .collect(),
-        );
-        InterpolateValue {
+        Self::with_layout(value, leaf_size, Layout::default())
+    }
+
+    // `value` is column by column, as for `new`; it is stored in `layout`.
+    pub fn with_layout(value: Vec<Fr>, leaf_size: usize, layout: Layout) -> Self {
+        let threads = ProveOptions::default().thread_num();
+        Self::try_with_layout(value, leaf_size, layout, threads, |_| true).unwrap()
+    }
+
+    // `threads` and `proceed` are as for `MerkleTreeProver::try_from_leaf_iter`.
+    pub(crate) fn try_with_layout<F: FnMut(usize) -> bool>(
+        value: Vec<Fr>,
+        leaf_size: usize,
+        layout: Layout,
+        threads: usize,
+        proceed: F,
+    ) -> Option<Self> {
+        let value = layout.arrange(value, leaf_size);
+        let merkle_tree = match layout {
+            Layout::Columnar => {
+                MerkleTreeProver::try_from_leaf_iter(leaf_iter(&value, leaf_size), threads, proceed)
+            }
+            Layout::RowMajor => MerkleTreeProver::try_from_leaf_iter(
+                value.chunks(leaf_size).map(Serialize::serialize_fields),
+                threads,
+                proceed,
+            ),
+        }?;
+        Some(InterpolateValue {
             value,
             leaf_size,
-            merkle_tree: mt,
-        }
+            layout,
+            merkle_tree,
+        })
     }
 
     pub fn leave_num(&self) -> usize {
         self.merkle_tree.leave_num()
     }
 
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
     pub fn commit(&self) -> [u8; 32] {
         self.merkle_tree.commit()
     }
 
-    pub fn query(&self, leaf_indices: &Vec<usize>) -> QueryResult {
+    pub fn get(&self, key: usize) -> Fr {
+        let len = self.merkle_tree.leave_num();
+        self.value[self.layout.offset(key, len, self.leaf_size)]
+    }
+
+    // The values column by column, whatever the layout.
+    pub fn codeword(&self) -> Cow<'_, [Fr]> {
+        match self.layout {
+            Layout::Columnar => Cow::Borrowed(&self.value),
+            Layout::RowMajor => Cow::Owned((0..self.value.len()).map(|k| self.get(k)).collect()),
+        }
+    }
+
+    pub fn leaf(&self, index: usize) -> Vec<Fr> {
+        let len = self.merkle_tree.leave_num();
+        match (self.layout, self.leaf_size) {
+            (Layout::RowMajor, _) => {
+                self.value[index * self.leaf_size..(index + 1) * self.leaf_size].to_vec()
+            }
+            (Layout::Columnar, 2) => gather::<2>(&self.value, len, index).to_vec(),
+            (Layout::Columnar, _) => (0..self.leaf_size)
+                .map(|j| self.value[len * j + index])
+                .collect(),
+        }
+    }
+
+    pub fn query(&self, leaf_indices: &[usize]) -> QueryResult {
         let len = self.merkle_tree.leave_num();
         assert_eq!(len * self.leaf_size, self.value.len());
         let proof_values = (0..self.leaf_size)
             .flat_map(|i| {
                 leaf_indices
                     .iter()
-                    .map(|j| (j.clone() + i * len, self.value[j.clone() + i * len]))
+                    .map(|j| (j + i * len, self.get(j + i * len)))
                     .collect::<Vec<_>>()
             })
             .collect();
-        let proof_bytes = self.merkle_tree.open(&leaf_indices);
+        let proof_bytes = self.merkle_tree.open(leaf_indices);
         QueryResult {
             paths: proof_bytes,
             values: proof_values,
@@ -94,8 +439,12 @@ impl InterpolateValue {
     }
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IoppCommits {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::roots"))]
     merkle_roots: Vec<[u8; 32]>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::field"))]
     final_value: Fr,
 }
 
@@ -110,47 +459,327 @@ impl IoppCommits {
     pub fn proof_size(&self) -> usize {
         self.merkle_roots.len() * 32 + size_of::<Fr>()
     }
+
+    pub fn roots(&self) -> &[[u8; 32]] {
+        &self.merkle_roots
+    }
+
+    pub fn final_value(&self) -> Fr {
+        self.final_value
+    }
+
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        for root in self.merkle_roots.iter() {
+            hasher.update(root);
+        }
+        hasher.update(&Serialize::serialize_fields(&[self.final_value]));
+        *hasher.finalize().as_bytes()
+    }
+}
+
+// What a commitment stands for, for explorers and debugging tools.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommitmentMeta {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
+    pub merkle_root: [u8; 32],
+    pub poly_num: usize,
+    pub log_degree: usize,
+    pub code_rate: usize,
+    pub leave_number: usize,
+    // Polynomials per first-oracle leaf.
+    pub leaf_rows: usize,
+    pub version: ProtocolVersion,
+}
+
+impl CommitmentMeta {
+    pub fn domain_size(&self) -> usize {
+        self.leave_number * 2
+    }
+
+    pub fn leaf_size(&self) -> usize {
+        self.leaf_rows * 2
+    }
 }
 
+#[cfg(feature = "prover")]
 pub struct IoppProverState {
     interpolations: Vec<InterpolateValue>,
+    commits: IoppCommits,
 }
 
+#[cfg(feature = "prover")]
 pub struct Prover {
     interpolation: InterpolateValue,
     poly_num: usize,
+    // Polynomials per first-oracle leaf.
+    leaf_rows: usize,
     log_degree: usize,
+    degree_bounds: Option<Vec<usize>>,
+    batching: Batching,
+    folded_leaf_size: usize,
 }
 
+#[cfg(feature = "prover")]
 impl Prover {
-    fn evaluate_next_domain(
-        last_interpolation: &Vec<Fr>,
+    // `folding::fold_domain` with a caller-supplied 1/2.
+    pub fn evaluate_next_domain(
+        last_interpolation: &[Fr],
         group: &Radix2Group,
         inv_2: Fr,
         challenge: Fr,
     ) -> Vec<Fr> {
-        let mut res = vec![];
-        let len = group.size();
-        for i in 0..(len / 2) {
-            let x = last_interpolation[i];
-            let nx = last_interpolation[i + len / 2];
-            let sum = x + nx;
-            let new_v = sum + challenge * ((x - nx) * group.element_inv_at(i) - sum);
-            res.push(new_v * inv_2);
+        let half = group.size() / 2;
+        (0..half)
+            .map(|i| {
+                folding::fold_pair(
+                    last_interpolation[i],
+                    last_interpolation[i + half],
+                    group.element_inv_at(i),
+                    inv_2,
+                    challenge,
+                )
+            })
+            .collect()
+    }
+
+    // A folded layer stored row-major already holds x and -x side by side.
+    fn evaluate_next_layer(
+        last: &InterpolateValue,
+        group: &Radix2Group,
+        inv_2: Fr,
+        challenge: Fr,
+    ) -> Vec<Fr> {
+        match (last.layout, last.leaf_size) {
+            (Layout::Columnar, _) => {
+                Self::evaluate_next_domain(&last.value, group, inv_2, challenge)
+            }
+            (Layout::RowMajor, 2) => last
+                .value
+                .chunks(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    folding::fold_pair(pair[0], pair[1], group.element_inv_at(i), inv_2, challenge)
+                })
+                .collect(),
+            (Layout::RowMajor, _) => {
+                let half = group.size() / 2;
+                (0..half)
+                    .map(|i| {
+                        folding::fold_pair(
+                            last.get(i),
+                            last.get(i + half),
+                            group.element_inv_at(i),
+                            inv_2,
+                            challenge,
+                        )
+                    })
+                    .collect()
+            }
         }
-        res
     }
 
-    pub fn new(polies: &[Vec<Fr>], group: &Radix2Group) -> Self {
-        let log_degree = polies[0].len().ilog2() as usize;
-        let value = polies
-            .iter()
-            .flat_map(|x| group.fft(x.clone()))
-            .collect::<Vec<_>>();
+    // `polies` are coefficient vectors; `from_input` takes evaluations too.
+    // `options` reports progress and is polled for cancellation between
+    // transforms and between batches of leaves and levels of the tree.
+    pub fn new(
+        polies: &[Vec<Fr>],
+        group: &Radix2Group,
+        options: &ProveOptions,
+    ) -> Result<Self, Cancelled> {
+        Self::build(polies, group, Layout::default(), polies.len(), options)
+    }
+
+    // Each polynomial is transformed as it arrives and dropped, so only the
+    // codeword is ever resident; leaves are hashed without being collected.
+    // The codeword is allocated once when the stream knows its length, so a
+    // large one is never regrown and copied.
+    pub fn from_stream<I: IntoIterator<Item = Vec<Fr>>>(polies: I, group: &Radix2Group) -> Self {
+        Self::stream(polies, group, None)
+    }
+
+    // `from_stream` with `rows` polynomials to a first-oracle leaf, as
+    // `interleaved`.
+    pub fn from_stream_interleaved<I: IntoIterator<Item = Vec<Fr>>>(
+        polies: I,
+        group: &Radix2Group,
+        rows: usize,
+    ) -> Self {
+        Self::stream(polies, group, Some(rows))
+    }
+
+    fn stream<I: IntoIterator<Item = Vec<Fr>>>(
+        polies: I,
+        group: &Radix2Group,
+        rows: Option<usize>,
+    ) -> Self {
+        let polies = polies.into_iter();
+        let mut value = Vec::with_capacity(polies.size_hint().0 * group.size());
+        let mut poly_num = 0;
+        let mut log_degree = None;
+        for poly in polies {
+            let degree = poly.len().ilog2() as usize;
+            assert_eq!(*log_degree.get_or_insert(degree), degree);
+            value.extend(group.fft(poly));
+            poly_num += 1;
+        }
+        assert!(poly_num > 0);
+        let leaf_rows = rows.unwrap_or(poly_num);
+        assert!(leaf_rows > 0 && poly_num.is_multiple_of(leaf_rows));
         Prover {
-            interpolation: InterpolateValue::new(value, polies.len() * 2),
+            interpolation: InterpolateValue::new(value, leaf_rows * 2),
+            poly_num,
+            leaf_rows,
+            log_degree: log_degree.unwrap(),
+            degree_bounds: None,
+            batching: Batching::Powers,
+            folded_leaf_size: 2,
+        }
+    }
+
+    // Polynomials are split across threads, each transforming straight into
+    // its own codewords' slice of the interleaved buffer.
+    fn batch_fft(
+        polies: &[Vec<Fr>],
+        group: &Radix2Group,
+        options: &ProveOptions,
+    ) -> Result<Vec<Fr>, Cancelled> {
+        let domain = group.size();
+        let per_thread = polies
+            .len()
+            .div_ceil(options.thread_num().min(polies.len()));
+        let mut value = options
+            .placement
+            .zeroed(domain * polies.len(), domain * per_thread);
+        let parts = polies.len().div_ceil(per_thread);
+        let total = polies.len();
+        let done = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for (part, (out, polies)) in value
+                .chunks_mut(domain * per_thread)
+                .zip(polies.chunks(per_thread))
+                .enumerate()
+            {
+                let done = &done;
+                scope.spawn(move || {
+                    options.placement.pin(part, parts);
+                    for (out, poly) in out.chunks_mut(domain).zip(polies) {
+                        if options.cancel.is_cancelled() {
+                            return;
+                        }
+                        group.fft_into(poly, out);
+                        let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        options.report(Phase::Encode, done, total);
+                    }
+                });
+            }
+        });
+        options.check()?;
+        Ok(value)
+    }
+
+    // Shorter polynomials are zero-padded to the longest; see `with_domains`
+    // for binding each to its own length.
+    pub fn with_layout(polies: &[Vec<Fr>], group: &Radix2Group, layout: Layout) -> Self {
+        Self::build(
+            polies,
+            group,
+            layout,
+            polies.len(),
+            &ProveOptions::default(),
+        )
+        .unwrap()
+    }
+
+    // `rows` polynomials to a first-oracle leaf, as `Interleave::Rows`; it
+    // must divide the polynomial count.
+    pub fn interleaved(polies: &[Vec<Fr>], group: &Radix2Group, rows: usize) -> Self {
+        Self::build(
+            polies,
+            group,
+            Layout::default(),
+            rows,
+            &ProveOptions::default(),
+        )
+        .unwrap()
+    }
+
+    fn build(
+        polies: &[Vec<Fr>],
+        group: &Radix2Group,
+        layout: Layout,
+        leaf_rows: usize,
+        options: &ProveOptions,
+    ) -> Result<Self, Cancelled> {
+        assert!(leaf_rows > 0 && polies.len().is_multiple_of(leaf_rows));
+        let log_degree = polies.iter().map(|x| x.len()).max().unwrap().ilog2() as usize;
+        let value = Self::batch_fft(polies, group, options)?;
+        let levels = group.size().ilog2() as usize;
+        let interpolation = InterpolateValue::try_with_layout(
+            value,
+            leaf_rows * 2,
+            layout,
+            options.thread_num(),
+            options.levels(Phase::Commit, levels),
+        )
+        .ok_or(Cancelled)?;
+        options.report(Phase::Commit, 1, 1);
+        Ok(Prover {
+            interpolation,
             poly_num: polies.len(),
+            leaf_rows,
             log_degree,
+            degree_bounds: None,
+            batching: Batching::Powers,
+            folded_leaf_size: 2,
+        })
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.interpolation.layout
+    }
+
+    // `evals[k]` holds polynomial k over the subgroup of size 2^log_degree.
+    // That subgroup is every 2^code_rate-th point of the codeword domain, so
+    // those positions are copied as they are and only the other cosets of
+    // the subgroup need a transform.
+    pub fn from_evaluations(evals: &[Vec<Fr>], config: &FridaConfig) -> Self {
+        assert_eq!(evals.len(), config.poly_num);
+        let subgroup = Radix2Group::new(config.log_degree);
+        let blowup = 1 << config.code_rate;
+        let omega = <Fr as FftField>::get_root_of_unity(config.domain_size() as u64).unwrap();
+        let value = evals
+            .iter()
+            .flat_map(|e| {
+                assert_eq!(e.len(), subgroup.size());
+                let mut codeword = vec![<Fr as Zero>::zero(); config.domain_size()];
+                for (t, x) in e.iter().enumerate() {
+                    codeword[t * blowup] = *x;
+                }
+                let coeff = subgroup.ifft(e.clone());
+                let mut shift = <Fr as One>::one();
+                for r in 1..blowup {
+                    shift *= omega;
+                    for (t, x) in subgroup
+                        .coset_fft(coeff.clone(), shift)
+                        .into_iter()
+                        .enumerate()
+                    {
+                        codeword[r + t * blowup] = x;
+                    }
+                }
+                codeword
+            })
+            .collect::<Vec<_>>();
+        Prover {
+            interpolation: InterpolateValue::new(value, config.leaf_rows() * 2),
+            poly_num: evals.len(),
+            leaf_rows: config.leaf_rows(),
+            log_degree: config.log_degree,
+            degree_bounds: None,
+            batching: Batching::Powers,
+            folded_leaf_size: 2,
         }
     }
 
@@ -158,20 +787,156 @@ impl Prover {
         self.interpolation.commit()
     }
 
+    pub fn poly_num(&self) -> usize {
+        self.poly_num
+    }
+
+    pub fn log_degree(&self) -> usize {
+        self.log_degree
+    }
+
+    pub fn leaf_rows(&self) -> usize {
+        self.leaf_rows
+    }
+
+    // Leaves of `size` for the folded oracles `commit_phase` builds; proofs
+    // take `FridaConfig::folded_leaf_size` instead.
+    pub fn with_folded_leaf_size(mut self, size: usize) -> Self {
+        assert!(size >= 2 && size.is_power_of_two());
+        self.folded_leaf_size = size;
+        self
+    }
+
+    fn leaf_groups(&self) -> usize {
+        self.poly_num / self.leaf_rows
+    }
+
+    // First-oracle positions; leaves are these times `leaf_groups`.
+    fn positions(&self) -> usize {
+        self.interpolation.leave_num() / self.leaf_groups()
+    }
+
+    pub fn domain_size(&self) -> usize {
+        self.positions() * 2
+    }
+
+    pub fn meta(&self) -> CommitmentMeta {
+        CommitmentMeta {
+            merkle_root: self.commit(),
+            poly_num: self.poly_num,
+            log_degree: self.log_degree,
+            code_rate: self.domain_size().ilog2() as usize - self.log_degree,
+            leave_number: self.positions(),
+            leaf_rows: self.leaf_rows,
+            version: ProtocolVersion::CURRENT,
+        }
+    }
+
+    // A retrieval opening of leaf `index` of the first oracle: the evaluations
+    // at x and -x of every polynomial plus a single merkle path, or under
+    // interleaving the half-codewords `group_leaves` places there.
+    pub fn open_chunk(&self, index: usize) -> (Vec<Fr>, Vec<u8>) {
+        assert!(index < self.interpolation.leave_num());
+        (
+            self.interpolation.leaf(index),
+            self.interpolation.merkle_tree.open(&[index]),
+        )
+    }
+
+    // Leaves `start..end` under one multiproof; interior siblings are
+    // recomputed by the verifier, so only the boundary hashes are shipped.
+    pub fn open_range(&self, start: usize, end: usize) -> (Vec<Vec<Fr>>, Vec<u8>) {
+        assert!(start < end && end <= self.interpolation.leave_num());
+        (
+            (start..end).map(|i| self.interpolation.leaf(i)).collect(),
+            self.interpolation
+                .merkle_tree
+                .open(&(start..end).collect::<Vec<_>>()),
+        )
+    }
+
+    // Progress is over the rounds, and cancellation is polled between them
+    // and within each round's tree, as for `new`.
     pub fn commit_phase(
         &self,
-        groups: &Vec<Radix2Group>,
+        groups: &[Radix2Group],
         challenges: &(Fr, Vec<Fr>),
+        options: &ProveOptions,
+    ) -> Result<(IoppProverState, IoppCommits), Cancelled> {
+        let mut betas = challenges.1.iter();
+        self.commit_phase_batched(
+            groups,
+            self.batching,
+            self.folded_leaf_size,
+            challenges.0,
+            options,
+            |_| *betas.next().unwrap(),
+        )
+    }
+
+    // The commit phase one round at a time, for interactive or MPC verifiers:
+    // `next_challenge` is handed the root of the previous folded layer, if
+    // any, and returns that round's challenge, so no challenge has to exist
+    // before the layer it answers is committed. `alpha` comes after `commit`.
+    pub fn commit_phase_interactive<F: FnMut(Option<&[u8; 32]>) -> Fr>(
+        &self,
+        groups: &[Radix2Group],
+        alpha: Fr,
+        next_challenge: F,
     ) -> (IoppProverState, IoppCommits) {
+        self.commit_phase_batched(
+            groups,
+            self.batching,
+            self.folded_leaf_size,
+            alpha,
+            &ProveOptions::default(),
+            next_challenge,
+        )
+        .unwrap()
+    }
+
+    fn commit_phase_batched<F: FnMut(Option<&[u8; 32]>) -> Fr>(
+        &self,
+        groups: &[Radix2Group],
+        batching: Batching,
+        folded_leaf_size: usize,
+        alpha: Fr,
+        options: &ProveOptions,
+        mut next_challenge: F,
+    ) -> Result<(IoppProverState, IoppCommits), Cancelled> {
+        let layout = self.interpolation.layout;
+        let shifts = degree::shifts(&self.degree_bounds, self.log_degree);
+        let weights = batching.weights(alpha, self.poly_num + shifts.len());
         let poly_interpolations = {
             let len = groups[0].size();
             let mut v = (0..len).map(|_| <Fr as Zero>::zero()).collect::<Vec<_>>();
-            for i in 0..len {
-                let mut j = i;
-                for _ in 0..self.poly_num {
-                    v[i] *= challenges.0;
-                    v[i] += self.interpolation.value[j];
-                    j += len;
+            match layout {
+                Layout::Columnar => {
+                    for (codeword, w) in self.interpolation.value.chunks(len).zip(&weights) {
+                        for (v, x) in v.iter_mut().zip(codeword) {
+                            *v += *w * x;
+                        }
+                    }
+                }
+                // Slot j of leaf i holds codeword entry i + j * leaves, so
+                // with fewer rows than polynomials a leaf need not hold x and
+                // -x of every one.
+                Layout::RowMajor => {
+                    let leaf_size = self.leaf_rows * 2;
+                    let leaves = self.interpolation.value.len() / leaf_size;
+                    for (i, leaf) in self.interpolation.value.chunks(leaf_size).enumerate() {
+                        for (j, x) in leaf.iter().enumerate() {
+                            let c = i + j * leaves;
+                            v[c % len] += weights[c / len] * x;
+                        }
+                    }
+                }
+            }
+            for ((k, e), w) in shifts.into_iter().zip(&weights[self.poly_num..]) {
+                for (i, v) in v.iter_mut().enumerate() {
+                    let shifted =
+                        groups[0].element_at(i * e % len) * self.interpolation.get(i + k * len);
+                    *v += *w * shifted;
                 }
             }
             v
@@ -180,58 +945,215 @@ impl Prover {
         let mut final_value = None;
         let inv_2 = <Fr as Field>::inverse(&2.into()).unwrap();
         for i in 0..self.log_degree {
-            let next_evaluation = Self::evaluate_next_domain(
-                if i == 0 {
-                    &poly_interpolations
-                } else {
-                    &interpolations[i - 1].value
-                },
-                &groups[i],
-                inv_2,
-                challenges.1[i],
-            );
+            options.check()?;
+            let root = interpolations.last().map(|x| x.commit());
+            let challenge = next_challenge(root.as_ref());
+            let next_evaluation = if i == 0 {
+                Self::evaluate_next_domain(&poly_interpolations, &groups[i], inv_2, challenge)
+            } else {
+                Self::evaluate_next_layer(&interpolations[i - 1], &groups[i], inv_2, challenge)
+            };
             if i < self.log_degree - 1 {
-                let new_interpolation = InterpolateValue::new(next_evaluation, 2);
+                let leaf_size = folded_leaf_size.min(next_evaluation.len());
+                let new_interpolation = InterpolateValue::try_with_layout(
+                    next_evaluation,
+                    leaf_size,
+                    layout,
+                    options.thread_num(),
+                    |_| !options.cancel.is_cancelled(),
+                )
+                .ok_or(Cancelled)?;
                 interpolations.push(new_interpolation);
             } else {
                 final_value = Some(next_evaluation[0]);
             }
+            options.report(Phase::Fold, i + 1, self.log_degree);
         }
         let iopp_commits = IoppCommits::new(
             interpolations.iter().map(|x| x.commit()).collect(),
             final_value.unwrap(),
         );
-        (IoppProverState { interpolations }, iopp_commits)
+        Ok((
+            IoppProverState {
+                interpolations,
+                commits: iopp_commits.clone(),
+            },
+            iopp_commits,
+        ))
+    }
+
+    // Positions are drawn from the transcript once every round is committed.
+    pub fn sample(&self, prover_state: &IoppProverState, config: &FridaConfig) -> Vec<QueryResult> {
+        self.sample_with::<Blake3Transcript>(prover_state, config)
     }
 
-    pub fn sample(
+    fn sample_with<T: Transcript>(
         &self,
         prover_state: &IoppProverState,
-        mut leaf_indices: Vec<usize>,
-        mut domain_size: usize,
+        config: &FridaConfig,
     ) -> Vec<QueryResult> {
-        let mut query_results = vec![];
-        for i in 0..self.log_degree {
-            domain_size >>= 1;
-            leaf_indices = leaf_indices
-                .iter_mut()
-                .map(|v| *v & (domain_size - 1))
-                .collect();
-            leaf_indices.sort();
-            leaf_indices.dedup();
-            if i == 0 {
-                query_results.push(self.interpolation.query(&leaf_indices));
-            } else {
-                query_results.push(prover_state.interpolations[i - 1].query(&leaf_indices));
-            }
-        }
-        query_results
+        assert_eq!(config.poly_num, self.poly_num);
+        assert_eq!(config.log_degree, self.log_degree);
+        assert_eq!(config.leaf_rows(), self.leaf_rows);
+        let (_, leaf_indices) = replay::<T>(config, &self.commit(), &prover_state.commits);
+        self.query_positions(prover_state, &leaf_indices)
+    }
+
+    fn query_positions(
+        &self,
+        prover_state: &IoppProverState,
+        positions: &[usize],
+    ) -> Vec<QueryResult> {
+        let indices =
+            QuerySchedule::new(self.positions(), self.log_degree).query_indices(positions);
+        (0..self.log_degree)
+            .map(|i| {
+                if i == 0 {
+                    self.interpolation.query(&group_leaves(
+                        indices.round(0),
+                        self.positions(),
+                        self.leaf_groups(),
+                    ))
+                } else {
+                    let oracle = &prover_state.interpolations[i - 1];
+                    oracle.query(&folded_leaves(indices.round(i), oracle.leave_num()))
+                }
+            })
+            .collect()
+    }
+
+    // One client-chosen position through every round, for light-client
+    // sampling; the first entry alone is a first-oracle membership proof.
+    pub fn open_cell(&self, prover_state: &IoppProverState, position: usize) -> Vec<QueryResult> {
+        self.query_positions(prover_state, &[position])
+    }
+
+    // Non-interactive proof: all challenges and query positions come from a
+    // transcript seeded with the config's context.
+    pub fn prove(&self, config: &FridaConfig) -> FridaProof {
+        self.prove_with::<Blake3Transcript>(config)
+    }
+
+    // `prove` under another Fiat-Shamir transcript; the verifier must replay
+    // the same one.
+    pub fn prove_with<T: Transcript>(&self, config: &FridaConfig) -> FridaProof {
+        self.prove_with_state::<T>(config).1
+    }
+
+    fn prove_with_state<T: Transcript>(
+        &self,
+        config: &FridaConfig,
+    ) -> (IoppProverState, FridaProof) {
+        let prover_state = self.fold_transcript::<T>(config);
+        let query_results = self.sample_with::<T>(&prover_state, config);
+        let proof = FridaProof {
+            merkle_root: self.commit(),
+            iopp_commits: prover_state.commits.clone(),
+            query_results,
+            version: ProtocolVersion::CURRENT,
+        };
+        (prover_state, proof)
+    }
+
+    // The commit phase with every challenge drawn from the transcript.
+    pub(crate) fn fold_transcript<T: Transcript>(&self, config: &FridaConfig) -> IoppProverState {
+        assert_eq!(config.poly_num, self.poly_num);
+        assert_eq!(config.log_degree, self.log_degree);
+        assert_eq!(config.leaf_rows(), self.leaf_rows);
+        // A zero size divides by zero and a non-power of two leaves a ragged
+        // last leaf; the verifier rejects both as a shape error.
+        assert!(config.folded_leaf_size >= 2 && config.folded_leaf_size.is_power_of_two());
+        let mut transcript = config.transcript_with::<T>();
+        transcript.absorb(b"merkle_root", &self.commit());
+        let alpha = transcript.challenge_field(b"alpha");
+        let options = ProveOptions::default();
+        self.commit_phase_batched(
+            &config.groups(),
+            config.batching,
+            config.folded_leaf_size,
+            alpha,
+            &options,
+            |root| {
+                if let Some(root) = root {
+                    transcript.absorb(b"round_root", root);
+                }
+                transcript.challenge_field(b"beta")
+            },
+        )
+        .unwrap()
+        .0
+    }
+}
+
+// Challenges and query positions exactly as the prover's transcript produced
+// them, reconstructed from the commitments alone.
+fn replay<T: Transcript>(
+    config: &FridaConfig,
+    merkle_root: &[u8; 32],
+    iopp_commits: &IoppCommits,
+) -> ((Fr, Vec<Fr>), Vec<usize>) {
+    let (_, challenges, leaf_indices) = replay_transcript::<T>(config, merkle_root, iopp_commits);
+    (challenges, leaf_indices)
+}
+
+fn replay_transcript<T: Transcript>(
+    config: &FridaConfig,
+    merkle_root: &[u8; 32],
+    iopp_commits: &IoppCommits,
+) -> (T, (Fr, Vec<Fr>), Vec<usize>) {
+    let mut transcript = config.transcript_with::<T>();
+    transcript.absorb(b"merkle_root", merkle_root);
+    let alpha = transcript.challenge_field(b"alpha");
+    let mut betas = vec![transcript.challenge_field(b"beta")];
+    for root in iopp_commits.merkle_roots.iter() {
+        transcript.absorb(b"round_root", root);
+        betas.push(transcript.challenge_field(b"beta"));
+    }
+    transcript.absorb_field(b"final_value", &iopp_commits.final_value);
+    let leaf_indices = (0..config.query_num)
+        .map(|_| transcript.challenge_index(b"query", config.leave_number()))
+        .collect();
+    (transcript, (alpha, betas), leaf_indices)
+}
+
+pub fn query_indices(
+    config: &FridaConfig,
+    merkle_root: &[u8; 32],
+    iopp_commits: &IoppCommits,
+) -> Vec<usize> {
+    replay::<Blake3Transcript>(config, merkle_root, iopp_commits).1
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FridaProof {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
+    pub merkle_root: [u8; 32],
+    pub iopp_commits: IoppCommits,
+    pub query_results: Vec<QueryResult>,
+    pub version: ProtocolVersion,
+}
+
+impl FridaProof {
+    pub fn proof_size(&self) -> usize {
+        32 + self.iopp_commits.proof_size()
+            + self
+                .query_results
+                .iter()
+                .map(|x| x.proof_size())
+                .sum::<usize>()
     }
 }
 
 pub struct Verifier {
+    // Over the first oracle's positions; see `round_leaves`.
     mt_verifier: MerkleTreeVerifier<Blake32>,
     poly_num: usize,
+    leaf_rows: usize,
+    folded_leaf_size: usize,
+    degree_bounds: Option<Vec<usize>>,
+    batching: Batching,
+    fold_check: FoldCheck,
 }
 
 impl Verifier {
@@ -239,99 +1161,532 @@ impl Verifier {
         Verifier {
             mt_verifier: MerkleTreeVerifier::new(leave_number, &merkle_root),
             poly_num,
+            leaf_rows: poly_num,
+            folded_leaf_size: 2,
+            degree_bounds: None,
+            batching: Batching::Powers,
+            fold_check: FoldCheck::PerIndex,
         }
     }
 
+    // The verifier of roots committed under `config`.
+    pub fn for_config(config: &FridaConfig, merkle_root: &[u8; 32]) -> Self {
+        Verifier::new(*merkle_root, config.poly_num, config.leave_number())
+            .with_batching(config.batching)
+            .with_interleave(config.leaf_rows())
+            .with_folded_leaf_size(config.folded_leaf_size)
+    }
+
+    pub fn merkle_root(&self) -> [u8; 32] {
+        self.mt_verifier.merkle_root
+    }
+
+    pub fn poly_num(&self) -> usize {
+        self.poly_num
+    }
+
+    // For roots committed with `Prover::interleaved`.
+    pub fn with_interleave(mut self, rows: usize) -> Self {
+        self.leaf_rows = rows;
+        self
+    }
+
+    pub fn leave_number(&self) -> usize {
+        self.mt_verifier.leave_number
+    }
+
+    pub fn with_folded_leaf_size(mut self, size: usize) -> Self {
+        self.folded_leaf_size = size;
+        self
+    }
+
+    // Values per leaf of round `round`, whose oracle has `len` positions, as
+    // `FridaConfig::leaf_size` derives them.
+    fn leaf_size(&self, round: usize, len: usize) -> usize {
+        match round {
+            0 => self.leaf_rows * 2,
+            _ => self.folded_leaf_size.min(len * 2),
+        }
+    }
+
+    // The leaves round `round` opens for `indices`, their size and how many
+    // leaves its tree has.
+    pub(crate) fn round_leaves(
+        &self,
+        round: usize,
+        indices: &[usize],
+        len: usize,
+    ) -> (Vec<usize>, usize, usize) {
+        let leaf_size = self.leaf_size(round, len);
+        match round {
+            0 => {
+                let groups = self.poly_num / self.leaf_rows;
+                (group_leaves(indices, len, groups), leaf_size, len * groups)
+            }
+            _ => {
+                let leaves = len * 2 / leaf_size;
+                (folded_leaves(indices, leaves), leaf_size, leaves)
+            }
+        }
+    }
+
+    pub fn domain_size(&self) -> usize {
+        self.mt_verifier.leave_number * 2
+    }
+
+    // Whether `openings` holds every first-oracle leaf of `positions` under
+    // the root.
+    pub(crate) fn verify_positions<O: Openings>(&self, positions: &[usize], openings: &O) -> bool {
+        let (leaves, leaf_size, tree_len) = self.round_leaves(0, positions, self.leave_number());
+        verify_openings(
+            openings,
+            &leaves,
+            leaf_size,
+            &MerkleTreeVerifier::new(tree_len, &self.merkle_root()),
+        )
+    }
+
+    // `leaf_size` is the committed leaf width; chunks of any other width are
+    // rejected before hashing.
+    pub fn verify_chunk(
+        merkle_root: &[u8; 32],
+        leave_number: usize,
+        leaf_size: usize,
+        index: usize,
+        symbols: &[Fr],
+        path: &[u8],
+    ) -> bool {
+        symbols.len() == leaf_size
+            && MerkleTreeVerifier::<Blake32>::new(leave_number, merkle_root).verify(
+                path.to_vec(),
+                &[index],
+                &[Serialize::serialize_fields(symbols)],
+            )
+    }
+
+    pub fn verify_range(
+        merkle_root: &[u8; 32],
+        leave_number: usize,
+        start: usize,
+        symbols: &[Vec<Fr>],
+        path: &[u8],
+    ) -> bool {
+        let end = match start.checked_add(symbols.len()) {
+            Some(end) if !symbols.is_empty() && end <= leave_number => end,
+            _ => return false,
+        };
+        MerkleTreeVerifier::<Blake32>::new(leave_number, merkle_root).verify(
+            path.to_vec(),
+            &(start..end).collect::<Vec<_>>(),
+            &symbols
+                .iter()
+                .map(|x| Serialize::serialize_fields(x))
+                .collect::<Vec<_>>(),
+        )
+    }
+
     pub fn verify(
         &self,
-        groups: &Vec<Radix2Group>,
+        config: &FridaConfig,
+        groups: &[Radix2Group],
         challenges: &(Fr, Vec<Fr>),
-        mut leaf_indices: Vec<usize>,
         iopp_commits: IoppCommits,
         query_results: Vec<QueryResult>,
-    ) {
-        let mt_verifiers = {
-            let mut v = vec![];
-            let mut leave_num = self.mt_verifier.leave_number;
-            for hash in iopp_commits.merkle_roots.iter() {
-                leave_num /= 2;
-                v.push(MerkleTreeVerifier::new(leave_num, hash));
+    ) -> VerificationReport {
+        let leaf_indices = query_indices(config, &self.mt_verifier.merkle_root, &iopp_commits);
+        let mut report = VerificationReport {
+            rounds: challenges.1.len(),
+            queries: leaf_indices.len(),
+            failure: None,
+            opened: vec![],
+        };
+        match self.check(
+            groups,
+            challenges,
+            leaf_indices,
+            &iopp_commits,
+            &query_results,
+        ) {
+            Ok(()) => report.opened = self.opened_symbols(&query_results[0]),
+            Err(e) => report.failure = Some(e),
+        }
+        report
+    }
+
+    // Splits verified first-oracle values per polynomial into (position in the
+    // evaluation domain, value) pairs, sorted by position.
+    fn opened_symbols<O: Openings>(&self, query_result: &O) -> Vec<Vec<(usize, Fr)>> {
+        let len = self.mt_verifier.leave_number;
+        let mut opened = vec![vec![]; self.poly_num];
+        for (key, value) in query_result.entries() {
+            let (leaf, column) = (key % len, key / len);
+            opened[column / 2].push((leaf + (column % 2) * len, value));
+        }
+        opened.iter_mut().for_each(|x| x.sort_by_key(|y| y.0));
+        opened
+    }
+
+    // Checks every length, index range and leaf size the cryptographic pass
+    // relies on, so that pass can index without bounds surprises.
+    pub fn validate(
+        &self,
+        groups: &[Radix2Group],
+        challenges: &(Fr, Vec<Fr>),
+        leaf_indices: &[usize],
+        iopp_commits: &IoppCommits,
+        query_results: &[QueryResult],
+    ) -> Result<(), ShapeError> {
+        self.validate_shapes(
+            groups,
+            challenges,
+            leaf_indices,
+            iopp_commits,
+            query_results,
+        )
+    }
+
+    fn validate_shapes<O: Openings>(
+        &self,
+        groups: &[Radix2Group],
+        challenges: &(Fr, Vec<Fr>),
+        leaf_indices: &[usize],
+        iopp_commits: &IoppCommits,
+        query_results: &[O],
+    ) -> Result<(), ShapeError> {
+        let log_degree = challenges.1.len();
+        self.validate_rounds(log_degree)?;
+        let leave_number = self.mt_verifier.leave_number;
+        if groups.len() < log_degree {
+            return Err(ShapeError::GroupCount {
+                expected: log_degree,
+                actual: groups.len(),
+            });
+        }
+        for (round, group) in groups[..log_degree].iter().enumerate() {
+            let expected = (leave_number * 2) >> round;
+            if group.size() != expected {
+                return Err(ShapeError::GroupSize {
+                    round,
+                    expected,
+                    actual: group.size(),
+                });
             }
-            v
+        }
+        self.validate_queries(log_degree, leaf_indices, iopp_commits, query_results)
+    }
+
+    fn validate_rounds(&self, log_degree: usize) -> Result<(), ShapeError> {
+        if log_degree == 0 {
+            return Err(ShapeError::NoRounds);
+        }
+        if self.leaf_rows == 0 || !self.poly_num.is_multiple_of(self.leaf_rows) {
+            return Err(ShapeError::Interleave {
+                poly_num: self.poly_num,
+                rows: self.leaf_rows,
+            });
+        }
+        if self.folded_leaf_size < 2 || !self.folded_leaf_size.is_power_of_two() {
+            return Err(ShapeError::FoldedLeafSize {
+                size: self.folded_leaf_size,
+            });
+        }
+        let leave_number = self.mt_verifier.leave_number;
+        if !leave_number.is_power_of_two() || leave_number < 1 << (log_degree - 1) {
+            return Err(ShapeError::LeaveNumber {
+                leave_number,
+                rounds: log_degree,
+            });
+        }
+        Ok(())
+    }
+
+    // Everything `validate` checks except the caller-supplied groups; assumes
+    // `validate_rounds` passed.
+    fn validate_queries<O: Openings>(
+        &self,
+        log_degree: usize,
+        leaf_indices: &[usize],
+        iopp_commits: &IoppCommits,
+        query_results: &[O],
+    ) -> Result<(), ShapeError> {
+        let leave_number = self.mt_verifier.leave_number;
+        if iopp_commits.merkle_roots.len() != log_degree - 1 {
+            return Err(ShapeError::RootCount {
+                expected: log_degree - 1,
+                actual: iopp_commits.merkle_roots.len(),
+            });
+        }
+        if query_results.len() != log_degree {
+            return Err(ShapeError::QueryResultCount {
+                expected: log_degree,
+                actual: query_results.len(),
+            });
+        }
+        if leaf_indices.is_empty() {
+            return Err(ShapeError::NoQueries);
+        }
+        if let Some(&index) = leaf_indices.iter().find(|&&x| x >= leave_number * 2) {
+            return Err(ShapeError::IndexOutOfRange {
+                index,
+                domain_size: leave_number * 2,
+            });
+        }
+
+        let schedule = QuerySchedule::new(leave_number, log_degree);
+        let query_indices = schedule.query_indices(leaf_indices);
+        for (round, query_result) in query_results.iter().enumerate() {
+            let (leaves, leaf_size, len) = self.round_leaves(
+                round,
+                query_indices.round(round),
+                schedule.leave_number(round),
+            );
+            if query_result.value_num() != leaf_size * leaves.len() {
+                return Err(ShapeError::LeafSize {
+                    round,
+                    expected: leaf_size * leaves.len(),
+                    actual: query_result.value_num(),
+                });
+            }
+            for position in leaves
+                .iter()
+                .flat_map(|x| (0..leaf_size).map(move |j| x + j * len))
+            {
+                if !query_result.contains(position) {
+                    return Err(ShapeError::MissingValue { round, position });
+                }
+            }
+            if !query_result.path().len().is_multiple_of(32) {
+                return Err(ShapeError::MalformedPath { round });
+            }
+        }
+        Ok(())
+    }
+
+    // Rejects unless `commitment` is the config's binding of the proof's root
+    // and the proof verifies under challenges replayed from the transcript.
+    pub fn verify_proof(
+        config: &FridaConfig,
+        commitment: &[u8; 32],
+        proof: &FridaProof,
+    ) -> VerificationReport {
+        Self::verify_proof_with::<Blake3Transcript>(config, commitment, proof)
+    }
+
+    // `commitment` must come from `FridaConfig::commitment_with::<T>`.
+    pub fn verify_proof_with<T: Transcript>(
+        config: &FridaConfig,
+        commitment: &[u8; 32],
+        proof: &FridaProof,
+    ) -> VerificationReport {
+        if let Some(report) = Self::check_version(config, proof.version) {
+            return report;
+        }
+        Self::verify_slots::<T, _>(
+            config,
+            commitment,
+            &proof.merkle_root,
+            &proof.iopp_commits,
+            &(0..config.query_num).collect::<Vec<_>>(),
+            &proof.query_results,
+        )
+    }
+
+    // Like `verify_proof`, but `query_results` only answers the transcript's
+    // query positions numbered by `slots`.
+    pub(crate) fn verify_slots<T: Transcript, O: Openings>(
+        config: &FridaConfig,
+        commitment: &[u8; 32],
+        merkle_root: &[u8; 32],
+        iopp_commits: &IoppCommits,
+        slots: &[usize],
+        query_results: &[O],
+    ) -> VerificationReport {
+        let mut report = VerificationReport {
+            rounds: config.log_degree,
+            queries: slots.len(),
+            failure: None,
+            opened: vec![],
         };
+        if config.commitment_with::<T>(merkle_root) != *commitment {
+            report.failure = Some(FailedCheck::Transcript);
+            return report;
+        }
+        let (challenges, leaf_indices) = replay::<T>(config, merkle_root, iopp_commits);
+        if let Some(&slot) = slots.iter().find(|&&x| x >= leaf_indices.len()) {
+            report.failure = Some(FailedCheck::Shape(ShapeError::QuerySlot {
+                slot,
+                query_num: leaf_indices.len(),
+            }));
+            return report;
+        }
+        let positions = slots.iter().map(|&x| leaf_indices[x]).collect();
+        let verifier = Verifier::for_config(config, merkle_root);
+        match verifier.check(
+            &config.groups(),
+            &challenges,
+            positions,
+            iopp_commits,
+            query_results,
+        ) {
+            Ok(()) => report.opened = verifier.opened_symbols(&query_results[0]),
+            Err(e) => report.failure = Some(e),
+        }
+        report
+    }
+
+    fn check<O: Openings>(
+        &self,
+        groups: &[Radix2Group],
+        challenges: &(Fr, Vec<Fr>),
+        leaf_indices: Vec<usize>,
+        iopp_commits: &IoppCommits,
+        query_results: &[O],
+    ) -> Result<(), FailedCheck> {
+        self.validate_shapes(
+            groups,
+            challenges,
+            &leaf_indices,
+            iopp_commits,
+            query_results,
+        )?;
+        self.check_rounds(
+            |i, j| groups[i].element_inv_at(j),
+            challenges,
+            &leaf_indices,
+            iopp_commits,
+            query_results,
+        )
+    }
 
+    // The merkle and folding checks proper. `inv_at(i, j)` is the inverse of
+    // the j-th element of round i's group; shapes must already be validated.
+    fn check_rounds<F: Fn(usize, usize) -> Fr, O: Openings>(
+        &self,
+        inv_at: F,
+        challenges: &(Fr, Vec<Fr>),
+        leaf_indices: &[usize],
+        iopp_commits: &IoppCommits,
+        query_results: &[O],
+    ) -> Result<(), FailedCheck> {
         let log_degree = challenges.1.len();
+        let schedule = QuerySchedule::new(self.mt_verifier.leave_number, log_degree);
+        let query_indices = schedule.query_indices(leaf_indices);
+        let shifts = degree::shifts(&self.degree_bounds, log_degree);
+        let weights = self
+            .batching
+            .weights(challenges.0, self.poly_num + shifts.len());
+        let fold_weights = match self.fold_check {
+            FoldCheck::PerIndex => vec![],
+            FoldCheck::Aggregated => {
+                amortized::fold_weights(&self.mt_verifier.merkle_root, iopp_commits, log_degree)
+            }
+        };
         for i in 0..log_degree {
-            let len = groups[i].size();
-            leaf_indices = leaf_indices.iter_mut().map(|v| *v % (len >> 1)).collect();
-            leaf_indices.sort();
-            leaf_indices.dedup();
-
-            query_results[i].verify_merkle_tree(
-                &leaf_indices,
-                if i == 0 { self.poly_num * 2 } else { 2 },
-                if i == 0 {
-                    &self.mt_verifier
-                } else {
-                    &mt_verifiers[i - 1]
-                },
-            );
+            let len = schedule.leave_number(i) * 2;
+            let leaf_indices = query_indices.round(i);
 
+            let (leaves, leaf_size, tree_len) = self.round_leaves(i, leaf_indices, len / 2);
+            let root = match i {
+                0 => &self.mt_verifier.merkle_root,
+                _ => &iopp_commits.merkle_roots[i - 1],
+            };
+            if !verify_openings(
+                &query_results[i],
+                &leaves,
+                leaf_size,
+                &MerkleTreeVerifier::new(tree_len, root),
+            ) {
+                return Err(FailedCheck::MerklePath { round: i });
+            }
+
+            let mut aggregate = Fr::zero();
             for j in leaf_indices.iter() {
                 let new_v = if i == 0 {
-                    let mut res = Fr::from(0);
-                    let mut k = j.clone();
-                    for _ in 0..self.poly_num {
-                        let x = query_results[0].values.get(&k).unwrap().clone();
-                        let nx = query_results[0].values.get(&(k + len / 2)).unwrap().clone();
-                        let sum = x + nx;
-                        res *= challenges.0;
-                        res +=
-                            sum + challenges.1[0] * ((x - nx) * groups[0].element_inv_at(*j) - sum);
-                        k += len;
+                    let values = &query_results[0];
+                    let value = |key: usize| values.value(key).unwrap();
+                    let x_inv = inv_at(0, *j);
+                    let pairs = (0..self.poly_num)
+                        .map(|t| (value(j + t * len), value(j + t * len + len / 2)))
+                        .chain(shifts.iter().map(|&(k, e)| {
+                            // x^e from x^-1, since x^len = 1 and e < len.
+                            let x_e = x_inv.pow([(len - e) as u64]);
+                            let nx_e = if e % 2 == 0 { x_e } else { -x_e };
+                            (
+                                x_e * value(k * len + j),
+                                nx_e * value(k * len + j + len / 2),
+                            )
+                        }))
+                        .zip(&weights);
+                    match self.fold_check {
+                        FoldCheck::PerIndex => pairs
+                            .map(|((x, nx), w)| {
+                                *w * folding::fold_point(x, nx, x_inv, challenges.1[0])
+                            })
+                            .sum(),
+                        // (1 - beta) sum + beta x^-1 diff, with the challenge
+                        // products taken once over the batched sums.
+                        FoldCheck::Aggregated => {
+                            let (sum, diff) = pairs.fold(
+                                (Fr::zero(), Fr::zero()),
+                                |(sum, diff), ((x, nx), w)| {
+                                    (sum + *w * (x + nx), diff + *w * (x - nx))
+                                },
+                            );
+                            (Fr::one() - challenges.1[0]) * sum + challenges.1[0] * x_inv * diff
+                        }
                     }
-                    res
                 } else {
-                    let x = query_results[i].values.get(&j).unwrap().clone();
-                    let nx = query_results[i].values.get(&(j + len / 2)).unwrap().clone();
-                    let sum = x + nx;
-                    sum + challenges.1[i] * ((x - nx) * groups[i].element_inv_at(*j) - sum)
+                    let x = query_results[i].value(*j).unwrap();
+                    let nx = query_results[i].value(j + len / 2).unwrap();
+                    folding::fold_point(x, nx, inv_at(i, *j), challenges.1[i])
                 };
-                if i < log_degree - 1 {
-                    assert_eq!(new_v, query_results[i + 1].values[j].double());
+                let expected = if i < log_degree - 1 {
+                    query_results[i + 1].value(*j).unwrap()
                 } else {
-                    assert_eq!(new_v, iopp_commits.final_value.double());
+                    iopp_commits.final_value
+                };
+                match self.fold_check {
+                    FoldCheck::PerIndex if expected.double() == new_v => {}
+                    FoldCheck::PerIndex if i < log_degree - 1 => {
+                        return Err(FailedCheck::Folding {
+                            round: i,
+                            index: *j,
+                        });
+                    }
+                    FoldCheck::PerIndex => return Err(FailedCheck::FinalValue { index: *j }),
+                    FoldCheck::Aggregated => {
+                        aggregate = aggregate * fold_weights[i] + expected.double() - new_v
+                    }
                 }
             }
+            if !aggregate.is_zero() {
+                return Err(FailedCheck::AggregatedFolding { round: i });
+            }
         }
+        Ok(())
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 mod tests {
     use ark_ff::UniformRand;
-    use rand::{thread_rng, RngCore};
+    use rand::thread_rng;
 
     use super::*;
+    use crate::fixtures::{random_polies, random_prover};
 
     #[test]
     fn it_works() {
         let mut rng = thread_rng();
         let poly_num = 16;
         let log_degree = 12;
-        let polies = (0..poly_num)
-            .map(|_| {
-                (0..(1 << log_degree))
-                    .map(|_| <Fr as UniformRand>::rand(&mut rng))
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+        let polies = random_polies(poly_num, 1 << log_degree);
         let coderate = 1;
         let groups = (0..log_degree)
             .rev()
             .map(|x| Radix2Group::new(x + 1 + coderate))
             .collect::<Vec<_>>();
-        let prover = Prover::new(&polies, &groups[0]);
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
         let challenges = {
             (
                 <Fr as UniformRand>::rand(&mut rng),
@@ -340,21 +1695,572 @@ mod tests {
                     .collect::<Vec<_>>(),
             )
         };
-        let leaf_indices = (0..30).map(|_| rng.next_u32() as usize).collect::<Vec<_>>();
-        let (prover_state, iopp_commits) = prover.commit_phase(&groups, &challenges);
-        let query_results = prover.sample(
-            &prover_state,
-            leaf_indices.clone(),
-            1 << (log_degree + coderate),
-        );
+        let config = FridaConfig::new(b"test", poly_num, log_degree, coderate, 30);
+        let (prover_state, iopp_commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        let query_results = prover.sample(&prover_state, &config);
         let commit = prover.commit();
         let verifier = Verifier::new(commit, poly_num, 1 << (log_degree + coderate - 1));
-        verifier.verify(
-            &groups,
-            &challenges,
-            leaf_indices,
-            iopp_commits,
-            query_results,
+        verifier
+            .verify(&config, &groups, &challenges, iopp_commits, query_results)
+            .into_result()
+            .unwrap();
+    }
+
+    #[test]
+    fn context_binding() {
+        let config = FridaConfig::new(b"chain-1/blobs/v1", 4, 8, 1, 20);
+        let (_, prover) = random_prover(&config);
+        let proof = prover.prove(&config);
+        let commitment = config.commitment(&prover.commit());
+        assert!(Verifier::verify_proof(&config, &commitment, &proof).accepted());
+
+        let other = FridaConfig {
+            context: b"chain-2/blobs/v1".to_vec(),
+            ..config.clone()
+        };
+        assert_ne!(other.commitment(&prover.commit()), commitment);
+        assert_eq!(
+            Verifier::verify_proof(&other, &commitment, &proof).failure,
+            Some(FailedCheck::Transcript)
+        );
+        let other_commitment = other.commitment(&prover.commit());
+        assert!(!Verifier::verify_proof(&other, &other_commitment, &proof).accepted());
+
+        // Same codeword read under a different encoding claim.
+        let other = FridaConfig {
+            poly_num: 2,
+            log_degree: 9,
+            ..config.clone()
+        };
+        assert!(!Verifier::verify_proof(&other, &commitment, &proof).accepted());
+    }
+
+    #[test]
+    fn shape_errors() {
+        let mut rng = thread_rng();
+        let (poly_num, log_degree, coderate) = (2, 6, 1);
+        let polies = random_polies(poly_num, 1 << log_degree);
+        let groups = (0..log_degree)
+            .rev()
+            .map(|x| Radix2Group::new(x + 1 + coderate))
+            .collect::<Vec<_>>();
+        let challenges = (
+            <Fr as UniformRand>::rand(&mut rng),
+            (0..log_degree)
+                .map(|_| <Fr as UniformRand>::rand(&mut rng))
+                .collect::<Vec<_>>(),
+        );
+        let config = FridaConfig::new(b"test", poly_num, log_degree, coderate, 2);
+        let domain_size = config.domain_size();
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        let (prover_state, iopp_commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        let leaf_indices = query_indices(&config, &prover.commit(), &iopp_commits);
+        let query_results = prover.sample(&prover_state, &config);
+        let verifier = Verifier::new(prover.commit(), poly_num, domain_size / 2);
+        let validate = |indices: &[usize], commits: &IoppCommits, results: &[QueryResult]| {
+            verifier.validate(&groups, &challenges, indices, commits, results)
+        };
+        assert_eq!(
+            validate(&leaf_indices, &iopp_commits, &query_results),
+            Ok(())
+        );
+
+        assert_eq!(
+            validate(&leaf_indices, &iopp_commits, &query_results[1..]),
+            Err(ShapeError::QueryResultCount {
+                expected: log_degree,
+                actual: log_degree - 1
+            })
+        );
+        let mut commits = iopp_commits.clone();
+        commits.merkle_roots.pop();
+        assert!(matches!(
+            validate(&leaf_indices, &commits, &query_results),
+            Err(ShapeError::RootCount { .. })
+        ));
+        assert_eq!(
+            validate(&[domain_size], &iopp_commits, &query_results),
+            Err(ShapeError::IndexOutOfRange {
+                index: domain_size,
+                domain_size
+            })
+        );
+        assert_eq!(
+            validate(&[], &iopp_commits, &query_results),
+            Err(ShapeError::NoQueries)
+        );
+        let mut results = query_results.clone();
+        let position = *results[2].values.keys().next().unwrap();
+        results[2].values.remove(&position);
+        assert!(matches!(
+            validate(&leaf_indices, &iopp_commits, &results),
+            Err(ShapeError::LeafSize { round: 2, .. })
+        ));
+        assert!(matches!(
+            verifier
+                .verify(
+                    &config,
+                    &groups[1..],
+                    &challenges,
+                    iopp_commits,
+                    query_results
+                )
+                .failure,
+            Some(FailedCheck::Shape(ShapeError::GroupCount { .. }))
+        ));
+    }
+
+    #[test]
+    fn failed_checks() {
+        let config = FridaConfig::new(b"report", 2, 6, 1, 8);
+        let (_, prover) = random_prover(&config);
+        let commitment = config.commitment(&prover.commit());
+        let proof = prover.prove(&config);
+        let report = Verifier::verify_proof(&config, &commitment, &proof);
+        assert_eq!(report.rounds, config.log_degree);
+        assert!(report.into_result().is_ok());
+
+        let mut bad = proof.clone();
+        let v = bad.query_results[0].values.values_mut().next().unwrap();
+        *v += Fr::from(1);
+        assert_eq!(
+            Verifier::verify_proof(&config, &commitment, &bad).failure,
+            Some(FailedCheck::MerklePath { round: 0 })
+        );
+
+        let mut bad = proof.clone();
+        bad.iopp_commits.final_value += Fr::from(1);
+        // The final value feeds the transcript, so the queries move as well.
+        assert!(!Verifier::verify_proof(&config, &commitment, &bad).accepted());
+
+        // Replay the transcript with a wrong folding challenge through the
+        // explicit-challenge API to hit the folding check.
+        let groups = config.groups();
+        let challenges = (
+            Fr::from(3),
+            (0..config.log_degree).map(|x| Fr::from(x as u64)).collect(),
+        );
+        let (state, commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        let results = prover.sample(&state, &config);
+        let verifier = Verifier::new(prover.commit(), config.poly_num, config.leave_number());
+        let mut wrong = challenges.clone();
+        wrong.1[2] += Fr::from(1);
+        assert!(matches!(
+            verifier
+                .verify(&config, &groups, &wrong, commits.clone(), results.clone())
+                .failure,
+            Some(FailedCheck::Folding { round: 2, .. })
+        ));
+        wrong = challenges.clone();
+        wrong.1[config.log_degree - 1] += Fr::from(1);
+        assert!(matches!(
+            verifier
+                .verify(&config, &groups, &wrong, commits, results)
+                .failure,
+            Some(FailedCheck::FinalValue { .. })
+        ));
+    }
+
+    #[test]
+    fn chunk_retrieval() {
+        let (poly_num, log_degree) = (3, 5);
+        let polies = random_polies(poly_num, 1 << log_degree);
+        let group = Radix2Group::new(log_degree + 1);
+        let prover = Prover::new(&polies, &group, &ProveOptions::default()).unwrap();
+        let root = prover.commit();
+        let leave_number = group.size() / 2;
+        let evaluations = polies
+            .iter()
+            .map(|x| group.fft(x.clone()))
+            .collect::<Vec<_>>();
+        for index in [0, 7, leave_number - 1] {
+            let (symbols, path) = prover.open_chunk(index);
+            assert_eq!(symbols.len(), poly_num * 2);
+            assert_eq!(symbols[0], evaluations[0][index]);
+            assert_eq!(symbols[1], evaluations[0][index + leave_number]);
+            assert!(Verifier::verify_chunk(
+                &root,
+                leave_number,
+                poly_num * 2,
+                index,
+                &symbols,
+                &path
+            ));
+            assert!(!Verifier::verify_chunk(
+                &root,
+                leave_number,
+                poly_num * 2,
+                (index + 1) % leave_number,
+                &symbols,
+                &path
+            ));
+            let mut tampered = symbols.clone();
+            tampered[poly_num] += Fr::from(1);
+            assert!(!Verifier::verify_chunk(
+                &root,
+                leave_number,
+                poly_num * 2,
+                index,
+                &tampered,
+                &path
+            ));
+            assert!(!Verifier::verify_chunk(
+                &root,
+                leave_number,
+                poly_num * 2,
+                index,
+                &symbols[..poly_num],
+                &path
+            ));
+        }
+    }
+
+    #[test]
+    fn range_retrieval() {
+        let (poly_num, log_degree) = (2, 8);
+        let polies = random_polies(poly_num, 1 << log_degree);
+        let group = Radix2Group::new(log_degree + 1);
+        let prover = Prover::new(&polies, &group, &ProveOptions::default()).unwrap();
+        let root = prover.commit();
+        let leave_number = group.size() / 2;
+
+        let (symbols, path) = prover.open_range(64, 128);
+        assert_eq!(symbols.len(), 64);
+        assert_eq!(symbols[5], prover.open_chunk(69).0);
+        assert!(Verifier::verify_range(
+            &root,
+            leave_number,
+            64,
+            &symbols,
+            &path
+        ));
+        // An aligned range of 64 leaves needs just the path of its subtree root.
+        assert_eq!(path.len(), 32 * 2);
+        let single_paths = (64..128)
+            .map(|i| prover.open_chunk(i).1.len())
+            .sum::<usize>();
+        assert!(path.len() * 10 < single_paths);
+
+        assert!(!Verifier::verify_range(
+            &root,
+            leave_number,
+            63,
+            &symbols,
+            &path
+        ));
+        let mut tampered = symbols.clone();
+        tampered[63][0] += Fr::from(1);
+        assert!(!Verifier::verify_range(
+            &root,
+            leave_number,
+            64,
+            &tampered,
+            &path
+        ));
+
+        let (symbols, path) = prover.open_range(leave_number - 3, leave_number);
+        assert!(Verifier::verify_range(
+            &root,
+            leave_number,
+            leave_number - 3,
+            &symbols,
+            &path
+        ));
+        assert!(!Verifier::verify_range(
+            &root,
+            leave_number,
+            leave_number - 2,
+            &symbols,
+            &path
+        ));
+        assert!(!Verifier::verify_range(
+            &root,
+            leave_number,
+            usize::MAX - 1,
+            &symbols,
+            &path
+        ));
+    }
+
+    #[test]
+    fn opened_symbols() {
+        let config = FridaConfig::new(b"opened", 3, 5, 1, 6);
+        let polies = random_polies(config.poly_num, 1 << config.log_degree);
+        let groups = config.groups();
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        let commitment = config.commitment(&prover.commit());
+        let proof = prover.prove(&config);
+        let report = Verifier::verify_proof(&config, &commitment, &proof);
+        assert!(report.accepted());
+
+        let indices = QuerySchedule::from_config(&config).indices(
+            0,
+            &query_indices(&config, &prover.commit(), &proof.iopp_commits),
+        );
+        assert_eq!(report.opened.len(), config.poly_num);
+        for (poly, opened) in polies.iter().zip(report.opened.iter()) {
+            let evaluations = groups[0].fft(poly.clone());
+            assert_eq!(opened.len(), indices.len() * 2);
+            for (position, value) in opened {
+                assert!(indices.contains(&(position % config.leave_number())));
+                assert_eq!(evaluations[*position], *value);
+            }
+        }
+
+        let mut bad = proof.clone();
+        bad.iopp_commits.final_value += Fr::from(1);
+        let report = Verifier::verify_proof(&config, &commitment, &bad);
+        assert!(!report.accepted() && report.opened.is_empty());
+    }
+
+    #[test]
+    fn metadata() {
+        let mut rng = thread_rng();
+        let polies = (0..4)
+            .map(|_| {
+                (0..(1 << 5))
+                    .map(|_| <Fr as UniformRand>::rand(&mut rng))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let groups = (0..5)
+            .rev()
+            .map(|x| Radix2Group::new(x + 1 + 2))
+            .collect::<Vec<_>>();
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        assert_eq!((prover.poly_num(), prover.log_degree()), (4, 5));
+        assert_eq!(prover.domain_size(), 128);
+        let meta = prover.meta();
+        assert_eq!(meta.code_rate, 2);
+        assert_eq!(meta.leaf_size(), 8);
+        assert_eq!(meta.domain_size(), prover.domain_size());
+        assert_eq!(
+            Prover::interleaved(&polies, &groups[0], 2)
+                .meta()
+                .leaf_size(),
+            4
+        );
+
+        let challenges = (Fr::from(2), (0..5).map(|x| Fr::from(x as u64)).collect());
+        let (_, iopp_commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        assert_eq!(iopp_commits.roots().len(), 4);
+        let verifier = Verifier::new(meta.merkle_root, meta.poly_num, meta.leave_number);
+        assert_eq!(verifier.merkle_root(), prover.commit());
+        assert_eq!(verifier.domain_size(), 128);
+        assert_eq!(verifier.leave_number(), 64);
+        assert_eq!(verifier.poly_num(), 4);
+    }
+
+    #[test]
+    fn fixed_leaf_widths() {
+        let mut rng = thread_rng();
+        for leaf_size in [2, 6, 8, 32] {
+            let value = (0..leaf_size * 16)
+                .map(|_| <Fr as UniformRand>::rand(&mut rng))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                leaf_bytes(&value, leaf_size),
+                leaf_bytes_dynamic(&value, leaf_size)
+            );
+        }
+    }
+
+    #[test]
+    fn layouts() {
+        let config = FridaConfig::new(b"layouts", 3, 6, 2, 12);
+        let polies = random_polies(config.poly_num, 1 << config.log_degree);
+        let group = &config.groups()[0];
+        let columnar = Prover::with_layout(&polies, group, Layout::Columnar);
+        let row_major = Prover::with_layout(&polies, group, Layout::RowMajor);
+        assert_eq!(row_major.layout(), Layout::RowMajor);
+        assert_eq!(columnar.commit(), row_major.commit());
+        assert_eq!(
+            columnar.interpolation.codeword(),
+            row_major.interpolation.codeword()
+        );
+        assert_eq!(columnar.open_chunk(5), row_major.open_chunk(5));
+
+        let proof = row_major.prove(&config);
+        assert_eq!(
+            proof.iopp_commits.digest(),
+            columnar.prove(&config).iopp_commits.digest()
+        );
+        let commitment = config.commitment(&row_major.commit());
+        assert!(Verifier::verify_proof(&config, &commitment, &proof).accepted());
+    }
+
+    #[test]
+    fn interleaved_leaves() {
+        let config = FridaConfig::new(b"interleave", 4, 5, 1, 8);
+        let polies = random_polies(config.poly_num, 1 << config.log_degree);
+        let group = &config.groups()[0];
+        let full = Prover::new(&polies, group, &ProveOptions::default()).unwrap();
+        for rows in [1, 2, 4] {
+            let config = FridaConfig {
+                interleave: Interleave::Rows(rows),
+                ..config.clone()
+            };
+            let prover = Prover::interleaved(&polies, group, rows);
+            let row_major = Prover::build(
+                &polies,
+                group,
+                Layout::RowMajor,
+                rows,
+                &ProveOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(
+                prover.prove(&config).iopp_commits.digest(),
+                row_major.prove(&config).iopp_commits.digest()
+            );
+            assert_eq!(prover.domain_size(), full.domain_size());
+            assert_eq!(prover.open_chunk(3).0.len(), 2 * rows);
+            assert_eq!(rows == 4, prover.commit() == full.commit());
+            let proof = prover.prove(&config);
+            let commitment = config.commitment(&prover.commit());
+            assert!(Verifier::verify_proof(&config, &commitment, &proof).accepted());
+            let cell = prover.open_cell(&prover.fold_transcript::<Blake3Transcript>(&config), 7);
+            assert!(cell::CellVerifier::membership(&config, prover.commit()).verify(7, &cell));
+        }
+        let odd = FridaConfig {
+            interleave: Interleave::Rows(3),
+            ..config.clone()
+        };
+        let proof = full.prove(&config);
+        assert_eq!(
+            Verifier::verify_proof(&odd, &odd.commitment(&proof.merkle_root), &proof).failure,
+            Some(FailedCheck::Shape(ShapeError::Interleave {
+                poly_num: 4,
+                rows: 3
+            }))
+        );
+    }
+
+    #[test]
+    fn folded_leaf_sizes() {
+        let config = FridaConfig::new(b"folded", 2, 6, 1, 10);
+        let polies = random_polies(config.poly_num, 1 << config.log_degree);
+        let group = &config.groups()[0];
+        let columnar = Prover::new(&polies, group, &ProveOptions::default()).unwrap();
+        let row_major = Prover::with_layout(&polies, group, Layout::RowMajor);
+        for size in [2, 8, 64] {
+            let config = FridaConfig {
+                folded_leaf_size: size,
+                ..config.clone()
+            };
+            let proof = columnar.prove(&config);
+            assert_eq!(
+                proof.iopp_commits.digest(),
+                row_major.prove(&config).iopp_commits.digest()
+            );
+            for (round, result) in proof.query_results.iter().enumerate() {
+                assert!(result.value_num().is_multiple_of(config.leaf_size(round)));
+            }
+            let commitment = config.commitment(&proof.merkle_root);
+            assert!(Verifier::verify_proof(&config, &commitment, &proof).accepted());
+        }
+        assert_eq!(
+            (1..6)
+                .map(|i| FridaConfig {
+                    folded_leaf_size: 16,
+                    ..config.clone()
+                }
+                .leaf_size(i))
+                .collect::<Vec<_>>(),
+            [16, 16, 16, 8, 4]
+        );
+        let odd = FridaConfig {
+            folded_leaf_size: 3,
+            ..config.clone()
+        };
+        let proof = columnar.prove(&config);
+        assert_eq!(
+            Verifier::verify_proof(&odd, &odd.commitment(&proof.merkle_root), &proof).failure,
+            Some(FailedCheck::Shape(ShapeError::FoldedLeafSize { size: 3 }))
+        );
+    }
+
+    #[test]
+    fn evaluation_input() {
+        let config = FridaConfig::new(b"evaluations", 3, 5, 2, 10);
+        let polies = random_polies(config.poly_num, 1 << config.log_degree);
+        let subgroup = Radix2Group::new(config.log_degree);
+        let evals = polies
+            .iter()
+            .map(|x| subgroup.fft(x.clone()))
+            .collect::<Vec<_>>();
+        let prover = Prover::from_evaluations(&evals, &config);
+        assert_eq!(
+            prover.commit(),
+            Prover::new(&polies, &config.groups()[0], &ProveOptions::default())
+                .unwrap()
+                .commit()
+        );
+        let proof = prover.prove(&config);
+        let commitment = config.commitment(&prover.commit());
+        assert!(Verifier::verify_proof(&config, &commitment, &proof).accepted());
+    }
+
+    #[test]
+    fn streamed_input() {
+        let poly_num = 5;
+        let log_degree = 6;
+        let groups = (0..log_degree)
+            .rev()
+            .map(|x| Radix2Group::new(x + 1 + 1))
+            .collect::<Vec<_>>();
+        let poly = |k: usize| {
+            (0..(1 << log_degree))
+                .map(|i| Fr::from((k * 131 + i) as u64))
+                .collect::<Vec<_>>()
+        };
+        let streamed = Prover::from_stream((0..poly_num).map(poly), &groups[0]);
+        let polies = (0..poly_num).map(poly).collect::<Vec<_>>();
+        let prover = Prover::with_layout(&polies, &groups[0], Layout::Columnar);
+        assert_eq!(streamed.commit(), prover.commit());
+        assert_eq!((streamed.poly_num(), streamed.log_degree()), (5, 6));
+    }
+
+    #[test]
+    fn interactive_rounds() {
+        let mut rng = thread_rng();
+        let polies = (0..2)
+            .map(|_| {
+                (0..(1 << 4))
+                    .map(|_| <Fr as UniformRand>::rand(&mut rng))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let groups = (0..4)
+            .rev()
+            .map(|x| Radix2Group::new(x + 1 + 1))
+            .collect::<Vec<_>>();
+        let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+        let alpha = <Fr as UniformRand>::rand(&mut rng);
+        let mut seen = vec![];
+        let mut betas = vec![];
+        let (_, interactive) = prover.commit_phase_interactive(&groups, alpha, |root| {
+            seen.push(root.copied());
+            let beta = <Fr as UniformRand>::rand(&mut rng);
+            betas.push(beta);
+            beta
+        });
+        assert_eq!(seen[0], None);
+        assert_eq!(
+            seen[1..].iter().map(|x| x.unwrap()).collect::<Vec<_>>(),
+            interactive.roots()
         );
+        let (_, upfront) = prover
+            .commit_phase(&groups, &(alpha, betas), &ProveOptions::default())
+            .unwrap();
+        assert_eq!(interactive.digest(), upfront.digest());
     }
 }
@@ -0,0 +1,284 @@
+// A mempool whose transactions are dispersed as one blob and retrieved one at
+// a time. Transactions are laid out in arrival order, each starting on an
+// element boundary, and committed systematically, so a transaction's bytes
+// are exactly the symbols at its elements' positions: fetching it takes the
+// few leaves holding those positions and one multiproof, not a
+// reconstruction. The index, keyed by transaction hash, only says where to
+// look; a retrieval is checked against the transaction's own hash, so a wrong
+// or stale index yields no transaction rather than a wrong one.
+use std::{collections::HashSet, fmt};
+
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+
+#[cfg(feature = "prover")]
+use crate::{address::Retrieval, Prover};
+use crate::{
+    address::{AddressMap, Encoding},
+    plan::BYTES_PER_ELEMENT,
+    service::SampleResponse,
+    FridaConfig,
+};
+
+pub fn tx_hash(tx: &[u8]) -> [u8; 32] {
+    *blake3::hash(tx).as_bytes()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxLocation {
+    pub hash: [u8; 32],
+    // In bytes, a multiple of BYTES_PER_ELEMENT.
+    pub offset: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolError {
+    NoTransactions,
+    EmptyTransaction { position: usize },
+    TooLarge { blob_len: usize, capacity: usize },
+}
+
+impl fmt::Display for MempoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MempoolError::NoTransactions => write!(f, "no transactions to commit"),
+            MempoolError::EmptyTransaction { position } => {
+                write!(f, "transaction {} is empty", position)
+            }
+            MempoolError::TooLarge { blob_len, capacity } => {
+                write!(f, "{} bytes exceed the config's {}", blob_len, capacity)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MempoolError {}
+
+// Transaction hash to blob range, sorted by hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MempoolIndex {
+    entries: Vec<TxLocation>,
+    blob_len: usize,
+}
+
+impl MempoolIndex {
+    // Where `txs` go, in order; a repeated transaction keeps its first slot.
+    pub fn new(txs: &[Vec<u8>]) -> Result<Self, MempoolError> {
+        if txs.is_empty() {
+            return Err(MempoolError::NoTransactions);
+        }
+        let mut entries: Vec<TxLocation> = vec![];
+        let mut seen = HashSet::new();
+        let mut offset = 0;
+        for (position, tx) in txs.iter().enumerate() {
+            if tx.is_empty() {
+                return Err(MempoolError::EmptyTransaction { position });
+            }
+            let hash = tx_hash(tx);
+            if !seen.insert(hash) {
+                continue;
+            }
+            entries.push(TxLocation {
+                hash,
+                offset,
+                len: tx.len(),
+            });
+            offset += tx.len().next_multiple_of(BYTES_PER_ELEMENT);
+        }
+        entries.sort_by_key(|x| x.hash);
+        Ok(MempoolIndex {
+            entries,
+            blob_len: offset,
+        })
+    }
+
+    // The bytes the blob needs, padding included; what a config is sized for.
+    pub fn blob_len(&self) -> usize {
+        self.blob_len
+    }
+
+    pub fn tx_num(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn locate(&self, hash: &[u8; 32]) -> Option<TxLocation> {
+        self.entries
+            .binary_search_by_key(hash, |x| x.hash)
+            .ok()
+            .map(|i| self.entries[i])
+    }
+
+    // The codeword positions and leaves of the transaction under `config`.
+    #[cfg(feature = "prover")]
+    pub fn retrieval(&self, config: &FridaConfig, hash: &[u8; 32]) -> Option<Retrieval> {
+        let location = self.locate(hash)?;
+        AddressMap::new(config, Encoding::Systematic).retrieval(location.offset, location.len)
+    }
+}
+
+fn element(bytes: &[u8]) -> Fr {
+    Fr::from_le_bytes_mod_order(bytes)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxProof {
+    pub location: TxLocation,
+    pub response: SampleResponse,
+}
+
+// Whether `tx` is the one `proof` opens from the blob under `root`.
+pub fn verify_tx(config: &FridaConfig, root: &[u8; 32], tx: &[u8], proof: &TxProof) -> bool {
+    let location = &proof.location;
+    if location.hash != tx_hash(tx)
+        || location.len != tx.len()
+        || !location.offset.is_multiple_of(BYTES_PER_ELEMENT)
+    {
+        return false;
+    }
+    let map = AddressMap::new(config, Encoding::Systematic);
+    let Some(retrieval) = map.retrieval(location.offset, location.len) else {
+        return false;
+    };
+    let response = &proof.response;
+    if response.leaves != retrieval.leaves
+        || !response.verify(root, config.first_oracle_leaves())
+        || response
+            .symbols
+            .iter()
+            .any(|x| x.len() != config.leaf_size(0))
+    {
+        return false;
+    }
+    retrieval
+        .positions
+        .iter()
+        .zip(tx.chunks(BYTES_PER_ELEMENT))
+        .all(|(position, chunk)| {
+            let (leaf, symbol) = map.leaf_of(*position);
+            let i = response.leaves.binary_search(&leaf).unwrap();
+            response.symbols[i][symbol] == element(chunk)
+        })
+}
+
+#[cfg(feature = "prover")]
+pub struct Mempool {
+    config: FridaConfig,
+    index: MempoolIndex,
+    prover: Prover,
+}
+
+#[cfg(feature = "prover")]
+impl Mempool {
+    pub fn build(config: &FridaConfig, txs: &[Vec<u8>]) -> Result<Self, MempoolError> {
+        let index = MempoolIndex::new(txs)?;
+        let map = AddressMap::new(config, Encoding::Systematic);
+        if index.blob_len > map.capacity() {
+            return Err(MempoolError::TooLarge {
+                blob_len: index.blob_len,
+                capacity: map.capacity(),
+            });
+        }
+        let degree = 1 << config.log_degree;
+        let mut elements = vec![Fr::from(0u64); config.poly_num * degree];
+        for tx in txs {
+            let location = index.locate(&tx_hash(tx)).unwrap();
+            let start = location.offset / BYTES_PER_ELEMENT;
+            for (i, chunk) in tx.chunks(BYTES_PER_ELEMENT).enumerate() {
+                elements[start + i] = element(chunk);
+            }
+        }
+        let evals = elements
+            .chunks(degree)
+            .map(|x| x.to_vec())
+            .collect::<Vec<_>>();
+        Ok(Mempool {
+            config: config.clone(),
+            index,
+            prover: Prover::from_evaluations(&evals, config),
+        })
+    }
+
+    pub fn index(&self) -> &MempoolIndex {
+        &self.index
+    }
+
+    // For the dispersal proof and the shares, as for any blob.
+    pub fn prover(&self) -> &Prover {
+        &self.prover
+    }
+
+    pub fn prove_tx(&self, hash: &[u8; 32]) -> Option<TxProof> {
+        let retrieval = self.index.retrieval(&self.config, hash)?;
+        Some(TxProof {
+            location: self.index.locate(hash)?,
+            response: self.prover.open_leaves(&retrieval.leaves).ok()?,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::{Interleave, Verifier};
+
+    #[test]
+    fn mempool_retrieval() {
+        let mut rng = thread_rng();
+        let config = FridaConfig::new(b"mempool", 4, 4, 2, 8);
+        let mut txs = [1, 31, 32, 200, 700]
+            .iter()
+            .map(|&len| (0..len).map(|_| rng.gen::<u8>()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        txs.push(txs[1].clone());
+        let mempool = Mempool::build(&config, &txs).unwrap();
+        assert_eq!(mempool.index().tx_num(), 5);
+        assert_eq!(mempool.index().blob_len(), 31 * (1 + 1 + 2 + 7 + 23));
+        let root = mempool.prover().commit();
+        let proof = mempool.prover().prove(&config);
+        assert!(Verifier::verify_proof(&config, &config.commitment(&root), &proof).accepted());
+
+        for tx in &txs {
+            let proof = mempool.prove_tx(&tx_hash(tx)).unwrap();
+            assert!(verify_tx(&config, &root, tx, &proof));
+            assert!(!verify_tx(&config, &[0; 32], tx, &proof));
+            let mut forged = tx.clone();
+            forged[0] ^= 1;
+            assert!(!verify_tx(&config, &root, &forged, &proof));
+        }
+        // A location pointing at another transaction's bytes.
+        let mut moved = mempool.prove_tx(&tx_hash(&txs[2])).unwrap();
+        moved.location.offset = mempool.index().locate(&tx_hash(&txs[3])).unwrap().offset;
+        assert!(!verify_tx(&config, &root, &txs[2], &moved));
+        assert!(mempool.prove_tx(&[0; 32]).is_none());
+
+        // Two polynomials to a leaf: each symbol is fetched from its group's
+        // leaf.
+        let rows = FridaConfig {
+            interleave: Interleave::Rows(2),
+            ..config.clone()
+        };
+        let mempool = Mempool::build(&rows, &txs).unwrap();
+        let root = mempool.prover().commit();
+        let proof = mempool.prover().prove(&rows);
+        assert!(Verifier::verify_proof(&rows, &rows.commitment(&root), &proof).accepted());
+        for tx in &txs {
+            let proof = mempool.prove_tx(&tx_hash(tx)).unwrap();
+            assert!(verify_tx(&rows, &root, tx, &proof));
+        }
+
+        assert_eq!(
+            MempoolIndex::new(&[vec![1], vec![]]),
+            Err(MempoolError::EmptyTransaction { position: 1 })
+        );
+        assert_eq!(
+            Mempool::build(&config, &[vec![0; 2000]]).err(),
+            Some(MempoolError::TooLarge {
+                blob_len: 2015,
+                capacity: 1984
+            })
+        );
+    }
+}
@@ -0,0 +1,199 @@
+// Progress reporting and cooperative cancellation for long commitments, taken
+// by `Prover::new` and `commit_phase`. The token is polled between polynomial
+// transforms, between batches of leaf hashes, between merkle tree levels and
+// between folding rounds, so an abort takes effect within one of those steps;
+// a cancelled job returns `Cancelled` and keeps nothing.
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use util::placement::Placement;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    // The polynomial transforms.
+    Encode,
+    // Hashing the first oracle's merkle tree.
+    Commit,
+    // The folding rounds.
+    Fold,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "proving was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+// The callback receives the phase and the fraction of it done; transforms
+// run on several threads, so it may be called from any of them. `threads`
+// caps the transform threads, all available cores if unset. Every thread
+// owns a fixed range of polynomials and writes only that range, so the
+// codeword, and with it every root and proof, is the same for any count.
+// `placement` decides where the codeword buffer lives, each thread's range
+// on the node the thread is pinned to.
+#[derive(Default)]
+pub struct ProveOptions {
+    pub progress: Option<Box<dyn Fn(Phase, f32) + Send + Sync>>,
+    pub cancel: CancellationToken,
+    pub threads: Option<usize>,
+    pub placement: Placement,
+}
+
+impl ProveOptions {
+    pub fn with_progress<F: Fn(Phase, f32) + Send + Sync + 'static>(mut self, progress: F) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0);
+        self.threads = Some(threads);
+        self
+    }
+
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    #[cfg(feature = "prover")]
+    pub(crate) fn thread_num(&self) -> usize {
+        self.threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |x| x.get()))
+    }
+
+    #[cfg(feature = "prover")]
+    pub(crate) fn report(&self, phase: Phase, done: usize, total: usize) {
+        if let Some(progress) = &self.progress {
+            progress(phase, done as f32 / total.max(1) as f32);
+        }
+    }
+
+    #[cfg(feature = "prover")]
+    pub(crate) fn check(&self) -> Result<(), Cancelled> {
+        if self.cancel.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    // A merkle build hook reporting `levels` in `phase`.
+    #[cfg(feature = "prover")]
+    pub(crate) fn levels(&self, phase: Phase, levels: usize) -> impl FnMut(usize) -> bool + '_ {
+        move |done| {
+            self.report(phase, done, levels);
+            !self.cancel.is_cancelled()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{fixtures::random_polies, replay, Blake3Transcript, FridaConfig, Prover};
+
+    #[test]
+    fn progress_and_cancel() {
+        let config = FridaConfig::new(b"options", 4, 6, 1, 8);
+        let polies = random_polies(4, 64);
+        let groups = config.groups();
+        let seen = Arc::new(Mutex::new(vec![]));
+        let log = seen.clone();
+        let options = ProveOptions::default()
+            .with_progress(move |phase, x| log.lock().unwrap().push((phase, x)));
+        let prover = Prover::new(&polies, &groups[0], &options).unwrap();
+        assert_eq!(
+            prover.commit(),
+            Prover::new(&polies, &groups[0], &ProveOptions::default())
+                .unwrap()
+                .commit()
+        );
+        let proof = prover.prove(&config);
+        let (challenges, _) =
+            replay::<Blake3Transcript>(&config, &proof.merkle_root, &proof.iopp_commits);
+        let (_, commits) = prover.commit_phase(&groups, &challenges, &options).unwrap();
+        assert_eq!(commits.merkle_roots, proof.iopp_commits.merkle_roots);
+        let seen = seen.lock().unwrap();
+        for phase in [Phase::Encode, Phase::Commit, Phase::Fold] {
+            let fractions = seen
+                .iter()
+                .filter(|x| x.0 == phase)
+                .map(|x| x.1)
+                .collect::<Vec<_>>();
+            assert!(fractions.iter().all(|x| (0.0..=1.0).contains(x)));
+            assert_eq!(fractions.last(), Some(&1.0));
+        }
+
+        let cancel = CancellationToken::new();
+        let options = ProveOptions::default().with_cancel(cancel.clone());
+        cancel.cancel();
+        assert_eq!(
+            Prover::new(&polies, &groups[0], &options).err(),
+            Some(Cancelled)
+        );
+        assert!(prover.commit_phase(&groups, &challenges, &options).is_err());
+
+        // Cancelling from the callback stops the job at the next poll.
+        let token = CancellationToken::new();
+        let trigger = token.clone();
+        let options = ProveOptions::default()
+            .with_cancel(token)
+            .with_progress(move |phase, _| {
+                if phase == Phase::Commit {
+                    trigger.cancel();
+                }
+            });
+        assert!(Prover::new(&polies, &groups[0], &options).is_err());
+    }
+
+    #[test]
+    fn thread_count_independent() {
+        let config = FridaConfig::new(b"threads", 7, 5, 2, 10);
+        let polies = random_polies(7, 32);
+        let group = &config.groups()[0];
+        let bytes = Prover::new(&polies, group, &ProveOptions::default())
+            .unwrap()
+            .prove(&config)
+            .to_bytes();
+        for threads in [1, 2, 3, 7, 16] {
+            let options = ProveOptions::default().with_threads(threads);
+            let prover = Prover::new(&polies, group, &options).unwrap();
+            assert_eq!(prover.prove(&config).to_bytes(), bytes);
+        }
+    }
+}
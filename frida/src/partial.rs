@@ -0,0 +1,119 @@
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::{
+    replay_transcript, Blake3Transcript, FridaConfig, FridaProof, IoppCommits, Transcript,
+    VerificationReport, Verifier,
+};
+
+// The `checked` query slots a constrained verifier looks at, drawn from the
+// transcript after the full query set so the prover cannot steer them.
+pub fn partial_slots(
+    config: &FridaConfig,
+    merkle_root: &[u8; 32],
+    iopp_commits: &IoppCommits,
+    checked: usize,
+) -> Vec<usize> {
+    assert!(checked <= config.query_num);
+    let (mut transcript, _, _) =
+        replay_transcript::<Blake3Transcript>(config, merkle_root, iopp_commits);
+    transcript.absorb(b"partial", &(checked as u64).to_le_bytes());
+    let mut slots = vec![];
+    while slots.len() < checked {
+        let slot = transcript.challenge_index(b"slot", config.query_num);
+        if !slots.contains(&slot) {
+            slots.push(slot);
+        }
+    }
+    slots.sort();
+    slots
+}
+
+#[derive(Debug, Clone)]
+pub struct PartialReport {
+    pub report: VerificationReport,
+    pub slots: Vec<usize>,
+    pub total_queries: usize,
+    // What the checked queries actually buy, next to what the full proof
+    // would have given.
+    pub soundness_bits: f64,
+    pub full_soundness_bits: f64,
+}
+
+impl PartialReport {
+    pub fn accepted(&self) -> bool {
+        self.report.accepted()
+    }
+
+    pub fn degraded(&self) -> bool {
+        self.slots.len() < self.total_queries
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // A proof answering only the partial slots; its `query_results` cover
+    // those positions alone.
+    pub fn prove_partial(&self, config: &FridaConfig, checked: usize) -> FridaProof {
+        let (prover_state, mut proof) = self.prove_with_state::<Blake3Transcript>(config);
+        let (_, _, leaf_indices) =
+            replay_transcript::<Blake3Transcript>(config, &proof.merkle_root, &proof.iopp_commits);
+        let positions = partial_slots(config, &proof.merkle_root, &proof.iopp_commits, checked)
+            .iter()
+            .map(|&x| leaf_indices[x])
+            .collect::<Vec<_>>();
+        proof.query_results = self.query_positions(&prover_state, &positions);
+        proof
+    }
+}
+
+impl Verifier {
+    pub fn verify_partial(
+        config: &FridaConfig,
+        commitment: &[u8; 32],
+        proof: &FridaProof,
+        checked: usize,
+    ) -> PartialReport {
+        let slots = partial_slots(config, &proof.merkle_root, &proof.iopp_commits, checked);
+        PartialReport {
+            report: Self::verify_slots::<Blake3Transcript, _>(
+                config,
+                commitment,
+                &proof.merkle_root,
+                &proof.iopp_commits,
+                &slots,
+                &proof.query_results,
+            ),
+            soundness_bits: config.soundness_bits(slots.len()),
+            full_soundness_bits: config.soundness_bits(config.query_num),
+            total_queries: config.query_num,
+            slots,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::random_prover;
+
+    #[test]
+    fn partial_verification() {
+        let config = FridaConfig::new(b"partial", 2, 6, 2, 40);
+        let (_, prover) = random_prover(&config);
+        let commitment = config.commitment(&prover.commit());
+
+        let proof = prover.prove_partial(&config, 10);
+        let report = Verifier::verify_partial(&config, &commitment, &proof, 10);
+        assert!(report.accepted());
+        assert!(report.degraded());
+        assert_eq!(report.slots.len(), 10);
+        assert!((report.soundness_bits * 4.0 - report.full_soundness_bits).abs() < 1e-9);
+        assert!(report.soundness_bits < report.full_soundness_bits);
+
+        // The partial proof does not answer a different slot selection.
+        assert!(!Verifier::verify_partial(&config, &commitment, &proof, 12).accepted());
+
+        let full = Verifier::verify_partial(&config, &commitment, &prover.prove(&config), 40);
+        assert!(full.accepted() && !full.degraded());
+    }
+}
@@ -0,0 +1,289 @@
+// An additively homomorphic Pedersen commitment C = <v, G> to the first-oracle
+// codeword v, published next to the merkle root so commitments to sub-blobs
+// can be summed. It is not hiding; the codeword is public data anyway.
+//
+// The binding proof opens transcript-chosen leaves against the root and shows
+// with an inner-product argument that C opens to a vector agreeing with them:
+// <v, b> = y for the sparse b = sum_t rho^t e_(key_t) over the opened keys.
+// A C committing to a vector that differs from the root's codeword in a
+// fraction delta of leaves passes with probability at most (1 - delta)^q.
+use std::collections::BTreeSet;
+
+use ark_bn254::{Fq, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::{group_leaves, Blake3Transcript, FridaConfig, QueryResult, Transcript, Verifier};
+
+pub struct PedersenParams {
+    generators: Vec<G1Affine>,
+    u: G1Affine,
+}
+
+// Try-and-increment hashing, so nobody knows discrete logs between the
+// generators. BN254 G1 has cofactor one.
+fn hash_to_curve(label: &[u8], index: u64) -> G1Affine {
+    (0u64..)
+        .find_map(|counter| {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(label);
+            hasher.update(&index.to_le_bytes());
+            hasher.update(&counter.to_le_bytes());
+            let x = Fq::from_le_bytes_mod_order(hasher.finalize().as_bytes());
+            G1Affine::get_point_from_x_unchecked(x, false)
+        })
+        .unwrap()
+}
+
+fn point_bytes(x: &G1Projective) -> Vec<u8> {
+    let mut bytes = vec![];
+    x.into_affine().serialize_compressed(&mut bytes).unwrap();
+    bytes
+}
+
+impl PedersenParams {
+    pub fn new(label: &[u8], len: usize) -> Self {
+        assert!(len.is_power_of_two());
+        PedersenParams {
+            generators: (0..len as u64).map(|i| hash_to_curve(label, i)).collect(),
+            u: hash_to_curve(label, u64::MAX),
+        }
+    }
+
+    // Params sized for the first oracle of `config`.
+    pub fn for_config(config: &FridaConfig) -> Self {
+        Self::new(
+            &config.params_hash(),
+            config.poly_num * config.domain_size(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.generators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generators.is_empty()
+    }
+
+    pub fn commit(&self, v: &[Fr]) -> G1Projective {
+        assert_eq!(v.len(), self.len());
+        G1Projective::msm(&self.generators, v).unwrap()
+    }
+}
+
+#[derive(Clone)]
+pub struct BindingProof {
+    pub opened: QueryResult,
+    pub rounds: Vec<(G1Affine, G1Affine)>,
+    pub a: Fr,
+}
+
+// Positions to open and the powers of rho weighting their keys, key-sorted.
+fn binding_challenges(
+    config: &FridaConfig,
+    merkle_root: &[u8; 32],
+    commitment: &G1Projective,
+) -> (Blake3Transcript, Vec<usize>, Fr) {
+    let mut transcript = config.transcript_with::<Blake3Transcript>();
+    transcript.absorb(b"merkle_root", merkle_root);
+    transcript.absorb(b"pedersen", &point_bytes(commitment));
+    let leaves = (0..config.query_num)
+        .map(|_| transcript.challenge_index(b"binding_query", config.leave_number()))
+        .collect::<BTreeSet<_>>();
+    let rho = transcript.challenge_field(b"binding_rho");
+    (transcript, leaves.into_iter().collect(), rho)
+}
+
+// The first-oracle leaves of `positions`.
+fn binding_leaves(config: &FridaConfig, positions: &[usize]) -> Vec<usize> {
+    group_leaves(positions, config.leave_number(), config.leaf_groups())
+}
+
+// b = sum_t rho^t e_(key_t) over every key of the opened leaves.
+fn weights(config: &FridaConfig, positions: &[usize], rho: Fr) -> Vec<(usize, Fr)> {
+    let len = config.first_oracle_leaves();
+    let mut keys = binding_leaves(config, positions)
+        .into_iter()
+        .flat_map(|i| (0..config.leaf_size(0)).map(move |j| i + j * len))
+        .collect::<Vec<_>>();
+    keys.sort_unstable();
+    let mut power = Fr::from(1u64);
+    keys.into_iter()
+        .map(|k| {
+            let w = (k, power);
+            power *= rho;
+            w
+        })
+        .collect()
+}
+
+fn fold_round(transcript: &mut Blake3Transcript, l: &G1Projective, r: &G1Projective) -> Fr {
+    transcript.absorb(b"ipa_l", &point_bytes(l));
+    transcript.absorb(b"ipa_r", &point_bytes(r));
+    transcript.challenge_field(b"ipa_challenge")
+}
+
+fn fold_generators(g: &[G1Affine], x: Fr, x_inv: Fr) -> Vec<G1Affine> {
+    let half = g.len() / 2;
+    let folded = (0..half)
+        .map(|i| g[i] * x_inv + g[i + half] * x)
+        .collect::<Vec<_>>();
+    G1Projective::normalize_batch(&folded)
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    pub fn pedersen(&self, params: &PedersenParams) -> G1Projective {
+        params.commit(&self.interpolation.codeword())
+    }
+
+    pub fn prove_binding(&self, config: &FridaConfig, params: &PedersenParams) -> BindingProof {
+        let commitment = self.pedersen(params);
+        let (mut transcript, positions, rho) =
+            binding_challenges(config, &self.commit(), &commitment);
+        let opened = self
+            .interpolation
+            .query(&binding_leaves(config, &positions));
+
+        let mut a = self.interpolation.codeword().into_owned();
+        let mut b = vec![Fr::zero(); a.len()];
+        for (k, w) in weights(config, &positions, rho) {
+            b[k] = w;
+        }
+        let mut g = params.generators.clone();
+        let mut rounds = vec![];
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let (a_lo, a_hi) = a.split_at(half);
+            let (b_lo, b_hi) = b.split_at(half);
+            let inner = |x: &[Fr], y: &[Fr]| x.iter().zip(y).map(|(x, y)| *x * y).sum::<Fr>();
+            let l = G1Projective::msm(&g[half..], a_lo).unwrap() + params.u * inner(a_lo, b_hi);
+            let r = G1Projective::msm(&g[..half], a_hi).unwrap() + params.u * inner(a_hi, b_lo);
+            let x = fold_round(&mut transcript, &l, &r);
+            let x_inv = x.inverse().unwrap();
+            a = (0..half).map(|i| a[i] * x + a[i + half] * x_inv).collect();
+            b = (0..half).map(|i| b[i] * x_inv + b[i + half] * x).collect();
+            g = fold_generators(&g, x, x_inv);
+            rounds.push((l.into_affine(), r.into_affine()));
+        }
+        BindingProof {
+            opened,
+            rounds,
+            a: a[0],
+        }
+    }
+}
+
+impl Verifier {
+    // Folding the generators makes this linear in the codeword length.
+    pub fn verify_binding(
+        config: &FridaConfig,
+        params: &PedersenParams,
+        merkle_root: &[u8; 32],
+        commitment: &G1Projective,
+        proof: &BindingProof,
+    ) -> bool {
+        let n = params.len();
+        if n != config.poly_num * config.domain_size() || proof.rounds.len() != n.ilog2() as usize {
+            return false;
+        }
+        let (mut transcript, positions, rho) = binding_challenges(config, merkle_root, commitment);
+        if !Verifier::for_config(config, merkle_root).verify_positions(&positions, &proof.opened) {
+            return false;
+        }
+        let weighted = weights(config, &positions, rho);
+        let mut y = Fr::zero();
+        for &(k, w) in weighted.iter() {
+            match proof.opened.values.get(&k) {
+                Some(v) => y += w * v,
+                None => return false,
+            }
+        }
+
+        let mut p = *commitment + params.u * y;
+        let mut b = vec![Fr::zero(); n];
+        for (k, w) in weighted {
+            b[k] = w;
+        }
+        let mut g = params.generators.clone();
+        for (l, r) in proof.rounds.iter() {
+            let (l, r) = (l.into_group(), r.into_group());
+            let x = fold_round(&mut transcript, &l, &r);
+            let x_inv = x.inverse().unwrap();
+            let half = b.len() / 2;
+            p = l * x.square() + p + r * x_inv.square();
+            b = (0..half).map(|i| b[i] * x_inv + b[i + half] * x).collect();
+            g = fold_generators(&g, x, x_inv);
+        }
+        p == g[0] * proof.a + params.u * (proof.a * b[0])
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::ProveOptions;
+
+    #[test]
+    fn homomorphic_binding() {
+        let mut rng = thread_rng();
+        let config = FridaConfig::new(b"pedersen", 2, 3, 2, 4);
+        let mut random = || {
+            (0..config.poly_num)
+                .map(|_| (0..8).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let (x, y) = (random(), random());
+        let params = PedersenParams::for_config(&config);
+        let group = &config.groups()[0];
+        let (px, py) = (
+            Prover::new(&x, group, &ProveOptions::default()).unwrap(),
+            Prover::new(&y, group, &ProveOptions::default()).unwrap(),
+        );
+        let sum = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| a.iter().zip(b).map(|(a, b)| *a + b).collect())
+            .collect::<Vec<Vec<Fr>>>();
+        assert_eq!(
+            px.pedersen(&params) + py.pedersen(&params),
+            Prover::new(&sum, group, &ProveOptions::default())
+                .unwrap()
+                .pedersen(&params)
+        );
+
+        let commitment = px.pedersen(&params);
+        let proof = px.prove_binding(&config, &params);
+        assert!(Verifier::verify_binding(
+            &config,
+            &params,
+            &px.commit(),
+            &commitment,
+            &proof
+        ));
+        // The same proof cannot bind the root to another blob's commitment.
+        assert!(!Verifier::verify_binding(
+            &config,
+            &params,
+            &px.commit(),
+            &py.pedersen(&params),
+            &proof
+        ));
+        let mut forged = proof.clone();
+        forged.a += Fr::from(1u64);
+        assert!(!Verifier::verify_binding(
+            &config,
+            &params,
+            &px.commit(),
+            &commitment,
+            &forged
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+// Conversion between first-oracle openings and the PeerDAS cell layout
+// (EIP-7594): an extended blob is cut into cells of FIELD_ELEMENTS_PER_CELL
+// elements addressed by a u64 cell index, each shipped with its own proof.
+// A cell here is a run of consecutive leaves, so its proof is the range
+// multiproof of those leaves rather than a 48-byte KZG proof, and elements
+// are BN254 rather than BLS12-381; only the container shape is shared.
+use ark_bn254::Fr;
+use ark_serialize::CanonicalDeserialize;
+use util::merkle_tree::Serialize;
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+use crate::{FridaConfig, Verifier};
+
+pub const FIELD_ELEMENTS_PER_CELL: usize = 64;
+pub const BYTES_PER_CELL: usize = 2048;
+pub const CELLS_PER_EXT_BLOB: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerDasCell {
+    pub index: u64,
+    pub cell: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+// Leaves per cell, or None when a leaf does not divide a cell evenly.
+pub fn leaves_per_cell(config: &FridaConfig) -> Option<usize> {
+    let leaf_size = config.leaf_size(0);
+    if leaf_size > FIELD_ELEMENTS_PER_CELL || !FIELD_ELEMENTS_PER_CELL.is_multiple_of(leaf_size) {
+        return None;
+    }
+    Some(FIELD_ELEMENTS_PER_CELL / leaf_size)
+}
+
+pub fn cell_num(config: &FridaConfig) -> Option<usize> {
+    Some(config.first_oracle_leaves() / leaves_per_cell(config)?)
+}
+
+// A config whose extended blob has exactly CELLS_PER_EXT_BLOB cells: 4096
+// elements at rate 1/2, split into `poly_num` polynomials.
+pub fn peerdas_config(context: &[u8], poly_num: usize, query_num: usize) -> FridaConfig {
+    assert!(poly_num.is_power_of_two() && poly_num <= FIELD_ELEMENTS_PER_CELL / 2);
+    FridaConfig::new(
+        context,
+        poly_num,
+        12 - poly_num.ilog2() as usize,
+        1,
+        query_num,
+    )
+}
+
+// Packs the leaves `start..start + symbols.len()` opened under `proof`.
+pub fn cell_from_openings(
+    config: &FridaConfig,
+    start: usize,
+    symbols: &[Vec<Fr>],
+    proof: Vec<u8>,
+) -> Option<PeerDasCell> {
+    let per_cell = leaves_per_cell(config)?;
+    if !start.is_multiple_of(per_cell) || symbols.len() != per_cell {
+        return None;
+    }
+    Some(PeerDasCell {
+        index: (start / per_cell) as u64,
+        cell: Serialize::serialize_fields(&symbols.concat()),
+        proof,
+    })
+}
+
+// Splits a cell back into (first leaf, per-leaf symbols, range proof).
+pub fn cell_to_openings(
+    config: &FridaConfig,
+    cell: &PeerDasCell,
+) -> Option<(usize, Vec<Vec<Fr>>, Vec<u8>)> {
+    let per_cell = leaves_per_cell(config)?;
+    let index = usize::try_from(cell.index).ok()?;
+    if index >= cell_num(config)? {
+        return None;
+    }
+    let mut bytes = cell.cell.as_slice();
+    let mut elements = Vec::with_capacity(FIELD_ELEMENTS_PER_CELL);
+    while !bytes.is_empty() {
+        elements.push(Fr::deserialize_compressed(&mut bytes).ok()?);
+    }
+    if elements.len() != per_cell * config.leaf_size(0) {
+        return None;
+    }
+    Some((
+        index * per_cell,
+        elements
+            .chunks(config.leaf_size(0))
+            .map(|x| x.to_vec())
+            .collect(),
+        cell.proof.clone(),
+    ))
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    pub fn open_peerdas_cell(&self, config: &FridaConfig, index: u64) -> Option<PeerDasCell> {
+        let per_cell = leaves_per_cell(config)?;
+        if index as usize >= cell_num(config)? {
+            return None;
+        }
+        let start = index as usize * per_cell;
+        let (symbols, proof) = self.open_range(start, start + per_cell);
+        cell_from_openings(config, start, &symbols, proof)
+    }
+}
+
+impl Verifier {
+    pub fn verify_peerdas_cell(
+        config: &FridaConfig,
+        merkle_root: &[u8; 32],
+        cell: &PeerDasCell,
+    ) -> bool {
+        match cell_to_openings(config, cell) {
+            Some((start, symbols, proof)) => Verifier::verify_range(
+                merkle_root,
+                config.first_oracle_leaves(),
+                start,
+                &symbols,
+                &proof,
+            ),
+            None => false,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::random_prover;
+
+    #[test]
+    fn cells() {
+        let config = peerdas_config(b"peerdas", 8, 10);
+        assert_eq!(cell_num(&config), Some(CELLS_PER_EXT_BLOB));
+        assert_eq!(leaves_per_cell(&config), Some(4));
+        let (_, prover) = random_prover(&config);
+        let root = prover.commit();
+        for index in [0, 37, CELLS_PER_EXT_BLOB as u64 - 1] {
+            let cell = prover.open_peerdas_cell(&config, index).unwrap();
+            assert_eq!(cell.index, index);
+            assert!(Verifier::verify_peerdas_cell(&config, &root, &cell));
+
+            let (start, symbols, _) = cell_to_openings(&config, &cell).unwrap();
+            assert_eq!(symbols[1], prover.open_chunk(start + 1).0);
+
+            let mut moved = cell.clone();
+            moved.index ^= 1;
+            assert!(!Verifier::verify_peerdas_cell(&config, &root, &moved));
+            let mut truncated = cell.clone();
+            truncated.cell.pop();
+            assert!(!Verifier::verify_peerdas_cell(&config, &root, &truncated));
+        }
+        assert!(prover
+            .open_peerdas_cell(&config, CELLS_PER_EXT_BLOB as u64)
+            .is_none());
+        assert_eq!(leaves_per_cell(&peerdas_config(b"", 32, 1)), Some(1));
+    }
+}
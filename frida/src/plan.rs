@@ -0,0 +1,137 @@
+use crate::confidence::{samples_for_failure, AdversaryModel};
+
+// Bytes packed into one field element, leaving the top bits of Fr unused.
+pub const BYTES_PER_ELEMENT: usize = 31;
+pub const MAX_CODE_RATE: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DispersalInputs {
+    pub blob_size: usize,
+    pub node_num: usize,
+    pub adversary_fraction: f64,
+    pub failure_probability: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DispersalPlan {
+    pub code_rate: usize,
+    pub log_degree: usize,
+    pub poly_num: usize,
+    pub leave_number: usize,
+    pub shares_per_node: usize,
+    pub sample_num: usize,
+}
+
+impl DispersalPlan {
+    pub fn log_blob_size(&self) -> usize {
+        self.log_degree + self.poly_num.ilog2() as usize
+    }
+
+    pub fn domain_size(&self) -> usize {
+        1 << (self.log_degree + self.code_rate)
+    }
+
+    // Leaves needed to reconstruct: each leaf holds a pair of evaluations of
+    // every polynomial, so half the degree bound.
+    pub fn required_leaves(&self) -> usize {
+        self.leave_number >> self.code_rate
+    }
+
+    pub fn pick(inputs: &DispersalInputs) -> Option<DispersalPlan> {
+        let adversary_num = (inputs.node_num as f64 * inputs.adversary_fraction).floor() as usize;
+        Self::pick_with(inputs, |leave_number| {
+            let shares_per_node = leave_number.div_ceil(inputs.node_num);
+            Some((shares_per_node, adversary_num * shares_per_node))
+        })
+    }
+
+    // `shares(leave_number)` is the most leaves a node holds and the most
+    // the adversary holds.
+    pub(crate) fn pick_with<F: Fn(usize) -> Option<(usize, usize)>>(
+        inputs: &DispersalInputs,
+        shares: F,
+    ) -> Option<DispersalPlan> {
+        let DispersalInputs {
+            blob_size,
+            node_num,
+            adversary_fraction,
+            failure_probability,
+        } = *inputs;
+        if node_num == 0
+            || !(0.0..1.0).contains(&adversary_fraction)
+            || !(0.0..1.0).contains(&failure_probability)
+            || failure_probability == 0.0
+        {
+            return None;
+        }
+        let elements = (blob_size.div_ceil(BYTES_PER_ELEMENT)).max(2);
+        let log_elements = elements.next_power_of_two().ilog2() as usize;
+        let log_node_num = node_num.next_power_of_two().ilog2() as usize;
+
+        for code_rate in 1..=MAX_CODE_RATE {
+            let log_leaves_max = log_elements + code_rate - 1;
+            // Widest leaves first; narrowing them spreads shares more evenly.
+            let widest = log_leaves_max
+                .saturating_sub(log_node_num)
+                .min(log_elements - 1);
+            for log_poly_num in (0..=widest).rev() {
+                let leave_number: usize = 1 << (log_leaves_max - log_poly_num);
+                let (shares_per_node, adversary) = shares(leave_number)?;
+                let required = leave_number >> code_rate;
+                let honest = leave_number.saturating_sub(adversary);
+                if honest < required {
+                    continue;
+                }
+                let sample_num = samples_for_failure(
+                    failure_probability,
+                    code_rate,
+                    AdversaryModel::WithReplacement,
+                )?;
+                return Some(DispersalPlan {
+                    code_rate,
+                    log_degree: log_elements - log_poly_num,
+                    poly_num: 1 << log_poly_num,
+                    leave_number,
+                    shares_per_node,
+                    sample_num: sample_num.max(1),
+                });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_plan() {
+        let inputs = DispersalInputs {
+            blob_size: 1 << 20,
+            node_num: 100,
+            adversary_fraction: 0.33,
+            failure_probability: 1e-9,
+        };
+        let plan = DispersalPlan::pick(&inputs).unwrap();
+        assert_eq!(plan.code_rate, 1);
+        assert!(plan.leave_number >= inputs.node_num);
+        assert!(plan.shares_per_node * inputs.node_num >= plan.leave_number);
+        assert!(0.5_f64.powi(plan.sample_num as i32) <= inputs.failure_probability);
+        assert!(plan.leave_number - 33 * plan.shares_per_node >= plan.required_leaves());
+
+        let inputs = DispersalInputs {
+            adversary_fraction: 0.7,
+            ..inputs
+        };
+        let plan = DispersalPlan::pick(&inputs).unwrap();
+        assert_eq!(plan.code_rate, 2);
+
+        let inputs = DispersalInputs {
+            adversary_fraction: 1.0,
+            ..inputs
+        };
+        assert!(DispersalPlan::pick(&inputs).is_none());
+    }
+}
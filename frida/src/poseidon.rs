@@ -0,0 +1,177 @@
+// A Poseidon sponge transcript over the scalar field, for verifiers that run
+// inside a circuit. Field elements are absorbed natively; bytes are packed 31
+// to an element behind their length.
+use std::sync::OnceLock;
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+
+use crate::transcript::Transcript;
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct PoseidonParams {
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    pub alpha: u64,
+    // One row per round.
+    pub ark: Vec<[Fr; WIDTH]>,
+    pub mds: [[Fr; WIDTH]; WIDTH],
+}
+
+impl PoseidonParams {
+    // x^5 with 8 full and 57 partial rounds, the usual width-3 choice for
+    // BN254. Round constants come from a blake3 XOF and the MDS matrix is the
+    // Cauchy matrix 1 / (i + WIDTH + j); deployments that must agree with
+    // another implementation's constants build their own params instead.
+    pub fn derived() -> Self {
+        let (full_rounds, partial_rounds) = (8, 57);
+        let mut xof = blake3::Hasher::new()
+            .update(b"frida-poseidon-constants")
+            .finalize_xof();
+        let ark = (0..full_rounds + partial_rounds)
+            .map(|_| {
+                std::array::from_fn(|_| {
+                    let mut bytes = [0u8; 64];
+                    xof.fill(&mut bytes);
+                    Fr::from_le_bytes_mod_order(&bytes)
+                })
+            })
+            .collect();
+        let mds = std::array::from_fn(|i| {
+            std::array::from_fn(|j| Fr::from((i + WIDTH + j) as u64).inverse().unwrap())
+        });
+        PoseidonParams {
+            full_rounds,
+            partial_rounds,
+            alpha: 5,
+            ark,
+            mds,
+        }
+    }
+
+    pub fn permute(&self, state: &mut [Fr; WIDTH]) {
+        let half = self.full_rounds / 2;
+        for (r, constants) in self.ark.iter().enumerate() {
+            for (x, c) in state.iter_mut().zip(constants) {
+                *x += c;
+            }
+            if r < half || r >= half + self.partial_rounds {
+                state.iter_mut().for_each(|x| *x = x.pow([self.alpha]));
+            } else {
+                state[0] = state[0].pow([self.alpha]);
+            }
+            let old = *state;
+            for (x, row) in state.iter_mut().zip(self.mds.iter()) {
+                *x = row.iter().zip(old.iter()).map(|(m, y)| *m * y).sum();
+            }
+        }
+    }
+}
+
+fn derived_params() -> &'static PoseidonParams {
+    static PARAMS: OnceLock<PoseidonParams> = OnceLock::new();
+    PARAMS.get_or_init(PoseidonParams::derived)
+}
+
+// Duplex sponge: state[0] is the capacity, the rest the rate.
+#[derive(Clone)]
+pub struct PoseidonTranscript {
+    params: &'static PoseidonParams,
+    state: [Fr; WIDTH],
+}
+
+impl PoseidonTranscript {
+    pub fn with_params(params: &'static PoseidonParams, context: &[u8]) -> Self {
+        let mut transcript = PoseidonTranscript {
+            params,
+            state: [Fr::zero(); WIDTH],
+        };
+        transcript.absorb(b"protocol", b"frida-transcript-v1");
+        transcript.absorb(b"context", context);
+        transcript
+    }
+
+    fn absorb_elements(&mut self, elements: &[Fr]) {
+        for chunk in elements.chunks(RATE) {
+            for (x, e) in self.state[1..].iter_mut().zip(chunk) {
+                *x += e;
+            }
+            self.params.permute(&mut self.state);
+        }
+    }
+
+    fn pack(bytes: &[u8]) -> Vec<Fr> {
+        std::iter::once(Fr::from(bytes.len() as u64))
+            .chain(bytes.chunks(31).map(Fr::from_le_bytes_mod_order))
+            .collect()
+    }
+
+    fn squeeze(&mut self, label: &'static [u8]) -> Fr {
+        self.absorb_elements(&Self::pack(label));
+        self.params.permute(&mut self.state);
+        self.state[1]
+    }
+
+    fn low_bytes(x: Fr) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        let le = x.into_bigint().to_bytes_le();
+        let n = le.len().min(16);
+        bytes[..n].copy_from_slice(&le[..n]);
+        bytes
+    }
+}
+
+impl Transcript for PoseidonTranscript {
+    fn new(context: &[u8]) -> Self {
+        Self::with_params(derived_params(), context)
+    }
+
+    fn absorb(&mut self, label: &'static [u8], bytes: &[u8]) {
+        let mut elements = Self::pack(label);
+        elements.extend(Self::pack(bytes));
+        self.absorb_elements(&elements);
+    }
+
+    fn absorb_field(&mut self, label: &'static [u8], value: &Fr) {
+        let mut elements = Self::pack(label);
+        elements.push(*value);
+        self.absorb_elements(&elements);
+    }
+
+    // The low 128 bits of two squeezed elements, which are close to uniform.
+    fn challenge_bytes(&mut self, label: &'static [u8]) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&Self::low_bytes(self.squeeze(label)));
+        bytes[16..].copy_from_slice(&Self::low_bytes(self.squeeze(label)));
+        bytes
+    }
+
+    fn challenge_field(&mut self, label: &'static [u8]) -> Fr {
+        self.squeeze(label)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_prover, Blake3Transcript, FridaConfig, Verifier};
+
+    #[test]
+    fn poseidon_proof() {
+        let config = FridaConfig::new(b"poseidon", 2, 5, 1, 10);
+        let (_, prover) = random_prover(&config);
+        let proof = prover.prove_with::<PoseidonTranscript>(&config);
+        let commitment = config.commitment_with::<PoseidonTranscript>(&prover.commit());
+        assert!(
+            Verifier::verify_proof_with::<PoseidonTranscript>(&config, &commitment, &proof)
+                .accepted()
+        );
+        assert!(
+            !Verifier::verify_proof_with::<Blake3Transcript>(&config, &commitment, &proof)
+                .accepted()
+        );
+    }
+}
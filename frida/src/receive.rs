@@ -0,0 +1,154 @@
+// Checking every dealing a node receives in a DKG round in one pass. Every
+// dealing must be made under the sharing config and threshold the node
+// expects, so the evaluation domains are set up once for the whole batch
+// instead of once per dealing, and each proof runs the aggregated fold
+// check, one combined comparison per round rather than one per query. The
+// per-dealing work that is left, replaying the transcript and hashing paths,
+// is a small constant per dealing. Ciphers here publish no proof of
+// encryption, so in PVSS mode the batch opens each package under the node's
+// key and checks the plaintexts together.
+use util::mul_group::Radix2Group;
+
+use crate::{
+    amortized::FoldCheck,
+    dealer::{DealingTranscript, NodePackage},
+    share::{check_share, SealedPackage, Share, ShareCipher, ShareError},
+    vss::SharingConfig,
+    Blake3Transcript, Verifier,
+};
+
+impl<K> DealingTranscript<K> {
+    // `verify` over domains set up by the caller.
+    fn verify_within(
+        &self,
+        expected: &SharingConfig,
+        threshold: usize,
+        groups: &[Radix2Group],
+    ) -> bool {
+        let config = &self.sharing.config;
+        let proof = &self.proof;
+        if !self.matches(expected, threshold)
+            || self.nodes.len() > config.leave_number()
+            || proof.merkle_root != self.root
+            || config.commitment(&self.root) != self.commitment
+            || !Verifier::accepts(proof.version)
+        {
+            return false;
+        }
+        let (challenges, _) =
+            crate::replay::<Blake3Transcript>(config, &self.root, &proof.iopp_commits);
+        Verifier::for_config(config, &self.root)
+            .with_fold_check(FoldCheck::Aggregated)
+            .verify(
+                config,
+                groups,
+                &challenges,
+                proof.iopp_commits.clone(),
+                proof.query_results.clone(),
+            )
+            .accepted()
+    }
+}
+
+// `verify_my_share` for every (transcript, package) received, in order.
+pub fn verify_dealings<K: PartialEq>(
+    received: &[(&DealingTranscript<K>, &NodePackage<K>)],
+    expected: &SharingConfig,
+    threshold: usize,
+    my_index: usize,
+) -> Vec<Result<Share, ShareError>> {
+    let groups = expected.config.groups();
+    received
+        .iter()
+        .map(|(transcript, package)| {
+            check_share(package, transcript, my_index, |x| {
+                x.verify_within(expected, threshold, &groups)
+            })
+        })
+        .collect()
+}
+
+type Sealed<'a, K> = (&'a DealingTranscript<K>, &'a SealedPackage<K>);
+
+// `verify_dealings` for packages sealed to the node's public key.
+pub fn verify_sealed_dealings<C: ShareCipher>(
+    received: &[Sealed<C::PublicKey>],
+    expected: &SharingConfig,
+    threshold: usize,
+    my_index: usize,
+    key: &C::SecretKey,
+) -> Vec<Result<Share, ShareError>>
+where
+    C::PublicKey: Clone + PartialEq,
+{
+    let opened = received
+        .iter()
+        .map(|(_, sealed)| sealed.open::<C>(key))
+        .collect::<Vec<_>>();
+    let readable = received
+        .iter()
+        .zip(&opened)
+        .filter_map(|((transcript, _), package)| Some((*transcript, package.as_ref()?)))
+        .collect::<Vec<_>>();
+    let mut checked = verify_dealings(&readable, expected, threshold, my_index).into_iter();
+    opened
+        .iter()
+        .map(|package| match package {
+            Some(_) => checked.next().unwrap(),
+            None => Err(ShareError::Undecryptable),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{dealer::Dealer, session::Session, share::verify_my_share, SoundnessRegime};
+
+    #[test]
+    fn batched_dealings() {
+        let mut rng = thread_rng();
+        let dealer = Dealer {
+            context: b"receive".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let nodes = (0..16u64).collect::<Vec<_>>();
+        let mut dealings = (0..6)
+            .map(|_| {
+                dealer
+                    .deal(&[Fr::rand(&mut rng)], &nodes, 4, &mut rng)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        // A dealing for another threshold than agreed is refused.
+        dealings.push(
+            dealer
+                .deal(&[Fr::rand(&mut rng)], &nodes, 8, &mut rng)
+                .unwrap(),
+        );
+        dealings[2].packages[5].symbols[0] += Fr::from(1u64);
+        dealings[4].transcript.commitment[0] ^= 1;
+
+        let received = dealings
+            .iter()
+            .map(|x| (&x.transcript, &x.packages[5]))
+            .collect::<Vec<_>>();
+        let expected = dealer.sharing(1, 16, 4).unwrap();
+        let batched = verify_dealings(&received, &expected, 4, 5);
+        let single = received
+            .iter()
+            .map(|(transcript, package)| verify_my_share(package, transcript, &expected, 4, 5))
+            .collect::<Vec<_>>();
+        assert_eq!(batched, single);
+        assert_eq!(batched[2], Err(ShareError::PathMismatch));
+        assert_eq!(batched[4], Err(ShareError::InvalidTranscript));
+        assert_eq!(batched[6], Err(ShareError::InvalidTranscript));
+        assert_eq!(batched.iter().filter(|x| x.is_ok()).count(), 4);
+    }
+}
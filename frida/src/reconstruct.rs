@@ -0,0 +1,281 @@
+// Recovering a shared secret from verified shares. Any `quorum` shares, as
+// `SharingConfig::quorum` counts them, hold the `2 * quorum` evaluations that
+// fix the sharing polynomial, and the rest are checked against it. Shares
+// open leaves of the committed codeword, but the codeword is only proven
+// close to low degree, so a cheating dealer can leave a few leaves off the
+// polynomial; with `Faults::Exclude` those are decoded around by
+// Berlekamp-Welch, cubic in the number of shares, and their nodes reported.
+use std::{collections::BTreeSet, fmt};
+
+use ark_bn254::Fr;
+use ark_ff::{Field, Zero};
+use util::mul_group::Radix2Group;
+
+use crate::{hygiene::scrub, share::Share};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Faults {
+    Reject,
+    Exclude,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconstructError {
+    TooFewShares { needed: usize, actual: usize },
+    MixedCommitments,
+    DuplicateShare { index: usize },
+    // Shares disagree and `Faults::Reject` was asked for.
+    Inconsistent,
+    // More shares are off the polynomial than can be decoded around.
+    Undecodable,
+}
+
+impl fmt::Display for ReconstructError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReconstructError::TooFewShares { needed, actual } => {
+                write!(f, "{} shares where {} are needed", actual, needed)
+            }
+            ReconstructError::MixedCommitments => write!(f, "shares of different dealings"),
+            ReconstructError::DuplicateShare { index } => {
+                write!(f, "share {} given twice", index)
+            }
+            ReconstructError::Inconsistent => write!(f, "shares disagree"),
+            ReconstructError::Undecodable => write!(f, "too many faulty shares to decode"),
+        }
+    }
+}
+
+impl std::error::Error for ReconstructError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reconstruction {
+    pub secret: Vec<Fr>,
+    // Node indices of shares off the sharing polynomial.
+    pub faulty: Vec<usize>,
+}
+
+fn evaluate(coeff: &[Fr], x: Fr) -> Fr {
+    coeff.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c)
+}
+
+// Some solution of `rows` * x = `rhs`, free variables zero.
+fn solve(mut rows: Vec<Vec<Fr>>, mut rhs: Vec<Fr>) -> Option<Vec<Fr>> {
+    let width = rows[0].len();
+    let mut pivots = vec![];
+    for column in 0..width {
+        let row = pivots.len();
+        let Some(found) = (row..rows.len()).find(|&i| !rows[i][column].is_zero()) else {
+            continue;
+        };
+        rows.swap(row, found);
+        rhs.swap(row, found);
+        let inv = rows[row][column].inverse().unwrap();
+        rows[row].iter_mut().for_each(|x| *x *= inv);
+        rhs[row] *= inv;
+        let (pivot, pivot_rhs) = (rows[row].clone(), rhs[row]);
+        for (i, (other, other_rhs)) in rows.iter_mut().zip(rhs.iter_mut()).enumerate() {
+            let factor = other[column];
+            if i != row && !factor.is_zero() {
+                for (x, p) in other[column..].iter_mut().zip(&pivot[column..]) {
+                    *x -= factor * p;
+                }
+                *other_rhs -= factor * pivot_rhs;
+            }
+        }
+        pivots.push(column);
+    }
+    if rhs[pivots.len()..].iter().any(|x| !x.is_zero()) {
+        return None;
+    }
+    let mut solution = vec![Fr::zero(); width];
+    for (row, column) in pivots.into_iter().enumerate() {
+        solution[column] = rhs[row];
+    }
+    Some(solution)
+}
+
+// The polynomial of degree < `len` through all but at most (n - len) / 2 of
+// `points`.
+fn berlekamp_welch(points: &[(Fr, Fr)], len: usize) -> Option<Vec<Fr>> {
+    let errors = (points.len() - len) / 2;
+    let (rows, rhs) = points
+        .iter()
+        .map(|&(x, y)| {
+            let powers = (0..len + errors)
+                .scan(Fr::from(1u64), |p, _| {
+                    let current = *p;
+                    *p *= x;
+                    Some(current)
+                })
+                .collect::<Vec<_>>();
+            let mut row = powers.clone();
+            row.extend(powers[..errors].iter().map(|p| -y * p));
+            (row, y * x.pow([errors as u64]))
+        })
+        .unzip();
+    let solution = solve(rows, rhs)?;
+    let (remainder, locator) = solution.split_at(len + errors);
+    let mut locator = locator.to_vec();
+    locator.push(Fr::from(1u64));
+    // Q / E by long division; E is monic.
+    let mut quotient = vec![Fr::zero(); len];
+    let mut remainder = remainder.to_vec();
+    for k in (0..len).rev() {
+        let c = remainder[k + errors];
+        quotient[k] = c;
+        for (j, e) in locator.iter().enumerate() {
+            remainder[k + j] -= c * e;
+        }
+    }
+    remainder.iter().all(|x| x.is_zero()).then_some(quotient)
+}
+
+pub fn reconstruct(
+    shares: &[Share],
+    quorum: usize,
+    secret_len: usize,
+    faults: Faults,
+) -> Result<Reconstruction, ReconstructError> {
+    if shares.len() < quorum || quorum == 0 {
+        return Err(ReconstructError::TooFewShares {
+            needed: quorum.max(1),
+            actual: shares.len(),
+        });
+    }
+    if shares
+        .iter()
+        .any(|x| x.commitment != shares[0].commitment || x.leave_number != shares[0].leave_number)
+    {
+        return Err(ReconstructError::MixedCommitments);
+    }
+    let mut seen = BTreeSet::new();
+    if let Some(x) = shares.iter().find(|x| !seen.insert(x.index)) {
+        return Err(ReconstructError::DuplicateShare { index: x.index });
+    }
+    let group = Radix2Group::new((2 * shares[0].leave_number).ilog2() as usize);
+    let mut evals = shares
+        .iter()
+        .flat_map(|x| x.evaluations(0))
+        .collect::<Vec<_>>();
+    let decoded = decode(&group, shares, &evals, quorum, faults);
+    scrub(evals.iter_mut().map(|x| &mut x.1));
+    let (mut coeff, faulty) = decoded?;
+    let secret = coeff[..secret_len].to_vec();
+    scrub(&mut coeff);
+    Ok(Reconstruction { secret, faulty })
+}
+
+// The sharing polynomial through `evals`, and the shares off it.
+fn decode(
+    group: &Radix2Group,
+    shares: &[Share],
+    evals: &[(usize, Fr)],
+    quorum: usize,
+    faults: Faults,
+) -> Result<(Vec<Fr>, Vec<usize>), ReconstructError> {
+    let len = 2 * quorum;
+    let off = |coeff: &[Fr]| {
+        shares
+            .iter()
+            .filter(|x| {
+                x.evaluations(0)
+                    .iter()
+                    .any(|&(i, y)| evaluate(coeff, group.element_at(i)) != y)
+            })
+            .map(|x| x.index)
+            .collect::<Vec<_>>()
+    };
+    let mut coeff = group.interpolate(evals, len).unwrap();
+    let faulty = off(&coeff);
+    if faulty.is_empty() {
+        return Ok((coeff, faulty));
+    }
+    scrub(&mut coeff);
+    if faults == Faults::Reject {
+        return Err(ReconstructError::Inconsistent);
+    }
+    let mut points = evals
+        .iter()
+        .map(|&(i, y)| (group.element_at(i), y))
+        .collect::<Vec<_>>();
+    let decoded = berlekamp_welch(&points, len);
+    scrub(points.iter_mut().map(|x| &mut x.1));
+    let mut coeff = decoded.ok_or(ReconstructError::Undecodable)?;
+    let faulty = off(&coeff);
+    if shares.len() - faulty.len() < quorum {
+        scrub(&mut coeff);
+        return Err(ReconstructError::Undecodable);
+    }
+    Ok((coeff, faulty))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{dealer::Dealer, session::Session, share::verify_my_share, SoundnessRegime};
+
+    #[test]
+    fn threshold_reconstruction() {
+        let mut rng = thread_rng();
+        let dealer = Dealer {
+            context: b"reconstruct".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let secret = [Fr::rand(&mut rng)];
+        let dealing = dealer
+            .deal(&secret, &(0..16u64).collect::<Vec<_>>(), 4, &mut rng)
+            .unwrap();
+        let expected = dealer.sharing(1, 16, 4).unwrap();
+        let shares = dealing
+            .packages
+            .iter()
+            .map(|x| verify_my_share(x, &dealing.transcript, &expected, 4, x.index).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(dealing.transcript.sharing.quorum(), 8);
+        for subset in [&shares[..8], &shares[8..], &shares[3..13]] {
+            let recovered = reconstruct(subset, 8, 1, Faults::Reject).unwrap();
+            assert_eq!(recovered.secret, secret);
+            assert!(recovered.faulty.is_empty());
+        }
+        assert_eq!(
+            reconstruct(&shares[..7], 8, 1, Faults::Reject),
+            Err(ReconstructError::TooFewShares {
+                needed: 8,
+                actual: 7
+            })
+        );
+        let mut doubled = shares[..8].to_vec();
+        doubled.push(shares[1].clone());
+        assert_eq!(
+            reconstruct(&doubled, 8, 1, Faults::Reject),
+            Err(ReconstructError::DuplicateShare { index: 1 })
+        );
+
+        // Two bad shares among sixteen are decoded around and named.
+        let mut tampered = shares.clone();
+        tampered[0].symbols[0] += Fr::from(1u64);
+        tampered[5].symbols[1] += Fr::from(1u64);
+        assert_eq!(
+            reconstruct(&tampered, 8, 1, Faults::Reject),
+            Err(ReconstructError::Inconsistent)
+        );
+        let recovered = reconstruct(&tampered, 8, 1, Faults::Exclude).unwrap();
+        assert_eq!(recovered.secret, secret);
+        assert_eq!(recovered.faulty, vec![0, 5]);
+        for share in tampered[11..].iter_mut() {
+            share.symbols[0] += Fr::from(1u64);
+            share.symbols[1] += Fr::from(1u64);
+        }
+        assert_eq!(
+            reconstruct(&tampered, 8, 1, Faults::Exclude),
+            Err(ReconstructError::Undecodable)
+        );
+    }
+}
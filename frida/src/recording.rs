@@ -0,0 +1,316 @@
+// A transcript wrapper that logs every absorption and challenge, for tracking
+// down prover/verifier disagreements. Transcripts are built deep inside
+// `prove_with` and `verify_proof_with`, so each session is handed over when
+// its transcript is dropped and collected with `take_recordings`; comparing a
+// prover session with the verifier's replay names the first message on which
+// they part.
+use std::{cell::RefCell, fmt};
+
+use ark_bn254::Fr;
+
+use crate::Transcript;
+
+thread_local! {
+    static RECORDINGS: RefCell<Vec<Recording>> = const { RefCell::new(vec![]) };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Absorb {
+        label: &'static [u8],
+        bytes: Vec<u8>,
+    },
+    AbsorbField {
+        label: &'static [u8],
+        value: Fr,
+    },
+    ChallengeBytes {
+        label: &'static [u8],
+        output: [u8; 32],
+    },
+    ChallengeField {
+        label: &'static [u8],
+        output: Fr,
+    },
+    ChallengeIndex {
+        label: &'static [u8],
+        bound: usize,
+        output: usize,
+    },
+}
+
+impl Event {
+    pub fn label(&self) -> &'static [u8] {
+        match self {
+            Event::Absorb { label, .. }
+            | Event::AbsorbField { label, .. }
+            | Event::ChallengeBytes { label, .. }
+            | Event::ChallengeField { label, .. }
+            | Event::ChallengeIndex { label, .. } => label,
+        }
+    }
+
+    // Applies the event to `transcript`; a challenge comes back as the event
+    // the transcript actually produced.
+    fn apply<T: Transcript>(&self, transcript: &mut T) -> Event {
+        match *self {
+            Event::Absorb { label, ref bytes } => transcript.absorb(label, bytes),
+            Event::AbsorbField { label, ref value } => transcript.absorb_field(label, value),
+            Event::ChallengeBytes { label, .. } => {
+                return Event::ChallengeBytes {
+                    label,
+                    output: transcript.challenge_bytes(label),
+                }
+            }
+            Event::ChallengeField { label, .. } => {
+                return Event::ChallengeField {
+                    label,
+                    output: transcript.challenge_field(label),
+                }
+            }
+            Event::ChallengeIndex { label, bound, .. } => {
+                return Event::ChallengeIndex {
+                    label,
+                    bound,
+                    output: transcript.challenge_index(label, bound),
+                }
+            }
+        }
+        self.clone()
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = String::from_utf8_lossy(self.label());
+        match self {
+            Event::Absorb { bytes, .. } => write!(f, "absorb {} ({} bytes)", label, bytes.len()),
+            Event::AbsorbField { value, .. } => write!(f, "absorb field {} = {}", label, value),
+            Event::ChallengeBytes { .. } => write!(f, "challenge bytes {}", label),
+            Event::ChallengeField { output, .. } => {
+                write!(f, "challenge field {} = {}", label, output)
+            }
+            Event::ChallengeIndex { bound, output, .. } => {
+                write!(f, "challenge index {} = {} of {}", label, output, bound)
+            }
+        }
+    }
+}
+
+// Everything one transcript saw, from construction to drop.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Recording {
+    pub context: Vec<u8>,
+    pub events: Vec<Event>,
+}
+
+// The first event on which two sessions differ; `None` on a side that ended
+// early.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub index: usize,
+    pub expected: Option<Event>,
+    pub actual: Option<Event>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |x: &Option<Event>| match x {
+            Some(x) => x.to_string(),
+            None => "end of session".to_string(),
+        };
+        write!(
+            f,
+            "transcripts diverge at event {}: expected {}, got {}",
+            self.index,
+            side(&self.expected),
+            side(&self.actual)
+        )
+    }
+}
+
+impl Recording {
+    // Compares the sessions message by message. A differing context is
+    // reported at index 0 only if the events agree, since every challenge
+    // after it differs too.
+    pub fn divergence(&self, other: &Recording) -> Option<Divergence> {
+        let index = (0..self.events.len().max(other.events.len()))
+            .find(|&i| self.events.get(i) != other.events.get(i));
+        match index {
+            Some(index) => Some(Divergence {
+                index,
+                expected: self.events.get(index).cloned(),
+                actual: other.events.get(index).cloned(),
+            }),
+            None if self.context != other.context => Some(Divergence {
+                index: 0,
+                expected: None,
+                actual: None,
+            }),
+            None => None,
+        }
+    }
+
+    // Re-runs the session against a fresh `T` and checks that every challenge
+    // comes out as recorded, so a log can be re-examined under a patched
+    // transcript.
+    pub fn replay<T: Transcript>(&self) -> Result<T, Divergence> {
+        let mut transcript = T::new(&self.context);
+        for (index, event) in self.events.iter().enumerate() {
+            let actual = event.apply(&mut transcript);
+            if actual != *event {
+                return Err(Divergence {
+                    index,
+                    expected: Some(event.clone()),
+                    actual: Some(actual),
+                });
+            }
+        }
+        Ok(transcript)
+    }
+}
+
+// Sessions finished on this thread since the last call, in drop order.
+pub fn take_recordings() -> Vec<Recording> {
+    RECORDINGS.with(|x| std::mem::take(&mut *x.borrow_mut()))
+}
+
+pub struct RecordingTranscript<T: Transcript> {
+    inner: T,
+    recording: Recording,
+    detached: bool,
+}
+
+impl<T: Transcript> RecordingTranscript<T> {
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    // Takes the session out instead of leaving it for `take_recordings`.
+    pub fn into_recording(mut self) -> Recording {
+        self.detached = true;
+        std::mem::take(&mut self.recording)
+    }
+
+    fn push(&mut self, event: Event) {
+        self.recording.events.push(event);
+    }
+}
+
+impl<T: Transcript> Drop for RecordingTranscript<T> {
+    fn drop(&mut self) {
+        if !self.detached {
+            let recording = std::mem::take(&mut self.recording);
+            RECORDINGS.with(|x| x.borrow_mut().push(recording));
+        }
+    }
+}
+
+impl<T: Transcript> Transcript for RecordingTranscript<T> {
+    fn new(context: &[u8]) -> Self {
+        RecordingTranscript {
+            inner: T::new(context),
+            recording: Recording {
+                context: context.to_vec(),
+                events: vec![],
+            },
+            detached: false,
+        }
+    }
+
+    fn absorb(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.inner.absorb(label, bytes);
+        self.push(Event::Absorb {
+            label,
+            bytes: bytes.to_vec(),
+        });
+    }
+
+    fn absorb_field(&mut self, label: &'static [u8], value: &Fr) {
+        self.inner.absorb_field(label, value);
+        self.push(Event::AbsorbField {
+            label,
+            value: *value,
+        });
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8]) -> [u8; 32] {
+        let output = self.inner.challenge_bytes(label);
+        self.push(Event::ChallengeBytes { label, output });
+        output
+    }
+
+    fn challenge_field(&mut self, label: &'static [u8]) -> Fr {
+        let output = self.inner.challenge_field(label);
+        self.push(Event::ChallengeField { label, output });
+        output
+    }
+
+    fn challenge_index(&mut self, label: &'static [u8], bound: usize) -> usize {
+        let output = self.inner.challenge_index(label, bound);
+        self.push(Event::ChallengeIndex {
+            label,
+            bound,
+            output,
+        });
+        output
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::One;
+
+    use super::*;
+    use crate::{
+        fixtures::random_polies, poseidon::PoseidonTranscript, Blake3Transcript, FridaConfig,
+        ProveOptions, Prover, Verifier,
+    };
+
+    #[test]
+    fn record_and_replay() {
+        let config = FridaConfig::new(b"recording", 2, 5, 1, 8);
+        let polies = random_polies(2, 32);
+        take_recordings();
+        let prover = Prover::new(&polies, &config.groups()[0], &ProveOptions::default()).unwrap();
+        let proof = prover.prove_with::<RecordingTranscript<Blake3Transcript>>(&config);
+        // The fold, then the replay that samples the queries.
+        let prover_sessions = take_recordings();
+        assert_eq!(prover_sessions.len(), 2);
+
+        let commitment = config.commitment(&proof.merkle_root);
+        let report = Verifier::verify_proof_with::<RecordingTranscript<Blake3Transcript>>(
+            &config,
+            &commitment,
+            &proof,
+        );
+        assert!(report.accepted());
+        let verifier_sessions = take_recordings();
+        assert_eq!(prover_sessions[1].divergence(&verifier_sessions[1]), None);
+        for session in prover_sessions.iter().chain(verifier_sessions.iter()) {
+            assert!(session.replay::<Blake3Transcript>().is_ok());
+        }
+        let foreign = prover_sessions[0]
+            .replay::<PoseidonTranscript>()
+            .err()
+            .unwrap();
+        assert_eq!(foreign.expected.unwrap().label(), b"alpha");
+
+        // A verifier fed a different final value parts at its absorption.
+        let mut tampered = proof.clone();
+        tampered.iopp_commits.final_value += Fr::one();
+        let _ = Verifier::verify_proof_with::<RecordingTranscript<Blake3Transcript>>(
+            &config,
+            &commitment,
+            &tampered,
+        );
+        let sessions = take_recordings();
+        let divergence = prover_sessions[1].divergence(&sessions[1]).unwrap();
+        assert_eq!(divergence.actual.unwrap().label(), b"final_value");
+
+        let mut transcript = RecordingTranscript::<Blake3Transcript>::new(b"detached");
+        transcript.absorb(b"x", b"y");
+        assert_eq!(transcript.into_recording().events.len(), 1);
+        assert!(take_recordings().is_empty());
+    }
+}
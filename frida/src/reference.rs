@@ -0,0 +1,131 @@
+// Independent, coefficient-space FRI used to cross-check the prover: every
+// codeword and folded layer is recomputed by naive evaluation from the
+// definition f(x) = f_e(x^2) + x f_o(x).
+use ark_bn254::Fr;
+use ark_ff::{FftField, Field, One, UniformRand, Zero};
+use rand::{thread_rng, Rng};
+use util::mul_group::Radix2Group;
+
+use crate::{fixtures::random_polies, FridaConfig, ProveOptions, Prover, Verifier};
+
+fn evaluate(coeff: &[Fr], point: Fr) -> Fr {
+    coeff
+        .iter()
+        .rev()
+        .fold(Fr::zero(), |acc, c| acc * point + c)
+}
+
+fn evaluate_domain(coeff: &[Fr], log_size: usize) -> Vec<Fr> {
+    let omega = Fr::get_root_of_unity(1 << log_size).unwrap();
+    std::iter::successors(Some(Fr::one()), |x| Some(*x * omega))
+        .take(1 << log_size)
+        .map(|x| evaluate(coeff, x))
+        .collect()
+}
+
+// The prover combines x and -x as sum + challenge * (diff / x - sum), i.e.
+// (1 - challenge) * f_e + challenge * f_o.
+fn fold(coeff: &[Fr], challenge: Fr) -> Vec<Fr> {
+    coeff
+        .chunks(2)
+        .map(|c| (Fr::one() - challenge) * c[0] + challenge * c[1])
+        .collect()
+}
+
+fn batch(polies: &[Vec<Fr>], alpha: Fr) -> Vec<Fr> {
+    (0..polies[0].len())
+        .map(|i| polies.iter().fold(Fr::zero(), |acc, x| acc * alpha + x[i]))
+        .collect()
+}
+
+fn differential(poly_num: usize, log_degree: usize, coderate: usize) {
+    let mut rng = thread_rng();
+    let polies = random_polies(poly_num, 1 << log_degree);
+    let groups = (0..log_degree)
+        .rev()
+        .map(|x| Radix2Group::new(x + 1 + coderate))
+        .collect::<Vec<_>>();
+    let challenges = (
+        <Fr as UniformRand>::rand(&mut rng),
+        (0..log_degree)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>(),
+    );
+    let log_domain = log_degree + coderate;
+    let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+
+    let domain = 1 << log_domain;
+    let codewords = polies
+        .iter()
+        .flat_map(|poly| evaluate_domain(poly, log_domain))
+        .collect::<Vec<_>>();
+    // Slot j of leaf i holds codeword entry i + j * leaves.
+    let leaf_size = prover.open_chunk(0).0.len();
+    let leaves = codewords.len() / leaf_size;
+    for i in 0..leaves {
+        for (j, value) in prover.open_chunk(i).0.into_iter().enumerate() {
+            assert_eq!(value, codewords[i + j * leaves], "leaf {} slot {}", i, j);
+        }
+    }
+
+    let (prover_state, iopp_commits) = prover
+        .commit_phase(&groups, &challenges, &ProveOptions::default())
+        .unwrap();
+    let mut layers = vec![codewords];
+    let mut coeff = batch(&polies, challenges.0);
+    for (i, challenge) in challenges.1.iter().enumerate() {
+        coeff = fold(&coeff, *challenge);
+        if i < log_degree - 1 {
+            layers.push(evaluate_domain(&coeff, log_domain - i - 1));
+        }
+    }
+    assert_eq!(coeff.len(), 1);
+    assert_eq!(iopp_commits.final_value, coeff[0]);
+
+    let config = FridaConfig::new(
+        b"reference",
+        poly_num,
+        log_degree,
+        coderate,
+        rng.gen_range(1..20),
+    );
+    let query_results = prover.sample(&prover_state, &config);
+    assert_eq!(query_results.len(), layers.len());
+    for (i, (query_result, layer)) in query_results.iter().zip(&layers).enumerate() {
+        for (key, value) in &query_result.values {
+            assert_eq!(*value, layer[*key], "round {} entry {}", i, key);
+        }
+    }
+    let verifier = Verifier::new(prover.commit(), poly_num, domain / 2);
+    verifier
+        .verify(&config, &groups, &challenges, iopp_commits, query_results)
+        .into_result()
+        .unwrap();
+}
+
+#[test]
+fn fold_definition() {
+    let mut rng = thread_rng();
+    let coeff = (0..16)
+        .map(|_| <Fr as UniformRand>::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let challenge = <Fr as UniformRand>::rand(&mut rng);
+    let inv_2 = Fr::from(2).inverse().unwrap();
+    let group = Radix2Group::new(5);
+    assert_eq!(
+        Prover::evaluate_next_domain(&evaluate_domain(&coeff, 5), &group, inv_2, challenge),
+        evaluate_domain(&fold(&coeff, challenge), 4)
+    );
+}
+
+#[test]
+fn fuzz_parameters() {
+    let mut rng = thread_rng();
+    for _ in 0..20 {
+        differential(
+            rng.gen_range(1..5),
+            rng.gen_range(1..7),
+            rng.gen_range(1..4),
+        );
+    }
+}
@@ -0,0 +1,318 @@
+// Proactive refresh. Every epoch each node deals a sharing of zero, and
+// every node adds the zero shares it was dealt to its own, so the secret
+// stays put while shares from different epochs no longer combine. A zero
+// dealing commits to r with degree below `degree - secret_len` rather than to
+// the sharing polynomial X^secret_len * r itself: the proof's degree bound on
+// r shows the low coefficients are zero, and a node derives its zero share
+// from its r share. Dealings are bound to their epoch through the config's
+// context, and the refreshed shares to the exact set of zero dealings used,
+// so nodes that applied different sets fail to reconstruct together.
+use std::fmt;
+
+use ark_bn254::Fr;
+use ark_ff::{Field, UniformRand};
+use rand::Rng;
+use util::mul_group::Radix2Group;
+
+use crate::{
+    dealer::{DealError, Dealer, Dealing, DealingTranscript, NodePackage},
+    degree::pad_to_domain,
+    hygiene::scrub,
+    share::{check_share, Share, ShareError},
+    vss::SharingConfig,
+    Blake3Transcript, ProveOptions, Prover, Verifier,
+};
+
+const REFRESH_LABEL: &[u8] = b"frida-refresh";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshSchedule {
+    // Seconds since the Unix epoch at which epoch 0 begins.
+    pub start: u64,
+    pub period: u64,
+}
+
+impl RefreshSchedule {
+    pub fn epoch(&self, now: u64) -> Option<u64> {
+        now.checked_sub(self.start).map(|x| x / self.period)
+    }
+
+    // Whether shares last refreshed in `epoch` are due at `now`.
+    pub fn due(&self, epoch: u64, now: u64) -> bool {
+        self.epoch(now).is_some_and(|x| x > epoch)
+    }
+}
+
+pub fn epoch_context(context: &[u8], epoch: u64) -> Vec<u8> {
+    let mut tagged = context.to_vec();
+    tagged.extend(REFRESH_LABEL);
+    tagged.extend(epoch.to_le_bytes());
+    tagged
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshError {
+    // The dealing of old node `from` is for another epoch or its proof
+    // fails.
+    InvalidDealing { from: usize },
+    Share { from: usize, error: ShareError },
+    MixedCommittees,
+    Deal(DealError),
+}
+
+impl fmt::Display for RefreshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefreshError::InvalidDealing { from } => {
+                write!(f, "zero dealing of node {} does not verify", from)
+            }
+            RefreshError::Share { from, error } => {
+                write!(f, "zero share dealt by node {}: {}", from, error)
+            }
+            RefreshError::MixedCommittees => {
+                write!(f, "zero dealings are for different committees")
+            }
+            RefreshError::Deal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RefreshError {}
+
+fn bounds(sharing: &SharingConfig) -> Vec<usize> {
+    let degree = sharing.degree();
+    vec![degree - sharing.secret_len, degree]
+}
+
+impl Dealer {
+    // A sharing of zero in the low `secret_len` coefficients for `epoch`.
+    pub fn deal_zero<K: Clone, R: Rng>(
+        &self,
+        secret_len: usize,
+        node_pubkeys: &[K],
+        threshold: usize,
+        epoch: u64,
+        rng: &mut R,
+    ) -> Result<Dealing<K>, DealError> {
+        let mut sharing = self.sharing(secret_len, node_pubkeys.len(), threshold)?;
+        sharing.config.context = epoch_context(&self.session_context(), epoch);
+        let bounds = bounds(&sharing);
+        let mut quotient = (0..bounds[0]).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let mask = (0..bounds[1]).map(|_| Fr::rand(rng)).collect();
+        let mut polies = [pad_to_domain(&quotient, bounds[0]), mask];
+        let prover = Prover::new(
+            &polies,
+            &sharing.config.groups()[0],
+            &ProveOptions::default(),
+        )
+        .unwrap()
+        .with_degree_bounds(bounds);
+        scrub(&mut quotient);
+        polies.iter_mut().for_each(scrub);
+        let root = prover.commit();
+        let packages = node_pubkeys
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let (symbols, path) = prover.open_chunk(index);
+                NodePackage {
+                    index,
+                    node: node.clone(),
+                    symbols,
+                    path,
+                }
+            })
+            .collect();
+        let transcript = DealingTranscript {
+            threshold,
+            nodes: node_pubkeys.to_vec(),
+            root,
+            commitment: sharing.config.commitment(&root),
+            proof: prover.prove(&sharing.config),
+            sharing,
+        };
+        Ok(Dealing {
+            transcript,
+            packages,
+        })
+    }
+
+    // Whether `transcript` is a zero dealing of this dealer for `epoch`,
+    // for the `secret_len` and `threshold` of the dealing it refreshes. A
+    // larger degree would still have zero low coefficients, but would no
+    // longer reconstruct with the original quorum.
+    pub fn verify_zero<K>(
+        &self,
+        transcript: &DealingTranscript<K>,
+        secret_len: usize,
+        threshold: usize,
+        epoch: u64,
+    ) -> bool {
+        let Ok(mut expected) = self.sharing(secret_len, transcript.nodes.len(), threshold) else {
+            return false;
+        };
+        expected.config.context = epoch_context(&self.session_context(), epoch);
+        let config = &transcript.sharing.config;
+        let proof = &transcript.proof;
+        if !transcript.matches(&expected, threshold)
+            || transcript.nodes.len() > config.leave_number()
+            || proof.merkle_root != transcript.root
+            || config.commitment(&transcript.root) != transcript.commitment
+            || !Verifier::accepts(proof.version)
+        {
+            return false;
+        }
+        let (challenges, _) =
+            crate::replay::<Blake3Transcript>(config, &transcript.root, &proof.iopp_commits);
+        Verifier::for_config(config, &transcript.root)
+            .with_degree_bounds(bounds(&transcript.sharing))
+            .verify(
+                config,
+                &config.groups(),
+                &challenges,
+                proof.iopp_commits.clone(),
+                proof.query_results.clone(),
+            )
+            .accepted()
+    }
+
+    // `share` of a dealing of `secret_len` and `threshold` refreshed for
+    // `epoch` with the zero package every node in `zero` dealt it, keyed by
+    // the dealing node's index.
+    pub fn refresh<K: PartialEq>(
+        &self,
+        share: &Share,
+        secret_len: usize,
+        threshold: usize,
+        epoch: u64,
+        zero: &[(usize, &DealingTranscript<K>, &NodePackage<K>)],
+    ) -> Result<Share, RefreshError> {
+        let mut sorted = zero.to_vec();
+        sorted.sort_by_key(|x| x.0);
+        let group = Radix2Group::new((2 * share.leave_number).ilog2() as usize);
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&share.commitment);
+        hasher.update(&epoch.to_le_bytes());
+        let mut symbols = share.symbols.clone();
+        for (from, transcript, package) in sorted {
+            let sharing = &transcript.sharing;
+            if sharing.config.leave_number() != share.leave_number {
+                return Err(RefreshError::MixedCommittees);
+            }
+            let delta = check_share(package, transcript, share.index, |x| {
+                self.verify_zero(x, secret_len, threshold, epoch)
+            })
+            .map_err(|error| match error {
+                ShareError::InvalidTranscript => RefreshError::InvalidDealing { from },
+                error => RefreshError::Share { from, error },
+            })?;
+            hasher.update(&(from as u64).to_le_bytes());
+            hasher.update(&delta.commitment);
+            let shift = [sharing.secret_len as u64];
+            for (k, (position, r)) in delta.evaluations(0).into_iter().enumerate() {
+                symbols[k] += group.element_at(position).pow(shift) * r;
+            }
+        }
+        Ok(Share {
+            commitment: *hasher.finalize().as_bytes(),
+            symbols,
+            ..share.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        reconstruct::{reconstruct, Faults},
+        session::Session,
+        share::verify_my_share,
+        SoundnessRegime,
+    };
+
+    #[test]
+    fn zero_refresh() {
+        let mut rng = StdRng::seed_from_u64(185);
+        let dealer = Dealer {
+            context: b"refresh".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let schedule = RefreshSchedule {
+            start: 1_700_000_000,
+            period: 3600,
+        };
+        assert_eq!(schedule.epoch(1_700_007_300), Some(2));
+        assert!(schedule.due(1, 1_700_007_300) && !schedule.due(2, 1_700_007_300));
+        let epoch = schedule.epoch(1_700_007_300).unwrap();
+
+        let nodes = (0..16u64).collect::<Vec<_>>();
+        let secret = [Fr::rand(&mut rng)];
+        let dealing = dealer.deal(&secret, &nodes, 4, &mut rng).unwrap();
+        let expected = dealer.sharing(1, 16, 4).unwrap();
+        let shares = dealing
+            .packages
+            .iter()
+            .map(|x| verify_my_share(x, &dealing.transcript, &expected, 4, x.index).unwrap())
+            .collect::<Vec<_>>();
+        let zeros = (0..3)
+            .map(|_| dealer.deal_zero(1, &nodes, 4, epoch, &mut rng).unwrap())
+            .collect::<Vec<_>>();
+        assert!(dealer.verify_zero(&zeros[0].transcript, 1, 4, epoch));
+        assert!(!dealer.verify_zero(&zeros[0].transcript, 1, 4, epoch + 1));
+        // A zero dealing of a larger degree would shift the quorum.
+        let wide = dealer.deal_zero(1, &nodes, 8, epoch, &mut rng).unwrap();
+        assert!(!dealer.verify_zero(&wide.transcript, 1, 4, epoch));
+        assert!(dealer.verify_zero(&wide.transcript, 1, 8, epoch));
+        // A committed polynomial of full degree fails the bound on r, so a
+        // nonzero secret cannot be passed off as a refresh. The proof runs
+        // more queries than the dealer asks for, so the cheat is caught.
+        let mut disguised = zeros[0].transcript.clone();
+        disguised.sharing.config.query_num = 30;
+        let config = &disguised.sharing.config;
+        let random = (0..2)
+            .map(|_| (0..16).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let prover = Prover::new(&random, &config.groups()[0], &ProveOptions::default())
+            .unwrap()
+            .with_degree_bounds(bounds(&disguised.sharing));
+        disguised.root = prover.commit();
+        disguised.commitment = config.commitment(&disguised.root);
+        disguised.proof = prover.prove(config);
+        assert!(!dealer.verify_zero(&disguised, 1, 4, epoch));
+
+        let zero = |j: usize| {
+            zeros
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (i, &x.transcript, &x.packages[j]))
+                .collect::<Vec<_>>()
+        };
+        let refreshed = shares
+            .iter()
+            .map(|x| dealer.refresh(x, 1, 4, epoch, &zero(x.index)).unwrap())
+            .collect::<Vec<_>>();
+        assert_ne!(refreshed[0].symbols, shares[0].symbols);
+        let recovered = reconstruct(&refreshed[2..10], 8, 1, Faults::Reject).unwrap();
+        assert_eq!(recovered.secret, secret);
+
+        // Old and refreshed shares do not mix, nor do shares refreshed with
+        // different zero dealings.
+        let mut mixed = refreshed[..7].to_vec();
+        mixed.push(shares[7].clone());
+        assert!(reconstruct(&mixed, 8, 1, Faults::Reject).is_err());
+        let partial = dealer
+            .refresh(&shares[7], 1, 4, epoch, &zero(7)[..2])
+            .unwrap();
+        mixed[7] = partial;
+        assert!(reconstruct(&mixed, 8, 1, Faults::Reject).is_err());
+        assert_eq!(
+            dealer.refresh(&shares[3], 1, 4, epoch + 1, &zero(3)),
+            Err(RefreshError::InvalidDealing { from: 0 })
+        );
+    }
+}
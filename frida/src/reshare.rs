@@ -0,0 +1,301 @@
+// Handing a shared secret to a new committee without reconstructing it. The
+// secret is a fixed linear function of the evaluations any qualified set of
+// old nodes holds, so each node of the set deals its own part of that
+// function, a secret of the same length, to the new committee with
+// `Dealer::deal`. A new node adds up what every forward dealing gave it; the
+// sums are shares of a polynomial whose low coefficients are the old secret,
+// under the new threshold. Each forward dealing is made under a context
+// binding the old dealing's commitment and the old node's index, and a new
+// node checks every one against the sharing and threshold agreed for the new
+// committee, so a dealing cannot be replayed from another resharing or
+// passed off as another old node's. The hash commitments still cannot show
+// that a node dealt its true part; a wrong part only surfaces when the
+// secret is used.
+use std::fmt;
+
+use ark_bn254::Fr;
+use ark_ff::{One, Zero};
+use rand::Rng;
+use util::mul_group::Radix2Group;
+
+use crate::{
+    dealer::{DealError, Dealer, Dealing, DealingTranscript, NodePackage},
+    hygiene::scrub,
+    share::{check_share, Share, ShareError},
+};
+
+const RESHARE_LABEL: &[u8] = b"frida-reshare";
+
+// The dealer old node `from` of the dealing committed to as `commitment`
+// deals forward as.
+fn forward_dealer(dealer: &Dealer, commitment: &[u8; 32], from: usize) -> Dealer {
+    let mut context = dealer.context.clone();
+    context.extend(RESHARE_LABEL);
+    context.extend(commitment);
+    context.extend((from as u64).to_le_bytes());
+    Dealer {
+        context,
+        ..dealer.clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReshareError {
+    // The old nodes are not `old_quorum` distinct members, or the share
+    // is not among them.
+    NotQualified,
+    MixedCommittees,
+    Share { from: usize, error: ShareError },
+    Deal(DealError),
+}
+
+impl fmt::Display for ReshareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReshareError::NotQualified => write!(f, "old nodes do not form a qualified set"),
+            ReshareError::MixedCommittees => {
+                write!(f, "forward dealings are for different committees")
+            }
+            ReshareError::Share { from, error } => {
+                write!(f, "share dealt by old node {}: {}", from, error)
+            }
+            ReshareError::Deal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReshareError {}
+
+fn qualified_set(qualified: &[usize], size: usize) -> Option<Vec<usize>> {
+    let mut set = qualified.to_vec();
+    set.sort_unstable();
+    set.dedup();
+    (size > 0 && set.len() == size && set.len() == qualified.len()).then_some(set)
+}
+
+// The old nodes taking part, the quorum of the old sharing and the
+// threshold of the new one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resharing {
+    pub qualified: Vec<usize>,
+    pub old_quorum: usize,
+    pub new_threshold: usize,
+    pub secret_len: usize,
+}
+
+impl Resharing {
+    // The part of the secret `share` contributes when `qualified` reconstruct.
+    pub fn contribution(&self, share: &Share) -> Result<Vec<Fr>, ReshareError> {
+        let set =
+            qualified_set(&self.qualified, self.old_quorum).ok_or(ReshareError::NotQualified)?;
+        if !set.contains(&share.index) {
+            return Err(ReshareError::NotQualified);
+        }
+        let group = Radix2Group::new((2 * share.leave_number).ilog2() as usize);
+        let positions = set
+            .iter()
+            .flat_map(|x| [*x, x + share.leave_number])
+            .collect::<Vec<_>>();
+        let mut part = vec![Fr::zero(); self.secret_len];
+        for (position, value) in share.evaluations(0) {
+            // The Lagrange basis polynomial of `position` over the set.
+            let unit = positions
+                .iter()
+                .map(|&x| (x, if x == position { Fr::one() } else { Fr::zero() }))
+                .collect::<Vec<_>>();
+            let basis = group.interpolate(&unit, unit.len()).unwrap();
+            for (p, b) in part.iter_mut().zip(basis) {
+                *p += b * value;
+            }
+        }
+        Ok(part)
+    }
+
+    // What old node `share.index` deals to the new committee.
+    pub fn deal_forward<K: Clone, R: Rng>(
+        &self,
+        dealer: &Dealer,
+        share: &Share,
+        new_nodes: &[K],
+        rng: &mut R,
+    ) -> Result<Dealing<K>, ReshareError> {
+        let mut part = self.contribution(share)?;
+        let dealing = forward_dealer(dealer, &share.commitment, share.index).deal(
+            &part,
+            new_nodes,
+            self.new_threshold,
+            rng,
+        );
+        scrub(&mut part);
+        dealing.map_err(ReshareError::Deal)
+    }
+
+    // Whether `transcript` is what old node `from` of the dealing committed
+    // to as `commitment` dealt forward under this resharing.
+    pub fn verify_forward<K>(
+        &self,
+        dealer: &Dealer,
+        commitment: &[u8; 32],
+        from: usize,
+        transcript: &DealingTranscript<K>,
+    ) -> bool {
+        forward_dealer(dealer, commitment, from).verify(
+            transcript,
+            self.secret_len,
+            self.new_threshold,
+        )
+    }
+
+    // New node `my_index`'s share, from the package every old node of
+    // `qualified` of the dealing committed to as `commitment` dealt it, keyed
+    // by that old node's index.
+    pub fn aggregate<K: PartialEq>(
+        &self,
+        dealer: &Dealer,
+        commitment: &[u8; 32],
+        forward: &[(usize, &DealingTranscript<K>, &NodePackage<K>)],
+        my_index: usize,
+    ) -> Result<Share, ReshareError> {
+        let set =
+            qualified_set(&self.qualified, self.old_quorum).ok_or(ReshareError::NotQualified)?;
+        let from = forward.iter().map(|x| x.0).collect::<Vec<_>>();
+        if qualified_set(&from, from.len()).as_ref() != Some(&set) {
+            return Err(ReshareError::NotQualified);
+        }
+        let first = forward[0].1;
+        if forward
+            .iter()
+            .any(|(_, transcript, _)| transcript.nodes != first.nodes)
+        {
+            return Err(ReshareError::MixedCommittees);
+        }
+        let mut sorted = forward.to_vec();
+        sorted.sort_by_key(|x| x.0);
+        let mut hasher = blake3::Hasher::new();
+        let mut symbols = vec![Fr::zero(); 2 * first.sharing.config.poly_num];
+        for (from, transcript, package) in sorted {
+            let share = check_share(package, transcript, my_index, |x| {
+                self.verify_forward(dealer, commitment, from, x)
+            })
+            .map_err(|error| ReshareError::Share { from, error })?;
+            hasher.update(&(from as u64).to_le_bytes());
+            hasher.update(&share.commitment);
+            for (s, x) in symbols.iter_mut().zip(&share.symbols) {
+                *s += x;
+            }
+        }
+        Ok(Share {
+            index: my_index,
+            leave_number: first.sharing.config.leave_number(),
+            commitment: *hasher.finalize().as_bytes(),
+            symbols,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{
+        reconstruct::{reconstruct, Faults},
+        session::Session,
+        share::verify_my_share,
+        SoundnessRegime,
+    };
+
+    #[test]
+    fn reshare_to_new_committee() {
+        let mut rng = thread_rng();
+        let dealer = Dealer {
+            context: b"reshare".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let secret = [Fr::rand(&mut rng)];
+        let old = dealer
+            .deal(&secret, &(0..16u64).collect::<Vec<_>>(), 4, &mut rng)
+            .unwrap();
+        let expected = dealer.sharing(1, 16, 4).unwrap();
+        let shares = old
+            .packages
+            .iter()
+            .map(|x| verify_my_share(x, &old.transcript, &expected, 4, x.index).unwrap())
+            .collect::<Vec<_>>();
+
+        // Eight old nodes hand the secret to sixteen new nodes with
+        // threshold four.
+        let resharing = Resharing {
+            qualified: vec![6, 1, 4, 3, 12, 9, 15, 0],
+            old_quorum: old.transcript.sharing.quorum(),
+            new_threshold: 4,
+            secret_len: 1,
+        };
+        let new_nodes = (100..116u64).collect::<Vec<_>>();
+        let commitment = &old.transcript.commitment;
+        let forward = resharing
+            .qualified
+            .iter()
+            .map(|&i| {
+                let dealing = resharing
+                    .deal_forward(&dealer, &shares[i], &new_nodes, &mut rng)
+                    .unwrap();
+                (i, dealing)
+            })
+            .collect::<Vec<_>>();
+        let parts = |j: usize| {
+            forward
+                .iter()
+                .map(|(i, x)| (*i, &x.transcript, &x.packages[j]))
+                .collect::<Vec<_>>()
+        };
+        let new_shares = (0..16)
+            .map(|j| {
+                resharing
+                    .aggregate(&dealer, commitment, &parts(j), j)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let recovered = reconstruct(&new_shares[5..13], 8, 1, Faults::Reject).unwrap();
+        assert_eq!(recovered.secret, secret);
+
+        assert_eq!(
+            resharing.contribution(&shares[2]),
+            Err(ReshareError::NotQualified)
+        );
+        assert_eq!(
+            resharing.aggregate(&dealer, commitment, &parts(0)[..7], 0),
+            Err(ReshareError::NotQualified)
+        );
+        let mut wrong = parts(0);
+        wrong[2].2 = &forward[2].1.packages[1];
+        assert!(matches!(
+            resharing.aggregate(&dealer, commitment, &wrong, 0),
+            Err(ReshareError::Share { from: 4, .. })
+        ));
+
+        // A forward dealing names the old node and dealing it comes from.
+        let dealt = &forward[2].1.transcript;
+        assert!(resharing.verify_forward(&dealer, commitment, 4, dealt));
+        assert!(!resharing.verify_forward(&dealer, commitment, 3, dealt));
+        assert!(!resharing.verify_forward(&dealer, &[0; 32], 4, dealt));
+        let mut swapped = parts(0);
+        swapped.swap(1, 2);
+        swapped[1].0 = 1;
+        swapped[2].0 = 4;
+        assert_eq!(
+            resharing.aggregate(&dealer, commitment, &swapped, 0),
+            Err(ReshareError::Share {
+                from: 1,
+                error: ShareError::InvalidTranscript
+            })
+        );
+        let unbound = dealer
+            .deal(&[Fr::rand(&mut rng)], &new_nodes, 4, &mut rng)
+            .unwrap();
+        assert!(!resharing.verify_forward(&dealer, commitment, 4, &unbound.transcript));
+    }
+}
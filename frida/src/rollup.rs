@@ -0,0 +1,393 @@
+// Posting an L2 batch: the batch bytes are packed, committed and dispersed to
+// the committee, each node signs the blob's header once its shares check, and
+// a quorum of those signatures is the certificate an L1 inbox accepts. The
+// config is a function of the rollup's settings and the batch length alone,
+// so the inbox recomputes the parameter hash instead of trusting it. The
+// signature scheme is the deployment's, as for evidence.
+use std::fmt;
+
+#[cfg(feature = "prover")]
+use crate::blob::commit_blob;
+use crate::{
+    plan::{DispersalInputs, DispersalPlan},
+    scheme::FridaShare,
+    slashing::SignatureScheme,
+    FridaConfig, FridaProof, SecurityLevel, SoundnessRegime, Verifier,
+};
+
+const ATTESTATION_DOMAIN: &[u8] = b"frida-rollup-attestation";
+const WORD: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollupConfig {
+    pub context: Vec<u8>,
+    pub node_num: usize,
+    pub adversary_fraction: f64,
+    pub failure_probability: f64,
+    pub security: SecurityLevel,
+    pub regime: SoundnessRegime,
+}
+
+impl RollupConfig {
+    // The config a batch of `blob_len` bytes is committed under.
+    pub fn config(&self, blob_len: usize) -> Option<(DispersalPlan, FridaConfig)> {
+        let plan = DispersalPlan::pick(&DispersalInputs {
+            blob_size: blob_len,
+            node_num: self.node_num,
+            adversary_fraction: self.adversary_fraction,
+            failure_probability: self.failure_probability,
+        })?;
+        let config = FridaConfig {
+            regime: self.regime,
+            ..FridaConfig::new(
+                &self.context,
+                plan.poly_num,
+                plan.log_degree,
+                plan.code_rate,
+                self.security.query_num(plan.code_rate, self.regime),
+            )
+        };
+        Some((plan, config))
+    }
+
+    // Node i holds leaves i, i + node_num, and so on.
+    pub fn leaves_of(&self, config: &FridaConfig, node: usize) -> Vec<usize> {
+        (node..config.first_oracle_leaves())
+            .step_by(self.node_num)
+            .collect()
+    }
+}
+
+// What a node signs: the header of the blob it holds shares of.
+pub fn attestation_message(root: &[u8; 32], params_hash: &[u8; 32], blob_len: u64) -> Vec<u8> {
+    let mut message = ATTESTATION_DOMAIN.to_vec();
+    message.extend(root);
+    message.extend(params_hash);
+    message.extend(blob_len.to_le_bytes());
+    message
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate<S: SignatureScheme> {
+    // Committee indices, ascending, one signature each.
+    pub signers: Vec<usize>,
+    pub signatures: Vec<S::Signature>,
+}
+
+impl<S: SignatureScheme> Certificate<S> {
+    pub fn new(mut signed: Vec<(usize, S::Signature)>) -> Self {
+        signed.sort_by_key(|x| x.0);
+        let (signers, signatures) = signed.into_iter().unzip();
+        Certificate {
+            signers,
+            signatures,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttestationInput<S: SignatureScheme> {
+    pub root: [u8; 32],
+    pub params_hash: [u8; 32],
+    pub blob_len: u64,
+    pub cert: Certificate<S>,
+}
+
+fn word(x: u64) -> [u8; WORD] {
+    let mut out = [0; WORD];
+    out[WORD - 8..].copy_from_slice(&x.to_be_bytes());
+    out
+}
+
+impl<S: SignatureScheme> AttestationInput<S>
+where
+    S::Signature: AsRef<[u8]>,
+{
+    // 32-byte words as an inbox contract reads them: root, parameter hash,
+    // blob length and signer count, then one word per signer index, then
+    // each signature as a length word and its bytes padded to whole words.
+    // Integers are big-endian.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = self.root.to_vec();
+        out.extend(self.params_hash);
+        out.extend(word(self.blob_len));
+        out.extend(word(self.cert.signers.len() as u64));
+        for signer in &self.cert.signers {
+            out.extend(word(*signer as u64));
+        }
+        for signature in &self.cert.signatures {
+            let bytes = signature.as_ref();
+            out.extend(word(bytes.len() as u64));
+            out.extend(bytes);
+            out.resize(out.len().next_multiple_of(WORD), 0);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttestationError {
+    // The batch length has no config under the rollup's settings.
+    NoConfig,
+    ParamsMismatch,
+    SignerCount,
+    // Signers must be ascending committee indices.
+    UnorderedSigners,
+    UnknownSigner { signer: usize },
+    BadSignature { signer: usize },
+    BelowQuorum { signers: usize, quorum: usize },
+}
+
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttestationError::NoConfig => write!(f, "no config fits the blob length"),
+            AttestationError::ParamsMismatch => write!(f, "parameter hash does not match"),
+            AttestationError::SignerCount => write!(f, "signers and signatures differ in number"),
+            AttestationError::UnorderedSigners => write!(f, "signers are not strictly ascending"),
+            AttestationError::UnknownSigner { signer } => {
+                write!(f, "signer {} is not in the committee", signer)
+            }
+            AttestationError::BadSignature { signer } => {
+                write!(f, "signature of signer {} does not verify", signer)
+            }
+            AttestationError::BelowQuorum { signers, quorum } => {
+                write!(f, "{} signers, {} needed", signers, quorum)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttestationError {}
+
+// The inbox-side check: `input` is for a config of the rollup's, and at least
+// `quorum` members of `committee` signed its header.
+pub fn verify_attestation<S: SignatureScheme>(
+    rollup: &RollupConfig,
+    input: &AttestationInput<S>,
+    committee: &[S::PublicKey],
+    quorum: usize,
+) -> Result<(), AttestationError> {
+    let (_, config) = usize::try_from(input.blob_len)
+        .ok()
+        .and_then(|x| rollup.config(x))
+        .ok_or(AttestationError::NoConfig)?;
+    if config.params_hash() != input.params_hash {
+        return Err(AttestationError::ParamsMismatch);
+    }
+    let cert = &input.cert;
+    if cert.signers.len() != cert.signatures.len() {
+        return Err(AttestationError::SignerCount);
+    }
+    if cert.signers.windows(2).any(|x| x[0] >= x[1]) {
+        return Err(AttestationError::UnorderedSigners);
+    }
+    let message = attestation_message(&input.root, &input.params_hash, input.blob_len);
+    for (&signer, signature) in cert.signers.iter().zip(&cert.signatures) {
+        let key = committee
+            .get(signer)
+            .ok_or(AttestationError::UnknownSigner { signer })?;
+        if !S::verify(key, &message, signature) {
+            return Err(AttestationError::BadSignature { signer });
+        }
+    }
+    if cert.signers.len() < quorum {
+        return Err(AttestationError::BelowQuorum {
+            signers: cert.signers.len(),
+            quorum,
+        });
+    }
+    Ok(())
+}
+
+// The node-side step: checks the proof and every share node `node` was sent,
+// and signs the header if they all hold.
+pub fn attest<S: SignatureScheme, F: FnOnce(&[u8]) -> S::Signature>(
+    rollup: &RollupConfig,
+    node: usize,
+    root: &[u8; 32],
+    blob_len: u64,
+    proof: &FridaProof,
+    shares: &[FridaShare],
+    sign: F,
+) -> Option<S::Signature> {
+    let (_, config) = rollup.config(usize::try_from(blob_len).ok()?)?;
+    let assigned = rollup.leaves_of(&config, node);
+    let valid = proof.merkle_root == *root
+        && Verifier::verify_proof(&config, &config.commitment(root), proof).accepted()
+        && shares.iter().map(|x| x.index).eq(assigned)
+        && shares.iter().all(|x| {
+            Verifier::verify_chunk(
+                root,
+                config.first_oracle_leaves(),
+                config.leaf_size(0),
+                x.index,
+                &x.symbols,
+                &x.path,
+            )
+        });
+    valid.then(|| sign(&attestation_message(root, &config.params_hash(), blob_len)))
+}
+
+#[cfg(feature = "prover")]
+pub struct BatchDispersal {
+    pub config: FridaConfig,
+    pub root: [u8; 32],
+    pub blob_len: u64,
+    pub proof: FridaProof,
+    // Node i's shares, as `RollupConfig::leaves_of` assigns them.
+    pub shares: Vec<Vec<FridaShare>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RollupError {
+    EmptyBatch,
+    NoConfig { blob_len: usize },
+}
+
+impl fmt::Display for RollupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RollupError::EmptyBatch => write!(f, "batch is empty"),
+            RollupError::NoConfig { blob_len } => {
+                write!(f, "no config fits a batch of {} bytes", blob_len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RollupError {}
+
+// Packs `batch`, commits to it and cuts every node's shares.
+#[cfg(feature = "prover")]
+pub fn disperse_batch(rollup: &RollupConfig, batch: &[u8]) -> Result<BatchDispersal, RollupError> {
+    if batch.is_empty() {
+        return Err(RollupError::EmptyBatch);
+    }
+    let (_, config) = rollup.config(batch.len()).ok_or(RollupError::NoConfig {
+        blob_len: batch.len(),
+    })?;
+    let prover = commit_blob(&config, batch);
+    let shares = (0..rollup.node_num)
+        .map(|node| {
+            rollup
+                .leaves_of(&config, node)
+                .into_iter()
+                .map(|index| {
+                    let (symbols, path) = prover.open_chunk(index);
+                    FridaShare {
+                        index,
+                        symbols,
+                        path,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Ok(BatchDispersal {
+        root: prover.commit(),
+        blob_len: batch.len() as u64,
+        proof: prover.prove(&config),
+        shares,
+        config,
+    })
+}
+
+#[cfg(feature = "prover")]
+impl BatchDispersal {
+    pub fn attestation<S: SignatureScheme>(&self, cert: Certificate<S>) -> AttestationInput<S> {
+        AttestationInput {
+            root: self.root,
+            params_hash: self.config.params_hash(),
+            blob_len: self.blob_len,
+            cert,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Keyed;
+
+    impl SignatureScheme for Keyed {
+        type PublicKey = [u8; 32];
+        type Signature = [u8; 32];
+
+        fn verify(key: &[u8; 32], message: &[u8], signature: &[u8; 32]) -> bool {
+            blake3::keyed_hash(key, message).as_bytes() == signature
+        }
+    }
+
+    #[test]
+    fn batch_attestation() {
+        let mut rng = thread_rng();
+        let rollup = RollupConfig {
+            context: b"rollup".to_vec(),
+            node_num: 8,
+            adversary_fraction: 0.25,
+            failure_probability: 1e-6,
+            security: SecurityLevel::Bits100,
+            regime: SoundnessRegime::Proven,
+        };
+        let batch = (0..2000).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let dispersal = disperse_batch(&rollup, &batch).unwrap();
+        let keys = (0..8u8).map(|x| [x; 32]).collect::<Vec<_>>();
+        let signed = (0..8)
+            .filter_map(|node| {
+                let mut shares = dispersal.shares[node].clone();
+                // Node 2 was sent a corrupted share and refuses to sign.
+                if node == 2 {
+                    shares[0].symbols[0] += ark_bn254::Fr::from(1u64);
+                }
+                let signature = attest::<Keyed, _>(
+                    &rollup,
+                    node,
+                    &dispersal.root,
+                    dispersal.blob_len,
+                    &dispersal.proof,
+                    &shares,
+                    |message| *blake3::keyed_hash(&keys[node], message).as_bytes(),
+                )?;
+                Some((node, signature))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(signed.len(), 7);
+
+        let input = dispersal.attestation(Certificate::<Keyed>::new(signed));
+        assert_eq!(verify_attestation(&rollup, &input, &keys, 6), Ok(()));
+        assert_eq!(
+            verify_attestation(&rollup, &input, &keys, 8),
+            Err(AttestationError::BelowQuorum {
+                signers: 7,
+                quorum: 8
+            })
+        );
+        assert_eq!(input.encode().len(), 4 * 32 + 7 * 32 + 7 * 64);
+
+        let other = RollupConfig {
+            regime: SoundnessRegime::Conjectured,
+            ..rollup.clone()
+        };
+        assert_eq!(
+            verify_attestation(&other, &input, &keys, 6),
+            Err(AttestationError::ParamsMismatch)
+        );
+        let mut forged = input.clone();
+        forged.root[0] ^= 1;
+        assert_eq!(
+            verify_attestation(&rollup, &forged, &keys, 6),
+            Err(AttestationError::BadSignature { signer: 0 })
+        );
+        let mut padded = input;
+        padded.cert.signers[1] = padded.cert.signers[0];
+        assert_eq!(
+            verify_attestation(&rollup, &padded, &keys, 6),
+            Err(AttestationError::UnorderedSigners)
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use crate::FridaConfig;
+
+// Leaves per oracle across the folding rounds. Both prover and verifier map
+// query positions through this, so their per-round index sets always agree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySchedule {
+    leave_numbers: Vec<usize>,
+}
+
+impl QuerySchedule {
+    pub fn new(leave_number: usize, rounds: usize) -> Self {
+        assert!(leave_number.is_power_of_two());
+        assert!(rounds == 0 || leave_number >> (rounds - 1) > 0);
+        QuerySchedule {
+            leave_numbers: (0..rounds).map(|i| leave_number >> i).collect(),
+        }
+    }
+
+    pub fn from_config(config: &FridaConfig) -> Self {
+        Self::new(config.leave_number(), config.log_degree)
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.leave_numbers.len()
+    }
+
+    pub fn leave_number(&self, round: usize) -> usize {
+        self.leave_numbers[round]
+    }
+
+    // Sorted, deduplicated leaves opened in `round` for the given positions.
+    pub fn indices(&self, round: usize, leaf_indices: &[usize]) -> Vec<usize> {
+        let len = self.leave_numbers[round];
+        let mut res = leaf_indices.iter().map(|x| x % len).collect::<Vec<_>>();
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    pub fn all_indices(&self, leaf_indices: &[usize]) -> Vec<Vec<usize>> {
+        self.query_indices(leaf_indices).rounds
+    }
+
+    pub fn query_indices(&self, leaf_indices: &[usize]) -> QueryIndices {
+        QueryIndices {
+            positions: leaf_indices.to_vec(),
+            rounds: (0..self.rounds())
+                .map(|round| self.indices(round, leaf_indices))
+                .collect(),
+        }
+    }
+}
+
+// Query positions normalized for every round at once, which is what both
+// prover and verifier open against. Each round's leaves are ascending and
+// distinct, the order openings are listed and encoded in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueryIndices {
+    positions: Vec<usize>,
+    rounds: Vec<Vec<usize>>,
+}
+
+impl QueryIndices {
+    // The positions as drawn, repeats included.
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds.len()
+    }
+
+    pub fn round(&self, round: usize) -> &[usize] {
+        &self.rounds[round]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule() {
+        let schedule = QuerySchedule::new(16, 4);
+        assert_eq!(schedule.rounds(), 4);
+        assert_eq!(schedule.leave_number(3), 2);
+        assert_eq!(
+            schedule.all_indices(&[31, 5, 21, 13]),
+            vec![vec![5, 13, 15], vec![5, 7], vec![1, 3], vec![1]]
+        );
+        let indices = schedule.query_indices(&[31, 5, 21, 13, 5]);
+        assert_eq!(indices.positions(), &[31, 5, 21, 13, 5]);
+        assert_eq!(indices.rounds(), 4);
+        assert_eq!(indices.round(0), &[5, 13, 15]);
+        let reordered = schedule.query_indices(&[13, 21, 5, 31]);
+        assert!((0..4).all(|r| indices.round(r) == reordered.round(r)));
+    }
+}
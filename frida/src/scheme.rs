@@ -0,0 +1,112 @@
+// FRIDA behind the shared `VrsScheme` interface. Data is split into
+// `poly_num` coefficient vectors; share i is first-oracle leaf i, holding
+// every polynomial at x_i and -x_i, and any 2^(log_degree - 1) distinct
+// shares recover the data. Under `Interleave::Rows` a leaf holds only its
+// group's polynomials, and that many of every group are needed.
+use ark_bn254::Fr;
+#[cfg(feature = "prover")]
+use util::{mul_group::Radix2Group, vrs::VrsScheme};
+
+use crate::FridaConfig;
+#[cfg(feature = "prover")]
+use crate::{blob::decode_poly, FridaProof, Prover, Verifier};
+
+pub struct FridaScheme {
+    pub config: FridaConfig,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FridaShare {
+    pub index: usize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::fields"))]
+    pub symbols: Vec<Fr>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
+    pub path: Vec<u8>,
+}
+
+#[cfg(feature = "prover")]
+impl VrsScheme for FridaScheme {
+    type Commitment = [u8; 32];
+    type State = Prover;
+    type Proof = FridaProof;
+    type Share = FridaShare;
+
+    fn commit(&self, data: &[Fr]) -> ([u8; 32], Prover) {
+        assert_eq!(data.len(), self.config.poly_num << self.config.log_degree);
+        let polies = data
+            .chunks(1 << self.config.log_degree)
+            .map(|x| x.to_vec())
+            .collect::<Vec<_>>();
+        let prover =
+            Prover::interleaved(&polies, &self.config.groups()[0], self.config.leaf_rows());
+        (prover.commit(), prover)
+    }
+
+    fn disperse(&self, prover: &Prover) -> (FridaProof, Vec<FridaShare>) {
+        let shares = (0..self.config.first_oracle_leaves())
+            .map(|i| self.sample(prover, i))
+            .collect();
+        (prover.prove(&self.config), shares)
+    }
+
+    fn sample(&self, prover: &Prover, index: usize) -> FridaShare {
+        let (symbols, path) = prover.open_chunk(index);
+        FridaShare {
+            index,
+            symbols,
+            path,
+        }
+    }
+
+    fn verify(&self, commitment: &[u8; 32], proof: &FridaProof, share: &FridaShare) -> bool {
+        proof.merkle_root == *commitment
+            && Verifier::verify_chunk(
+                commitment,
+                self.config.first_oracle_leaves(),
+                self.config.leaf_size(0),
+                share.index,
+                &share.symbols,
+                &share.path,
+            )
+            && Verifier::verify_proof(&self.config, &self.config.commitment(commitment), proof)
+                .accepted()
+    }
+
+    fn reconstruct(&self, shares: &[FridaShare]) -> Option<Vec<Fr>> {
+        let group = Radix2Group::new(self.config.log_degree + self.config.code_rate);
+        let mut data = vec![];
+        for k in 0..self.config.poly_num {
+            data.extend(decode_poly(&self.config, &group, shares, k)?);
+        }
+        Some(data)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+    use util::vrs::roundtrip;
+
+    use super::*;
+
+    #[test]
+    fn vrs_roundtrip() {
+        let mut rng = thread_rng();
+        let scheme = FridaScheme {
+            config: FridaConfig::new(b"vrs", 2, 4, 1, 8),
+        };
+        let data = (0..32)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        assert!(roundtrip(&scheme, &data, 8));
+
+        let (commitment, prover) = scheme.commit(&data);
+        let (proof, shares) = scheme.disperse(&prover);
+        let mut forged = shares[3].clone();
+        forged.symbols[0] += Fr::from(1u64);
+        assert!(!scheme.verify(&commitment, &proof, &forged));
+        assert!(scheme.reconstruct(&shares[..7]).is_none());
+    }
+}
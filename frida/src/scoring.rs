@@ -0,0 +1,305 @@
+// Reputation of sampling peers across sessions. A peer earns a point per
+// response that verifies in time and loses points for refusals, timeouts and
+// responses slower than `slow`, so unreliable peers sink in the client's
+// order; scores are clamped so a long good record cannot hide a bad streak.
+// A peer serving a response that fails verification is banned outright and
+// the response is kept, so the ban can be shown to anyone holding the root.
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read, Seek, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::service::{PeerId, SampleResponse};
+
+const SCORES_MAGIC: &[u8; 4] = b"FRPS";
+// Peer id, five counters and the offense tag.
+const PEER_BYTES: u64 = 6 * 8 + 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringConfig {
+    pub slow: Duration,
+    pub max_score: i64,
+    pub min_score: i64,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig {
+            slow: Duration::from_millis(500),
+            max_score: 100,
+            min_score: -100,
+        }
+    }
+}
+
+// An invalid response as received for `requested` leaves of `root`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Offense {
+    pub root: [u8; 32],
+    pub leave_number: usize,
+    pub requested: Vec<usize>,
+    pub response: SampleResponse,
+}
+
+impl Offense {
+    // Whether the response really is invalid for the request, so a ban
+    // carried by a saved score file can be rechecked.
+    pub fn holds(&self) -> bool {
+        self.response.leaves != self.requested
+            || !self.response.verify(&self.root, self.leave_number)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerScore {
+    pub score: i64,
+    pub served: u64,
+    pub slow: u64,
+    pub timeouts: u64,
+    pub refused: u64,
+    pub offense: Option<Offense>,
+}
+
+impl PeerScore {
+    pub fn banned(&self) -> bool {
+        self.offense.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scoreboard {
+    config: ScoringConfig,
+    peers: BTreeMap<PeerId, PeerScore>,
+}
+
+impl Scoreboard {
+    pub fn new(config: ScoringConfig) -> Self {
+        Scoreboard {
+            config,
+            peers: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, peer: PeerId) -> Option<&PeerScore> {
+        self.peers.get(&peer)
+    }
+
+    pub fn score(&self, peer: PeerId) -> i64 {
+        self.peers.get(&peer).map_or(0, |x| x.score)
+    }
+
+    pub fn banned(&self, peer: PeerId) -> bool {
+        self.peers.get(&peer).is_some_and(|x| x.banned())
+    }
+
+    fn adjust(&mut self, peer: PeerId, delta: i64) -> &mut PeerScore {
+        let (min, max) = (self.config.min_score, self.config.max_score);
+        let entry = self.peers.entry(peer).or_default();
+        entry.score = (entry.score + delta).clamp(min, max);
+        entry
+    }
+
+    pub fn record_served(&mut self, peer: PeerId, latency: Duration) {
+        if latency > self.config.slow {
+            self.adjust(peer, -1).slow += 1;
+        } else {
+            self.adjust(peer, 1).served += 1;
+        }
+    }
+
+    pub fn record_timeout(&mut self, peer: PeerId) {
+        self.adjust(peer, -5).timeouts += 1;
+    }
+
+    pub fn record_refused(&mut self, peer: PeerId) {
+        self.adjust(peer, -2).refused += 1;
+    }
+
+    // Bans `peer` if `offense` holds; returns whether it did.
+    pub fn record_invalid(&mut self, peer: PeerId, offense: Offense) -> bool {
+        if !offense.holds() {
+            return false;
+        }
+        let min = self.config.min_score;
+        let entry = self.peers.entry(peer).or_default();
+        entry.score = min;
+        entry.offense.get_or_insert(offense);
+        true
+    }
+
+    // Clears a ban after it was resolved out of band.
+    pub fn pardon(&mut self, peer: PeerId) {
+        if let Some(entry) = self.peers.get_mut(&peer) {
+            entry.offense = None;
+            entry.score = 0;
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        file.write_all(SCORES_MAGIC)?;
+        file.write_all(&(self.peers.len() as u64).to_le_bytes())?;
+        for (peer, score) in self.peers.iter() {
+            file.write_all(&peer.to_le_bytes())?;
+            file.write_all(&score.score.to_le_bytes())?;
+            for x in [score.served, score.slow, score.timeouts, score.refused] {
+                file.write_all(&x.to_le_bytes())?;
+            }
+            match &score.offense {
+                None => file.write_all(&[0])?,
+                Some(offense) => {
+                    file.write_all(&[1])?;
+                    file.write_all(&offense.root)?;
+                    file.write_all(&(offense.leave_number as u64).to_le_bytes())?;
+                    file.write_all(&(offense.requested.len() as u64).to_le_bytes())?;
+                    for leaf in offense.requested.iter() {
+                        file.write_all(&(*leaf as u64).to_le_bytes())?;
+                    }
+                    let response = offense.response.to_bytes();
+                    file.write_all(&(response.len() as u64).to_le_bytes())?;
+                    file.write_all(&response)?;
+                }
+            }
+        }
+        file.flush()
+    }
+
+    // Bans whose offense no longer holds are dropped on load. Every count and
+    // length is checked against the bytes left in the file before anything
+    // is allocated for it, so a corrupt file is InvalidData, not an abort.
+    pub fn load<P: AsRef<Path>>(path: P, config: ScoringConfig) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let file = fs::File::open(path)?;
+        let total = file.metadata()?.len();
+        let mut file = io::BufReader::new(file);
+        let remaining = |file: &mut io::BufReader<fs::File>| -> io::Result<u64> {
+            Ok(total.saturating_sub(file.stream_position()?))
+        };
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != SCORES_MAGIC {
+            return Err(invalid("not a score file"));
+        }
+        let read_u64 = |file: &mut io::BufReader<fs::File>| -> io::Result<u64> {
+            let mut bytes = [0u8; 8];
+            file.read_exact(&mut bytes)?;
+            Ok(u64::from_le_bytes(bytes))
+        };
+        let mut board = Scoreboard::new(config);
+        let peers = read_u64(&mut file)?;
+        if peers > remaining(&mut file)? / PEER_BYTES {
+            return Err(invalid("peer count exceeds the file"));
+        }
+        for _ in 0..peers {
+            let peer = read_u64(&mut file)?;
+            let mut score = PeerScore {
+                score: read_u64(&mut file)? as i64,
+                served: read_u64(&mut file)?,
+                slow: read_u64(&mut file)?,
+                timeouts: read_u64(&mut file)?,
+                refused: read_u64(&mut file)?,
+                offense: None,
+            };
+            let mut tag = [0u8];
+            file.read_exact(&mut tag)?;
+            if tag[0] == 1 {
+                let mut root = [0u8; 32];
+                file.read_exact(&mut root)?;
+                let leave_number = read_u64(&mut file)? as usize;
+                let requested = read_u64(&mut file)?;
+                if requested > remaining(&mut file)? / 8 {
+                    return Err(invalid("requested count exceeds the file"));
+                }
+                let requested = (0..requested)
+                    .map(|_| read_u64(&mut file).map(|x| x as usize))
+                    .collect::<io::Result<Vec<_>>>()?;
+                let len = read_u64(&mut file)?;
+                let mut response = vec![];
+                (&mut file).take(len).read_to_end(&mut response)?;
+                if response.len() as u64 != len {
+                    return Err(invalid("truncated offending response"));
+                }
+                let response = SampleResponse::from_bytes(&response)
+                    .ok_or_else(|| invalid("malformed offending response"))?;
+                score.offense = Some(Offense {
+                    root,
+                    leave_number,
+                    requested,
+                    response,
+                })
+                .filter(|x| x.holds());
+            } else if tag[0] != 0 {
+                return Err(invalid("bad offense tag"));
+            }
+            score.score = score.score.clamp(config.min_score, config.max_score);
+            board.peers.insert(peer, score);
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_bn254::Fr;
+
+    use super::*;
+    use crate::{fixtures::random_prover, FridaConfig};
+
+    #[test]
+    fn scores_and_bans() {
+        let config = FridaConfig::new(b"scoring", 2, 4, 1, 8);
+        let (_, prover) = random_prover(&config);
+        let root = prover.commit();
+        let offense = |response: SampleResponse| Offense {
+            root,
+            leave_number: config.leave_number(),
+            requested: vec![1, 4],
+            response,
+        };
+
+        let mut board = Scoreboard::new(ScoringConfig::default());
+        for _ in 0..3 {
+            board.record_served(1, Duration::from_millis(10));
+        }
+        board.record_served(2, Duration::from_secs(1));
+        board.record_timeout(2);
+        board.record_refused(3);
+        assert_eq!(
+            (board.score(1), board.score(2), board.score(3)),
+            (3, -6, -2)
+        );
+
+        // An honest response is no offense; a forged one bans its server.
+        let honest = prover.open_leaves(&[1, 4]).unwrap();
+        assert!(!board.record_invalid(4, offense(honest.clone())));
+        assert!(!board.banned(4));
+        let mut forged = honest.clone();
+        forged.symbols[1][0] += Fr::from(1u64);
+        assert!(board.record_invalid(4, offense(forged)));
+        let wrong_leaves = prover.open_leaves(&[1, 5]).unwrap();
+        assert!(board.record_invalid(5, offense(wrong_leaves)));
+        assert!(board.banned(4) && board.banned(5));
+
+        let path = std::env::temp_dir().join(format!("frida-scores-{}", std::process::id()));
+        board.save(&path).unwrap();
+        let loaded = Scoreboard::load(&path, ScoringConfig::default()).unwrap();
+        assert_eq!(loaded, board);
+
+        let saved = std::fs::read(&path).unwrap();
+        let mut huge = saved.clone();
+        huge[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &huge).unwrap();
+        let err = Scoreboard::load(&path, ScoringConfig::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // Peer 5's offending response is the last record.
+        std::fs::write(&path, &saved[..saved.len() - 1]).unwrap();
+        let err = Scoreboard::load(&path, ScoringConfig::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+        board.pardon(4);
+        assert!(!board.banned(4));
+    }
+}
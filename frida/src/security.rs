@@ -0,0 +1,83 @@
+// Named security targets that expand into complete configs, so integrators
+// pick a level instead of a query count. Query counts follow
+// `FridaConfig::soundness_bits`; grinding and merkle caps are not part of this
+// protocol, so every bit comes from queries.
+use crate::{plan::BYTES_PER_ELEMENT, FridaConfig, SoundnessRegime};
+
+// Blob sizes, in bytes, the presets are tuned and tested for.
+pub const COMMON_BLOB_SIZES: [usize; 3] = [128 << 10, 512 << 10, 2 << 20];
+pub const PRESET_CODE_RATE: usize = 2;
+pub const PRESET_LOG_POLY_NUM: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SecurityLevel {
+    Bits80,
+    Bits100,
+    Bits128,
+}
+
+impl SecurityLevel {
+    pub fn bits(self) -> usize {
+        match self {
+            SecurityLevel::Bits80 => 80,
+            SecurityLevel::Bits100 => 100,
+            SecurityLevel::Bits128 => 128,
+        }
+    }
+
+    // The fewest queries reaching this level at `code_rate`.
+    pub fn query_num(self, code_rate: usize, regime: SoundnessRegime) -> usize {
+        (self.bits() as f64 / -regime.query_error(code_rate).log2()).ceil() as usize
+    }
+
+    // A config for `blob_size` bytes: the blob is packed into field elements,
+    // rounded up to a power of two and split into up to 2^PRESET_LOG_POLY_NUM
+    // polynomials.
+    pub fn config(self, context: &[u8], blob_size: usize, regime: SoundnessRegime) -> FridaConfig {
+        let elements = blob_size.div_ceil(BYTES_PER_ELEMENT).max(4);
+        let log_elements = elements.next_power_of_two().ilog2() as usize;
+        let log_poly_num = PRESET_LOG_POLY_NUM.min(log_elements - 1);
+        FridaConfig {
+            regime,
+            ..FridaConfig::new(
+                context,
+                1 << log_poly_num,
+                log_elements - log_poly_num,
+                PRESET_CODE_RATE,
+                self.query_num(PRESET_CODE_RATE, regime),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets() {
+        for level in [
+            SecurityLevel::Bits80,
+            SecurityLevel::Bits100,
+            SecurityLevel::Bits128,
+        ] {
+            for (blob_size, regime) in COMMON_BLOB_SIZES.into_iter().flat_map(|x| {
+                [
+                    (x, SoundnessRegime::Proven),
+                    (x, SoundnessRegime::Conjectured),
+                ]
+            }) {
+                let config = level.config(b"preset", blob_size, regime);
+                let capacity = config.poly_num << config.log_degree;
+                assert!(capacity * BYTES_PER_ELEMENT >= blob_size);
+                assert!(capacity * BYTES_PER_ELEMENT < 2 * blob_size);
+                assert!(config.soundness_bits(config.query_num) >= level.bits() as f64);
+                assert!(config.soundness_bits(config.query_num - 1) < level.bits() as f64);
+            }
+        }
+        let tiny = SecurityLevel::Bits100.config(b"", 1, SoundnessRegime::Proven);
+        assert_eq!(tiny.poly_num, 2);
+        assert_eq!(tiny.query_num, 100);
+    }
+}
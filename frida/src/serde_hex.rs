@@ -0,0 +1,140 @@
+// Field encodings for the `serde` feature. Byte strings, roots and field
+// elements (compressed) are lowercase hex strings so proofs read the same in
+// JSON-RPC payloads and document stores; query values are a key-sorted list of
+// (key, value) pairs so equal proofs serialize identically.
+use std::collections::HashMap;
+
+use ark_bn254::Fr;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+pub(crate) fn decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn field_hex(x: &Fr) -> String {
+    let mut bytes = vec![];
+    x.serialize_compressed(&mut bytes).unwrap();
+    encode(&bytes)
+}
+
+fn hex_field<E: Error>(s: &str) -> Result<Fr, E> {
+    let bytes = decode(s).ok_or_else(|| E::custom("invalid hex"))?;
+    Fr::deserialize_compressed(bytes.as_slice()).map_err(|_| E::custom("invalid field element"))
+}
+
+// `Vec<u8>` and `[u8; N]`.
+pub mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(x: &T, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&encode(x.as_ref()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: TryFrom<Vec<u8>>>(
+        d: D,
+    ) -> Result<T, D::Error> {
+        let bytes =
+            decode(&String::deserialize(d)?).ok_or_else(|| D::Error::custom("invalid hex"))?;
+        T::try_from(bytes).map_err(|_| D::Error::custom("wrong byte length"))
+    }
+}
+
+pub mod roots {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(x: &[[u8; 32]], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(x.iter().map(|x| encode(x)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<[u8; 32]>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .iter()
+            .map(|x| {
+                decode(x)
+                    .and_then(|x| x.try_into().ok())
+                    .ok_or_else(|| D::Error::custom("invalid root"))
+            })
+            .collect()
+    }
+}
+
+pub mod field {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(x: &Fr, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&field_hex(x))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Fr, D::Error> {
+        hex_field(&String::deserialize(d)?)
+    }
+}
+
+pub mod fields {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(x: &[Fr], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(x.iter().map(field_hex))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Fr>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .iter()
+            .map(|x| hex_field(x))
+            .collect()
+    }
+}
+
+pub mod values {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(x: &HashMap<usize, Fr>, s: S) -> Result<S::Ok, S::Error> {
+        let mut entries = x.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(k, _)| **k);
+        s.collect_seq(entries.into_iter().map(|(k, v)| (*k as u64, field_hex(v))))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<HashMap<usize, Fr>, D::Error> {
+        let entries = Vec::<(u64, String)>::deserialize(d)?;
+        let mut values = HashMap::with_capacity(entries.len());
+        for (k, v) in entries {
+            let k = usize::try_from(k).map_err(|_| D::Error::custom("key out of range"))?;
+            if values.insert(k, hex_field(&v)?).is_some() {
+                return Err(D::Error::custom("duplicate key"));
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_roundtrip() {
+        let bytes = vec![0u8, 1, 0xab, 0xff];
+        assert_eq!(encode(&bytes), "0001abff");
+        assert_eq!(decode("0001abff"), Some(bytes.clone()));
+        assert_eq!(decode("0x0001ABFF"), Some(bytes));
+        assert_eq!(decode("abc"), None);
+        assert_eq!(decode("zz"), None);
+        let x = Fr::from(12345u64);
+        assert_eq!(
+            hex_field::<serde::de::value::Error>(&field_hex(&x)).unwrap(),
+            x
+        );
+    }
+}
@@ -0,0 +1,434 @@
+// A pool of worker threads answering sample requests against registered
+// provers. Requests queue per peer and workers take them round-robin across
+// peers, so one peer flooding the service delays only its own requests. The
+// queue is bounded overall and per peer, and a full queue refuses at submit
+// time instead of growing. A handle is a future and can also be waited on
+// from synchronous code; no runtime is assumed.
+use std::fmt;
+#[cfg(feature = "prover")]
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, RwLock},
+    task::{Context, Poll, Waker},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use ark_bn254::Fr;
+use util::merkle_tree::{Blake32, MerkleTreeVerifier, Serialize};
+
+#[cfg(feature = "prover")]
+use crate::Prover;
+
+pub type PeerId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceConfig {
+    pub workers: usize,
+    pub queue_capacity: usize,
+    pub per_peer_capacity: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceError {
+    QueueFull,
+    PeerQueueFull,
+    UnknownRoot,
+    NoLeaves,
+    LeafOutOfRange { leaf: usize, leave_number: usize },
+    ShutDown,
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::QueueFull => write!(f, "service queue is full"),
+            ServiceError::PeerQueueFull => write!(f, "peer has too many queued requests"),
+            ServiceError::UnknownRoot => write!(f, "no prover registered for root"),
+            ServiceError::NoLeaves => write!(f, "request names no leaves"),
+            ServiceError::LeafOutOfRange { leaf, leave_number } => {
+                write!(f, "leaf {} beyond {} leaves", leaf, leave_number)
+            }
+            ServiceError::ShutDown => write!(f, "service shut down"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+// Leaves of one first oracle with a single multiproof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleResponse {
+    pub root: [u8; 32],
+    // Ascending and distinct.
+    pub leaves: Vec<usize>,
+    pub symbols: Vec<Vec<Fr>>,
+    pub path: Vec<u8>,
+}
+
+impl SampleResponse {
+    pub fn verify(&self, root: &[u8; 32], leave_number: usize) -> bool {
+        self.root == *root
+            && !self.leaves.is_empty()
+            && self.leaves.windows(2).all(|x| x[0] < x[1])
+            && self.leaves.len() == self.symbols.len()
+            && MerkleTreeVerifier::<Blake32>::new(leave_number, root).verify(
+                self.path.clone(),
+                &self.leaves,
+                &self
+                    .symbols
+                    .iter()
+                    .map(|x| Serialize::serialize_fields(x))
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    pub fn open_leaves(&self, leaves: &[usize]) -> Result<SampleResponse, ServiceError> {
+        let leave_number = self.interpolation.leave_num();
+        let leaves = crate::cache::normalize(leaves);
+        if leaves.is_empty() {
+            return Err(ServiceError::NoLeaves);
+        }
+        if let Some(&leaf) = leaves.iter().find(|x| **x >= leave_number) {
+            return Err(ServiceError::LeafOutOfRange { leaf, leave_number });
+        }
+        Ok(SampleResponse {
+            root: self.commit(),
+            symbols: leaves.iter().map(|&i| self.interpolation.leaf(i)).collect(),
+            path: self.interpolation.merkle_tree.open(&leaves),
+            leaves,
+        })
+    }
+}
+
+#[cfg(feature = "prover")]
+type Outcome = Result<SampleResponse, ServiceError>;
+
+#[cfg(feature = "prover")]
+#[derive(Default)]
+struct Slot {
+    state: Mutex<(Option<Outcome>, Option<Waker>)>,
+    done: Condvar,
+}
+
+#[cfg(feature = "prover")]
+impl Slot {
+    fn complete(&self, outcome: Outcome) {
+        let mut state = self.state.lock().unwrap();
+        state.0 = Some(outcome);
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+        self.done.notify_all();
+    }
+}
+
+#[cfg(feature = "prover")]
+pub struct ResponseHandle(Arc<Slot>);
+
+#[cfg(feature = "prover")]
+impl ResponseHandle {
+    pub fn wait(self) -> Outcome {
+        let mut state = self.0.state.lock().unwrap();
+        loop {
+            if let Some(outcome) = state.0.take() {
+                return outcome;
+            }
+            state = self.0.done.wait(state).unwrap();
+        }
+    }
+
+    // `wait` giving up after `timeout`, leaving the request queued.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<Outcome> {
+        let state = self.0.state.lock().unwrap();
+        let (mut state, _) = self
+            .0
+            .done
+            .wait_timeout_while(state, timeout, |x| x.0.is_none())
+            .unwrap();
+        state.0.take()
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Future for ResponseHandle {
+    type Output = Outcome;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Outcome> {
+        let mut state = self.0.state.lock().unwrap();
+        match state.0.take() {
+            Some(outcome) => Poll::Ready(outcome),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+struct Job {
+    root: [u8; 32],
+    leaves: Vec<usize>,
+    slot: Arc<Slot>,
+}
+
+// Per-peer queues served round-robin.
+#[cfg(feature = "prover")]
+struct Queue<J> {
+    pending: HashMap<PeerId, VecDeque<J>>,
+    rotation: VecDeque<PeerId>,
+    len: usize,
+    capacity: usize,
+    per_peer_capacity: usize,
+}
+
+#[cfg(feature = "prover")]
+impl<J> Queue<J> {
+    fn new(capacity: usize, per_peer_capacity: usize) -> Self {
+        Queue {
+            pending: HashMap::new(),
+            rotation: VecDeque::new(),
+            len: 0,
+            capacity,
+            per_peer_capacity,
+        }
+    }
+
+    fn push(&mut self, peer: PeerId, job: J) -> Result<(), ServiceError> {
+        if self.len >= self.capacity {
+            return Err(ServiceError::QueueFull);
+        }
+        let jobs = self.pending.entry(peer).or_default();
+        if jobs.len() >= self.per_peer_capacity {
+            return Err(ServiceError::PeerQueueFull);
+        }
+        if jobs.is_empty() {
+            self.rotation.push_back(peer);
+        }
+        jobs.push_back(job);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<J> {
+        let peer = self.rotation.pop_front()?;
+        let jobs = self.pending.get_mut(&peer).unwrap();
+        let job = jobs.pop_front().unwrap();
+        if jobs.is_empty() {
+            self.pending.remove(&peer);
+        } else {
+            self.rotation.push_back(peer);
+        }
+        self.len -= 1;
+        Some(job)
+    }
+}
+
+#[cfg(feature = "prover")]
+struct Shared {
+    queue: Mutex<(Queue<Job>, bool)>,
+    ready: Condvar,
+    provers: RwLock<HashMap<[u8; 32], Arc<Prover>>>,
+}
+
+#[cfg(feature = "prover")]
+pub struct ProofService {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+#[cfg(feature = "prover")]
+impl ProofService {
+    pub fn new(config: ServiceConfig) -> Self {
+        assert!(config.workers > 0);
+        let shared = Arc::new(Shared {
+            queue: Mutex::new((
+                Queue::new(config.queue_capacity, config.per_peer_capacity),
+                false,
+            )),
+            ready: Condvar::new(),
+            provers: Default::default(),
+        });
+        let workers = (0..config.workers)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || work(&shared))
+            })
+            .collect();
+        ProofService { shared, workers }
+    }
+
+    // Serves samples of `prover`'s first oracle under its root.
+    pub fn register(&self, prover: Arc<Prover>) {
+        self.shared
+            .provers
+            .write()
+            .unwrap()
+            .insert(prover.commit(), prover);
+    }
+
+    // Requests already queued for `root` fail with `UnknownRoot`.
+    pub fn unregister(&self, root: &[u8; 32]) {
+        self.shared.provers.write().unwrap().remove(root);
+    }
+
+    pub fn submit(
+        &self,
+        peer: PeerId,
+        root: &[u8; 32],
+        leaves: &[usize],
+    ) -> Result<ResponseHandle, ServiceError> {
+        if !self.shared.provers.read().unwrap().contains_key(root) {
+            return Err(ServiceError::UnknownRoot);
+        }
+        let slot = Arc::new(Slot::default());
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.1 {
+            return Err(ServiceError::ShutDown);
+        }
+        queue.0.push(
+            peer,
+            Job {
+                root: *root,
+                leaves: leaves.to_vec(),
+                slot: slot.clone(),
+            },
+        )?;
+        self.shared.ready.notify_one();
+        Ok(ResponseHandle(slot))
+    }
+
+    pub fn queued(&self) -> usize {
+        self.shared.queue.lock().unwrap().0.len
+    }
+}
+
+#[cfg(feature = "prover")]
+fn work(shared: &Shared) {
+    loop {
+        let job = {
+            let mut queue = shared.queue.lock().unwrap();
+            loop {
+                if let Some(job) = queue.0.pop() {
+                    break job;
+                }
+                if queue.1 {
+                    return;
+                }
+                queue = shared.ready.wait(queue).unwrap();
+            }
+        };
+        let prover = shared.provers.read().unwrap().get(&job.root).cloned();
+        job.slot.complete(match prover {
+            Some(prover) => prover.open_leaves(&job.leaves),
+            None => Err(ServiceError::UnknownRoot),
+        });
+    }
+}
+
+// Queued requests fail with `ShutDown`; running ones finish.
+#[cfg(feature = "prover")]
+impl Drop for ProofService {
+    fn drop(&mut self) {
+        {
+            let mut queue = self.shared.queue.lock().unwrap();
+            queue.1 = true;
+            while let Some(job) = queue.0.pop() {
+                job.slot.complete(Err(ServiceError::ShutDown));
+            }
+        }
+        self.shared.ready.notify_all();
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_polies, FridaConfig, ProveOptions};
+
+    #[test]
+    fn fair_bounded_service() {
+        // A peer with many queued requests is interleaved with the others.
+        let mut queue = Queue::new(8, 4);
+        for job in 0..4 {
+            queue.push(1, (1, job)).unwrap();
+        }
+        assert_eq!(queue.push(1, (1, 4)), Err(ServiceError::PeerQueueFull));
+        queue.push(2, (2, 0)).unwrap();
+        queue.push(3, (3, 0)).unwrap();
+        queue.push(2, (2, 1)).unwrap();
+        queue.push(3, (3, 1)).unwrap();
+        assert_eq!(queue.push(4, (4, 0)), Err(ServiceError::QueueFull));
+        let order = std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            [
+                (1, 0),
+                (2, 0),
+                (3, 0),
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (1, 2),
+                (1, 3)
+            ]
+        );
+
+        let config = FridaConfig::new(b"service", 2, 5, 1, 8);
+        let polies = random_polies(2, 32);
+        let prover =
+            Arc::new(Prover::new(&polies, &config.groups()[0], &ProveOptions::default()).unwrap());
+        let root = prover.commit();
+        let service = ProofService::new(ServiceConfig {
+            workers: 3,
+            queue_capacity: 64,
+            per_peer_capacity: 16,
+        });
+        assert!(matches!(
+            service.submit(0, &root, &[0]),
+            Err(ServiceError::UnknownRoot)
+        ));
+        service.register(prover.clone());
+        let handles = (0..12)
+            .map(|i| {
+                let leaves = vec![i, (i * 5 + 1) % 32, i];
+                (
+                    leaves.clone(),
+                    service.submit(i as u64 % 4, &root, &leaves).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        for (leaves, handle) in handles {
+            let response = handle.wait().unwrap();
+            assert_eq!(response.leaves, crate::cache::normalize(&leaves));
+            assert!(response.verify(&root, config.leave_number()));
+        }
+        assert_eq!(
+            service.submit(0, &root, &[32]).unwrap().wait(),
+            Err(ServiceError::LeafOutOfRange {
+                leaf: 32,
+                leave_number: 32
+            })
+        );
+
+        // Polled as a future the handle resolves once a worker finishes.
+        let mut handle = service.submit(5, &root, &[7]).unwrap();
+        let mut cx = Context::from_waker(Waker::noop());
+        let response = loop {
+            if let Poll::Ready(x) = Pin::new(&mut handle).poll(&mut cx) {
+                break x.unwrap();
+            }
+            std::thread::yield_now();
+        };
+        assert_eq!(response, prover.open_leaves(&[7]).unwrap());
+    }
+}
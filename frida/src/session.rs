@@ -0,0 +1,156 @@
+// Binding artifacts to the DKG session or DA epoch they were made for. The
+// session is appended to the deployment context, which every transcript
+// absorbs before anything else, so commitments, challenges and proofs of one
+// session do not verify in another and nothing downstream of the config
+// needs to know sessions exist.
+#[cfg(feature = "prover")]
+use crate::{
+    dealer::{Dealer, DealingTranscript, NodePackage},
+    share::{check_share, Share, ShareError},
+    Prover,
+};
+use crate::{FridaConfig, FridaProof, VerificationReport, Verifier};
+
+const SESSION_LABEL: &[u8] = b"frida-session";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Session {
+    Dkg(u64),
+    Epoch(u64),
+}
+
+impl Session {
+    fn id(self) -> (u8, u64) {
+        match self {
+            Session::Dkg(x) => (0, x),
+            Session::Epoch(x) => (1, x),
+        }
+    }
+
+    // `context` with the session absorbed after it.
+    pub fn bind(self, context: &[u8]) -> Vec<u8> {
+        let (kind, id) = self.id();
+        let mut bound = context.to_vec();
+        bound.extend(SESSION_LABEL);
+        bound.push(kind);
+        bound.extend(id.to_le_bytes());
+        bound
+    }
+}
+
+impl FridaConfig {
+    pub fn in_session(&self, session: Session) -> FridaConfig {
+        FridaConfig {
+            context: session.bind(&self.context),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // `prove` for `config` in `session`; the commitment to publish is
+    // `config.in_session(session).commitment(root)`.
+    pub fn prove_in(&self, config: &FridaConfig, session: Session) -> FridaProof {
+        self.prove(&config.in_session(session))
+    }
+}
+
+impl Verifier {
+    pub fn verify_proof_in(
+        config: &FridaConfig,
+        session: Session,
+        commitment: &[u8; 32],
+        proof: &FridaProof,
+    ) -> VerificationReport {
+        Self::verify_proof(&config.in_session(session), commitment, proof)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Dealer {
+    // The context dealings of this dealer's session are made under.
+    pub fn session_context(&self) -> Vec<u8> {
+        self.session.bind(&self.context)
+    }
+
+    // `DealingTranscript::verify` against the sharing this dealer would make
+    // for the committee: of this session, and with at least its queries.
+    pub fn verify<K>(
+        &self,
+        transcript: &DealingTranscript<K>,
+        secret_len: usize,
+        threshold: usize,
+    ) -> bool {
+        self.sharing(secret_len, transcript.nodes.len(), threshold)
+            .is_ok_and(|expected| transcript.verify(&expected, threshold))
+    }
+
+    // `verify_my_share` for a dealing of this session.
+    pub fn verify_share<K: PartialEq>(
+        &self,
+        package: &NodePackage<K>,
+        transcript: &DealingTranscript<K>,
+        secret_len: usize,
+        threshold: usize,
+        my_index: usize,
+    ) -> Result<Share, ShareError> {
+        check_share(package, transcript, my_index, |x| {
+            self.verify(x, secret_len, threshold)
+        })
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{fixtures::random_prover, FailedCheck, SoundnessRegime};
+
+    #[test]
+    fn session_binding() {
+        let mut rng = thread_rng();
+        let config = FridaConfig::new(b"session", 2, 4, 2, 4);
+        let (_, prover) = random_prover(&config);
+        let epoch = Session::Epoch(7);
+        let proof = prover.prove_in(&config, epoch);
+        let commitment = config.in_session(epoch).commitment(&prover.commit());
+        let report = Verifier::verify_proof_in(&config, epoch, &commitment, &proof);
+        assert!(report.failure.is_none());
+        for other in [Session::Epoch(8), Session::Dkg(7)] {
+            let report = Verifier::verify_proof_in(&config, other, &commitment, &proof);
+            assert_eq!(report.failure, Some(FailedCheck::Transcript));
+        }
+
+        let dealer = Dealer {
+            context: b"session".to_vec(),
+            session: Session::Dkg(3),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let nodes = (0..8u64).collect::<Vec<_>>();
+        let dealing = dealer
+            .deal(&[Fr::rand(&mut rng)], &nodes, 4, &mut rng)
+            .unwrap();
+        assert!(dealer
+            .verify_share(&dealing.packages[2], &dealing.transcript, 1, 4, 2)
+            .is_ok());
+        assert!(!dealer.verify(&dealing.transcript, 1, 2));
+        // Everything but the context must match too.
+        let mut conjectured = dealing.transcript.clone();
+        conjectured.sharing.config.regime = SoundnessRegime::Conjectured;
+        assert!(!dealer.verify(&conjectured, 1, 4));
+        // A dealing replayed into the next session is refused.
+        let next = Dealer {
+            session: Session::Dkg(4),
+            ..dealer
+        };
+        assert_eq!(
+            next.verify_share(&dealing.packages[2], &dealing.transcript, 1, 4, 2),
+            Err(ShareError::InvalidTranscript)
+        );
+    }
+}
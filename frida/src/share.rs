@@ -0,0 +1,319 @@
+// What a node keeps of a dealing, and the checks before it keeps it: the
+// package opens the node's own leaf under the transcript's root, and the
+// transcript proves the root commits to a sharing. In PVSS mode packages
+// travel sealed to the node's key, so anyone may relay them; the cipher is
+// the deployment's, as signatures are, and only the recipient can check what
+// a sealed package holds.
+use std::fmt;
+
+use ark_bn254::Fr;
+use rand::Rng;
+
+use crate::{
+    dealer::{Dealing, DealingTranscript, NodePackage},
+    hygiene::scrub_bytes,
+    vss::SharingConfig,
+    Verifier,
+};
+
+pub trait ShareCipher {
+    type PublicKey;
+    type SecretKey;
+
+    fn encrypt<R: Rng>(key: &Self::PublicKey, plaintext: &[u8], rng: &mut R) -> Vec<u8>;
+
+    // None unless `ciphertext` was encrypted to the matching public key.
+    fn decrypt(key: &Self::SecretKey, ciphertext: &[u8]) -> Option<Vec<u8>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedPackage<K> {
+    pub index: usize,
+    pub node: K,
+    pub ciphertext: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    pub index: usize,
+    pub leave_number: usize,
+    pub commitment: [u8; 32],
+    // x and -x of every polynomial, the mask last.
+    pub symbols: Vec<Fr>,
+}
+
+impl Share {
+    // (position in the evaluation domain, value) of polynomial `poly`.
+    pub fn evaluations(&self, poly: usize) -> [(usize, Fr); 2] {
+        [
+            (self.index, self.symbols[2 * poly]),
+            (self.index + self.leave_number, self.symbols[2 * poly + 1]),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareError {
+    NotInCommittee { index: usize, nodes: usize },
+    IndexMismatch { expected: usize, actual: usize },
+    // The package is addressed to another key than the transcript lists.
+    WrongNode,
+    InvalidTranscript,
+    LeafWidth { expected: usize, actual: usize },
+    PathMismatch,
+    Undecryptable,
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::NotInCommittee { index, nodes } => {
+                write!(f, "node {} is not among {} nodes", index, nodes)
+            }
+            ShareError::IndexMismatch { expected, actual } => {
+                write!(f, "package is for node {}, expected {}", actual, expected)
+            }
+            ShareError::WrongNode => write!(f, "package is addressed to another key"),
+            ShareError::InvalidTranscript => write!(f, "dealing transcript does not verify"),
+            ShareError::LeafWidth { expected, actual } => {
+                write!(f, "share has {} symbols, expected {}", actual, expected)
+            }
+            ShareError::PathMismatch => write!(f, "share does not open under the root"),
+            ShareError::Undecryptable => write!(f, "package does not decrypt"),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+// The share of node `my_index` in a dealing made under the `expected`
+// sharing and `threshold` the node agreed to; see `DealingTranscript::verify`.
+pub fn verify_my_share<K: PartialEq>(
+    package: &NodePackage<K>,
+    transcript: &DealingTranscript<K>,
+    expected: &SharingConfig,
+    threshold: usize,
+    my_index: usize,
+) -> Result<Share, ShareError> {
+    check_share(package, transcript, my_index, |x| {
+        x.verify(expected, threshold)
+    })
+}
+
+// `verify_my_share` with the transcript's own check left to `verify`.
+pub(crate) fn check_share<K: PartialEq, F: FnOnce(&DealingTranscript<K>) -> bool>(
+    package: &NodePackage<K>,
+    transcript: &DealingTranscript<K>,
+    my_index: usize,
+    verify: F,
+) -> Result<Share, ShareError> {
+    let nodes = transcript.nodes.len();
+    if my_index >= nodes {
+        return Err(ShareError::NotInCommittee {
+            index: my_index,
+            nodes,
+        });
+    }
+    if package.index != my_index {
+        return Err(ShareError::IndexMismatch {
+            expected: my_index,
+            actual: package.index,
+        });
+    }
+    if package.node != transcript.nodes[my_index] {
+        return Err(ShareError::WrongNode);
+    }
+    if !verify(transcript) {
+        return Err(ShareError::InvalidTranscript);
+    }
+    let config = &transcript.sharing.config;
+    let expected = config.leaf_size(0);
+    if package.symbols.len() != expected {
+        return Err(ShareError::LeafWidth {
+            expected,
+            actual: package.symbols.len(),
+        });
+    }
+    let leave_number = config.first_oracle_leaves();
+    if !Verifier::verify_chunk(
+        &transcript.root,
+        leave_number,
+        expected,
+        my_index,
+        &package.symbols,
+        &package.path,
+    ) {
+        return Err(ShareError::PathMismatch);
+    }
+    Ok(Share {
+        index: my_index,
+        leave_number,
+        commitment: transcript.commitment,
+        symbols: package.symbols.clone(),
+    })
+}
+
+// `verify_my_share` for a package sealed to the node's public key.
+pub fn verify_sealed_share<C: ShareCipher>(
+    sealed: &SealedPackage<C::PublicKey>,
+    transcript: &DealingTranscript<C::PublicKey>,
+    expected: &SharingConfig,
+    threshold: usize,
+    my_index: usize,
+    key: &C::SecretKey,
+) -> Result<Share, ShareError>
+where
+    C::PublicKey: Clone + PartialEq,
+{
+    let package = sealed.open::<C>(key).ok_or(ShareError::Undecryptable)?;
+    verify_my_share(&package, transcript, expected, threshold, my_index)
+}
+
+impl<K: Clone> NodePackage<K> {
+    pub fn seal<C: ShareCipher<PublicKey = K>, R: Rng>(&self, rng: &mut R) -> SealedPackage<K> {
+        SealedPackage {
+            index: self.index,
+            node: self.node.clone(),
+            ciphertext: {
+                let mut plaintext = self.to_bytes();
+                let ciphertext = C::encrypt(&self.node, &plaintext, rng);
+                scrub_bytes(&mut plaintext);
+                ciphertext
+            },
+        }
+    }
+}
+
+impl<K: Clone> SealedPackage<K> {
+    pub fn open<C: ShareCipher<PublicKey = K>>(
+        &self,
+        key: &C::SecretKey,
+    ) -> Option<NodePackage<K>> {
+        let mut plaintext = C::decrypt(key, &self.ciphertext)?;
+        let package = NodePackage::from_bytes(&plaintext, self.node.clone());
+        scrub_bytes(&mut plaintext);
+        package.filter(|x| x.index == self.index)
+    }
+}
+
+impl<K: Clone> Dealing<K> {
+    pub fn sealed<C: ShareCipher<PublicKey = K>, R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> Vec<SealedPackage<K>> {
+        self.packages.iter().map(|x| x.seal::<C, R>(rng)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{dealer::Dealer, session::Session, SoundnessRegime};
+
+    // A keyed stream cipher with a tag standing in for public-key
+    // encryption: both keys are the same secret.
+    struct Xor;
+
+    fn keystream(key: &[u8; 32], len: usize) -> Vec<u8> {
+        (0..len.div_ceil(32) as u64)
+            .flat_map(|i| *blake3::keyed_hash(key, &i.to_le_bytes()).as_bytes())
+            .take(len)
+            .collect()
+    }
+
+    impl ShareCipher for Xor {
+        type PublicKey = [u8; 32];
+        type SecretKey = [u8; 32];
+
+        fn encrypt<R: Rng>(key: &[u8; 32], plaintext: &[u8], _: &mut R) -> Vec<u8> {
+            let mut out = plaintext
+                .iter()
+                .zip(keystream(key, plaintext.len()))
+                .map(|(x, k)| x ^ k)
+                .collect::<Vec<_>>();
+            out.extend(blake3::keyed_hash(key, plaintext).as_bytes());
+            out
+        }
+
+        fn decrypt(key: &[u8; 32], ciphertext: &[u8]) -> Option<Vec<u8>> {
+            let (body, tag) = ciphertext.split_at(ciphertext.len().checked_sub(32)?);
+            let plaintext = body
+                .iter()
+                .zip(keystream(key, body.len()))
+                .map(|(x, k)| x ^ k)
+                .collect::<Vec<_>>();
+            (blake3::keyed_hash(key, &plaintext).as_bytes() == tag).then_some(plaintext)
+        }
+    }
+
+    #[test]
+    fn share_checks() {
+        let mut rng = thread_rng();
+        let dealer = Dealer {
+            context: b"share".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let keys = (0..8u8).map(|x| [x; 32]).collect::<Vec<_>>();
+        let dealing = dealer
+            .deal(&[Fr::rand(&mut rng)], &keys, 4, &mut rng)
+            .unwrap();
+        let transcript = &dealing.transcript;
+        let expected = dealer.sharing(1, 8, 4).unwrap();
+        let share = verify_my_share(&dealing.packages[3], transcript, &expected, 4, 3).unwrap();
+        assert_eq!(share.commitment, transcript.commitment);
+        assert_eq!(
+            share.evaluations(0)[1].0,
+            3 + transcript.sharing.config.leave_number()
+        );
+
+        assert_eq!(
+            verify_my_share(&dealing.packages[3], transcript, &expected, 4, 4),
+            Err(ShareError::IndexMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            verify_my_share(&dealing.packages[3], transcript, &expected, 4, 8),
+            Err(ShareError::NotInCommittee { index: 8, nodes: 8 })
+        );
+        let mut forged = dealing.packages[3].clone();
+        forged.symbols[0] += Fr::from(1u64);
+        assert_eq!(
+            verify_my_share(&forged, transcript, &expected, 4, 3),
+            Err(ShareError::PathMismatch)
+        );
+        forged.node = keys[4];
+        assert_eq!(
+            verify_my_share(&forged, transcript, &expected, 4, 3),
+            Err(ShareError::WrongNode)
+        );
+
+        // The dealer's own config is no proof it is the agreed one.
+        assert_eq!(
+            verify_my_share(&dealing.packages[3], transcript, &expected, 3, 3),
+            Err(ShareError::InvalidTranscript)
+        );
+        let mut relaxed = dealing.transcript.clone();
+        relaxed.sharing.config.code_rate += 1;
+        assert_eq!(
+            verify_my_share(&dealing.packages[3], &relaxed, &expected, 4, 3),
+            Err(ShareError::InvalidTranscript)
+        );
+
+        let sealed = dealing.sealed::<Xor, _>(&mut rng);
+        assert_eq!(
+            verify_sealed_share::<Xor>(&sealed[5], transcript, &expected, 4, 5, &keys[5]),
+            verify_my_share(&dealing.packages[5], transcript, &expected, 4, 5)
+        );
+        assert_eq!(
+            verify_sealed_share::<Xor>(&sealed[5], transcript, &expected, 4, 5, &keys[6]),
+            Err(ShareError::Undecryptable)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+// A commitment signed by its disperser. The signature covers the first-oracle
+// root, the parameter hash, the blob length and a timestamp, so a node
+// holding shares can later show who dispersed the blob, when and under which
+// parameters. The signature scheme is the deployment's, as for evidence.
+use crate::{
+    address::{AddressMap, Encoding},
+    slashing::SignatureScheme,
+    FridaConfig,
+};
+
+const COMMITMENT_DOMAIN: &[u8] = b"frida-signed-commitment";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCommitment<S: SignatureScheme> {
+    pub root: [u8; 32],
+    pub params_hash: [u8; 32],
+    pub blob_len: u64,
+    // Seconds since the Unix epoch, as the disperser's clock had it.
+    pub timestamp: u64,
+    pub signature: S::Signature,
+}
+
+fn message(root: &[u8; 32], params_hash: &[u8; 32], blob_len: u64, timestamp: u64) -> Vec<u8> {
+    let mut message = COMMITMENT_DOMAIN.to_vec();
+    message.extend(root);
+    message.extend(params_hash);
+    message.extend(blob_len.to_le_bytes());
+    message.extend(timestamp.to_le_bytes());
+    message
+}
+
+impl<S: SignatureScheme> SignedCommitment<S> {
+    // `sign` signs the canonical message with the disperser's key.
+    pub fn new<F: FnOnce(&[u8]) -> S::Signature>(
+        config: &FridaConfig,
+        root: &[u8; 32],
+        blob_len: u64,
+        timestamp: u64,
+        sign: F,
+    ) -> Self {
+        let params_hash = config.params_hash();
+        SignedCommitment {
+            root: *root,
+            params_hash,
+            blob_len,
+            timestamp,
+            signature: sign(&message(root, &params_hash, blob_len, timestamp)),
+        }
+    }
+
+    pub fn message(&self) -> Vec<u8> {
+        message(&self.root, &self.params_hash, self.blob_len, self.timestamp)
+    }
+
+    // The anonymous commitment this one signs.
+    pub fn commitment(&self, config: &FridaConfig) -> [u8; 32] {
+        config.commitment(&self.root)
+    }
+
+    // Signed by `disperser` for a blob that fits `config`.
+    pub fn verify(&self, config: &FridaConfig, disperser: &S::PublicKey) -> bool {
+        let capacity = AddressMap::new(config, Encoding::Coefficients).capacity();
+        self.params_hash == config.params_hash()
+            && self.blob_len <= capacity as u64
+            && S::verify(disperser, &self.message(), &self.signature)
+    }
+
+    // Whether the timestamp is within `tolerance` seconds of `now`.
+    pub fn is_fresh(&self, now: u64, tolerance: u64) -> bool {
+        self.timestamp.abs_diff(now) <= tolerance
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_prover, Verifier};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Keyed;
+
+    impl SignatureScheme for Keyed {
+        type PublicKey = [u8; 32];
+        type Signature = [u8; 32];
+
+        fn verify(key: &[u8; 32], message: &[u8], signature: &[u8; 32]) -> bool {
+            blake3::keyed_hash(key, message).as_bytes() == signature
+        }
+    }
+
+    #[test]
+    fn signed_commitments() {
+        let config = FridaConfig::new(b"signed", 2, 4, 1, 8);
+        let (_, prover) = random_prover(&config);
+        let key = [3u8; 32];
+        let signed = SignedCommitment::<Keyed>::new(
+            &config,
+            &prover.commit(),
+            900,
+            1_700_000_000,
+            |message| *blake3::keyed_hash(&key, message).as_bytes(),
+        );
+        assert!(signed.verify(&config, &key));
+        assert!(!signed.verify(&config, &[4u8; 32]));
+        assert!(signed.is_fresh(1_700_000_030, 60));
+        assert!(!signed.is_fresh(1_700_000_100, 60));
+
+        // A recipient checks its share against the signed root.
+        let commitment = signed.commitment(&config);
+        assert_eq!(commitment, config.commitment(&prover.commit()));
+        let (symbols, path) = prover.open_chunk(5);
+        assert!(Verifier::verify_chunk(
+            &signed.root,
+            config.leave_number(),
+            config.leaf_size(0),
+            5,
+            &symbols,
+            &path
+        ));
+
+        let other = FridaConfig {
+            code_rate: 2,
+            ..config.clone()
+        };
+        assert!(!signed.verify(&other, &key));
+        let mut backdated = signed.clone();
+        backdated.timestamp -= 3600;
+        assert!(!backdated.verify(&config, &key));
+        let mut inflated = signed;
+        inflated.blob_len = u64::MAX;
+        assert!(!inflated.verify(&config, &key));
+    }
+}
@@ -0,0 +1,433 @@
+use ark_bn254::Fr;
+use ark_ff::One;
+use rand::{seq::SliceRandom, Rng};
+use util::merkle_tree::{Blake32, MerkleTreeVerifier};
+
+use crate::{plan::DispersalPlan, Prover, QueryResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Behavior {
+    Honest,
+    Withhold,
+    Corrupt,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub node_num: usize,
+    pub withhold_fraction: f64,
+    pub corrupt_fraction: f64,
+    pub client_num: usize,
+    pub sample_num: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimReport {
+    pub detected_clients: usize,
+    pub client_num: usize,
+    pub detection_probability: f64,
+    pub available_leaves: usize,
+    pub required_leaves: usize,
+    pub reconstruction_success: bool,
+}
+
+pub struct VirtualNode {
+    behavior: Behavior,
+    leaves: Vec<usize>,
+}
+
+impl VirtualNode {
+    pub fn behavior(&self) -> Behavior {
+        self.behavior
+    }
+
+    pub fn leaves(&self) -> &[usize] {
+        &self.leaves
+    }
+
+    // Openings are served out of the disperser's prover, the node only
+    // decides whether and how faithfully to answer.
+    fn respond(&self, prover: &Prover, leaf: usize) -> Option<QueryResult> {
+        match self.behavior {
+            Behavior::Honest => Some(prover.interpolation.query(&[leaf])),
+            Behavior::Withhold => None,
+            Behavior::Corrupt => {
+                let mut res = prover.interpolation.query(&[leaf]);
+                if let Some(v) = res.values.get_mut(&leaf) {
+                    *v += Fr::one();
+                }
+                Some(res)
+            }
+        }
+    }
+}
+
+pub struct Network<'a> {
+    prover: &'a Prover,
+    nodes: Vec<VirtualNode>,
+    leave_number: usize,
+}
+
+impl<'a> Network<'a> {
+    pub fn new<R: Rng>(prover: &'a Prover, config: &SimConfig, rng: &mut R) -> Self {
+        assert!(config.node_num > 0);
+        assert!(config.withhold_fraction + config.corrupt_fraction <= 1.0);
+        let withhold_num = (config.node_num as f64 * config.withhold_fraction).round() as usize;
+        let corrupt_num = ((config.node_num as f64 * config.corrupt_fraction).round() as usize)
+            .min(config.node_num - withhold_num);
+        let mut behaviors = (0..config.node_num)
+            .map(|i| {
+                if i < withhold_num {
+                    Behavior::Withhold
+                } else if i < withhold_num + corrupt_num {
+                    Behavior::Corrupt
+                } else {
+                    Behavior::Honest
+                }
+            })
+            .collect::<Vec<_>>();
+        behaviors.shuffle(rng);
+
+        let leave_number = prover.interpolation.leave_num();
+        let mut nodes = behaviors
+            .into_iter()
+            .map(|behavior| VirtualNode {
+                behavior,
+                leaves: vec![],
+            })
+            .collect::<Vec<_>>();
+        for i in 0..leave_number {
+            nodes[i % config.node_num].leaves.push(i);
+        }
+        Network {
+            prover,
+            nodes,
+            leave_number,
+        }
+    }
+
+    pub fn nodes(&self) -> &[VirtualNode] {
+        &self.nodes
+    }
+
+    fn owner(&self, leaf: usize) -> &VirtualNode {
+        &self.nodes[leaf % self.nodes.len()]
+    }
+
+    // A light client knows only the commitment; it succeeds iff every sampled
+    // leaf is answered and opens correctly against the root.
+    pub fn sample<R: Rng>(
+        &self,
+        verifier: &MerkleTreeVerifier<Blake32>,
+        sample_num: usize,
+        rng: &mut R,
+    ) -> bool {
+        let leaf_size = self.prover.leaf_rows * 2;
+        (0..sample_num).all(|_| {
+            let leaf = rng.gen_range(0..self.leave_number);
+            match self.owner(leaf).respond(self.prover, leaf) {
+                Some(res) => res.verify_merkle_tree(&[leaf], leaf_size, verifier),
+                None => false,
+            }
+        })
+    }
+
+    // Honest leaves each group needs: every first-oracle leaf of a group
+    // carries two evaluations of each of its polynomial halves, and groups
+    // hold disjoint halves, as `AddressMap` lays them out.
+    fn required_per_group(&self) -> usize {
+        ((1 << self.prover.log_degree) / 2).max(1)
+    }
+
+    // 2^log_degree / 2 honest leaves in each of the groups, all polynomials
+    // being in the single group of a prover that is not interleaved.
+    pub fn required_leaves(&self) -> usize {
+        self.prover.leaf_groups() * self.required_per_group()
+    }
+
+    pub fn available_leaves(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|x| x.behavior == Behavior::Honest)
+            .map(|x| x.leaves.len())
+            .sum()
+    }
+
+    // Whether every group keeps enough honest leaves; a total above
+    // `required_leaves` is not enough if one group falls short.
+    pub fn recoverable(&self) -> bool {
+        let positions = self.prover.positions();
+        let mut honest = vec![0; self.prover.leaf_groups()];
+        for node in self.nodes.iter().filter(|x| x.behavior == Behavior::Honest) {
+            for leaf in node.leaves.iter() {
+                honest[leaf / positions] += 1;
+            }
+        }
+        honest.iter().all(|&x| x >= self.required_per_group())
+    }
+
+    pub fn run<R: Rng>(&self, config: &SimConfig, rng: &mut R) -> SimReport {
+        let verifier = MerkleTreeVerifier::new(self.leave_number, &self.prover.commit());
+        let detected_clients = (0..config.client_num)
+            .filter(|_| !self.sample(&verifier, config.sample_num, rng))
+            .count();
+        let available_leaves = self.available_leaves();
+        let required_leaves = self.required_leaves();
+        SimReport {
+            detected_clients,
+            client_num: config.client_num,
+            detection_probability: if config.client_num == 0 {
+                0.0
+            } else {
+                detected_clients as f64 / config.client_num as f64
+            },
+            available_leaves,
+            required_leaves,
+            reconstruction_success: self.recoverable(),
+        }
+    }
+}
+
+// Plan-level Monte-Carlo estimates. Openings are not materialized: a leaf
+// either is served honestly or is not, which is all that reconstruction and
+// sampling depend on, so trials stay cheap enough to run by the thousand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WithholdStrategy {
+    // Each node withholds all of its leaves independently with probability
+    // `fraction`.
+    RandomNodes { fraction: f64 },
+    // The adversary withholds the fewest leaves that makes the data
+    // unrecoverable, chosen uniformly, regardless of node boundaries.
+    MinimalUnrecoverable,
+    // The first `fraction` of nodes, by leaf ownership, withhold together.
+    Coalition { fraction: f64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct MonteCarloConfig {
+    pub plan: DispersalPlan,
+    pub node_num: usize,
+    pub strategy: WithholdStrategy,
+    pub trials: usize,
+    // Samples a client takes before giving up on detecting withholding.
+    pub max_samples: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MonteCarloReport {
+    // Served leaves in each trial.
+    pub available_leaves: Vec<usize>,
+    pub required_leaves: usize,
+    // Samples until the first unanswered one, per trial; None if all
+    // `max_samples` were answered.
+    pub detection_latency: Vec<Option<usize>>,
+}
+
+impl MonteCarloReport {
+    pub fn trials(&self) -> usize {
+        self.available_leaves.len()
+    }
+
+    pub fn reconstruction_probability(&self) -> f64 {
+        let ok = self
+            .available_leaves
+            .iter()
+            .filter(|&&x| x >= self.required_leaves)
+            .count();
+        ok as f64 / self.trials().max(1) as f64
+    }
+
+    pub fn detection_probability(&self) -> f64 {
+        let detected = self.detection_latency.iter().flatten().count();
+        detected as f64 / self.trials().max(1) as f64
+    }
+
+    // histogram[n] counts trials detected at exactly sample n + 1; the last
+    // bin also counts those detected later, so every detection is in one.
+    pub fn latency_histogram(&self, max_samples: usize) -> Vec<usize> {
+        let mut histogram = vec![0; max_samples];
+        if let Some(last) = max_samples.checked_sub(1) {
+            for n in self.detection_latency.iter().flatten() {
+                histogram[n.saturating_sub(1).min(last)] += 1;
+            }
+        }
+        histogram
+    }
+
+    // Latency at quantile `q` over detected trials.
+    pub fn latency_quantile(&self, q: f64) -> Option<usize> {
+        let mut detected = self
+            .detection_latency
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        if detected.is_empty() {
+            return None;
+        }
+        detected.sort_unstable();
+        let rank = ((q.clamp(0.0, 1.0) * detected.len() as f64).ceil() as usize).max(1);
+        Some(detected[rank - 1])
+    }
+}
+
+// Served flags per leaf; leaf i belongs to node i % node_num as in `Network`.
+fn withhold<R: Rng>(config: &MonteCarloConfig, rng: &mut R) -> Vec<bool> {
+    let leave_number = config.plan.leave_number;
+    match config.strategy {
+        WithholdStrategy::RandomNodes { fraction } => {
+            let withheld = (0..config.node_num)
+                .map(|_| rng.gen_bool(fraction))
+                .collect::<Vec<_>>();
+            (0..leave_number)
+                .map(|i| !withheld[i % config.node_num])
+                .collect()
+        }
+        WithholdStrategy::MinimalUnrecoverable => {
+            let kept = config.plan.required_leaves().saturating_sub(1);
+            let mut served = (0..leave_number).map(|i| i < kept).collect::<Vec<_>>();
+            served.shuffle(rng);
+            served
+        }
+        WithholdStrategy::Coalition { fraction } => {
+            let coalition = (config.node_num as f64 * fraction).round() as usize;
+            (0..leave_number)
+                .map(|i| i % config.node_num >= coalition)
+                .collect()
+        }
+    }
+}
+
+pub fn monte_carlo<R: Rng>(config: &MonteCarloConfig, rng: &mut R) -> MonteCarloReport {
+    assert!(config.node_num > 0 && config.plan.leave_number > 0);
+    let mut available_leaves = Vec::with_capacity(config.trials);
+    let mut detection_latency = Vec::with_capacity(config.trials);
+    for _ in 0..config.trials {
+        let served = withhold(config, rng);
+        available_leaves.push(served.iter().filter(|&&x| x).count());
+        detection_latency.push(
+            (1..=config.max_samples).find(|_| !served[rng.gen_range(0..config.plan.leave_number)]),
+        );
+    }
+    MonteCarloReport {
+        available_leaves,
+        required_leaves: config.plan.required_leaves(),
+        detection_latency,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+    use util::mul_group::Radix2Group;
+
+    use super::*;
+    use crate::{fixtures::random_polies, ProveOptions};
+
+    fn prover(poly_num: usize, log_degree: usize, coderate: usize) -> Prover {
+        let polies = random_polies(poly_num, 1 << log_degree);
+        Prover::new(
+            &polies,
+            &Radix2Group::new(log_degree + coderate),
+            &ProveOptions::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn simulate() {
+        let mut rng = thread_rng();
+        let prover = prover(4, 8, 1);
+        let mut config = SimConfig {
+            node_num: 16,
+            withhold_fraction: 0.0,
+            corrupt_fraction: 0.0,
+            client_num: 20,
+            sample_num: 10,
+        };
+        let report = Network::new(&prover, &config, &mut rng).run(&config, &mut rng);
+        assert_eq!(report.detected_clients, 0);
+        assert!(report.reconstruction_success);
+
+        config.withhold_fraction = 0.5;
+        config.corrupt_fraction = 0.5;
+        let report = Network::new(&prover, &config, &mut rng).run(&config, &mut rng);
+        assert_eq!(report.detected_clients, config.client_num);
+        assert!(!report.reconstruction_success);
+    }
+
+    #[test]
+    fn interleaved_recovery() {
+        let mut rng = thread_rng();
+        let polies = random_polies(4, 16);
+        let prover = Prover::interleaved(&polies, &Radix2Group::new(5), 1);
+        let config = SimConfig {
+            node_num: 64,
+            withhold_fraction: 0.0,
+            corrupt_fraction: 0.0,
+            client_num: 4,
+            sample_num: 4,
+        };
+        let mut network = Network::new(&prover, &config, &mut rng);
+        // Four groups of 16 leaves, each needing 8.
+        assert_eq!(network.required_leaves(), 32);
+        assert!(network.run(&config, &mut rng).reconstruction_success);
+        // Withholding 10 leaves of the first group leaves 54 in total, but
+        // only 6 there.
+        for node in network.nodes.iter_mut() {
+            if node.leaves.iter().any(|&x| x < 10) {
+                node.behavior = Behavior::Withhold;
+            }
+        }
+        let report = network.run(&config, &mut rng);
+        assert_eq!(report.available_leaves, 54);
+        assert!(report.available_leaves >= report.required_leaves);
+        assert!(!report.reconstruction_success);
+    }
+
+    #[test]
+    fn monte_carlo_estimates() {
+        let mut rng = thread_rng();
+        let plan = DispersalPlan {
+            code_rate: 1,
+            log_degree: 8,
+            poly_num: 4,
+            leave_number: 256,
+            shares_per_node: 16,
+            sample_num: 20,
+        };
+        let mut config = MonteCarloConfig {
+            plan,
+            node_num: 16,
+            strategy: WithholdStrategy::MinimalUnrecoverable,
+            trials: 400,
+            max_samples: 20,
+        };
+        let report = monte_carlo(&config, &mut rng);
+        assert_eq!(report.trials(), 400);
+        assert_eq!(report.reconstruction_probability(), 0.0);
+        // Each sample is answered with probability just under 1/2.
+        assert!(report.detection_probability() > 0.99);
+        let histogram = report.latency_histogram(config.max_samples);
+        assert_eq!(
+            histogram.iter().sum::<usize>(),
+            report.detection_latency.iter().flatten().count()
+        );
+        assert!(histogram[0] > histogram[3]);
+        let clamped = report.latency_histogram(2);
+        assert_eq!(clamped[0], histogram[0]);
+        assert_eq!(clamped[1], histogram[1..].iter().sum::<usize>());
+        assert!(report.latency_histogram(0).is_empty());
+        assert!(report.latency_quantile(0.5).unwrap() <= report.latency_quantile(0.99).unwrap());
+
+        config.strategy = WithholdStrategy::Coalition { fraction: 0.25 };
+        let report = monte_carlo(&config, &mut rng);
+        assert!(report.available_leaves.iter().all(|&x| x == 192));
+        assert_eq!(report.reconstruction_probability(), 1.0);
+
+        config.strategy = WithholdStrategy::RandomNodes { fraction: 0.0 };
+        let report = monte_carlo(&config, &mut rng);
+        assert_eq!(report.detection_probability(), 0.0);
+        assert_eq!(report.latency_quantile(0.5), None);
+    }
+}
@@ -0,0 +1,252 @@
+// Misbehavior proofs from signed sample responses. A server signs the
+// published commitment, the leaves it was asked for and the response it
+// serves; if the response fails to verify, the client packages that signed
+// message with what it claims went wrong, and anyone holding the commitment,
+// the config and the server's key can recheck the claim without the client.
+// The signature scheme is left to the deployment.
+use std::fmt;
+
+use crate::{cache::normalize, service::SampleResponse, FridaConfig};
+
+const RESPONSE_DOMAIN: &[u8] = b"frida-sample-response";
+
+pub trait SignatureScheme {
+    type PublicKey;
+    type Signature: Clone + fmt::Debug + Eq;
+
+    fn verify(key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> bool;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedResponse<S: SignatureScheme> {
+    pub requested: Vec<usize>,
+    pub response: SampleResponse,
+    pub signature: S::Signature,
+}
+
+pub fn signed_message(
+    commitment: &[u8; 32],
+    requested: &[usize],
+    response: &SampleResponse,
+) -> Vec<u8> {
+    let mut message = RESPONSE_DOMAIN.to_vec();
+    message.extend(commitment);
+    message.extend((requested.len() as u32).to_le_bytes());
+    for leaf in requested {
+        message.extend((*leaf as u64).to_le_bytes());
+    }
+    message.extend(response.to_bytes());
+    message
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Misbehavior {
+    // The response is for another root than the commitment's.
+    WrongRoot,
+    WrongLeaves {
+        requested: Vec<usize>,
+        served: Vec<usize>,
+    },
+    // A leaf has the wrong number of symbols for the config.
+    LeafWidth {
+        leaf: usize,
+        width: usize,
+    },
+    // Symbols and path do not rebuild the root.
+    PathMismatch,
+}
+
+// What the response does wrong, if anything. Servers answer the requested
+// leaves sorted and deduplicated, so that is what the response must hold.
+pub fn trace(
+    config: &FridaConfig,
+    commitment: &[u8; 32],
+    requested: &[usize],
+    response: &SampleResponse,
+) -> Option<Misbehavior> {
+    if config.commitment(&response.root) != *commitment {
+        return Some(Misbehavior::WrongRoot);
+    }
+    if response.leaves != normalize(requested) {
+        return Some(Misbehavior::WrongLeaves {
+            requested: requested.to_vec(),
+            served: response.leaves.clone(),
+        });
+    }
+    let width = config.leaf_size(0);
+    if let Some((leaf, symbols)) = response
+        .leaves
+        .iter()
+        .zip(&response.symbols)
+        .find(|x| x.1.len() != width)
+    {
+        return Some(Misbehavior::LeafWidth {
+            leaf: *leaf,
+            width: symbols.len(),
+        });
+    }
+    (!response.verify(&response.root, config.first_oracle_leaves()))
+        .then_some(Misbehavior::PathMismatch)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evidence<S: SignatureScheme> {
+    pub commitment: [u8; 32],
+    pub signed: SignedResponse<S>,
+    pub claim: Misbehavior,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvidenceError {
+    WrongCommitment,
+    BadSignature,
+    // The response is fine; nothing to slash.
+    NoMisbehavior,
+    // The response misbehaves, but not as claimed.
+    ClaimMismatch { actual: Misbehavior },
+}
+
+impl<S: SignatureScheme> Evidence<S> {
+    // None if the response verifies. The signature is not checked here.
+    pub fn build(
+        config: &FridaConfig,
+        commitment: &[u8; 32],
+        signed: SignedResponse<S>,
+    ) -> Option<Self> {
+        let claim = trace(config, commitment, &signed.requested, &signed.response)?;
+        Some(Evidence {
+            commitment: *commitment,
+            signed,
+            claim,
+        })
+    }
+}
+
+// The misbehavior `evidence` proves of the holder of `server`.
+pub fn verify_evidence<S: SignatureScheme>(
+    config: &FridaConfig,
+    commitment: &[u8; 32],
+    server: &S::PublicKey,
+    evidence: &Evidence<S>,
+) -> Result<Misbehavior, EvidenceError> {
+    if evidence.commitment != *commitment {
+        return Err(EvidenceError::WrongCommitment);
+    }
+    let signed = &evidence.signed;
+    let message = signed_message(commitment, &signed.requested, &signed.response);
+    if !S::verify(server, &message, &signed.signature) {
+        return Err(EvidenceError::BadSignature);
+    }
+    match trace(config, commitment, &signed.requested, &signed.response) {
+        None => Err(EvidenceError::NoMisbehavior),
+        Some(actual) if actual != evidence.claim => Err(EvidenceError::ClaimMismatch { actual }),
+        Some(actual) => Ok(actual),
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_bn254::Fr;
+
+    use super::*;
+    use crate::fixtures::random_prover;
+
+    // A keyed hash standing in for a real scheme: the public key is the
+    // secret, which is enough to exercise the evidence checks.
+    struct Keyed;
+
+    impl SignatureScheme for Keyed {
+        type PublicKey = [u8; 32];
+        type Signature = [u8; 32];
+
+        fn verify(key: &[u8; 32], message: &[u8], signature: &[u8; 32]) -> bool {
+            blake3::keyed_hash(key, message).as_bytes() == signature
+        }
+    }
+
+    #[test]
+    fn evidence_checks() {
+        let config = FridaConfig::new(b"slashing", 2, 4, 1, 8);
+        let (_, prover) = random_prover(&config);
+        let commitment = config.commitment(&prover.commit());
+        let key = [7u8; 32];
+        let sign = |requested: &[usize], response: SampleResponse| SignedResponse::<Keyed> {
+            requested: requested.to_vec(),
+            signature: *blake3::keyed_hash(
+                &key,
+                &signed_message(&commitment, requested, &response),
+            )
+            .as_bytes(),
+            response,
+        };
+
+        let honest = prover.open_leaves(&[2, 9]).unwrap();
+        assert!(Evidence::build(&config, &commitment, sign(&[2, 9], honest.clone())).is_none());
+        let framed = Evidence {
+            commitment,
+            signed: sign(&[2, 9], honest.clone()),
+            claim: Misbehavior::PathMismatch,
+        };
+        assert_eq!(
+            verify_evidence(&config, &commitment, &key, &framed),
+            Err(EvidenceError::NoMisbehavior)
+        );
+
+        let mut forged = honest.clone();
+        forged.symbols[0][1] += Fr::from(1u64);
+        let evidence = Evidence::build(&config, &commitment, sign(&[2, 9], forged)).unwrap();
+        assert_eq!(
+            verify_evidence(&config, &commitment, &key, &evidence),
+            Ok(Misbehavior::PathMismatch)
+        );
+        assert_eq!(
+            verify_evidence(&config, &commitment, &[8u8; 32], &evidence),
+            Err(EvidenceError::BadSignature)
+        );
+        assert_eq!(
+            verify_evidence(&config, &[0u8; 32], &key, &evidence),
+            Err(EvidenceError::WrongCommitment)
+        );
+        let mut misclaimed = evidence;
+        misclaimed.claim = Misbehavior::WrongRoot;
+        assert_eq!(
+            verify_evidence(&config, &commitment, &key, &misclaimed),
+            Err(EvidenceError::ClaimMismatch {
+                actual: Misbehavior::PathMismatch
+            })
+        );
+
+        let mut truncated = honest;
+        truncated.symbols[1].pop();
+        let evidence = Evidence::build(&config, &commitment, sign(&[2, 9], truncated)).unwrap();
+        assert_eq!(
+            verify_evidence(&config, &commitment, &key, &evidence),
+            Ok(Misbehavior::LeafWidth { leaf: 9, width: 3 })
+        );
+        let evidence = Evidence::build(
+            &config,
+            &commitment,
+            sign(&[2, 9], prover.open_leaves(&[2, 10]).unwrap()),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_evidence(&config, &commitment, &key, &evidence),
+            Ok(Misbehavior::WrongLeaves { .. })
+        ));
+
+        let unsorted = prover.open_leaves(&[9, 2, 9]).unwrap();
+        let framed = Evidence {
+            commitment,
+            signed: sign(&[9, 2, 9], unsorted.clone()),
+            claim: Misbehavior::WrongLeaves {
+                requested: vec![9, 2, 9],
+                served: vec![2, 9],
+            },
+        };
+        assert!(Evidence::build(&config, &commitment, sign(&[9, 2, 9], unsorted)).is_none());
+        assert_eq!(
+            verify_evidence(&config, &commitment, &key, &framed),
+            Err(EvidenceError::NoMisbehavior)
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use util::merkle_tree::Serialize;
+
+const PROTOCOL: &[u8] = b"frida-transcript-v1";
+
+// Fiat-Shamir as prover and verifier see it. Labels are static so sponge
+// constructions like Merlin can use them directly as domain separators.
+pub trait Transcript {
+    fn new(context: &[u8]) -> Self
+    where
+        Self: Sized;
+
+    fn absorb(&mut self, label: &'static [u8], bytes: &[u8]);
+
+    fn absorb_field(&mut self, label: &'static [u8], value: &Fr) {
+        self.absorb(label, &Serialize::serialize_fields(&[*value]));
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8]) -> [u8; 32];
+
+    fn challenge_field(&mut self, label: &'static [u8]) -> Fr;
+
+    fn challenge_index(&mut self, label: &'static [u8], bound: usize) -> usize {
+        let bytes = self.challenge_bytes(label);
+        (u128::from_le_bytes(bytes[..16].try_into().unwrap()) % bound as u128) as usize
+    }
+}
+
+// Hash-chain Fiat-Shamir transcript. Every absorbed message is length-prefixed
+// and labelled, so distinct message sequences never collide.
+#[derive(Clone)]
+pub struct Blake3Transcript {
+    state: [u8; 32],
+}
+
+impl Blake3Transcript {
+    fn squeeze(&mut self, label: &'static [u8], out: &mut [u8]) {
+        self.absorb(b"squeeze", label);
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.state);
+        hasher.finalize_xof().fill(out);
+    }
+}
+
+impl Transcript for Blake3Transcript {
+    fn new(context: &[u8]) -> Self {
+        let mut transcript = Blake3Transcript {
+            state: *blake3::hash(PROTOCOL).as_bytes(),
+        };
+        transcript.absorb(b"context", context);
+        transcript
+    }
+
+    fn absorb(&mut self, label: &'static [u8], bytes: &[u8]) {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.state);
+        hasher.update(&(label.len() as u64).to_le_bytes());
+        hasher.update(label);
+        hasher.update(&(bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+        self.state = *hasher.finalize().as_bytes();
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8]) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        self.squeeze(label, &mut bytes);
+        bytes
+    }
+
+    // 64 bytes reduced mod p keeps the bias negligible.
+    fn challenge_field(&mut self, label: &'static [u8]) -> Fr {
+        let mut bytes = [0u8; 64];
+        self.squeeze(label, &mut bytes);
+        Fr::from_le_bytes_mod_order(&bytes)
+    }
+
+    fn challenge_index(&mut self, label: &'static [u8], bound: usize) -> usize {
+        let mut bytes = [0u8; 16];
+        self.squeeze(label, &mut bytes);
+        (u128::from_le_bytes(bytes) % bound as u128) as usize
+    }
+}
+
+// Merlin's STROBE-based transcript, for stacks that already run their other
+// proofs through it.
+#[cfg(feature = "merlin")]
+#[derive(Clone)]
+pub struct MerlinTranscript {
+    transcript: merlin::Transcript,
+}
+
+#[cfg(feature = "merlin")]
+impl Transcript for MerlinTranscript {
+    fn new(context: &[u8]) -> Self {
+        let mut transcript = merlin::Transcript::new(PROTOCOL);
+        transcript.append_message(b"context", context);
+        MerlinTranscript { transcript }
+    }
+
+    fn absorb(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.transcript.append_message(label, bytes);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8]) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        self.transcript.challenge_bytes(label, &mut bytes);
+        bytes
+    }
+
+    fn challenge_field(&mut self, label: &'static [u8]) -> Fr {
+        let mut bytes = [0u8; 64];
+        self.transcript.challenge_bytes(label, &mut bytes);
+        Fr::from_le_bytes_mod_order(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deterministic_and_separated<T: Transcript>() {
+        let mut a = T::new(b"chain-1");
+        let mut b = T::new(b"chain-1");
+        let mut c = T::new(b"chain-2");
+        for t in [&mut a, &mut b, &mut c] {
+            t.absorb(b"root", &[7u8; 32]);
+        }
+        let x = a.challenge_field(b"alpha");
+        assert_eq!(x, b.challenge_field(b"alpha"));
+        assert_ne!(x, c.challenge_field(b"alpha"));
+        assert_ne!(x, a.challenge_field(b"alpha"));
+        assert!(a.challenge_index(b"query", 10) < 10);
+
+        let mut d = T::new(b"ab");
+        d.absorb(b"c", b"");
+        let mut e = T::new(b"a");
+        e.absorb(b"bc", b"");
+        assert_ne!(d.challenge_field(b"x"), e.challenge_field(b"x"));
+    }
+
+    #[test]
+    fn transcripts() {
+        deterministic_and_separated::<Blake3Transcript>();
+        deterministic_and_separated::<crate::poseidon::PoseidonTranscript>();
+        #[cfg(feature = "merlin")]
+        deterministic_and_separated::<MerlinTranscript>();
+    }
+}
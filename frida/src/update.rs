@@ -0,0 +1,191 @@
+// In-place updates of a committed blob. A change to coefficient t of
+// polynomial k moves f_k by delta * X^t, so only the codewords of touched
+// polynomials are re-evaluated, each by one FFT of its sparse delta instead of
+// re-encoding the whole blob. Every leaf still changes, but only in the
+// symbols of those polynomials, which is all a node's delta share carries.
+#[cfg(feature = "prover")]
+use std::collections::BTreeMap;
+
+use ark_bn254::Fr;
+#[cfg(feature = "prover")]
+use ark_ff::Zero;
+#[cfg(feature = "prover")]
+use util::mul_group::Radix2Group;
+
+use crate::Verifier;
+#[cfg(feature = "prover")]
+use crate::{InterpolateValue, Prover};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diff {
+    pub poly: usize,
+    pub index: usize,
+    // Added to the coefficient.
+    pub delta: Fr,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeltaLeaf {
+    pub index: usize,
+    // (symbol slot within the leaf, value to add)
+    pub updates: Vec<(usize, Fr)>,
+    pub path: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeltaShare {
+    pub node: usize,
+    pub leaves: Vec<DeltaLeaf>,
+}
+
+impl DeltaLeaf {
+    // Applies the update to the node's stored symbols and checks the result
+    // against the new root; on failure the symbols are left untouched.
+    pub fn apply(&self, new_root: &[u8; 32], leave_number: usize, symbols: &mut [Fr]) -> bool {
+        let mut updated = symbols.to_vec();
+        for &(slot, delta) in self.updates.iter() {
+            match updated.get_mut(slot) {
+                Some(x) => *x += delta,
+                None => return false,
+            }
+        }
+        if !Verifier::verify_chunk(
+            new_root,
+            leave_number,
+            symbols.len(),
+            self.index,
+            &updated,
+            &self.path,
+        ) {
+            return false;
+        }
+        symbols.copy_from_slice(&updated);
+        true
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Prover {
+    // Applies `diffs` to the committed coefficients and returns the new root
+    // with one delta share per node, leaf i belonging to node i % node_num;
+    // leaves holding none of the touched polynomials are left out.
+    // Folded prover states built before the update are stale afterwards.
+    pub fn recommit(&mut self, diffs: &[Diff], node_num: usize) -> ([u8; 32], Vec<DeltaShare>) {
+        assert!(node_num > 0);
+        let degree = 1 << self.log_degree;
+        let mut touched = BTreeMap::<usize, Vec<Fr>>::new();
+        for diff in diffs {
+            assert!(diff.poly < self.poly_num && diff.index < degree);
+            touched
+                .entry(diff.poly)
+                .or_insert_with(|| vec![Fr::zero(); degree])[diff.index] += diff.delta;
+        }
+
+        let domain = self.domain_size();
+        let group = Radix2Group::new(domain.ilog2() as usize);
+        let leaves = self.interpolation.leave_num();
+        let mut codeword = self.interpolation.codeword().into_owned();
+        // Column key c sits at slot c / leaves of leaf c % leaves.
+        let mut updates = BTreeMap::<usize, Vec<(usize, Fr)>>::new();
+        for (k, coeff) in touched {
+            let evals = group.fft(coeff);
+            for (x, d) in evals.into_iter().enumerate() {
+                let key = k * domain + x;
+                codeword[key] += d;
+                updates
+                    .entry(key % leaves)
+                    .or_default()
+                    .push((key / leaves, d));
+            }
+        }
+        self.interpolation =
+            InterpolateValue::with_layout(codeword, self.leaf_rows * 2, self.interpolation.layout);
+
+        let mut shares = (0..node_num)
+            .map(|node| DeltaShare {
+                node,
+                leaves: vec![],
+            })
+            .collect::<Vec<_>>();
+        for (i, mut updates) in updates {
+            updates.sort_unstable_by_key(|x| x.0);
+            shares[i % node_num].leaves.push(DeltaLeaf {
+                index: i,
+                updates,
+                path: self.interpolation.merkle_tree.open(&[i]),
+            });
+        }
+        (self.commit(), shares)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{fixtures::random_polies, Layout};
+
+    #[test]
+    fn delta_shares() {
+        let mut rng = thread_rng();
+        let polies = random_polies(4, 16);
+        let group = Radix2Group::new(6);
+        // (prover, polynomials per leaf)
+        let cases = [
+            (Prover::with_layout(&polies, &group, Layout::Columnar), 4),
+            (Prover::with_layout(&polies, &group, Layout::RowMajor), 4),
+            (Prover::interleaved(&polies, &group, 2), 2),
+            (Prover::interleaved(&polies, &group, 1), 1),
+        ];
+        for (mut prover, rows) in cases {
+            let layout = prover.layout();
+            let leave_number = prover.interpolation.leave_num();
+            let mut stored = (0..leave_number)
+                .map(|i| prover.open_chunk(i).0)
+                .collect::<Vec<_>>();
+
+            let diffs = [
+                Diff {
+                    poly: 1,
+                    index: 3,
+                    delta: Fr::rand(&mut rng),
+                },
+                Diff {
+                    poly: 3,
+                    index: 15,
+                    delta: Fr::rand(&mut rng),
+                },
+            ];
+            let (root, shares) = prover.recommit(&diffs, 5);
+            let mut updated = polies.clone();
+            for diff in diffs.iter() {
+                updated[diff.poly][diff.index] += diff.delta;
+            }
+            assert_eq!(root, Prover::interleaved(&updated, &group, rows).commit());
+            assert_eq!(prover.layout(), layout);
+
+            let mut touched = 0;
+            for share in shares.iter() {
+                for leaf in share.leaves.iter() {
+                    assert_eq!(leaf.index % 5, share.node);
+                    assert!(leaf.apply(&root, leave_number, &mut stored[leaf.index]));
+                    touched += leaf.updates.len();
+                }
+            }
+            // Every symbol of the two touched codewords, once.
+            assert_eq!(touched, 2 * prover.domain_size());
+            for (i, symbols) in stored.iter().enumerate() {
+                assert_eq!(*symbols, prover.open_chunk(i).0);
+            }
+
+            let mut stale = shares[0].leaves[0].clone();
+            stale.updates[0].1 += Fr::from(1u64);
+            let index = stale.index;
+            let before = stored[index].clone();
+            assert!(!stale.apply(&root, leave_number, &mut stored[index]));
+            assert_eq!(stored[index], before);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// The wire version of commitments and proofs. A minor release only adds what
+// older readers can skip, so a verifier takes any version of its own major up
+// to its own minor; a new major changes the encoding or the checks and is
+// never accepted across. The version is not bound into the commitment: it
+// tells a node how to read a proof, while `FridaConfig::params_header` already
+// binds everything that decides whether the proof is sound.
+use std::fmt;
+
+use crate::{FailedCheck, FridaConfig, VerificationReport, Verifier};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProtocolVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    Same,
+    // An older minor of our major, which we read.
+    Older,
+    // A newer minor of our major; the sender has to downgrade for us.
+    Newer,
+    // Another major.
+    Incompatible,
+}
+
+impl Compatibility {
+    pub fn readable(self) -> bool {
+        matches!(self, Compatibility::Same | Compatibility::Older)
+    }
+}
+
+impl ProtocolVersion {
+    pub const V1_0: ProtocolVersion = ProtocolVersion { major: 1, minor: 0 };
+    pub const CURRENT: ProtocolVersion = Self::V1_0;
+    // Every released version, oldest first.
+    pub const RELEASED: &'static [ProtocolVersion] = &[Self::V1_0];
+
+    pub fn to_bytes(self) -> [u8; 4] {
+        let mut bytes = [0; 4];
+        bytes[..2].copy_from_slice(&self.major.to_le_bytes());
+        bytes[2..].copy_from_slice(&self.minor.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; 4]) -> Self {
+        ProtocolVersion {
+            major: u16::from_le_bytes([bytes[0], bytes[1]]),
+            minor: u16::from_le_bytes([bytes[2], bytes[3]]),
+        }
+    }
+
+    // How a reader at `self` treats data written at `other`.
+    pub fn compatibility(self, other: ProtocolVersion) -> Compatibility {
+        if self.major != other.major {
+            Compatibility::Incompatible
+        } else if self.minor == other.minor {
+            Compatibility::Same
+        } else if self.minor > other.minor {
+            Compatibility::Older
+        } else {
+            Compatibility::Newer
+        }
+    }
+
+    // The compatibility of every released reader with every released writer,
+    // as (reader, writer, compatibility).
+    pub fn matrix() -> Vec<(ProtocolVersion, ProtocolVersion, Compatibility)> {
+        Self::RELEASED
+            .iter()
+            .flat_map(|reader| {
+                Self::RELEASED
+                    .iter()
+                    .map(move |writer| (*reader, *writer, reader.compatibility(*writer)))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+// The version two nodes should speak: the highest either lists that both
+// read, or None if they share no major.
+pub fn negotiate(ours: &[ProtocolVersion], theirs: &[ProtocolVersion]) -> Option<ProtocolVersion> {
+    let reads = |readers: &[ProtocolVersion], v: &ProtocolVersion| {
+        readers.iter().any(|r| r.compatibility(*v).readable())
+    };
+    ours.iter()
+        .chain(theirs)
+        .filter(|v| reads(ours, v) && reads(theirs, v))
+        .max()
+        .copied()
+}
+
+impl Verifier {
+    // Whether this build verifies proofs written at `version`.
+    pub fn accepts(version: ProtocolVersion) -> bool {
+        ProtocolVersion::CURRENT.compatibility(version).readable()
+    }
+
+    // A failing report for a proof at a version we do not read.
+    pub(crate) fn check_version(
+        config: &FridaConfig,
+        version: ProtocolVersion,
+    ) -> Option<VerificationReport> {
+        (!Self::accepts(version)).then(|| VerificationReport {
+            rounds: config.log_degree,
+            queries: config.query_num,
+            failure: Some(FailedCheck::Version {
+                supported: ProtocolVersion::CURRENT,
+                actual: version,
+            }),
+            opened: vec![],
+        })
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::{fixtures::random_prover, FridaProof};
+
+    #[test]
+    fn version_checks() {
+        let v = |major, minor| ProtocolVersion { major, minor };
+        assert_eq!(v(1, 2).compatibility(v(1, 2)), Compatibility::Same);
+        assert_eq!(v(1, 2).compatibility(v(1, 1)), Compatibility::Older);
+        assert_eq!(v(1, 2).compatibility(v(1, 3)), Compatibility::Newer);
+        assert_eq!(v(1, 2).compatibility(v(2, 0)), Compatibility::Incompatible);
+        assert!(ProtocolVersion::matrix()
+            .iter()
+            .all(|(r, w, c)| (r == w) == (*c == Compatibility::Same)));
+        assert_eq!(ProtocolVersion::from_bytes(v(3, 7).to_bytes()), v(3, 7));
+
+        // A node on 1.2 and one on 1.1 speak 1.1; 1.x and 2.x share nothing
+        // unless one side still speaks the other's major.
+        assert_eq!(negotiate(&[v(1, 2)], &[v(1, 1)]), Some(v(1, 1)));
+        assert_eq!(negotiate(&[v(1, 2)], &[v(2, 0)]), None);
+        assert_eq!(negotiate(&[v(1, 2), v(2, 0)], &[v(2, 1)]), Some(v(2, 0)));
+
+        let config = FridaConfig::new(b"version", 2, 4, 1, 8);
+        let (_, prover) = random_prover(&config);
+        assert_eq!(prover.meta().version, ProtocolVersion::CURRENT);
+        let commitment = config.commitment(&prover.commit());
+        let mut proof = prover.prove(&config);
+        assert!(Verifier::accepts(proof.version));
+        proof.version = v(ProtocolVersion::CURRENT.major + 1, 0);
+        assert!(!Verifier::accepts(proof.version));
+        let decoded = FridaProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(decoded.version, proof.version);
+        assert_eq!(
+            Verifier::verify_proof(&config, &commitment, &decoded).failure,
+            Some(FailedCheck::Version {
+                supported: ProtocolVersion::CURRENT,
+                actual: proof.version,
+            })
+        );
+    }
+}
@@ -0,0 +1,381 @@
+// Adapter exposing FRIDA through jellyfish's `VidScheme`, the interface
+// HotShot uses for VID backends. The payload is packed BYTES_PER_ELEMENT bytes
+// per field element and sized by `SecurityLevel::config`; first-oracle leaf l
+// goes to storage node l % num_storage_nodes. The commit is the config-bound
+// `FridaConfig::commitment`, and the common data carries the config and the
+// encoded proximity proof so any node can check it without the payload.
+use std::collections::BTreeMap;
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, FftField, PrimeField};
+use ark_serialize::CanonicalDeserialize;
+use jf_vid::{VidDisperse, VidError, VidResult, VidScheme};
+use serde::{Deserialize, Serialize};
+use util::{merkle_tree::Serialize as FieldBytes, vrs::VrsScheme};
+
+use crate::{
+    plan::{BYTES_PER_ELEMENT, MAX_CODE_RATE},
+    scheme::{FridaScheme, FridaShare},
+    FridaConfig, FridaProof, Prover, SecurityLevel, SoundnessRegime, Verifier,
+};
+
+pub struct FridaVid {
+    num_storage_nodes: u32,
+    level: SecurityLevel,
+    regime: SoundnessRegime,
+    context: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FridaVidLeaf {
+    pub index: u32,
+    pub symbols: Vec<u8>,
+    pub path: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FridaVidShare {
+    pub index: u32,
+    pub leaves: Vec<FridaVidLeaf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FridaVidCommon {
+    pub payload_byte_len: u32,
+    pub num_storage_nodes: u32,
+    pub context: Vec<u8>,
+    pub poly_num: u32,
+    pub log_degree: u32,
+    pub code_rate: u32,
+    pub query_num: u32,
+    pub conjectured: bool,
+    pub proof: Vec<u8>,
+}
+
+impl FridaVidCommon {
+    fn config(&self) -> FridaConfig {
+        FridaConfig {
+            regime: if self.conjectured {
+                SoundnessRegime::Conjectured
+            } else {
+                SoundnessRegime::Proven
+            },
+            ..FridaConfig::new(
+                &self.context,
+                self.poly_num as usize,
+                self.log_degree as usize,
+                self.code_rate as usize,
+                self.query_num as usize,
+            )
+        }
+    }
+
+    // First-oracle leaves, computed without trusting the fields: a shift that
+    // is zero or overflows u32 is an error rather than a panic.
+    fn leave_number(&self) -> VidResult<u32> {
+        self.log_degree
+            .checked_add(self.code_rate)
+            .and_then(|log| log.checked_sub(1))
+            .and_then(|log| 1u32.checked_shl(log))
+            .ok_or(argument("common data has an invalid degree or code rate"))
+    }
+
+    fn multiplicity(&self) -> VidResult<u32> {
+        if self.num_storage_nodes == 0 {
+            return Err(argument("common data has no storage nodes"));
+        }
+        Ok(self.leave_number()?.div_ceil(self.num_storage_nodes))
+    }
+}
+
+fn argument(msg: &str) -> VidError {
+    VidError::Argument(msg.to_string())
+}
+
+fn pack(payload: &[u8], capacity: usize) -> Vec<Fr> {
+    let mut data = payload
+        .chunks(BYTES_PER_ELEMENT)
+        .map(Fr::from_le_bytes_mod_order)
+        .collect::<Vec<_>>();
+    data.resize(capacity, Fr::from(0u64));
+    data
+}
+
+fn unpack(data: &[Fr], len: usize) -> Vec<u8> {
+    let mut payload = data
+        .iter()
+        .flat_map(|x| x.into_bigint().to_bytes_le()[..BYTES_PER_ELEMENT].to_vec())
+        .collect::<Vec<_>>();
+    payload.truncate(len);
+    payload
+}
+
+fn field_elements(mut bytes: &[u8]) -> Option<Vec<Fr>> {
+    let mut out = vec![];
+    while !bytes.is_empty() {
+        out.push(Fr::deserialize_compressed(&mut bytes).ok()?);
+    }
+    Some(out)
+}
+
+impl FridaVid {
+    pub fn new(
+        num_storage_nodes: u32,
+        level: SecurityLevel,
+        regime: SoundnessRegime,
+        context: &[u8],
+    ) -> VidResult<Self> {
+        if num_storage_nodes == 0 {
+            return Err(argument("num_storage_nodes must be positive"));
+        }
+        Ok(FridaVid {
+            num_storage_nodes,
+            level,
+            regime,
+            context: context.to_vec(),
+        })
+    }
+
+    fn config(&self, payload_byte_len: usize) -> FridaConfig {
+        self.level
+            .config(&self.context, payload_byte_len, self.regime)
+    }
+
+    fn prover(&self, payload: &[u8]) -> VidResult<(FridaConfig, Prover)> {
+        if u32::try_from(payload.len()).is_err() {
+            return Err(argument("payload longer than u32::MAX bytes"));
+        }
+        let scheme = FridaScheme {
+            config: self.config(payload.len()),
+        };
+        let capacity = scheme.config.poly_num << scheme.config.log_degree;
+        let (_, prover) = scheme.commit(&pack(payload, capacity));
+        Ok((scheme.config, prover))
+    }
+
+    // Checks the common data was produced under this instance's parameters
+    // before trusting any of its fields.
+    fn checked_config(&self, common: &FridaVidCommon) -> VidResult<FridaConfig> {
+        let config = common.config();
+        if common.num_storage_nodes != self.num_storage_nodes
+            || config != self.config(common.payload_byte_len as usize)
+        {
+            return Err(argument("common data does not match this scheme"));
+        }
+        Ok(config)
+    }
+}
+
+impl VidScheme for FridaVid {
+    type Commit = [u8; 32];
+    type Share = FridaVidShare;
+    type Common = FridaVidCommon;
+
+    fn commit_only<B: AsRef<[u8]>>(&mut self, payload: B) -> VidResult<[u8; 32]> {
+        let (config, prover) = self.prover(payload.as_ref())?;
+        Ok(config.commitment(&prover.commit()))
+    }
+
+    fn disperse<B: AsRef<[u8]>>(&mut self, payload: B) -> VidResult<VidDisperse<Self>> {
+        let payload = payload.as_ref();
+        let (config, prover) = self.prover(payload)?;
+        let nodes = self.num_storage_nodes as usize;
+        let mut shares = (0..self.num_storage_nodes)
+            .map(|index| FridaVidShare {
+                index,
+                leaves: vec![],
+            })
+            .collect::<Vec<_>>();
+        for l in 0..config.first_oracle_leaves() {
+            let (symbols, path) = prover.open_chunk(l);
+            shares[l % nodes].leaves.push(FridaVidLeaf {
+                index: l as u32,
+                symbols: FieldBytes::serialize_fields(&symbols),
+                path,
+            });
+        }
+        let common = FridaVidCommon {
+            payload_byte_len: payload.len() as u32,
+            num_storage_nodes: self.num_storage_nodes,
+            context: config.context.clone(),
+            poly_num: config.poly_num as u32,
+            log_degree: config.log_degree as u32,
+            code_rate: config.code_rate as u32,
+            query_num: config.query_num as u32,
+            conjectured: config.regime == SoundnessRegime::Conjectured,
+            proof: prover.prove(&config).to_bytes(),
+        };
+        Ok(VidDisperse {
+            shares,
+            common,
+            commit: config.commitment(&prover.commit()),
+        })
+    }
+
+    fn verify_share(
+        &self,
+        share: &FridaVidShare,
+        common: &FridaVidCommon,
+        commit: &[u8; 32],
+    ) -> VidResult<Result<(), ()>> {
+        let config = self.checked_config(common)?;
+        Self::is_consistent(commit, common)?;
+        let proof = FridaProof::from_bytes(&common.proof).ok_or(argument("malformed proof"))?;
+        let leave_number = config.first_oracle_leaves();
+        let valid = share.index < self.num_storage_nodes
+            && share.leaves.iter().all(|leaf| {
+                let index = leaf.index as usize;
+                index < leave_number
+                    && index % self.num_storage_nodes as usize == share.index as usize
+                    && field_elements(&leaf.symbols).is_some_and(|symbols| {
+                        Verifier::verify_chunk(
+                            &proof.merkle_root,
+                            leave_number,
+                            config.leaf_size(0),
+                            index,
+                            &symbols,
+                            &leaf.path,
+                        )
+                    })
+            });
+        Ok(if valid { Ok(()) } else { Err(()) })
+    }
+
+    // Shares are assumed verified; leaves are deduplicated by index and any
+    // 2^(log_degree - 1) distinct ones suffice.
+    fn recover_payload(
+        &self,
+        shares: &[FridaVidShare],
+        common: &FridaVidCommon,
+    ) -> VidResult<Vec<u8>> {
+        let config = self.checked_config(common)?;
+        let mut leaves = BTreeMap::new();
+        for leaf in shares.iter().flat_map(|x| x.leaves.iter()) {
+            let symbols =
+                field_elements(&leaf.symbols).ok_or(argument("malformed share symbols"))?;
+            if symbols.len() != config.leaf_size(0) {
+                return Err(argument("share symbols have the wrong length"));
+            }
+            leaves.insert(leaf.index as usize, symbols);
+        }
+        let shares = leaves
+            .into_iter()
+            .map(|(index, symbols)| FridaShare {
+                index,
+                symbols,
+                path: vec![],
+            })
+            .collect::<Vec<_>>();
+        let data = FridaScheme { config }
+            .reconstruct(&shares)
+            .ok_or(argument("not enough distinct shares to recover"))?;
+        Ok(unpack(&data, common.payload_byte_len as usize))
+    }
+
+    fn is_consistent(commit: &[u8; 32], common: &FridaVidCommon) -> VidResult<()> {
+        common.multiplicity()?;
+        let config = common.config();
+        let proof = FridaProof::from_bytes(&common.proof).ok_or(argument("malformed proof"))?;
+        // The round domains are built from these fields, so bound them by the
+        // proof and the field before verifying.
+        if !(1..=MAX_CODE_RATE).contains(&config.code_rate)
+            || proof.iopp_commits.merkle_roots.len() + 1 != config.log_degree
+            || config.log_degree + config.code_rate > Fr::TWO_ADICITY as usize
+        {
+            return Err(argument("proof shape does not match common data"));
+        }
+        if config.commitment(&proof.merkle_root) != *commit {
+            return Err(argument("commit does not match common data"));
+        }
+        if !Verifier::verify_proof(&config, commit, &proof).accepted() {
+            return Err(argument("proximity proof rejected"));
+        }
+        Ok(())
+    }
+
+    fn get_payload_byte_len(common: &FridaVidCommon) -> u32 {
+        common.payload_byte_len
+    }
+
+    fn get_num_storage_nodes(common: &FridaVidCommon) -> u32 {
+        common.num_storage_nodes
+    }
+
+    // The trait leaves no room for an error, so malformed common data has a
+    // multiplicity of 0; `is_consistent` rejects it.
+    fn get_multiplicity(common: &FridaVidCommon) -> u32 {
+        common.multiplicity().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, RngCore};
+
+    use super::*;
+
+    #[test]
+    fn vid_roundtrip() {
+        let mut rng = thread_rng();
+        let mut payload = vec![0u8; 60000];
+        rng.fill_bytes(&mut payload);
+        let mut vid =
+            FridaVid::new(10, SecurityLevel::Bits80, SoundnessRegime::Proven, b"vid").unwrap();
+        let disperse = vid.disperse(&payload).unwrap();
+        assert_eq!(vid.commit_only(&payload).unwrap(), disperse.commit);
+        assert_eq!(FridaVid::get_payload_byte_len(&disperse.common), 60000);
+        for share in disperse.shares.iter() {
+            assert_eq!(
+                vid.verify_share(share, &disperse.common, &disperse.commit)
+                    .unwrap(),
+                Ok(())
+            );
+        }
+
+        // 128 leaves over 10 nodes; 32 distinct leaves recover the payload.
+        assert_eq!(FridaVid::get_multiplicity(&disperse.common), 13);
+        let recovered = vid
+            .recover_payload(&disperse.shares[..3], &disperse.common)
+            .unwrap();
+        assert_eq!(recovered, payload);
+        assert!(vid
+            .recover_payload(&disperse.shares[..2], &disperse.common)
+            .is_err());
+
+        let mut forged = disperse.shares[2].clone();
+        forged.leaves[0].symbols[0] ^= 1;
+        assert_eq!(
+            vid.verify_share(&forged, &disperse.common, &disperse.commit)
+                .unwrap(),
+            Err(())
+        );
+        let mut moved = disperse.shares[2].clone();
+        moved.index = 3;
+        assert_eq!(
+            vid.verify_share(&moved, &disperse.common, &disperse.commit)
+                .unwrap(),
+            Err(())
+        );
+        assert!(FridaVid::is_consistent(&[0; 32], &disperse.common).is_err());
+
+        for (log_degree, code_rate) in [(0, 0), (32, 1), (u32::MAX, 1)] {
+            let malformed = FridaVidCommon {
+                log_degree,
+                code_rate,
+                ..disperse.common.clone()
+            };
+            assert_eq!(FridaVid::get_multiplicity(&malformed), 0);
+            assert!(FridaVid::is_consistent(&disperse.commit, &malformed).is_err());
+        }
+        let proof = FridaProof::from_bytes(&disperse.common.proof).unwrap();
+        for (log_degree, code_rate) in [(27, 1), (disperse.common.log_degree, 20)] {
+            let oversized = FridaVidCommon {
+                log_degree,
+                code_rate,
+                ..disperse.common.clone()
+            };
+            let commit = oversized.config().commitment(&proof.merkle_root);
+            assert!(FridaVid::is_consistent(&commit, &oversized).is_err());
+        }
+    }
+}
@@ -0,0 +1,289 @@
+// A secret-sharing mode, so a commitment doubles as a transparent VSS. Each
+// secret polynomial carries its secret in the lowest `secret_len`
+// coefficients and fresh randomness above, so any `privacy_threshold` of its
+// evaluations are uniform whatever the secret. One more uniformly random
+// polynomial is batched last as a mask: the batched polynomial stays uniform
+// under it, which blinds every folded oracle and the final value, and every
+// leaf holds two of its evaluations, which salts the leaf hashes. As long as
+// fewer than `degree` mask values are ever revealed, the root and the proof
+// give away nothing beyond the first-oracle evaluations the proof opens.
+// This is weaker than a hiding commitment: the root is a plain hash, hiding
+// only while a coalition holds at most `coalition_bound` leaves next to the
+// proof, and `hiding` refuses a config that cannot hide from the coalition
+// asked for.
+use std::fmt;
+
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use rand::Rng;
+
+use crate::{hygiene::scrub, FridaConfig, Interleave, ProveOptions, Prover};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharingError {
+    // Needs one secret polynomial besides the mask and a non-empty secret.
+    NoSecret,
+    SecretTooLong { secret_len: usize, degree: usize },
+    // A proof alone opens `exposed` evaluations where `threshold` are hidden.
+    ProofExposes { exposed: usize, threshold: usize },
+    // `coalition` leaves and the proof together pin down the secret.
+    Coalition { coalition: usize, bound: usize },
+    // A node's share is one whole first-oracle leaf, so every polynomial
+    // must share it.
+    Interleaved,
+}
+
+impl fmt::Display for SharingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharingError::NoSecret => write!(f, "sharing has no secret polynomial"),
+            SharingError::SecretTooLong { secret_len, degree } => write!(
+                f,
+                "secret of {} coefficients leaves no randomness below degree {}",
+                secret_len, degree
+            ),
+            SharingError::ProofExposes { exposed, threshold } => write!(
+                f,
+                "proof exposes {} evaluations, only {} are hidden",
+                exposed, threshold
+            ),
+            SharingError::Coalition { coalition, bound } => write!(
+                f,
+                "a coalition of {} leaves exceeds the {} the sharing hides from",
+                coalition, bound
+            ),
+            SharingError::Interleaved => write!(f, "shares need every polynomial in each leaf"),
+        }
+    }
+}
+
+impl std::error::Error for SharingError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharingConfig {
+    // The commitment's config; its last polynomial is the mask.
+    pub config: FridaConfig,
+    pub secret_len: usize,
+}
+
+impl SharingConfig {
+    pub fn new(config: FridaConfig, secret_len: usize) -> Result<Self, SharingError> {
+        if config.poly_num < 2 || secret_len == 0 {
+            return Err(SharingError::NoSecret);
+        }
+        if config.interleave != Interleave::Full {
+            return Err(SharingError::Interleaved);
+        }
+        let sharing = SharingConfig { config, secret_len };
+        let degree = sharing.degree();
+        if secret_len >= degree {
+            return Err(SharingError::SecretTooLong { secret_len, degree });
+        }
+        for (exposed, threshold) in [
+            (sharing.proof_exposure(), sharing.privacy_threshold()),
+            (sharing.mask_exposure(), degree),
+        ] {
+            if exposed > threshold {
+                return Err(SharingError::ProofExposes { exposed, threshold });
+            }
+        }
+        Ok(sharing)
+    }
+
+    // `new` for a sharing that stays hidden from any `coalition` leaves.
+    pub fn hiding(
+        config: FridaConfig,
+        secret_len: usize,
+        coalition: usize,
+    ) -> Result<Self, SharingError> {
+        let sharing = Self::new(config, secret_len)?;
+        let bound = sharing.coalition_bound();
+        if coalition > bound {
+            return Err(SharingError::Coalition { coalition, bound });
+        }
+        Ok(sharing)
+    }
+
+    pub fn secret_num(&self) -> usize {
+        self.config.poly_num - 1
+    }
+
+    pub fn degree(&self) -> usize {
+        1 << self.config.log_degree
+    }
+
+    // Leaves that reconstruct, each holding two evaluations.
+    pub fn quorum(&self) -> usize {
+        self.degree() / 2
+    }
+
+    // Evaluations of a secret polynomial that reveal nothing of its secret;
+    // `degree` of them pin it down.
+    pub fn privacy_threshold(&self) -> usize {
+        self.degree() - self.secret_len
+    }
+
+    // Evaluations of every polynomial a proof opens: x and -x per query.
+    pub fn proof_exposure(&self) -> usize {
+        2 * self.config.query_num
+    }
+
+    // Mask values a proof depends on: its first-oracle openings, x and -x per
+    // query, a whole folded leaf per query in every later round, and the
+    // final value.
+    pub fn mask_exposure(&self) -> usize {
+        let folded = (1..self.config.log_degree)
+            .map(|round| self.config.leaf_size(round))
+            .sum::<usize>();
+        self.config.query_num * (2 + folded) + 1
+    }
+
+    // The most leaves a coalition may hold, on top of a published proof,
+    // while the secret and the mask stay hidden.
+    pub fn coalition_bound(&self) -> usize {
+        let secret = self.privacy_threshold() - self.proof_exposure();
+        let mask = self.degree() - self.mask_exposure();
+        secret.min(mask) / 2
+    }
+
+    // Coefficients of a sharing polynomial for `secret`.
+    pub fn blind<R: Rng>(&self, secret: &[Fr], rng: &mut R) -> Vec<Fr> {
+        assert_eq!(secret.len(), self.secret_len);
+        let mut coeff = secret.to_vec();
+        coeff.extend((self.secret_len..self.degree()).map(|_| Fr::rand(rng)));
+        coeff
+    }
+
+    // Commits to one blinded polynomial per secret plus the mask. The prover
+    // proves and opens as usual under `config`.
+    pub fn share<R: Rng>(&self, secrets: &[Vec<Fr>], rng: &mut R) -> Prover {
+        assert_eq!(secrets.len(), self.secret_num());
+        let mut polies = secrets
+            .iter()
+            .map(|x| self.blind(x, rng))
+            .collect::<Vec<_>>();
+        polies.push((0..self.degree()).map(|_| Fr::rand(rng)).collect());
+        let prover =
+            Prover::new(&polies, &self.config.groups()[0], &ProveOptions::default()).unwrap();
+        polies.iter_mut().for_each(scrub);
+        prover
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{Field, One, Zero};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{Interleave, Verifier};
+
+    fn evaluate(coeff: &[Fr], x: Fr) -> Fr {
+        coeff
+            .iter()
+            .rev()
+            .fold(<Fr as Zero>::zero(), |acc, c| acc * x + c)
+    }
+
+    #[test]
+    fn hiding_sharing() {
+        let mut rng = thread_rng();
+        let config = FridaConfig::new(b"vss", 2, 6, 1, 4);
+        let sharing = SharingConfig::new(config.clone(), 4).unwrap();
+        assert_eq!(sharing.privacy_threshold(), 60);
+        assert_eq!(sharing.mask_exposure(), 49);
+        assert_eq!(sharing.coalition_bound(), 7);
+        assert_eq!(
+            SharingConfig::hiding(config.clone(), 4, 7),
+            Ok(sharing.clone())
+        );
+        assert_eq!(
+            SharingConfig::hiding(config.clone(), 4, 8),
+            Err(SharingError::Coalition {
+                coalition: 8,
+                bound: 7
+            })
+        );
+        assert_eq!(
+            SharingConfig::new(config.clone(), 64),
+            Err(SharingError::SecretTooLong {
+                secret_len: 64,
+                degree: 64
+            })
+        );
+        let exposed = FridaConfig {
+            query_num: 8,
+            ..config.clone()
+        };
+        assert!(matches!(
+            SharingConfig::new(exposed, 4),
+            Err(SharingError::ProofExposes { .. })
+        ));
+        // Wider folded leaves open more of the mask per query.
+        let wide = FridaConfig {
+            folded_leaf_size: 8,
+            ..config.clone()
+        };
+        assert_eq!(
+            SharingConfig::new(wide, 4),
+            Err(SharingError::ProofExposes {
+                exposed: 4 * (2 + 8 + 8 + 8 + 8 + 4) + 1,
+                threshold: 64
+            })
+        );
+        let wide = FridaConfig {
+            folded_leaf_size: 4,
+            query_num: 2,
+            ..config.clone()
+        };
+        let sharing_wide = SharingConfig::new(wide, 4).unwrap();
+        assert_eq!(sharing_wide.mask_exposure(), 2 * (2 + 4 * 5) + 1);
+        assert_eq!(sharing_wide.coalition_bound(), (64 - 45) / 2);
+        let interleaved = FridaConfig {
+            interleave: Interleave::Rows(1),
+            ..config.clone()
+        };
+        assert_eq!(
+            SharingConfig::new(interleaved, 4),
+            Err(SharingError::Interleaved)
+        );
+
+        let secret = (0..4).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let prover = sharing.share(std::slice::from_ref(&secret), &mut rng);
+        let again = sharing.share(std::slice::from_ref(&secret), &mut rng);
+        assert_ne!(prover.commit(), again.commit());
+        let commitment = config.commitment(&prover.commit());
+        let report = Verifier::verify_proof(&config, &commitment, &prover.prove(&config));
+        assert!(report.failure.is_none());
+
+        // Any other secret fits what the proof opened: some randomness above
+        // `secret_len` makes it agree with every opened evaluation.
+        let group = &config.groups()[0];
+        let opened = &report.opened[0];
+        assert!(opened.len() <= sharing.proof_exposure());
+        let other = (0..4).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let mut points = opened
+            .iter()
+            .map(|&(i, y)| {
+                let x = group.element_at(i);
+                (
+                    i,
+                    (y - evaluate(&other, x)) * x.pow([4u64]).inverse().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let free = (0..group.size()).filter(|i| opened.iter().all(|x| x.0 != *i));
+        points.extend(
+            free.take(sharing.privacy_threshold() - opened.len())
+                .map(|i| (i, Fr::one())),
+        );
+        let randomness = group
+            .interpolate(&points, sharing.privacy_threshold())
+            .unwrap();
+        let mut forged = other;
+        forged.extend(randomness);
+        for &(i, y) in opened.iter() {
+            assert_eq!(evaluate(&forged, group.element_at(i)), y);
+        }
+    }
+}
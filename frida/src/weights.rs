@@ -0,0 +1,259 @@
+// Stake-weighted share assignment. Nodes hold runs of consecutive leaves in
+// proportion to their weight, apportioned by largest remainder, and every
+// per-node count elsewhere becomes a per-leaf one: a dealing goes to one
+// virtual node per leaf, shares are checked and reconstructed per leaf, and
+// thresholds are stated in leaves. What a coalition of a given weight can
+// hold is bounded by the fractional knapsack, which is never below the best
+// integral coalition.
+use std::{cmp::Ordering, ops::Range};
+
+use ark_bn254::Fr;
+use rand::Rng;
+
+use crate::{
+    dealer::{DealError, Dealer, Dealing},
+    plan::{DispersalInputs, DispersalPlan},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    weights: Vec<u64>,
+    // Node i holds leaves starts[i]..starts[i + 1].
+    starts: Vec<usize>,
+}
+
+impl Assignment {
+    // None if every weight is zero.
+    pub fn apportion(weights: &[u64], leaves: usize) -> Option<Self> {
+        let total = weights.iter().map(|x| *x as u128).sum::<u128>();
+        if total == 0 {
+            return None;
+        }
+        let quota = weights
+            .iter()
+            .map(|x| *x as u128 * leaves as u128)
+            .collect::<Vec<_>>();
+        let mut counts = quota
+            .iter()
+            .map(|x| (x / total) as usize)
+            .collect::<Vec<_>>();
+        let mut order = (0..weights.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(quota[i] % total));
+        let left = leaves - counts.iter().sum::<usize>();
+        order.iter().take(left).for_each(|&i| counts[i] += 1);
+        let starts = std::iter::once(0)
+            .chain(counts.iter().scan(0, |acc, x| {
+                *acc += x;
+                Some(*acc)
+            }))
+            .collect();
+        Some(Assignment {
+            weights: weights.to_vec(),
+            starts,
+        })
+    }
+
+    pub fn node_num(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn leaves(&self) -> usize {
+        self.starts[self.node_num()]
+    }
+
+    pub fn leaves_of(&self, node: usize) -> Range<usize> {
+        self.starts[node]..self.starts[node + 1]
+    }
+
+    pub fn owner(&self, leaf: usize) -> Option<usize> {
+        (leaf < self.leaves()).then(|| self.starts.partition_point(|x| *x <= leaf) - 1)
+    }
+
+    pub fn max_leaves(&self) -> usize {
+        (0..self.node_num())
+            .map(|x| self.leaves_of(x).len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn held(&self, nodes: &[usize]) -> usize {
+        nodes.iter().map(|x| self.leaves_of(*x).len()).sum()
+    }
+
+    // (weight, leaves) per node, ordered by `better` first.
+    fn ranked<F: Fn(&(u64, usize), &(u64, usize)) -> Ordering>(
+        &self,
+        better: F,
+    ) -> Vec<(u64, usize)> {
+        let mut items = (0..self.node_num())
+            .map(|x| (self.weights[x], self.leaves_of(x).len()))
+            .collect::<Vec<_>>();
+        items.sort_by(better);
+        items
+    }
+
+    // The most leaves nodes of total weight at most `weight` can hold.
+    pub fn max_leaves_within(&self, weight: u64) -> usize {
+        let items =
+            self.ranked(|a, b| (b.1 as u128 * a.0 as u128).cmp(&(a.1 as u128 * b.0 as u128)));
+        let (mut budget, mut held) = (weight as u128, 0u128);
+        for (w, s) in items {
+            if w as u128 <= budget {
+                budget -= w as u128;
+                held += s as u128;
+            } else {
+                held += s as u128 * budget / w as u128;
+                break;
+            }
+        }
+        held as usize
+    }
+
+    // The most weight nodes holding fewer than `leaves` leaves can have, so
+    // any nodes of more weight reconstruct with a threshold of `leaves`.
+    pub fn max_weight_below(&self, leaves: usize) -> u64 {
+        let items =
+            self.ranked(|a, b| (b.0 as u128 * a.1 as u128).cmp(&(a.0 as u128 * b.1 as u128)));
+        let (mut budget, mut weight) = (leaves.saturating_sub(1) as u128, 0u128);
+        for (w, s) in items {
+            if s as u128 <= budget {
+                budget -= s as u128;
+                weight += w as u128;
+            } else {
+                weight += w as u128 * budget / s as u128;
+                break;
+            }
+        }
+        weight as u64
+    }
+}
+
+impl DispersalPlan {
+    // `pick` with `adversary_fraction` read as a fraction of the total weight
+    // and leaves apportioned to `weights`.
+    pub fn pick_weighted(
+        inputs: &DispersalInputs,
+        weights: &[u64],
+    ) -> Option<(DispersalPlan, Assignment)> {
+        let total = weights.iter().sum::<u64>();
+        let budget = (total as f64 * inputs.adversary_fraction).floor() as u64;
+        let inputs = DispersalInputs {
+            node_num: weights.len(),
+            ..*inputs
+        };
+        let plan = Self::pick_with(&inputs, |leave_number| {
+            let assignment = Assignment::apportion(weights, leave_number)?;
+            Some((
+                assignment.max_leaves(),
+                assignment.max_leaves_within(budget),
+            ))
+        })?;
+        let assignment = Assignment::apportion(weights, plan.leave_number)?;
+        Some((plan, assignment))
+    }
+}
+
+impl Dealer {
+    // `deal` to one virtual node per leaf of `assignment`; node i's packages
+    // are those of `assignment.leaves_of(i)`, and `threshold` counts leaves.
+    pub fn deal_weighted<K: Clone, R: Rng>(
+        &self,
+        secret: &[Fr],
+        node_pubkeys: &[K],
+        assignment: &Assignment,
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<Dealing<K>, DealError> {
+        assert_eq!(node_pubkeys.len(), assignment.node_num());
+        let virtual_nodes = (0..assignment.node_num())
+            .flat_map(|x| {
+                assignment
+                    .leaves_of(x)
+                    .map(move |_| node_pubkeys[x].clone())
+            })
+            .collect::<Vec<_>>();
+        self.deal(secret, &virtual_nodes, threshold, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{
+        reconstruct::{reconstruct, Faults},
+        session::Session,
+        share::verify_my_share,
+        SoundnessRegime,
+    };
+
+    #[test]
+    fn weighted_shares() {
+        let mut rng = thread_rng();
+        let weights = [5, 3, 1, 1, 0, 6];
+        let assignment = Assignment::apportion(&weights, 16).unwrap();
+        assert_eq!(assignment.leaves(), 16);
+        assert_eq!(
+            (0..6)
+                .map(|x| assignment.leaves_of(x).len())
+                .collect::<Vec<_>>(),
+            vec![5, 3, 1, 1, 0, 6]
+        );
+        assert_eq!(assignment.owner(7), Some(1));
+        assert_eq!(assignment.owner(8), Some(2));
+        assert_eq!(assignment.owner(15), Some(5));
+        assert_eq!(assignment.owner(16), None);
+        assert!(Assignment::apportion(&[0, 0], 4).is_none());
+
+        // The bounds hold for every coalition.
+        let bound = assignment.max_weight_below(8);
+        for subset in 0..1u32 << 6 {
+            let nodes = (0..6).filter(|x| subset >> x & 1 == 1).collect::<Vec<_>>();
+            let weight = nodes.iter().map(|x| weights[*x]).sum::<u64>();
+            let held = assignment.held(&nodes);
+            assert!(held <= assignment.max_leaves_within(weight));
+            assert!(weight <= bound || held >= 8);
+        }
+
+        let dealer = Dealer {
+            context: b"weights".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let keys = (0..6u64).collect::<Vec<_>>();
+        let secret = [Fr::rand(&mut rng)];
+        let dealing = dealer
+            .deal_weighted(&secret, &keys, &assignment, 4, &mut rng)
+            .unwrap();
+        let expected = dealer.sharing(1, 16, 4).unwrap();
+        // Nodes 0 and 5 hold eleven leaves between them.
+        let shares = [0, 5]
+            .iter()
+            .flat_map(|x| assignment.leaves_of(*x))
+            .map(|leaf| {
+                let package = &dealing.packages[leaf];
+                assert_eq!(assignment.owner(leaf), Some(package.node as usize));
+                verify_my_share(package, &dealing.transcript, &expected, 4, leaf).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(dealing.transcript.sharing.quorum(), 8);
+        let recovered = reconstruct(&shares, 8, 1, Faults::Reject).unwrap();
+        assert_eq!(recovered.secret, secret);
+
+        let inputs = DispersalInputs {
+            blob_size: 1 << 16,
+            node_num: 0,
+            adversary_fraction: 0.33,
+            failure_probability: 1e-9,
+        };
+        let stakes = (1..=40u64).map(|x| x * x).collect::<Vec<_>>();
+        let (plan, assignment) = DispersalPlan::pick_weighted(&inputs, &stakes).unwrap();
+        let budget = (stakes.iter().sum::<u64>() as f64 * 0.33) as u64;
+        assert_eq!(assignment.leaves(), plan.leave_number);
+        assert_eq!(plan.shares_per_node, assignment.max_leaves());
+        assert!(plan.leave_number - assignment.max_leaves_within(budget) >= plan.required_leaves());
+    }
+}
@@ -0,0 +1,119 @@
+// The supported public API of the workspace. Each module is one stage of a
+// dispersal, under the names this crate keeps stable; what `frida`, `util`
+// and `veri_rs` expose beyond it is internal and may change between releases.
+// `Frida` goes through every stage behind `VrsScheme`, and the modules carry
+// the committee-facing pieces: dealing shares to nodes, checking them and
+// reconstructing a secret from enough of them.
+pub use ark_bn254::Fr;
+#[cfg(feature = "prover")]
+pub use frida::scheme::FridaScheme as Frida;
+pub use util::vrs::VrsScheme;
+#[cfg(feature = "veri-rs")]
+pub use veri_rs::consolidation::{VeriRsProver as VeriRs, VeriRsShare};
+
+pub mod config {
+    #[cfg(feature = "prover")]
+    pub use frida::FridaBuilder as ConfigBuilder;
+    pub use frida::{
+        confidence::{samples_needed, AdversaryModel},
+        plan::{DispersalInputs, DispersalPlan},
+        session::Session,
+        Batching, FridaConfig as Config, Interleave, ProtocolVersion, SecurityLevel,
+        SoundnessRegime,
+    };
+}
+
+pub mod commit {
+    #[cfg(feature = "prover")]
+    pub use frida::Prover;
+    pub use frida::{CancellationToken, Cancelled, CommitmentMeta, Layout, Phase, ProveOptions};
+
+    // What `Config::commitment` binds a merkle root to and verifiers check.
+    pub type Commitment = [u8; 32];
+}
+
+pub mod disperse {
+    pub use frida::FridaProof as Proof;
+    #[cfg(feature = "prover")]
+    pub use frida::{
+        dealer::{DealError, Dealer, Dealing, DealingTranscript, NodePackage},
+        scheme::FridaShare as BlobShare,
+        share::{SealedPackage, ShareCipher},
+    };
+}
+
+pub mod sample {
+    #[cfg(feature = "prover")]
+    pub use frida::client::LocalPeer;
+    #[cfg(feature = "prover")]
+    pub use frida::service::{ProofService, ResponseHandle};
+    pub use frida::{
+        client::{
+            ClientConfig, FetchError, PeerStats, SamplePeer, SamplingClient, SamplingReport,
+            Verdict,
+        },
+        service::{PeerId, SampleResponse, ServiceConfig, ServiceError},
+    };
+}
+
+pub mod verify {
+    #[cfg(feature = "prover")]
+    pub use frida::{
+        receive::{verify_dealings, verify_sealed_dealings},
+        share::{verify_my_share, verify_sealed_share, Share, ShareError},
+    };
+    pub use frida::{FailedCheck, VerificationReport, Verifier};
+}
+
+#[cfg(feature = "prover")]
+pub mod reconstruct {
+    pub use frida::reconstruct::{reconstruct, Faults, ReconstructError, Reconstruction};
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    use super::{
+        config::{Config, Session, SoundnessRegime},
+        disperse::Dealer,
+        reconstruct::{reconstruct, Faults},
+        verify::verify_my_share,
+        *,
+    };
+
+    #[test]
+    fn facade_roundtrip() {
+        let mut rng = thread_rng();
+        let frida = Frida {
+            config: Config::new(b"facade", 2, 4, 1, 8),
+        };
+        let data = (0..32).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let (commitment, prover) = frida.commit(&data);
+        let (proof, shares) = frida.disperse(&prover);
+        assert!(shares.iter().all(|x| frida.verify(&commitment, &proof, x)));
+        assert!(frida.verify(&commitment, &proof, &frida.sample(&prover, 5)));
+        assert_eq!(frida.reconstruct(&shares[8..]), Some(data));
+
+        let dealer = Dealer {
+            context: b"facade".to_vec(),
+            session: Session::Dkg(0),
+            query_num: 1,
+            regime: SoundnessRegime::Proven,
+        };
+        let secret = [Fr::rand(&mut rng)];
+        let keys = (0..8u64).collect::<Vec<_>>();
+        let dealing = dealer.deal(&secret, &keys, 4, &mut rng).unwrap();
+        let expected = dealer.sharing(1, 8, 4).unwrap();
+        let shares = dealing
+            .packages
+            .iter()
+            .map(|x| verify_my_share(x, &dealing.transcript, &expected, 4, x.index).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reconstruct(&shares, 8, 1, Faults::Reject).unwrap().secret,
+            secret
+        );
+    }
+}
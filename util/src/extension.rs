@@ -0,0 +1,178 @@
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use ark_bn254::Fr;
+use ark_ff::{FftField, Field, One, Zero};
+
+// What the radix-2 transforms run on: Fr itself, or a vector space over it.
+// Twiddles stay in Fr, so a butterfly multiplies by a base field scalar.
+pub trait FftElement:
+    Copy + Zero + Sub<Output = Self> + AddAssign + Mul<Fr, Output = Self> + MulAssign<Fr>
+{
+}
+
+impl FftElement for Fr {}
+
+// Fr[X] / (X^D - g) for the multiplicative generator g. As 6 divides r - 1,
+// g is neither a square nor a cube, so D = 2 and D = 3 give fields.
+// Coefficients are lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtField<const D: usize>(pub [Fr; D]);
+
+pub type QuadExt = ExtField<2>;
+pub type CubicExt = ExtField<3>;
+
+impl<const D: usize> ExtField<D> {
+    pub fn from_base(x: Fr) -> Self {
+        let mut c = [Fr::zero(); D];
+        c[0] = x;
+        ExtField(c)
+    }
+
+    pub fn non_residue() -> Fr {
+        <Fr as FftField>::GENERATOR
+    }
+
+    // The D x D matrix of multiplication by self, column j being self * X^j.
+    fn mul_matrix(&self) -> [[Fr; D]; D] {
+        let mut m = [[Fr::zero(); D]; D];
+        let mut column = *self;
+        for j in 0..D {
+            m.iter_mut().zip(column.0).for_each(|(row, x)| row[j] = x);
+            column = column.shift();
+        }
+        m
+    }
+
+    // self * X.
+    fn shift(self) -> Self {
+        let mut c = [Fr::zero(); D];
+        c[0] = self.0[D - 1] * Self::non_residue();
+        c[1..].copy_from_slice(&self.0[..D - 1]);
+        ExtField(c)
+    }
+
+    // Solves self * y = 1 by elimination over the multiplication matrix.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut m = self.mul_matrix();
+        let mut y = Self::one().0;
+        for col in 0..D {
+            let pivot = (col..D).find(|&r| !m[r][col].is_zero())?;
+            m.swap(col, pivot);
+            y.swap(col, pivot);
+            let inv = m[col][col].inverse()?;
+            m[col].iter_mut().for_each(|x| *x *= inv);
+            y[col] *= inv;
+            let pivot_row = m[col];
+            for r in (0..D).filter(|&r| r != col) {
+                let factor = m[r][col];
+                m[r].iter_mut()
+                    .zip(pivot_row)
+                    .for_each(|(x, p)| *x -= factor * p);
+                let delta = factor * y[col];
+                y[r] -= delta;
+            }
+        }
+        Some(ExtField(y))
+    }
+}
+
+impl<const D: usize> From<Fr> for ExtField<D> {
+    fn from(x: Fr) -> Self {
+        Self::from_base(x)
+    }
+}
+
+impl<const D: usize> Add for ExtField<D> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl<const D: usize> AddAssign for ExtField<D> {
+    fn add_assign(&mut self, other: Self) {
+        self.0.iter_mut().zip(other.0).for_each(|(a, b)| *a += b);
+    }
+}
+
+impl<const D: usize> Sub for ExtField<D> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        self -= other;
+        self
+    }
+}
+
+impl<const D: usize> SubAssign for ExtField<D> {
+    fn sub_assign(&mut self, other: Self) {
+        self.0.iter_mut().zip(other.0).for_each(|(a, b)| *a -= b);
+    }
+}
+
+impl<const D: usize> Neg for ExtField<D> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ExtField(self.0.map(|x| -x))
+    }
+}
+
+impl<const D: usize> Mul<Fr> for ExtField<D> {
+    type Output = Self;
+
+    fn mul(mut self, scalar: Fr) -> Self {
+        self *= scalar;
+        self
+    }
+}
+
+impl<const D: usize> MulAssign<Fr> for ExtField<D> {
+    fn mul_assign(&mut self, scalar: Fr) {
+        self.0.iter_mut().for_each(|x| *x *= scalar);
+    }
+}
+
+// Schoolbook, folding X^(D + k) back to g * X^k.
+impl<const D: usize> Mul for ExtField<D> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut c = [Fr::zero(); D];
+        for i in 0..D {
+            for j in 0..D {
+                let t = self.0[i] * other.0[j];
+                if i + j < D {
+                    c[i + j] += t;
+                } else {
+                    c[i + j - D] += t * Self::non_residue();
+                }
+            }
+        }
+        ExtField(c)
+    }
+}
+
+impl<const D: usize> Zero for ExtField<D> {
+    fn zero() -> Self {
+        ExtField([Fr::zero(); D])
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|x| x.is_zero())
+    }
+}
+
+impl<const D: usize> One for ExtField<D> {
+    fn one() -> Self {
+        Self::from_base(Fr::one())
+    }
+
+    fn is_one(&self) -> bool {
+        *self == Self::one()
+    }
+}
+
+impl<const D: usize> FftElement for ExtField<D> {}
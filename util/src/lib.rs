@@ -1,2 +1,8 @@
 pub mod mul_group;
 pub mod merkle_tree;
+pub mod memory;
+pub mod vrs;
+pub mod nmt;
+pub mod extension;
+pub mod sharded;
+pub mod placement;
@@ -0,0 +1,109 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static BASE: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// Install with `#[global_allocator]` in a bench binary to get heap statistics.
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    fn grow(size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn reset() {
+        let current = CURRENT.load(Ordering::Relaxed);
+        BASE.store(current, Ordering::Relaxed);
+        PEAK.store(current, Ordering::Relaxed);
+        ALLOCATIONS.store(0, Ordering::Relaxed);
+    }
+
+    // Largest live heap size above the level at the last `reset`.
+    pub fn peak() -> usize {
+        PEAK.load(Ordering::Relaxed)
+            .saturating_sub(BASE.load(Ordering::Relaxed))
+    }
+
+    pub fn current() -> usize {
+        CURRENT.load(Ordering::Relaxed)
+    }
+
+    pub fn allocations() -> usize {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            Self::grow(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn status_kb(key: &str) -> Option<usize> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|x| x.starts_with(key))?;
+    line[key.len()..]
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+// Peak resident set size in bytes; only available on Linux.
+pub fn peak_rss() -> Option<usize> {
+    status_kb("VmHWM:").map(|x| x * 1024)
+}
+
+// Resets the kernel's high-water mark so `peak_rss` covers the next run only.
+pub fn reset_peak_rss() -> bool {
+    fs::write("/proc/self/clear_refs", "5").is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rss() {
+        if peak_rss().is_some() {
+            reset_peak_rss();
+            let v = vec![1u8; 1 << 24];
+            assert!(peak_rss().unwrap() >= v.len());
+        }
+    }
+}
@@ -1,8 +1,14 @@
-use std::marker::PhantomData;
+use std::{
+    fs,
+    io::{self, Read, Seek, Write},
+    marker::PhantomData,
+    mem::size_of,
+    path::Path,
+};
 
 use ark_bn254::Fr;
 use ark_serialize::CanonicalSerialize;
-use rs_merkle::{Hasher, MerkleProof, MerkleTree};
+use rs_merkle::{Hasher, MerkleProof};
 
 #[derive(Debug, Clone)]
 pub struct Blake16 {}
@@ -22,13 +28,48 @@ impl Hasher for Blake32 {
     type Hash = [u8; 32];
 
     fn hash(data: &[u8]) -> [u8; 32] {
-        blake3::hash(data).as_bytes().clone()
+        *blake3::hash(data).as_bytes()
     }
 }
 
+// Blake3 with RFC 6962-style domain separation, the form partner
+// implementations standardize on: a leaf hashes as blake3(0x00 || leaf), an
+// inner node as blake3(0x01 || left || right), and an unpaired node is carried
+// up unchanged. `Blake32` hashes leaves and nodes alike and so cannot match
+// their roots.
+#[derive(Debug, Clone)]
+pub struct Blake3Merkle {}
+
+impl Hasher for Blake3Merkle {
+    type Hash = [u8; 32];
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&[0]);
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+
+    fn concat_and_hash(left: &[u8; 32], right: Option<&[u8; 32]>) -> [u8; 32] {
+        match right {
+            Some(right) => {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(&[1]);
+                hasher.update(left);
+                hasher.update(right);
+                hasher.finalize().into()
+            }
+            None => *left,
+        }
+    }
+}
+
+// Nodes are kept level by level, leaf hashes first, in rs_merkle's layout:
+// an unpaired last node is carried up unchanged. Holding the levels lets a
+// single leaf be rehashed in place.
 #[derive(Clone)]
 pub struct MerkleTreeProver<H: Hasher> {
-    pub merkle_tree: MerkleTree<H>,
+    layers: Vec<Vec<H::Hash>>,
     leave_num: usize,
 }
 
@@ -37,9 +78,8 @@ pub struct Serialize;
 impl Serialize {
     pub fn serialize_fields(v: &[Fr]) -> Vec<u8> {
         let mut bytes = vec![];
-        v.iter().for_each(|x| {
-            <Fr as CanonicalSerialize>::serialize_compressed(&x, &mut bytes).unwrap()
-        });
+        v.iter()
+            .for_each(|x| <Fr as CanonicalSerialize>::serialize_compressed(x, &mut bytes).unwrap());
         bytes
     }
 }
@@ -50,14 +90,135 @@ pub struct MerkleTreeVerifier<H: Hasher> {
     pub leave_number: usize,
 }
 
+// Leaves hashed between polls of a build's `proceed`, and gathered per batch
+// under `batch-hash`. blake3 has no multi-message interface for inputs this
+// short, so a batch is split across cores instead, each hashing its run of
+// leaves with blake3's own SIMD compression.
+const HASH_BATCH: usize = 1 << 12;
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |x| x.get())
+}
+
+// Leaf hashes in order, one at a time as the leaves are produced unless
+// `batch-hash` is on, when each batch is split across `threads`; the batches
+// are joined in order, so the hashes are the same for any count.
+// `proceed(0)` is asked before each batch, and the hashing is abandoned once
+// it returns false.
+#[cfg_attr(not(feature = "batch-hash"), allow(unused_variables))]
+fn hash_leaves<
+    H: Hasher,
+    T: AsRef<[u8]> + Sync,
+    I: IntoIterator<Item = T>,
+    F: FnMut(usize) -> bool,
+>(
+    leaf_values: I,
+    threads: usize,
+    proceed: &mut F,
+) -> Option<Vec<H::Hash>>
+where
+    H::Hash: Send,
+{
+    let mut leaf_values = leaf_values.into_iter();
+    let mut hashes = vec![];
+    loop {
+        if !proceed(0) {
+            return None;
+        }
+        let batch = leaf_values.by_ref().take(HASH_BATCH);
+        #[cfg(not(feature = "batch-hash"))]
+        {
+            let before = hashes.len();
+            hashes.extend(batch.map(|x| H::hash(x.as_ref())));
+            if hashes.len() == before {
+                return Some(hashes);
+            }
+        }
+        #[cfg(feature = "batch-hash")]
+        {
+            let batch = batch.collect::<Vec<_>>();
+            if batch.is_empty() {
+                return Some(hashes);
+            }
+            let per_thread = batch.len().div_ceil(threads.max(1));
+            std::thread::scope(|scope| {
+                let handles = batch
+                    .chunks(per_thread)
+                    .map(|x| {
+                        scope.spawn(move || {
+                            x.iter().map(|x| H::hash(x.as_ref())).collect::<Vec<_>>()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                for handle in handles {
+                    hashes.extend(handle.join().unwrap());
+                }
+            });
+        }
+    }
+}
+
 impl<H: Hasher> MerkleTreeProver<H> {
-    pub fn new(leaf_values: &Vec<Vec<u8>>) -> Self {
-        let leaves = leaf_values.iter().map(|x| H::hash(x)).collect::<Vec<_>>();
-        let merkle_tree = MerkleTree::<H>::from_leaves(&leaves);
-        Self {
-            merkle_tree,
-            leave_num: leaf_values.len(),
+    pub fn new(leaf_values: &Vec<Vec<u8>>) -> Self
+    where
+        H::Hash: Send,
+    {
+        Self::from_hashes(
+            hash_leaves::<H, _, _, _>(leaf_values, available_threads(), &mut |_| true).unwrap(),
+        )
+    }
+
+    // Hashes each leaf as it is produced, so the serialized leaves are never
+    // all held at once.
+    pub fn from_leaf_iter<I: IntoIterator<Item = Vec<u8>>>(leaf_values: I) -> Self
+    where
+        H::Hash: Send,
+    {
+        Self::from_hashes(
+            hash_leaves::<H, _, _, _>(leaf_values, available_threads(), &mut |_| true).unwrap(),
+        )
+    }
+
+    // `from_leaf_iter` for long builds: `proceed` is asked before each batch
+    // of leaves and before each level above them, with the number of levels
+    // hashed so far, and the build is abandoned once it returns false. Leaves
+    // are hashed on up to `threads` threads under `batch-hash`.
+    pub fn try_from_leaf_iter<I: IntoIterator<Item = Vec<u8>>, F: FnMut(usize) -> bool>(
+        leaf_values: I,
+        threads: usize,
+        mut proceed: F,
+    ) -> Option<Self>
+    where
+        H::Hash: Send,
+    {
+        let leaves = hash_leaves::<H, _, _, _>(leaf_values, threads, &mut proceed)?;
+        Self::try_from_hashes(leaves, proceed)
+    }
+
+    fn from_hashes(leaves: Vec<H::Hash>) -> Self {
+        Self::try_from_hashes(leaves, |_| true).unwrap()
+    }
+
+    fn try_from_hashes<F: FnMut(usize) -> bool>(
+        leaves: Vec<H::Hash>,
+        mut proceed: F,
+    ) -> Option<Self> {
+        assert!(!leaves.is_empty());
+        let leave_num = leaves.len();
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            if !proceed(layers.len()) {
+                return None;
+            }
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|x| H::concat_and_hash(&x[0], x.get(1)))
+                .collect();
+            layers.push(next);
         }
+        Some(Self { layers, leave_num })
     }
 
     pub fn leave_num(&self) -> usize {
@@ -65,11 +226,219 @@ impl<H: Hasher> MerkleTreeProver<H> {
     }
 
     pub fn commit(&self) -> H::Hash {
-        self.merkle_tree.root().unwrap()
+        self.layers.last().unwrap()[0]
     }
 
+    // Siblings not derivable from the opened nodes, level by level in index
+    // order, which is the byte layout of an rs_merkle multiproof.
     pub fn open(&self, leaf_indices: &[usize]) -> Vec<u8> {
-        self.merkle_tree.proof(leaf_indices).to_bytes()
+        let mut current = leaf_indices.to_vec();
+        current.sort();
+        current.dedup();
+        let mut hashes = vec![];
+        for layer in self.layers[..self.layers.len() - 1].iter() {
+            let mut siblings = current
+                .iter()
+                .map(|x| x ^ 1)
+                .filter(|x| *x < layer.len() && current.binary_search(x).is_err())
+                .collect::<Vec<_>>();
+            siblings.sort();
+            siblings.dedup();
+            hashes.extend(siblings.into_iter().map(|x| layer[x]));
+            current = current.iter().map(|x| x / 2).collect();
+            current.dedup();
+        }
+        MerkleProof::<H>::new(hashes).to_bytes()
+    }
+
+    // Replaces one leaf and rehashes its path, log(n) hashes; returns the new
+    // root.
+    pub fn update_leaf(&mut self, index: usize, new_leaf: &[u8]) -> H::Hash {
+        assert!(index < self.leave_num);
+        self.layers[0][index] = H::hash(new_leaf);
+        let mut i = index;
+        for level in 1..self.layers.len() {
+            i /= 2;
+            let below = &self.layers[level - 1];
+            let node = H::concat_and_hash(&below[2 * i], below.get(2 * i + 1));
+            self.layers[level][i] = node;
+        }
+        self.commit()
+    }
+
+    // On-disk layout: magic, hash size as u32 and leaf count as u64, both
+    // little endian, then every level's hashes from the root down to the
+    // leaves. Loading trusts the file and rehashes nothing.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.save_with_leaves(path, &[])
+    }
+
+    // `save`, followed when `leaf_values` is non-empty by leave_num + 1
+    // offsets as u64 into the concatenated leaf bytes that come after them,
+    // so `load_leaf` reads one leaf without the rest.
+    pub fn save_with_leaves<P: AsRef<Path>>(
+        &self,
+        path: P,
+        leaf_values: &[Vec<u8>],
+    ) -> io::Result<()> {
+        assert!(leaf_values.is_empty() || leaf_values.len() == self.leave_num);
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        file.write_all(TREE_MAGIC)?;
+        file.write_all(&(size_of::<H::Hash>() as u32).to_le_bytes())?;
+        file.write_all(&(self.leave_num as u64).to_le_bytes())?;
+        for layer in self.layers.iter().rev() {
+            for hash in layer {
+                file.write_all(&(*hash).into())?;
+            }
+        }
+        if !leaf_values.is_empty() {
+            let mut offset = 0u64;
+            file.write_all(&offset.to_le_bytes())?;
+            for leaf in leaf_values {
+                offset += leaf.len() as u64;
+                file.write_all(&offset.to_le_bytes())?;
+            }
+            for leaf in leaf_values {
+                file.write_all(leaf)?;
+            }
+        }
+        file.flush()
+    }
+
+    // Sizes are checked against the file length before anything is
+    // allocated, so a corrupt header is an error rather than an abort.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let mut file = fs::File::open(path)?;
+        let total = file.metadata()?.len();
+        let (hash_size, leave_num, hashes_end) = read_tree_header::<H>(&mut file, total)?;
+        let mut file = io::BufReader::new(file);
+        let mut widths = vec![leave_num];
+        while *widths.last().unwrap() > 1 {
+            widths.push(widths.last().unwrap().div_ceil(2));
+        }
+        let mut layers = Vec::with_capacity(widths.len());
+        let mut buf = vec![0u8; hash_size];
+        for width in widths.into_iter().rev() {
+            let mut layer = Vec::with_capacity(width);
+            for _ in 0..width {
+                file.read_exact(&mut buf)?;
+                layer.push(H::Hash::try_from(buf.clone()).map_err(|_| invalid("malformed hash"))?);
+            }
+            layers.push(layer);
+        }
+        if total > hashes_end {
+            // The leaf offsets must be ascending from 0 and end at the file.
+            let mut previous = 0u64;
+            let mut bytes = [0u8; 8];
+            for i in 0..=leave_num {
+                file.read_exact(&mut bytes)?;
+                let offset = u64::from_le_bytes(bytes);
+                if (i == 0 && offset != 0) || offset < previous {
+                    return Err(invalid("malformed leaf offsets"));
+                }
+                previous = offset;
+            }
+            let data_start = hashes_end + 8 * (leave_num as u64 + 1);
+            if data_start.checked_add(previous) != Some(total) {
+                return Err(invalid("leaf data does not match its offsets"));
+            }
+        }
+        layers.reverse();
+        Ok(Self { layers, leave_num })
+    }
+
+    // Leaf `index` of a file written by `save_with_leaves`, read through its
+    // offsets without loading the tree.
+    pub fn load_leaf<P: AsRef<Path>>(path: P, index: usize) -> io::Result<Vec<u8>> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let mut file = fs::File::open(path)?;
+        let total = file.metadata()?.len();
+        let (_, leave_num, hashes_end) = read_tree_header::<H>(&mut file, total)?;
+        if index >= leave_num {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "leaf index out of range",
+            ));
+        }
+        let data_start = hashes_end + 8 * (leave_num as u64 + 1);
+        if data_start > total {
+            return Err(invalid("no leaf data"));
+        }
+        file.seek(io::SeekFrom::Start(hashes_end + 8 * index as u64))?;
+        let mut bytes = [0u8; 16];
+        file.read_exact(&mut bytes)?;
+        let start = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let end = u64::from_le_bytes(bytes[8..].try_into().unwrap());
+        if start > end || end > total - data_start {
+            return Err(invalid("malformed leaf offsets"));
+        }
+        file.seek(io::SeekFrom::Start(data_start + start))?;
+        let mut leaf = vec![0u8; (end - start) as usize];
+        file.read_exact(&mut leaf)?;
+        Ok(leaf)
+    }
+}
+
+// Hash size, leaf count and where the hashes end, after checking the magic,
+// the hash size and that every level fits in the `total` bytes of the file.
+fn read_tree_header<H: Hasher>(file: &mut fs::File, total: u64) -> io::Result<(usize, usize, u64)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut header = [0u8; 16];
+    file.read_exact(&mut header)?;
+    if &header[..4] != TREE_MAGIC {
+        return Err(invalid("not a merkle tree file"));
+    }
+    let hash_size = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+    let leave_num = u64::from_le_bytes(header[8..].try_into().unwrap());
+    if hash_size != size_of::<H::Hash>() || leave_num == 0 {
+        return Err(invalid("hash size or leaf count mismatch"));
+    }
+    if leave_num > total / hash_size as u64 {
+        return Err(invalid("leaf count exceeds the file"));
+    }
+    let (mut width, mut nodes) = (leave_num, leave_num);
+    while width > 1 {
+        width = width.div_ceil(2);
+        nodes += width;
+    }
+    let hashes_end = header.len() as u64 + nodes * hash_size as u64;
+    if hashes_end > total {
+        return Err(invalid("leaf count exceeds the file"));
+    }
+    Ok((hash_size, leave_num as usize, hashes_end))
+}
+
+const TREE_MAGIC: &[u8; 4] = b"FMT1";
+
+impl<H: Hasher> MerkleTreeProver<H>
+where
+    H::Hash: AsRef<[u8]>,
+{
+    // `open`, appended to `out`, with `scratch` for the index sets, so a
+    // caller reusing both allocates nothing in steady state.
+    pub fn open_into(&self, leaf_indices: &[usize], scratch: &mut Vec<usize>, out: &mut Vec<u8>) {
+        scratch.clear();
+        scratch.extend_from_slice(leaf_indices);
+        scratch.sort_unstable();
+        scratch.dedup();
+        for layer in self.layers[..self.layers.len() - 1].iter() {
+            // Sorted distinct indices have ascending siblings, up to pairs
+            // that are both opened and so skipped.
+            for (i, x) in scratch.iter().enumerate() {
+                let sibling = x ^ 1;
+                let opened = if sibling < *x {
+                    i > 0 && scratch[i - 1] == sibling
+                } else {
+                    scratch.get(i + 1) == Some(&sibling)
+                };
+                if sibling < layer.len() && !opened {
+                    out.extend_from_slice(layer[sibling].as_ref());
+                }
+            }
+            scratch.iter_mut().for_each(|x| *x /= 2);
+            scratch.dedup();
+        }
     }
 }
 
@@ -77,27 +446,64 @@ impl<H: Hasher> MerkleTreeVerifier<H> {
     pub fn new(leave_number: usize, merkle_root: &H::Hash) -> Self {
         Self {
             leave_number,
-            merkle_root: merkle_root.clone(),
+            merkle_root: *merkle_root,
         }
     }
 
-    pub fn verify(
+    pub fn verify(&self, proof_bytes: Vec<u8>, indices: &[usize], leaves: &[Vec<u8>]) -> bool {
+        if indices.len() != leaves.len() {
+            return false;
+        }
+        let leaf_hashes: Vec<H::Hash> = leaves.iter().map(|x| H::hash(x)).collect();
+        self.verify_hashes(&proof_bytes, indices, &leaf_hashes)
+    }
+
+    // `verify` for callers that hash their leaves themselves, e.g. straight
+    // from borrowed buffers.
+    pub fn verify_hashes(
         &self,
-        proof_bytes: Vec<u8>,
-        indices: &Vec<usize>,
-        leaves: &Vec<Vec<u8>>,
+        proof_bytes: &[u8],
+        indices: &[usize],
+        leaf_hashes: &[H::Hash],
     ) -> bool {
-        let proof = MerkleProof::<H>::try_from(proof_bytes).unwrap();
-        let leaves_to_prove: Vec<H::Hash> = leaves.iter().map(|x| H::hash(x)).collect();
-        proof.verify(
-            self.merkle_root,
-            indices,
-            &leaves_to_prove,
-            self.leave_number,
-        )
+        if indices.len() != leaf_hashes.len()
+            || indices.iter().any(|x| *x >= self.leave_number)
+            || proof_bytes.len()
+                != proof_hash_count(indices, self.leave_number) * size_of::<H::Hash>()
+        {
+            return false;
+        }
+        let proof = match MerkleProof::<H>::try_from(proof_bytes) {
+            Ok(proof) => proof,
+            Err(_) => return false,
+        };
+        proof.verify(self.merkle_root, indices, leaf_hashes, self.leave_number)
     }
 }
 
+// Sibling hashes a multiproof for `indices` carries. rs_merkle panics instead
+// of rejecting when a proof is shorter than this.
+fn proof_hash_count(indices: &[usize], leave_number: usize) -> usize {
+    let mut layer = indices.to_vec();
+    layer.sort();
+    layer.dedup();
+    let mut width = leave_number;
+    let mut count = 0;
+    while width > 1 {
+        count += layer
+            .iter()
+            .filter(|x| {
+                let sibling = *x ^ 1;
+                sibling < width && layer.binary_search(&sibling).is_err()
+            })
+            .count();
+        layer = layer.iter().map(|x| x / 2).collect();
+        layer.dedup();
+        width = width.div_ceil(2);
+    }
+    count
+}
+
 pub struct MerkleRoot<H: Hasher>(PhantomData<H>);
 impl<H: Hasher> MerkleRoot<H> {
     pub fn get_root(
@@ -108,9 +514,7 @@ impl<H: Hasher> MerkleRoot<H> {
     ) -> H::Hash {
         let proof = MerkleProof::<H>::try_from(proof_bytes).unwrap();
         let leaf_hashes = vec![H::hash(&leaf)];
-        proof
-            .root(&vec![index], &leaf_hashes, leave_number)
-            .unwrap()
+        proof.root(&[index], &leaf_hashes, leave_number).unwrap()
     }
 }
 
@@ -138,7 +542,183 @@ mod tests {
             Serialize::serialize_fields(&[Fr::from(2 * 2), Fr::from(2 * 2 + 1)]),
             Serialize::serialize_fields(&[Fr::from(3 * 2), Fr::from(3 * 2 + 1)]),
         ];
-        assert!(verifier.verify(proof_bytes, &leaf_indices, &open_values));
+        assert!(verifier.verify(proof_bytes.clone(), &leaf_indices, &open_values));
+
+        assert!(!verifier.verify(proof_bytes[16..].to_vec(), &leaf_indices, &open_values));
+        assert!(!verifier.verify(proof_bytes[1..].to_vec(), &leaf_indices, &open_values));
+        assert!(!verifier.verify(proof_bytes.clone(), &[1, 2], &open_values));
+        assert!(!verifier.verify(proof_bytes, &[2, 8], &open_values));
+
+        let mut scratch = vec![];
+        for indices in [vec![2, 3], vec![7, 0, 5, 5], vec![1, 2, 3, 4, 6]] {
+            let mut out = vec![9];
+            prover.open_into(&indices, &mut scratch, &mut out);
+            assert_eq!(out[1..], prover.open(&indices)[..]);
+        }
+
+        let streamed = MerkleTreeProver::<Blake16>::from_leaf_iter(leaf_values);
+        assert_eq!(streamed.commit(), root);
+        assert_eq!(streamed.leave_num(), leave_number);
+    }
+
+    #[test]
+    fn batched_leaf_hashes() {
+        let leaf_values = (0..10_000u64)
+            .map(|x| Serialize::serialize_fields(&[Fr::from(x)]))
+            .collect::<Vec<_>>();
+        let expected = leaf_values
+            .iter()
+            .map(|x| Blake32::hash(x))
+            .collect::<Vec<_>>();
+        for threads in [1, 3, 16] {
+            let hashes = hash_leaves::<Blake32, _, _, _>(&leaf_values, threads, &mut |_| true);
+            assert_eq!(hashes.unwrap(), expected);
+        }
+        assert_eq!(
+            MerkleTreeProver::<Blake32>::from_leaf_iter(leaf_values.clone()).commit(),
+            MerkleTreeProver::<Blake32>::new(&leaf_values).commit()
+        );
+
+        // Cancelling between batches of leaves stops before the levels.
+        let mut polls = 0;
+        let cancelled = MerkleTreeProver::<Blake32>::try_from_leaf_iter(leaf_values, 2, |level| {
+            polls += 1;
+            assert_eq!(level, 0);
+            polls < 2
+        });
+        assert!(cancelled.is_none());
+        assert_eq!(polls, 2);
+    }
+
+    #[test]
+    fn blake3_vectors() {
+        let hex = |x: &str| -> [u8; 32] {
+            (0..32)
+                .map(|i| u8::from_str_radix(&x[2 * i..2 * i + 2], 16).unwrap())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap()
+        };
+        // A leaf of no bytes is blake3 of the single byte 0x00.
+        assert_eq!(
+            Blake3Merkle::hash(b""),
+            hex("2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213")
+        );
+        assert_eq!(
+            Blake3Merkle::hash(b"frida"),
+            hex("74f9313ac84b146b04ab555c747023cd580b6ec06c68ca6ed252a859adf6a2ec")
+        );
+        let leaves = [b"a", b"b", b"c", b"d"].map(|x| x.to_vec());
+        let (a, b) = (Blake3Merkle::hash(b"a"), Blake3Merkle::hash(b"b"));
+        assert_eq!(
+            Blake3Merkle::concat_and_hash(&a, Some(&b)),
+            hex("6564e87d8619ea09c801c567c641d47fe817ae3b2cf80685cde2eb6557247eca")
+        );
+        let three = MerkleTreeProver::<Blake3Merkle>::new(&leaves[..3].to_vec());
+        assert_eq!(
+            three.commit(),
+            hex("6c62dd52a0971b7d00a7cead004e0c3f3c0766e3f5359a0f8297768d2b02d03c")
+        );
+        let four = MerkleTreeProver::<Blake3Merkle>::new(&leaves.to_vec());
+        assert_eq!(
+            four.commit(),
+            hex("ab907076358a51f0ac078d433e405dd69e1a632ec5be0c6c54cae29e99368d9d")
+        );
+
+        let verifier = MerkleTreeVerifier::<Blake3Merkle>::new(3, &three.commit());
+        assert!(verifier.verify(three.open(&[2]), &[2], &[leaves[2].clone()]));
+        assert!(!verifier.verify(three.open(&[2]), &[2], &[leaves[1].clone()]));
+    }
+
+    #[test]
+    fn update_leaf() {
+        let leaf = |x: u64| Serialize::serialize_fields(&[Fr::from(x)]);
+        for n in [1, 7, 8] {
+            let mut leaf_values = (0..n).map(leaf).collect::<Vec<_>>();
+            let mut prover = MerkleTreeProver::<Blake32>::new(&leaf_values);
+            let reference = rs_merkle::MerkleTree::<Blake32>::from_leaves(
+                &leaf_values
+                    .iter()
+                    .map(|x| Blake32::hash(x))
+                    .collect::<Vec<_>>(),
+            );
+            assert_eq!(prover.commit(), reference.root().unwrap());
+            for indices in [vec![0], vec![n as usize - 1], vec![0, 2, 3]] {
+                let indices = indices
+                    .into_iter()
+                    .filter(|x| *x < n as usize)
+                    .collect::<Vec<_>>();
+                assert_eq!(prover.open(&indices), reference.proof(&indices).to_bytes());
+            }
+
+            let index = n as usize / 2;
+            leaf_values[index] = leaf(100);
+            let root = prover.update_leaf(index, &leaf_values[index]);
+            assert_eq!(
+                root,
+                MerkleTreeProver::<Blake32>::new(&leaf_values).commit()
+            );
+            let verifier = MerkleTreeVerifier::<Blake32>::new(n as usize, &root);
+            assert!(verifier.verify(
+                prover.open(&[index]),
+                &[index],
+                &[leaf_values[index].clone()]
+            ));
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let leaf_values = (0..11)
+            .map(|x| Serialize::serialize_fields(&[Fr::from(x)]))
+            .collect::<Vec<_>>();
+        let prover = MerkleTreeProver::<Blake32>::new(&leaf_values);
+        let path = std::env::temp_dir().join(format!("merkle_{}.bin", std::process::id()));
+        prover.save(&path).unwrap();
+        assert_eq!(
+            fs::metadata(&path).unwrap().len(),
+            16 + 32 * (11 + 6 + 3 + 2 + 1)
+        );
+        let mut loaded = MerkleTreeProver::<Blake32>::load(&path).unwrap();
+        assert_eq!(loaded.commit(), prover.commit());
+        assert_eq!(loaded.leave_num(), 11);
+        assert_eq!(loaded.open(&[3, 10]), prover.open(&[3, 10]));
+        let mut updated = prover.clone();
+        assert_eq!(
+            loaded.update_leaf(4, b"new"),
+            updated.update_leaf(4, b"new")
+        );
+
+        assert!(MerkleTreeProver::<Blake16>::load(&path).is_err());
+        assert!(MerkleTreeProver::<Blake32>::load_leaf(&path, 0).is_err());
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(MerkleTreeProver::<Blake32>::load(&path).is_err());
+        let mut huge = bytes.clone();
+        huge[8..16].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+        fs::write(&path, &huge).unwrap();
+        assert_eq!(
+            MerkleTreeProver::<Blake32>::load(&path)
+                .err()
+                .map(|x| x.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+
+        prover.save_with_leaves(&path, &leaf_values).unwrap();
+        let loaded = MerkleTreeProver::<Blake32>::load(&path).unwrap();
+        assert_eq!(loaded.open(&[3, 10]), prover.open(&[3, 10]));
+        for (i, leaf) in leaf_values.iter().enumerate() {
+            assert_eq!(
+                &MerkleTreeProver::<Blake32>::load_leaf(&path, i).unwrap(),
+                leaf
+            );
+        }
+        assert!(MerkleTreeProver::<Blake32>::load_leaf(&path, 11).is_err());
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(MerkleTreeProver::<Blake32>::load(&path).is_err());
+        assert!(MerkleTreeProver::<Blake32>::load_leaf(&path, 10).is_err());
+        fs::remove_file(&path).unwrap();
     }
 
     #[test]
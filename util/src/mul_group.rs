@@ -1,59 +1,139 @@
-use std::rc::Rc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
 
 use ark_bn254::Fr;
 use ark_ff::{FftField, Field, One, Zero};
 
+use crate::{extension::FftElement, placement::Placement};
+
+// The multiplicative subgroup <omega>, or its coset shift * <omega>.
 #[derive(Debug, Clone)]
 pub struct Radix2Group {
     log_order: usize,
-    omega: Fr,
-    elements: Rc<Vec<Fr>>,
+    shift: Fr,
+    // omega^i, shared with `elements` when the shift is one.
+    twiddles: Arc<Vec<Fr>>,
+    elements: Arc<Vec<Fr>>,
+    barycentric: Arc<OnceLock<Vec<Fr>>>,
+}
+
+// Process-wide domains keyed by (field, log order, coset shift), so twiddle
+// tables are built once however many blobs are committed. Entries live until
+// `Radix2Group::clear_registry`.
+type DomainKey = (&'static str, usize, Fr);
+
+const FIELD_ID: &str = "bn254-fr";
+
+fn registry() -> &'static Mutex<HashMap<DomainKey, Arc<Radix2Group>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<DomainKey, Arc<Radix2Group>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
 }
 
 impl Radix2Group {
-    pub fn new(log_order: usize) -> Self {
+    fn build(log_order: usize, shift: Fr) -> Self {
         let omega = <Fr as FftField>::get_root_of_unity(1 << log_order).unwrap();
-        let elements = std::iter::successors(Some(<Fr as One>::one()), |&last| Some(last * omega))
-            .take(1 << log_order)
-            .collect();
+        let twiddles = Arc::new(
+            std::iter::successors(Some(<Fr as One>::one()), |&last| Some(last * omega))
+                .take(1 << log_order)
+                .collect::<Vec<_>>(),
+        );
+        let elements = if shift.is_one() {
+            twiddles.clone()
+        } else {
+            Arc::new(twiddles.iter().map(|x| shift * x).collect())
+        };
         Radix2Group {
             log_order,
-            omega,
-            elements: Rc::new(elements),
+            shift,
+            twiddles,
+            elements,
+            barycentric: Arc::new(OnceLock::new()),
+        }
+    }
+
+    // Cheap: clones the registry's copy, whose tables are reference counted.
+    pub fn new(log_order: usize) -> Self {
+        (*Self::shared(log_order)).clone()
+    }
+
+    pub fn coset(log_order: usize, shift: Fr) -> Self {
+        (*Self::shared_coset(log_order, shift)).clone()
+    }
+
+    pub fn shared(log_order: usize) -> Arc<Radix2Group> {
+        Self::shared_coset(log_order, <Fr as One>::one())
+    }
+
+    pub fn shared_coset(log_order: usize, shift: Fr) -> Arc<Radix2Group> {
+        assert!(!shift.is_zero());
+        let key = (FIELD_ID, log_order, shift);
+        if let Some(group) = registry().lock().unwrap().get(&key) {
+            return group.clone();
         }
+        // Built outside the lock; a racing thread may build the same domain,
+        // and whichever lands first is kept.
+        let group = Arc::new(Self::build(log_order, shift));
+        registry()
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert(group)
+            .clone()
+    }
+
+    pub fn clear_registry() {
+        registry().lock().unwrap().clear();
     }
+
     pub fn size(&self) -> usize {
         1 << self.log_order
     }
 
+    pub fn shift(&self) -> Fr {
+        self.shift
+    }
+
     pub fn element_at(&self, index: usize) -> Fr {
         self.elements[index]
     }
 
     pub fn element_inv_at(&self, index: usize) -> Fr {
-        if index == 0 {
+        let inv = if index == 0 {
             Fr::one()
         } else {
-            self.elements[self.size() - index]
+            self.twiddles[self.size() - index]
+        };
+        if self.shift.is_one() {
+            inv
+        } else {
+            inv * self.shift.inverse().unwrap()
         }
     }
 
     pub fn exp(&self, index: usize) -> Radix2Group {
         assert_eq!(index & (index - 1), 0);
-        Radix2Group::new(self.log_order - index.ilog2() as usize)
+        Radix2Group::coset(
+            self.log_order - index.ilog2() as usize,
+            self.shift.pow([index as u64]),
+        )
     }
 
     fn batch_bit_reverse(log_n: usize) -> Vec<usize> {
         let n = 1 << log_n;
-        let mut res = (0..n).into_iter().map(|_| 0).collect::<Vec<usize>>();
+        let mut res = vec![0usize; n];
         for i in 0..n {
             res[i] = (res[i >> 1] >> 1) | ((i & 1) << (log_n - 1));
         }
         res
     }
 
-    fn _fft(coeff: &mut Vec<Fr>, omega: Fr) {
+    // In-place radix-2 transform over the whole group, with twiddles read
+    // from the cached table; `inverse` walks it backwards for omega^-1.
+    fn _fft<E: FftElement>(&self, coeff: &mut [E], inverse: bool) {
         let n = coeff.len();
+        assert_eq!(n, self.size());
         let log_n = n.ilog2() as usize;
         let rank = Self::batch_bit_reverse(log_n);
         for i in 0..n {
@@ -61,40 +141,216 @@ impl Radix2Group {
                 (coeff[i], coeff[rank[i]]) = (coeff[rank[i]], coeff[i]);
             }
         }
-        let mut ws = vec![<Fr as One>::one()];
-        for i in 1..n {
-            ws.push(ws[i - 1] * omega);
-        }
-        let mut log_m = 0usize;
-        for _ in 0..log_n {
+        let ws = |k: usize| {
+            if inverse && k != 0 {
+                self.twiddles[n - k]
+            } else {
+                self.twiddles[k]
+            }
+        };
+        for log_m in 0..log_n {
             let m = 1 << log_m;
             let ws_i = log_n - log_m - 1;
             for j in (0..n).step_by(m * 2) {
                 for k in 0..m {
-                    let t = ws[k << ws_i] * coeff[j + k + m];
+                    let t = coeff[j + k + m] * ws(k << ws_i);
                     coeff[j + k + m] = coeff[j + k] - t;
                     coeff[j + k] += t;
                 }
             }
-            log_m += 1;
+        }
+    }
+
+    // c_i -> c_i * s^i, moving evaluation from <omega> to s * <omega>.
+    fn scale<E: FftElement>(coeff: &mut [E], s: Fr) {
+        let mut power = <Fr as One>::one();
+        for c in coeff.iter_mut() {
+            *c *= power;
+            power *= s;
         }
     }
 
     pub fn fft(&self, coeff: Vec<Fr>) -> Vec<Fr> {
-        let mut coeff = coeff.into_iter().map(|x| Fr::from(x)).collect::<Vec<_>>();
-        let padding_zero = self.size() - coeff.len();
-        for _ in 0..padding_zero {
-            coeff.push(<Fr as Zero>::zero());
+        self.fft_placed(coeff, &Placement::default())
+    }
+
+    // `fft`, growing a short `coeff` into a buffer placed as `placement`
+    // says rather than reallocating it. Growing in place would also free the
+    // old buffer with the coefficients still in it.
+    pub fn fft_placed(&self, coeff: Vec<Fr>, placement: &Placement) -> Vec<Fr> {
+        if coeff.capacity() >= self.size() {
+            return self.fft_ext(coeff);
+        }
+        let mut grown = placement.local(self.size());
+        self.fft_into(&coeff, &mut grown);
+        #[cfg(feature = "zeroize")]
+        {
+            let mut coeff = coeff;
+            zeroize::Zeroize::zeroize(&mut coeff);
+        }
+        grown
+    }
+
+    // Writes the evaluations of `coeff` straight into `out`, which must span
+    // the whole group, so a caller can transform into a slice of a larger
+    // buffer without an intermediate vector.
+    pub fn fft_into(&self, coeff: &[Fr], out: &mut [Fr]) {
+        assert_eq!(out.len(), self.size());
+        out[..coeff.len()].copy_from_slice(coeff);
+        out[coeff.len()..].fill(<Fr as Zero>::zero());
+        if !self.shift.is_one() {
+            Self::scale(out, self.shift);
+        }
+        self._fft(out, false);
+    }
+
+    // Evaluations over shift times this domain.
+    pub fn coset_fft(&self, coeff: Vec<Fr>, shift: Fr) -> Vec<Fr> {
+        let mut coeff = coeff;
+        Self::scale(&mut coeff, shift);
+        self.fft(coeff)
+    }
+
+    // w_i = x_i / (n * shift^n), so that L_i(z) = Z(z) * w_i / (z - x_i) with
+    // Z(X) = X^n - shift^n. Computed on first use and shared through the
+    // registry.
+    fn barycentric_weights(&self) -> &[Fr] {
+        self.barycentric.get_or_init(|| {
+            let scale = (Fr::from(self.size() as u64) * self.shift.pow([self.size() as u64]))
+                .inverse()
+                .unwrap();
+            self.elements.iter().map(|x| *x * scale).collect()
+        })
+    }
+
+    // The polynomial of degree < size taking `evals` on this domain, at `z`;
+    // linear in the domain size with one field inversion.
+    pub fn evaluate(&self, evals: &[Fr], z: Fr) -> Fr {
+        assert_eq!(evals.len(), self.size());
+        let mut denominators = self.elements.iter().map(|x| z - x).collect::<Vec<_>>();
+        if let Some(i) = denominators.iter().position(|d| d.is_zero()) {
+            return evals[i];
+        }
+        // Batch inversion: prefix products, one inverse, then unwind.
+        let mut prefix = Vec::with_capacity(denominators.len());
+        let mut acc = <Fr as One>::one();
+        for d in denominators.iter() {
+            prefix.push(acc);
+            acc *= d;
+        }
+        let mut inv = acc.inverse().unwrap();
+        for (d, p) in denominators.iter_mut().zip(prefix).rev() {
+            let next = inv * *d;
+            *d = inv * p;
+            inv = next;
+        }
+        let sum = self
+            .barycentric_weights()
+            .iter()
+            .zip(evals)
+            .zip(denominators)
+            .map(|((w, f), d)| *w * f * d)
+            .sum::<Fr>();
+        (z.pow([self.size() as u64]) - self.shift.pow([self.size() as u64])) * sum
+    }
+
+    // Coefficients of the polynomial of degree < `len` through the first
+    // `len` of `evals`, given as (element index, value) at distinct indices.
+    // Lagrange form, quadratic in `len`, for recovering from arbitrary subsets.
+    pub fn interpolate(&self, evals: &[(usize, Fr)], len: usize) -> Option<Vec<Fr>> {
+        if evals.len() < len {
+            return None;
+        }
+        let points = evals[..len]
+            .iter()
+            .map(|&(i, y)| (self.element_at(i), y))
+            .collect::<Vec<_>>();
+        // prod (X - x_i), lowest coefficient first.
+        let mut master = vec![<Fr as One>::one()];
+        for (x, _) in points.iter() {
+            master.insert(0, <Fr as Zero>::zero());
+            for k in 0..master.len() - 1 {
+                let next = master[k + 1];
+                master[k] -= next * x;
+            }
+        }
+        let mut coeff = vec![<Fr as Zero>::zero(); len];
+        for (x, y) in points.iter() {
+            // master / (X - x) by synthetic division.
+            let mut quotient = vec![<Fr as Zero>::zero(); len];
+            let mut carry = <Fr as Zero>::zero();
+            for k in (0..len).rev() {
+                carry = master[k + 1] + carry * x;
+                quotient[k] = carry;
+            }
+            let denominator = quotient
+                .iter()
+                .rev()
+                .fold(<Fr as Zero>::zero(), |acc, c| acc * x + c);
+            let weight = *y * denominator.inverse()?;
+            for (c, q) in coeff.iter_mut().zip(quotient) {
+                *c += weight * q;
+            }
+        }
+        #[cfg(feature = "zeroize")]
+        {
+            let mut points = points;
+            points
+                .iter_mut()
+                .for_each(|(_, y)| zeroize::Zeroize::zeroize(y));
+        }
+        Some(coeff)
+    }
+
+    // `interpolate`, but in n log n when `evals` hold a whole coset of the
+    // order `len` subgroup, as when every leaf of one residue class survived.
+    // Falls back to Lagrange otherwise.
+    pub fn recover(&self, evals: &[(usize, Fr)], len: usize) -> Option<Vec<Fr>> {
+        if len.is_power_of_two() && len <= self.size() {
+            let stride = self.size() / len;
+            let mut slots = vec![None; self.size()];
+            evals.iter().for_each(|&(i, y)| slots[i] = Some(y));
+            let coset = (0..stride).find_map(|r| {
+                let values = (0..len)
+                    .map(|k| slots[r + k * stride])
+                    .collect::<Option<Vec<_>>>()?;
+                Some((r, values))
+            });
+            #[cfg(feature = "zeroize")]
+            slots
+                .iter_mut()
+                .flatten()
+                .for_each(zeroize::Zeroize::zeroize);
+            if let Some((r, values)) = coset {
+                let group = Radix2Group::coset(len.ilog2() as usize, self.element_at(r));
+                return Some(group.ifft(values));
+            }
+        }
+        self.interpolate(evals, len)
+    }
+
+    pub fn ifft(&self, evals: Vec<Fr>) -> Vec<Fr> {
+        self.ifft_ext(evals)
+    }
+
+    // `fft` of coefficients in an extension of Fr, twiddles staying in Fr.
+    pub fn fft_ext<E: FftElement>(&self, mut coeff: Vec<E>) -> Vec<E> {
+        coeff.resize(self.size(), E::zero());
+        if !self.shift.is_one() {
+            Self::scale(&mut coeff, self.shift);
         }
-        Self::_fft(&mut coeff, self.omega);
+        self._fft(&mut coeff, false);
         coeff
     }
 
-    pub fn ifft(&self, mut evals: Vec<Fr>) -> Vec<Fr> {
+    pub fn ifft_ext<E: FftElement>(&self, mut evals: Vec<E>) -> Vec<E> {
         assert_eq!(self.size(), evals.len());
-        Self::_fft(&mut evals, self.omega.inverse().unwrap());
-        let t = Fr::from(self.size() as u32).inverse().unwrap();
+        self._fft(&mut evals, true);
+        let t = Fr::from(self.size() as u64).inverse().unwrap();
         evals.iter_mut().for_each(|x| *x *= t);
+        if !self.shift.is_one() {
+            Self::scale(&mut evals, self.shift.inverse().unwrap());
+        }
         evals
     }
 }
@@ -136,6 +392,14 @@ mod tests {
         }
         assert_eq!(fft_a_times_b, a_times_b);
         let b = mul_group.fft(a.clone());
+        let mut into = vec![<Fr as One>::one(); 32];
+        mul_group.fft_into(&a[..16], &mut into);
+        assert_eq!(into, b);
+        let placement = Placement {
+            hugepages: true,
+            numa: true,
+        };
+        assert_eq!(mul_group.fft_placed(a[..16].to_vec(), &placement), b);
         let c = mul_group.ifft(b);
         assert_eq!(a, c);
     }
@@ -145,7 +409,7 @@ mod tests {
         let coset = Radix2Group::new(5);
         assert_eq!(coset.element_at(0), Fr::one());
         assert_eq!(coset.element_inv_at(0), Fr::one());
-        let omega = coset.omega;
+        let omega = coset.element_at(1);
         for i in 0..30 {
             assert_eq!(coset.element_at(i) * omega, coset.element_at(i + 1));
             assert_eq!(
@@ -164,4 +428,139 @@ mod tests {
             assert_eq!(*i, coset.element_at(idx + coset_square.size()).pow([2]));
         }
     }
+
+    #[test]
+    fn coset_fft() {
+        let mut rng = rand::thread_rng();
+        let coeff = (0..8)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let shift = <Fr as UniformRand>::rand(&mut rng);
+        let group = Radix2Group::new(3);
+        let evals = group.coset_fft(coeff.clone(), shift);
+        for (i, e) in evals.iter().enumerate() {
+            let x = shift * group.element_at(i);
+            let direct = coeff
+                .iter()
+                .rev()
+                .fold(<Fr as Zero>::zero(), |acc, c| acc * x + c);
+            assert_eq!(*e, direct);
+        }
+    }
+
+    #[test]
+    fn interpolate() {
+        let mut rng = rand::thread_rng();
+        let coeff = (0..8)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let group = Radix2Group::new(5);
+        let evals = group.fft(coeff.clone());
+        let subset = [3, 30, 7, 12, 0, 21, 9, 17, 5]
+            .iter()
+            .map(|&i| (i, evals[i]))
+            .collect::<Vec<_>>();
+        assert_eq!(group.interpolate(&subset, 8), Some(coeff));
+        assert_eq!(group.interpolate(&subset[..7], 8), None);
+    }
+
+    #[test]
+    fn recover() {
+        let mut rng = rand::thread_rng();
+        let coeff = (0..8)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let group = Radix2Group::coset(5, Fr::from(3u64));
+        let evals = group.fft(coeff.clone());
+        // Indices 1 mod 4 are a coset of the order 8 subgroup.
+        let coset = (1..32)
+            .step_by(4)
+            .chain([0, 2])
+            .map(|i| (i, evals[i]))
+            .collect::<Vec<_>>();
+        assert_eq!(group.recover(&coset, 8), Some(coeff.clone()));
+        let scattered = [3, 30, 7, 12, 0, 21, 9, 17]
+            .iter()
+            .map(|&i| (i, evals[i]))
+            .collect::<Vec<_>>();
+        assert_eq!(group.recover(&scattered, 8), Some(coeff));
+        assert_eq!(group.recover(&scattered[..7], 8), None);
+    }
+
+    #[test]
+    fn registry() {
+        let a = Radix2Group::shared(6);
+        let b = Radix2Group::shared(6);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(Arc::ptr_eq(&a.twiddles, &Radix2Group::new(6).twiddles));
+
+        let mut rng = rand::thread_rng();
+        let shift = <Fr as UniformRand>::rand(&mut rng);
+        let coset = Radix2Group::shared_coset(3, shift);
+        assert!(!Arc::ptr_eq(&coset, &Radix2Group::shared(3)));
+        let coeff = (0..8)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let evals = coset.fft(coeff.clone());
+        assert_eq!(evals, Radix2Group::new(3).coset_fft(coeff.clone(), shift));
+        assert_eq!(coset.ifft(evals), coeff);
+        for i in 0..8 {
+            assert_eq!(coset.element_at(i) * coset.element_inv_at(i), Fr::one());
+        }
+    }
+
+    #[test]
+    fn barycentric() {
+        let mut rng = rand::thread_rng();
+        let coeff = (0..4)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let horner = |x: Fr| {
+            coeff
+                .iter()
+                .rev()
+                .fold(<Fr as Zero>::zero(), |acc, c| acc * x + c)
+        };
+        for group in [
+            Radix2Group::new(3),
+            Radix2Group::coset(3, <Fr as UniformRand>::rand(&mut rng)),
+        ] {
+            let evals = group.fft(coeff.clone());
+            let z = <Fr as UniformRand>::rand(&mut rng);
+            assert_eq!(group.evaluate(&evals, z), horner(z));
+            assert_eq!(group.evaluate(&evals, group.element_at(5)), evals[5]);
+        }
+    }
+
+    #[test]
+    fn extension_fft() {
+        use crate::extension::{CubicExt, ExtField, QuadExt};
+
+        let mut rng = rand::thread_rng();
+        let mut rand_ext = || ExtField([0; 3].map(|_| <Fr as UniformRand>::rand(&mut rng)));
+        let coeff: Vec<CubicExt> = (0..8).map(|_| rand_ext()).collect();
+        let z = rand_ext();
+        let group = Radix2Group::coset(4, Fr::from(3u64));
+        let evals = group.fft_ext(coeff.clone());
+        // Componentwise the transform over Fr, and Horner at each point.
+        for k in 0..3 {
+            let component = coeff.iter().map(|x| x.0[k]).collect::<Vec<_>>();
+            let base = group.fft(component);
+            assert!(evals.iter().zip(base).all(|(e, b)| e.0[k] == b));
+        }
+        for (i, e) in evals.iter().enumerate() {
+            let x = group.element_at(i);
+            let direct = coeff
+                .iter()
+                .rev()
+                .fold(CubicExt::zero(), |acc, c| acc * x + *c);
+            assert_eq!(*e, direct);
+        }
+        assert_eq!(group.ifft_ext(evals)[..8], coeff[..]);
+
+        assert_eq!(z * z.inverse().unwrap(), CubicExt::one());
+        let q: QuadExt = ExtField([Fr::from(2u64), Fr::from(5u64)]);
+        assert_eq!(q.inverse().unwrap() * q, QuadExt::one());
+        assert!(QuadExt::zero().inverse().is_none());
+    }
 }
@@ -0,0 +1,379 @@
+// A namespaced merkle tree: every leaf carries a namespace, leaves are sorted
+// by it, and every node records the least and greatest namespace below it, so
+// several applications can share one blob and each prove exactly which rows
+// are theirs. Hashing is blake3, domain-separated as in `Blake3Merkle`:
+// a leaf is blake3(0x00 || ns || data) and an inner node
+// blake3(0x01 || left || right) over both children's (min, max, hash). An
+// unpaired node is carried up unchanged, as in `MerkleTreeProver`.
+pub const NAMESPACE_LEN: usize = 8;
+
+pub type Namespace = [u8; NAMESPACE_LEN];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NmtNode {
+    pub min: Namespace,
+    pub max: Namespace,
+    pub hash: [u8; 32],
+}
+
+impl NmtNode {
+    pub fn leaf(namespace: &Namespace, data: &[u8]) -> Self {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&[0]);
+        hasher.update(namespace);
+        hasher.update(data);
+        NmtNode {
+            min: *namespace,
+            max: *namespace,
+            hash: hasher.finalize().into(),
+        }
+    }
+
+    // None if the children are out of namespace order, which no honest tree
+    // contains.
+    pub fn parent(left: &NmtNode, right: &NmtNode) -> Option<Self> {
+        if left.max > right.min {
+            return None;
+        }
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&[1]);
+        for node in [left, right] {
+            hasher.update(&node.min);
+            hasher.update(&node.max);
+            hasher.update(&node.hash);
+        }
+        Some(NmtNode {
+            min: left.min,
+            max: right.max,
+            hash: hasher.finalize().into(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&self.min[..], &self.max, &self.hash].concat()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NamespacedMerkleTree {
+    leaves: Vec<(Namespace, Vec<u8>)>,
+    layers: Vec<Vec<NmtNode>>,
+}
+
+// The nodes not derivable from leaves `start..end`, level by level in index
+// order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NmtProof {
+    pub start: usize,
+    pub end: usize,
+    pub nodes: Vec<NmtNode>,
+}
+
+// Proof of every leaf a namespace has. Completeness rests on the proof nodes:
+// those left of the opened range must end below the namespace and those right
+// of it start above, so no leaf of it can hide under either.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamespaceProof {
+    // The namespace is leaves `start..end` of the range proof.
+    Present(NmtProof),
+    // The namespace lies outside the root's `min..=max`.
+    OutOfRange,
+    // `leaf` is the first leaf of a greater namespace and everything left of
+    // it is smaller; the leaf's data is not needed, only its node.
+    Absent { leaf: NmtNode, proof: NmtProof },
+}
+
+impl NamespacedMerkleTree {
+    // None for no leaves or leaves not sorted by namespace.
+    pub fn new(leaves: &[(Namespace, Vec<u8>)]) -> Option<Self> {
+        if leaves.is_empty() || leaves.windows(2).any(|x| x[0].0 > x[1].0) {
+            return None;
+        }
+        let mut layers = vec![leaves
+            .iter()
+            .map(|(ns, data)| NmtNode::leaf(ns, data))
+            .collect::<Vec<_>>()];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|x| match x {
+                    [left, right] => NmtNode::parent(left, right).unwrap(),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Some(NamespacedMerkleTree {
+            leaves: leaves.to_vec(),
+            layers,
+        })
+    }
+
+    pub fn root(&self) -> NmtNode {
+        self.layers.last().unwrap()[0]
+    }
+
+    pub fn leave_num(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    pub fn namespace(&self, index: usize) -> Namespace {
+        self.layers[0][index].min
+    }
+
+    pub fn open_range(&self, start: usize, end: usize) -> NmtProof {
+        assert!(start < end && end <= self.leave_num());
+        let mut current = (start..end).collect::<Vec<_>>();
+        let mut nodes = vec![];
+        for layer in self.layers[..self.layers.len() - 1].iter() {
+            nodes.extend(
+                [current[0] ^ 1, current[current.len() - 1] ^ 1]
+                    .into_iter()
+                    .filter(|x| *x < layer.len() && current.binary_search(x).is_err())
+                    .collect::<std::collections::BTreeSet<_>>()
+                    .into_iter()
+                    .map(|x| layer[x]),
+            );
+            current = current.iter().map(|x| x / 2).collect();
+            current.dedup();
+        }
+        NmtProof { start, end, nodes }
+    }
+
+    // The leaves of `namespace`, empty if it has none, and the proof that
+    // they are all of them.
+    pub fn open_namespace(
+        &self,
+        namespace: &Namespace,
+    ) -> (Vec<(Namespace, Vec<u8>)>, NamespaceProof) {
+        let root = self.root();
+        if *namespace < root.min || *namespace > root.max {
+            return (vec![], NamespaceProof::OutOfRange);
+        }
+        let start = self.leaves.partition_point(|x| x.0 < *namespace);
+        let end = self.leaves.partition_point(|x| x.0 <= *namespace);
+        if start == end {
+            // Inside the range, so some leaf has a greater namespace.
+            let proof = self.open_range(start, start + 1);
+            return (
+                vec![],
+                NamespaceProof::Absent {
+                    leaf: self.layers[0][start],
+                    proof,
+                },
+            );
+        }
+        (
+            self.leaves[start..end].to_vec(),
+            NamespaceProof::Present(self.open_range(start, end)),
+        )
+    }
+}
+
+impl NmtProof {
+    // The root over `leaves` at `start..end` and the proof's nodes, or None if
+    // they do not fit a tree of `leave_num` leaves in namespace order.
+    pub fn root(&self, leave_num: usize, leaves: &[(Namespace, Vec<u8>)]) -> Option<NmtNode> {
+        let leaves = leaves
+            .iter()
+            .map(|(ns, data)| NmtNode::leaf(ns, data))
+            .collect();
+        self.fold(leave_num, leaves, |_, _| true)
+    }
+
+    // Rebuilds the root from hashed leaves, also requiring `bound` of every
+    // proof node and whether it lies left of the range.
+    fn fold(
+        &self,
+        leave_num: usize,
+        leaves: Vec<NmtNode>,
+        bound: impl Fn(&NmtNode, bool) -> bool,
+    ) -> Option<NmtNode> {
+        if self.start >= self.end || self.end > leave_num || leaves.len() != self.end - self.start {
+            return None;
+        }
+        let mut current = (self.start..self.end).zip(leaves).collect::<Vec<_>>();
+        let mut nodes = self.nodes.iter();
+        let mut len = leave_num;
+        while len > 1 {
+            let mut next = vec![];
+            let mut i = 0;
+            while i < current.len() {
+                let (index, node) = current[i];
+                let sibling = index ^ 1;
+                let parent = if current.get(i + 1).map(|x| x.0) == Some(sibling) {
+                    i += 1;
+                    NmtNode::parent(&node, &current[i].1)?
+                } else if sibling < len {
+                    let other = nodes.next()?;
+                    let left = index % 2 == 1;
+                    if !bound(other, left) {
+                        return None;
+                    }
+                    if left {
+                        NmtNode::parent(other, &node)?
+                    } else {
+                        NmtNode::parent(&node, other)?
+                    }
+                } else {
+                    node
+                };
+                next.push((index / 2, parent));
+                i += 1;
+            }
+            current = next;
+            len = len.div_ceil(2);
+        }
+        if nodes.next().is_some() {
+            return None;
+        }
+        Some(current[0].1)
+    }
+
+    pub fn verify_range(
+        &self,
+        root: &NmtNode,
+        leave_num: usize,
+        leaves: &[(Namespace, Vec<u8>)],
+    ) -> bool {
+        self.root(leave_num, leaves).as_ref() == Some(root)
+    }
+}
+
+impl NamespaceProof {
+    // Whether `leaves` are exactly the leaves of `namespace` under `root`.
+    pub fn verify(
+        &self,
+        root: &NmtNode,
+        leave_num: usize,
+        namespace: &Namespace,
+        leaves: &[(Namespace, Vec<u8>)],
+    ) -> bool {
+        let outside = |node: &NmtNode, left: bool| {
+            if left {
+                node.max < *namespace
+            } else {
+                node.min > *namespace
+            }
+        };
+        match self {
+            NamespaceProof::Present(proof) => {
+                !leaves.is_empty()
+                    && leaves.iter().all(|x| x.0 == *namespace)
+                    && proof
+                        .fold(
+                            leave_num,
+                            leaves
+                                .iter()
+                                .map(|(ns, data)| NmtNode::leaf(ns, data))
+                                .collect(),
+                            outside,
+                        )
+                        .as_ref()
+                        == Some(root)
+            }
+            NamespaceProof::OutOfRange => {
+                leaves.is_empty() && (*namespace < root.min || *namespace > root.max)
+            }
+            NamespaceProof::Absent { leaf, proof } => {
+                leaves.is_empty()
+                    && proof.end == proof.start + 1
+                    && leaf.min == leaf.max
+                    && leaf.min > *namespace
+                    && proof
+                        .fold(leave_num, vec![*leaf], |node, left| {
+                            !left || outside(node, left)
+                        })
+                        .as_ref()
+                        == Some(root)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ns(x: u64) -> Namespace {
+        x.to_be_bytes()
+    }
+
+    #[test]
+    fn range_openings() {
+        for namespaces in [vec![1, 1, 2, 2, 2, 5, 7, 7], vec![1, 3, 3, 4, 6, 6, 9]] {
+            let leaves = namespaces
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (ns(*x), vec![i as u8; 5]))
+                .collect::<Vec<_>>();
+            let tree = NamespacedMerkleTree::new(&leaves).unwrap();
+            let root = tree.root();
+            assert_eq!(root.min, ns(namespaces[0]));
+            assert_eq!(root.max, ns(*namespaces.last().unwrap()));
+            let n = leaves.len();
+            for start in 0..n {
+                for end in start + 1..=n {
+                    let proof = tree.open_range(start, end);
+                    assert!(proof.verify_range(&root, n, &leaves[start..end]));
+                    let mut forged = leaves[start..end].to_vec();
+                    forged[0].1[0] ^= 1;
+                    assert!(!proof.verify_range(&root, n, &forged));
+                }
+            }
+            let proof = tree.open_range(1, 3);
+            assert!(!proof.verify_range(&root, n, &leaves[2..4]));
+            assert!(!proof.verify_range(&root, 2 * n, &leaves[1..3]));
+        }
+
+        let unsorted = vec![(ns(2), vec![]), (ns(1), vec![])];
+        assert!(NamespacedMerkleTree::new(&unsorted).is_none());
+        assert!(NamespacedMerkleTree::new(&[]).is_none());
+    }
+
+    #[test]
+    fn namespace_proofs() {
+        let namespaces = [1, 1, 2, 2, 2, 5, 7, 7, 9];
+        let leaves = namespaces
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (ns(*x), vec![i as u8; 3]))
+            .collect::<Vec<_>>();
+        let tree = NamespacedMerkleTree::new(&leaves).unwrap();
+        let (root, n) = (tree.root(), tree.leave_num());
+        for x in 0..11 {
+            let (found, proof) = tree.open_namespace(&ns(x));
+            let expected = leaves
+                .iter()
+                .filter(|y| y.0 == ns(x))
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected);
+            assert!(proof.verify(&root, n, &ns(x), &found));
+            match (&proof, found.len()) {
+                (NamespaceProof::Present(_), 2..) => {
+                    // Withholding a leaf at either end is caught.
+                    assert!(!proof.verify(&root, n, &ns(x), &found[1..]));
+                    assert!(!proof.verify(&root, n, &ns(x), &found[..found.len() - 1]));
+                }
+                (NamespaceProof::Present(_), _) => {
+                    assert!(!proof.verify(&root, n, &ns(x), &[]));
+                }
+                (NamespaceProof::OutOfRange, _) => assert!(x == 0 || x == 10),
+                (NamespaceProof::Absent { .. }, _) => assert!([3, 4, 6, 8].contains(&x)),
+            }
+        }
+
+        // A real range proof for part of namespace 2 is not a namespace proof.
+        let partial = NamespaceProof::Present(tree.open_range(2, 4));
+        assert!(!partial.verify(&root, n, &ns(2), &leaves[2..4]));
+        // Nor does an absence proof for 3 carry over to 2 or 5.
+        let (_, absent) = tree.open_namespace(&ns(3));
+        assert!(!absent.verify(&root, n, &ns(2), &[]));
+        assert!(!absent.verify(&root, n, &ns(5), &[]));
+        assert!(!NamespaceProof::OutOfRange.verify(&root, n, &ns(3), &[]));
+    }
+}
@@ -0,0 +1,183 @@
+// Where the prover's largest buffers live. Pages land on the NUMA node of the
+// thread that first writes them, so a buffer that `numa` spreads is zeroed
+// part by part, each part by a worker pinned to the node whose cores will
+// later process it; `pin` pins those workers the same way. `hugepages` asks
+// the kernel to back the buffer with transparent hugepages, cutting TLB
+// misses over multi-gigabyte codewords. Both are hints: off Linux, or when
+// the kernel refuses, buffers are ordinary heap memory and workers float.
+use std::{fs, mem::MaybeUninit};
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Placement {
+    pub hugepages: bool,
+    pub numa: bool,
+}
+
+// "0-3,8,10-11" as 0, 1, 2, 3, 8, 10, 11.
+fn parse_cpulist(list: &str) -> Vec<usize> {
+    list.trim()
+        .split(',')
+        .filter(|x| !x.is_empty())
+        .flat_map(|range| {
+            let (low, high) = range.split_once('-').unwrap_or((range, range));
+            match (low.parse::<usize>(), high.parse::<usize>()) {
+                (Ok(low), Ok(high)) => low..high + 1,
+                _ => 0..0,
+            }
+        })
+        .collect()
+}
+
+// The cores of each NUMA node, by node id; empty where the kernel does not
+// say.
+pub fn numa_nodes() -> Vec<Vec<usize>> {
+    let mut nodes = vec![];
+    while let Ok(list) = fs::read_to_string(format!(
+        "/sys/devices/system/node/node{}/cpulist",
+        nodes.len()
+    )) {
+        nodes.push(parse_cpulist(&list));
+    }
+    nodes
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    // cpu_set_t as glibc lays it out, for up to 1024 cores.
+    pub const CPU_SET_WORDS: usize = 16;
+    pub const MADV_HUGEPAGE: i32 = 14;
+    pub const PAGE: usize = 4096;
+
+    extern "C" {
+        pub fn sched_setaffinity(pid: i32, size: usize, mask: *const u64) -> i32;
+        pub fn madvise(addr: *mut u8, len: usize, advice: i32) -> i32;
+    }
+}
+
+// Pins the calling thread to `cores`; whether the kernel accepted.
+#[cfg(target_os = "linux")]
+pub fn pin_to_cores(cores: &[usize]) -> bool {
+    let mut mask = [0u64; sys::CPU_SET_WORDS];
+    for &core in cores.iter().filter(|&&x| x < 64 * sys::CPU_SET_WORDS) {
+        mask[core / 64] |= 1 << (core % 64);
+    }
+    if mask.iter().all(|x| *x == 0) {
+        return false;
+    }
+    unsafe { sys::sched_setaffinity(0, std::mem::size_of_val(&mask), mask.as_ptr()) == 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_to_cores(_cores: &[usize]) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn advise_hugepages(buffer: &mut [MaybeUninit<Fr>]) {
+    let start = buffer.as_mut_ptr() as usize;
+    let end = start + std::mem::size_of_val(buffer);
+    let aligned = start.next_multiple_of(sys::PAGE);
+    if end > aligned {
+        // Advisory: a kernel without transparent hugepages just refuses.
+        unsafe { sys::madvise(aligned as *mut u8, end - aligned, sys::MADV_HUGEPAGE) };
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn advise_hugepages(_buffer: &mut [MaybeUninit<Fr>]) {}
+
+impl Placement {
+    // The node worker `part` of `parts` runs on, spreading them evenly.
+    fn node(&self, nodes: &[Vec<usize>], part: usize, parts: usize) -> Option<usize> {
+        (self.numa && nodes.len() > 1).then(|| part * nodes.len() / parts.max(1))
+    }
+
+    // Pins the calling thread, worker `part` of `parts`, to its node.
+    pub fn pin(&self, part: usize, parts: usize) {
+        if !self.numa {
+            return;
+        }
+        let nodes = numa_nodes();
+        if let Some(node) = self.node(&nodes, part, parts) {
+            pin_to_cores(&nodes[node]);
+        }
+    }
+
+    // `len` zeros, in parts of `part_len` placed as `pin` places the worker
+    // that owns each part.
+    pub fn zeroed(&self, len: usize, part_len: usize) -> Vec<Fr> {
+        let mut buffer = Vec::<Fr>::with_capacity(len);
+        let spare = &mut buffer.spare_capacity_mut()[..len];
+        if self.hugepages {
+            advise_hugepages(spare);
+        }
+        let nodes = if self.numa { numa_nodes() } else { vec![] };
+        let parts = len.div_ceil(part_len.max(1));
+        match nodes.len() {
+            0 | 1 => spare.iter_mut().for_each(|x| {
+                x.write(Fr::zero());
+            }),
+            _ => std::thread::scope(|scope| {
+                for (part, chunk) in spare.chunks_mut(part_len.max(1)).enumerate() {
+                    let cores = self.node(&nodes, part, parts).map(|x| &nodes[x]);
+                    scope.spawn(move || {
+                        if let Some(cores) = cores {
+                            pin_to_cores(cores);
+                        }
+                        chunk.iter_mut().for_each(|x| {
+                            x.write(Fr::zero());
+                        });
+                    });
+                }
+            }),
+        }
+        // Every element was written above.
+        unsafe { buffer.set_len(len) };
+        buffer
+    }
+
+    // `len` zeros for the calling thread's own use, as FFT scratch. It is
+    // touched only by that thread, which `pin` has already placed, so only
+    // `hugepages` applies.
+    pub fn local(&self, len: usize) -> Vec<Fr> {
+        Placement {
+            numa: false,
+            ..*self
+        }
+        .zeroed(len, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placed_buffers() {
+        assert_eq!(parse_cpulist("0-3,8,10-11\n"), [0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpulist(""), Vec::<usize>::new());
+        let nodes = [vec![0, 1], vec![2, 3]];
+        let placement = Placement {
+            hugepages: false,
+            numa: true,
+        };
+        assert_eq!(placement.node(&nodes, 0, 4), Some(0));
+        assert_eq!(placement.node(&nodes, 3, 4), Some(1));
+        assert_eq!(Placement::default().node(&nodes, 3, 4), None);
+        for placement in [
+            Placement::default(),
+            Placement {
+                hugepages: true,
+                numa: true,
+            },
+        ] {
+            let buffer = placement.zeroed(5000, 1024);
+            assert_eq!(buffer.len(), 5000);
+            assert!(buffer.iter().all(|x| x.is_zero()));
+            assert_eq!(placement.local(5000), buffer);
+        }
+    }
+}
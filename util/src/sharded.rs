@@ -0,0 +1,244 @@
+// The four-step FFT, for domains too large for one device. A size n = n1 * n2
+// transform is n2 column transforms of size n1, a twiddle by omega^(j2 * k1),
+// a transpose and n1 row transforms of size n2. Each step's batch is split
+// across the topology's shards, which run concurrently and see only their own
+// slice, so a shard need not share the host's memory: only `HostShard`, a
+// thread, ships here, and another kind of shard copies its slice out and
+// back. The twiddles and the transposes between the steps run on the calling
+// host, in `fft_sharded` itself, into buffers placed as the topology's
+// `Placement` says, as is each host shard's scratch.
+use ark_bn254::Fr;
+use ark_ff::{Field, One};
+
+use crate::{mul_group::Radix2Group, placement::Placement};
+
+// One device's part of a step.
+pub trait FftShard: Send + Sync {
+    // Replaces each contiguous 2^log_len run of `batch`, coefficients lowest
+    // first, with its evaluations over the order 2^log_len subgroup in natural
+    // order, as `Radix2Group::fft` does. Scratch the shard allocates on this
+    // host goes where `placement` says.
+    fn fft_batch(&self, log_len: usize, batch: &mut [Fr], placement: &Placement);
+}
+
+// A shard running on the calling host.
+pub struct HostShard;
+
+impl FftShard for HostShard {
+    fn fft_batch(&self, log_len: usize, batch: &mut [Fr], placement: &Placement) {
+        let group = Radix2Group::new(log_len);
+        let mut scratch = placement.local(1 << log_len);
+        for run in batch.chunks_mut(1 << log_len) {
+            scratch.copy_from_slice(run);
+            group.fft_into(&scratch, run);
+        }
+    }
+}
+
+pub struct Topology {
+    shards: Vec<Box<dyn FftShard>>,
+    placement: Placement,
+}
+
+impl Topology {
+    pub fn new(shards: Vec<Box<dyn FftShard>>) -> Self {
+        assert!(!shards.is_empty());
+        Topology {
+            shards,
+            placement: Placement::default(),
+        }
+    }
+
+    // Shard i's thread is pinned as worker i of the shard count.
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    // `shard_num` host threads.
+    pub fn host(shard_num: usize) -> Self {
+        Self::new(
+            (0..shard_num)
+                .map(|_| Box::new(HostShard) as Box<dyn FftShard>)
+                .collect(),
+        )
+    }
+
+    pub fn shard_num(&self) -> usize {
+        self.shards.len()
+    }
+
+    // Transforms every 2^log_len run of `batch`, whole runs to each shard.
+    fn run(&self, log_len: usize, batch: &mut [Fr]) {
+        let runs = batch.len() >> log_len;
+        let per_shard = runs.div_ceil(self.shards.len()) << log_len;
+        let parts = self.shards.len();
+        std::thread::scope(|scope| {
+            for (i, (shard, part)) in self
+                .shards
+                .iter()
+                .zip(batch.chunks_mut(per_shard))
+                .enumerate()
+            {
+                scope.spawn(move || {
+                    self.placement.pin(i, parts);
+                    shard.fft_batch(log_len, part, &self.placement)
+                });
+            }
+        });
+    }
+
+    // The transpose of a rows x columns matrix stored row by row.
+    fn transpose(&self, value: &[Fr], rows: usize, columns: usize) -> Vec<Fr> {
+        let mut out = self
+            .placement
+            .zeroed(value.len(), value.len().div_ceil(self.shards.len()));
+        out.iter_mut()
+            .enumerate()
+            .for_each(|(i, x)| *x = value[(i % rows) * columns + i / rows]);
+        out
+    }
+}
+
+impl Radix2Group {
+    // `fft`, computed across `topology`.
+    pub fn fft_sharded(&self, mut coeff: Vec<Fr>, topology: &Topology) -> Vec<Fr> {
+        let n = self.size();
+        coeff.resize(n, Fr::from(0u64));
+        if !self.shift().is_one() {
+            let mut power = Fr::one();
+            for c in coeff.iter_mut() {
+                *c *= power;
+                power *= self.shift();
+            }
+        }
+        let log_n = n.ilog2() as usize;
+        let log_rows = log_n / 2;
+        let (rows, columns) = (1 << log_rows, 1 << (log_n - log_rows));
+        // x[columns * j1 + j2] as column j2, contiguous.
+        let mut value = topology.transpose(&coeff, rows, columns);
+        drop(coeff);
+        topology.run(log_rows, &mut value);
+        let unit = Radix2Group::new(log_n);
+        for (j2, column) in value.chunks_mut(rows).enumerate() {
+            for (k1, x) in column.iter_mut().enumerate() {
+                *x *= unit.element_at(j2 * k1 % n);
+            }
+        }
+        let mut value = topology.transpose(&value, columns, rows);
+        topology.run(log_n - log_rows, &mut value);
+        // Row k1 holds X[k1 + rows * k2] at k2.
+        topology.transpose(&value, rows, columns)
+    }
+
+    pub fn ifft_sharded(&self, evals: Vec<Fr>, topology: &Topology) -> Vec<Fr> {
+        let n = self.size();
+        assert_eq!(evals.len(), n);
+        // The inverse transform is the forward one read at -i, over n.
+        let forward = Radix2Group::new(n.ilog2() as usize).fft_sharded(evals, topology);
+        let t = Fr::from(n as u64).inverse().unwrap();
+        let s = self.shift().inverse().unwrap();
+        let mut power = t;
+        (0..n)
+            .map(|i| {
+                let c = forward[(n - i) % n] * power;
+                power *= s;
+                c
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    };
+
+    use ark_ff::UniformRand;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+    use super::*;
+
+    // Stands in for a shard in another process: the batch crosses to a worker
+    // only as bytes, and the worker transforms into its own memory.
+    type Request = (usize, Vec<u8>, mpsc::Sender<Vec<u8>>);
+
+    struct RemoteShard {
+        requests: Mutex<mpsc::Sender<Request>>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl RemoteShard {
+        fn spawn(calls: Arc<AtomicUsize>) -> Self {
+            let (requests, inbox) = mpsc::channel::<Request>();
+            std::thread::spawn(move || {
+                for (log_len, bytes, reply) in inbox {
+                    let mut batch = Vec::<Fr>::deserialize_compressed(&bytes[..]).unwrap();
+                    HostShard.fft_batch(log_len, &mut batch, &Placement::default());
+                    let mut out = vec![];
+                    batch.serialize_compressed(&mut out).unwrap();
+                    reply.send(out).unwrap();
+                }
+            });
+            RemoteShard {
+                requests: Mutex::new(requests),
+                calls,
+            }
+        }
+    }
+
+    impl FftShard for RemoteShard {
+        fn fft_batch(&self, log_len: usize, batch: &mut [Fr], _: &Placement) {
+            let mut bytes = vec![];
+            batch.to_vec().serialize_compressed(&mut bytes).unwrap();
+            let (reply, answer) = mpsc::channel();
+            self.requests
+                .lock()
+                .unwrap()
+                .send((log_len, bytes, reply))
+                .unwrap();
+            let out = Vec::<Fr>::deserialize_compressed(&answer.recv().unwrap()[..]).unwrap();
+            batch.copy_from_slice(&out);
+            self.calls.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn sharded_fft() {
+        let mut rng = rand::thread_rng();
+        let coeff = (0..100)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        for group in [Radix2Group::new(7), Radix2Group::coset(7, Fr::from(5u64))] {
+            for shards in [1, 3, 16] {
+                let topology = Topology::host(shards);
+                let evals = group.fft_sharded(coeff.clone(), &topology);
+                assert_eq!(evals, group.fft(coeff.clone()));
+                let mut padded = coeff.clone();
+                padded.resize(128, Fr::from(0u64));
+                assert_eq!(group.ifft_sharded(evals, &topology), padded);
+            }
+        }
+    }
+
+    #[test]
+    fn remote_shards() {
+        let mut rng = rand::thread_rng();
+        let coeff = (0..256)
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let topology = Topology::new(vec![
+            Box::new(HostShard),
+            Box::new(RemoteShard::spawn(calls.clone())),
+        ]);
+        let group = Radix2Group::coset(8, Fr::from(3u64));
+        let evals = group.fft_sharded(coeff.clone(), &topology);
+        assert_eq!(evals, group.fft(coeff.clone()));
+        assert_eq!(group.ifft_sharded(evals, &topology), coeff);
+        // Each of the two steps of both transforms sent the remote shard its half.
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+    }
+}
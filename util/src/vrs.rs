@@ -0,0 +1,48 @@
+use ark_bn254::Fr;
+
+// The interface every verifiable dispersal backend offers, so an application
+// can switch schemes by configuration and benchmark them through one path.
+pub trait VrsScheme {
+    type Commitment: Clone;
+    // What the disperser keeps between committing and serving shares.
+    type State;
+    // Broadcast next to the commitment; schemes whose shares carry their own
+    // evidence use `()`.
+    type Proof;
+    type Share;
+
+    fn commit(&self, data: &[Fr]) -> (Self::Commitment, Self::State);
+
+    fn disperse(&self, state: &Self::State) -> (Self::Proof, Vec<Self::Share>);
+
+    fn sample(&self, state: &Self::State, index: usize) -> Self::Share;
+
+    fn verify(
+        &self,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+        share: &Self::Share,
+    ) -> bool;
+
+    // The original data from enough distinct verified shares.
+    fn reconstruct(&self, shares: &[Self::Share]) -> Option<Vec<Fr>>;
+}
+
+// Commit, disperse, check every share and recover from the last `needed`
+// of them; the common path backends are tested and benchmarked through.
+pub fn roundtrip<S: VrsScheme>(scheme: &S, data: &[Fr], needed: usize) -> bool {
+    let (commitment, state) = scheme.commit(data);
+    let (proof, shares) = scheme.disperse(&state);
+    if !shares
+        .iter()
+        .all(|share| scheme.verify(&commitment, &proof, share))
+    {
+        return false;
+    }
+    let sampled = scheme.sample(&state, 0);
+    if !scheme.verify(&commitment, &proof, &sampled) {
+        return false;
+    }
+    let recovered = scheme.reconstruct(&shares[shares.len() - needed..]);
+    recovered.as_deref() == Some(data)
+}
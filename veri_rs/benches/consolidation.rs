@@ -6,10 +6,11 @@ use csv::Writer;
 use rand::thread_rng;
 use veri_rs::consolidation::{VeriRsProver, VeriRsVerifier};
 
+#[allow(dead_code)]
 fn vid() {
     let mut rng = thread_rng();
     let mut wtr = Writer::from_path("vid_consolidation.csv").unwrap();
-    wtr.write_record(&["log_blob_size", "encode_time", "prover_time", "proof_size"])
+    wtr.write_record(["log_blob_size", "encode_time", "prover_time", "proof_size"])
         .unwrap();
     for log_blob_size in 16..23 {
         let step = 3;
@@ -34,7 +35,7 @@ fn vid() {
         let symbol = proofs.n_th_replica(103);
         let proof_size = symbol.proof_size();
         wtr.write_record(
-            &[log_blob_size, encode_time, prover_time, proof_size].map(|x| x.to_string()),
+            [log_blob_size, encode_time, prover_time, proof_size].map(|x| x.to_string()),
         )
         .unwrap();
         let verifier = VeriRsVerifier::setup(
@@ -47,10 +48,11 @@ fn vid() {
     }
 }
 
+#[allow(dead_code)]
 fn das() {
     let mut rng = thread_rng();
     let mut wtr = Writer::from_path("das_consolidation.csv").unwrap();
-    wtr.write_record(&["log_blob_size", "encode_time", "prover_time", "proof_size"])
+    wtr.write_record(["log_blob_size", "encode_time", "prover_time", "proof_size"])
         .unwrap();
     for log_blob_size in 16..23 {
         let step = 3;
@@ -75,7 +77,7 @@ fn das() {
         let symbol = proofs.n_th_replica(103);
         let proof_size = symbol.proof_size();
         wtr.write_record(
-            &[log_blob_size, encode_time, prover_time, proof_size].map(|x| x.to_string()),
+            [log_blob_size, encode_time, prover_time, proof_size].map(|x| x.to_string()),
         )
         .unwrap();
         let verifier = VeriRsVerifier::setup(
@@ -91,7 +93,7 @@ fn das() {
 fn vss() {
     let mut rng = thread_rng();
     let mut wtr = Writer::from_path("vss_consolidation.csv").unwrap();
-    wtr.write_record(&["log_blob_size", "encode_time", "prover_time", "proof_size"])
+    wtr.write_record(["log_blob_size", "encode_time", "prover_time", "proof_size"])
         .unwrap();
     for log_blob_size in 16..21 {
         let step = 3;
@@ -116,7 +118,7 @@ fn vss() {
         let symbol = proofs.n_th_replica(103);
         let proof_size = symbol.proof_size();
         wtr.write_record(
-            &[log_blob_size, encode_time, prover_time, proof_size].map(|x| x.to_string()),
+            [log_blob_size, encode_time, prover_time, proof_size].map(|x| x.to_string()),
         )
         .unwrap();
         let verifier = VeriRsVerifier::setup(
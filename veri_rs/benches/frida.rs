@@ -0,0 +1,158 @@
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use ark_std::test_rng;
+use csv::Writer;
+use frida::{FridaConfig, ProveOptions, Prover, Verifier};
+use std::time::Instant;
+use util::{
+    memory::{peak_rss, reset_peak_rss, TrackingAllocator},
+    mul_group::Radix2Group,
+};
+
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
+
+fn main() {
+    let size = 23;
+    let mut wtr = Writer::from_path("frida.csv").unwrap();
+    wtr.write_record([
+        "nv",
+        "commit_time",
+        "proof_size",
+        "open_time",
+        "verify_time",
+        "sample_bandwidth",
+        "peak_heap",
+        "allocations",
+        "peak_rss",
+    ])
+    .unwrap();
+    for nv in 6..size {
+        let repetition = 10;
+        TrackingAllocator::reset();
+        reset_peak_rss();
+        let (commit_time, proof_size, open_time, verify_time, sample_bandwidth) =
+            frida(nv, repetition);
+        wtr.write_record(
+            [
+                nv,
+                commit_time,
+                proof_size,
+                open_time,
+                verify_time,
+                sample_bandwidth,
+                TrackingAllocator::peak(),
+                TrackingAllocator::allocations(),
+                peak_rss().unwrap_or(0),
+            ]
+            .map(|x| x.to_string()),
+        )
+        .unwrap();
+    }
+}
+
+fn frida(nv: usize, repetition: usize) -> (usize, usize, usize, usize, usize) {
+    let mut rng = test_rng();
+    let log_poly_num = 5;
+    let poly_num = 1 << log_poly_num;
+    let log_degree = nv - log_poly_num;
+    let coderate = 2;
+    let polies = (0..poly_num)
+        .map(|_| {
+            (0..(1 << log_degree))
+                .map(|_| Fr::rand(&mut rng))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let groups = (0..log_degree)
+        .rev()
+        .map(|x| Radix2Group::new(x + 1 + coderate))
+        .collect::<Vec<_>>();
+    let challenges = (
+        Fr::rand(&mut rng),
+        (0..log_degree)
+            .map(|_| Fr::rand(&mut rng))
+            .collect::<Vec<_>>(),
+    );
+    let query_num =
+        (100.0 / (2.0 / (1.0 + 0.5_f32.powi(coderate as i32))).log2()).ceil() as usize - 20;
+    let config = FridaConfig::new(b"bench", poly_num, log_degree, coderate, query_num);
+
+    // commit
+    let (commit_time, prover) = {
+        let start = Instant::now();
+        for _ in 0..repetition {
+            let prover = Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap();
+            let _commit = prover.commit();
+        }
+
+        (
+            start.elapsed().as_micros() as usize / repetition,
+            Prover::new(&polies, &groups[0], &ProveOptions::default()).unwrap(),
+        )
+    };
+
+    // open
+    let (open_time, prover_state, iopp_commits, query_results) = {
+        let start = Instant::now();
+        for _ in 0..repetition {
+            let (prover_state, _) = prover
+                .commit_phase(&groups, &challenges, &ProveOptions::default())
+                .unwrap();
+            let _sample = prover.sample(&prover_state, &config);
+        }
+        let open_time = start.elapsed().as_micros() as usize / repetition;
+
+        let (prover_state, iopp_commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        let query_results = prover.sample(&prover_state, &config);
+        (open_time, prover_state, iopp_commits, query_results)
+    };
+
+    let proof_size =
+        iopp_commits.proof_size() + query_results.iter().map(|x| x.proof_size()).sum::<usize>();
+
+    // A sampler asking for a single position receives the round roots plus
+    // one query path through every round.
+    let sample_bandwidth = iopp_commits.proof_size()
+        + prover
+            .sample(
+                &prover_state,
+                &FridaConfig {
+                    query_num: 1,
+                    ..config.clone()
+                },
+            )
+            .iter()
+            .map(|x| x.proof_size())
+            .sum::<usize>();
+
+    // verify
+    let verify_time = {
+        let commit = prover.commit();
+        let verifier = Verifier::new(commit, poly_num, 1 << (log_degree + coderate - 1));
+        let start = Instant::now();
+        for _ in 0..repetition {
+            verifier
+                .verify(
+                    &config,
+                    &groups,
+                    &challenges,
+                    iopp_commits.clone(),
+                    query_results.clone(),
+                )
+                .into_result()
+                .unwrap();
+        }
+        start.elapsed().as_micros() as usize / repetition
+    };
+
+    (
+        commit_time,
+        proof_size,
+        open_time,
+        verify_time,
+        sample_bandwidth,
+    )
+}
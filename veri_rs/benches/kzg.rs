@@ -12,19 +12,49 @@ use hp::{
     MultilinearUniversalParams,
 };
 use std::{mem::size_of, time::Instant};
+use util::memory::{peak_rss, reset_peak_rss, TrackingAllocator};
+
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
 
 fn main() {
     let size = 23;
     let mut rng = test_rng();
     let uni_params = MultilinearKzgPCS::<Bn254>::gen_srs_for_testing(&mut rng, size).unwrap();
     let mut wtr = Writer::from_path("mkzg.csv").unwrap();
-    wtr.write_record(["nv", "commit_time", "proof_size"])
-        .unwrap();
+    wtr.write_record([
+        "nv",
+        "commit_time",
+        "proof_size",
+        "open_time",
+        "verify_time",
+        "sample_bandwidth",
+        "peak_heap",
+        "allocations",
+        "peak_rss",
+    ])
+    .unwrap();
     for nv in 6..size {
         let repetition = 10;
-        let (commit_time, proof_size) = mkzg(nv, repetition, &uni_params);
-        wtr.write_record([nv, commit_time, proof_size].map(|x| x.to_string()))
-            .unwrap();
+        TrackingAllocator::reset();
+        reset_peak_rss();
+        let (commit_time, proof_size, open_time, verify_time, sample_bandwidth) =
+            mkzg(nv, repetition, &uni_params);
+        wtr.write_record(
+            [
+                nv,
+                commit_time,
+                proof_size,
+                open_time,
+                verify_time,
+                sample_bandwidth,
+                TrackingAllocator::peak(),
+                TrackingAllocator::allocations(),
+                peak_rss().unwrap_or(0),
+            ]
+            .map(|x| x.to_string()),
+        )
+        .unwrap();
     }
 }
 
@@ -32,7 +62,7 @@ fn mkzg(
     nv: usize,
     repetition: usize,
     uni_params: &MultilinearUniversalParams<Bn254>,
-) -> (usize, usize) {
+) -> (usize, usize, usize, usize, usize) {
     let mut rng = test_rng();
     let poly = Arc::new(DenseMultilinearExtension::rand(nv, &mut rng));
     let (ck, vk) = uni_params.trim(nv).unwrap();
@@ -53,17 +83,36 @@ fn mkzg(
     };
 
     // open
-    let (proof, value) = {
-        for _ in 0..repetition - 1 {
+    let (open_time, (proof, value)) = {
+        let start = Instant::now();
+        for _ in 0..repetition {
             let _open = MultilinearKzgPCS::open(&ck, &poly, &point).unwrap();
         }
 
-        let open = MultilinearKzgPCS::open(&ck, &poly, &point).unwrap();
+        (
+            start.elapsed().as_micros() as usize / repetition,
+            MultilinearKzgPCS::open(&ck, &poly, &point).unwrap(),
+        )
+    };
 
-        open
+    // verify
+    let verify_time = {
+        let start = Instant::now();
+        for _ in 0..repetition {
+            assert!(MultilinearKzgPCS::verify(&vk, &com, &point, &value, &proof).unwrap());
+        }
+        start.elapsed().as_micros() as usize / repetition
     };
 
     let proof_size = proof.proofs.len() * size_of::<<Bn254 as Pairing>::G1Affine>();
+    // A sampler receives the opening proof together with the claimed evaluation.
+    let sample_bandwidth = proof_size + size_of::<Fr>();
 
-    (commit_time, proof_size)
+    (
+        commit_time,
+        proof_size,
+        open_time,
+        verify_time,
+        sample_bandwidth,
+    )
 }
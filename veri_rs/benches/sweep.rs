@@ -0,0 +1,126 @@
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use ark_std::test_rng;
+use csv::Writer;
+use frida::{
+    FoldCheck, FridaConfig, Layout, ProveOptions, Prover, SecurityLevel, SoundnessRegime, Verifier,
+};
+use std::time::Instant;
+
+// Prover time, proof size and verifier time over code rates and folding
+// variants at a fixed polynomial count. Folding always halves the domain, so
+// the variants are how folded layers are stored and how their equations are
+// checked.
+fn main() {
+    let mut wtr = Writer::from_path("frida_sweep.csv").unwrap();
+    wtr.write_record([
+        "nv",
+        "code_rate",
+        "layout",
+        "fold_check",
+        "query_num",
+        "prove_time",
+        "proof_size",
+        "verify_time",
+    ])
+    .unwrap();
+    let repetition = 5;
+    for nv in [12, 16, 20] {
+        for code_rate in 1..=4 {
+            for layout in [Layout::Columnar, Layout::RowMajor] {
+                for fold_check in [FoldCheck::PerIndex, FoldCheck::Aggregated] {
+                    let (query_num, prove_time, proof_size, verify_time) =
+                        sweep(nv, code_rate, layout, fold_check, repetition);
+                    wtr.write_record([
+                        nv.to_string(),
+                        code_rate.to_string(),
+                        format!("{:?}", layout),
+                        format!("{:?}", fold_check),
+                        query_num.to_string(),
+                        prove_time.to_string(),
+                        proof_size.to_string(),
+                        verify_time.to_string(),
+                    ])
+                    .unwrap();
+                }
+            }
+        }
+        wtr.flush().unwrap();
+    }
+}
+
+fn sweep(
+    nv: usize,
+    code_rate: usize,
+    layout: Layout,
+    fold_check: FoldCheck,
+    repetition: usize,
+) -> (usize, usize, usize, usize) {
+    let mut rng = test_rng();
+    let log_poly_num = 5;
+    let poly_num = 1 << log_poly_num;
+    let log_degree = nv - log_poly_num;
+    let regime = SoundnessRegime::Proven;
+    let config = FridaConfig {
+        regime,
+        ..FridaConfig::new(
+            b"sweep",
+            poly_num,
+            log_degree,
+            code_rate,
+            SecurityLevel::Bits100.query_num(code_rate, regime),
+        )
+    };
+    let polies = (0..poly_num)
+        .map(|_| {
+            (0..(1 << log_degree))
+                .map(|_| Fr::rand(&mut rng))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let groups = config.groups();
+    let challenges = (
+        Fr::rand(&mut rng),
+        (0..log_degree)
+            .map(|_| Fr::rand(&mut rng))
+            .collect::<Vec<_>>(),
+    );
+
+    // commit, fold and sample
+    let prove = || {
+        let prover = Prover::with_layout(&polies, &groups[0], layout);
+        let (prover_state, iopp_commits) = prover
+            .commit_phase(&groups, &challenges, &ProveOptions::default())
+            .unwrap();
+        let query_results = prover.sample(&prover_state, &config);
+        (prover.commit(), iopp_commits, query_results)
+    };
+    let start = Instant::now();
+    for _ in 0..repetition {
+        prove();
+    }
+    let prove_time = start.elapsed().as_micros() as usize / repetition;
+    let (commit, iopp_commits, query_results) = prove();
+    let proof_size =
+        iopp_commits.proof_size() + query_results.iter().map(|x| x.proof_size()).sum::<usize>();
+
+    // verify
+    let verifier =
+        Verifier::new(commit, poly_num, config.leave_number()).with_fold_check(fold_check);
+    let start = Instant::now();
+    for _ in 0..repetition {
+        verifier
+            .verify(
+                &config,
+                &groups,
+                &challenges,
+                iopp_commits.clone(),
+                query_results.clone(),
+            )
+            .into_result()
+            .unwrap();
+    }
+    let verify_time = start.elapsed().as_micros() as usize / repetition;
+
+    (config.query_num, prove_time, proof_size, verify_time)
+}
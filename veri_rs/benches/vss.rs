@@ -0,0 +1,253 @@
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Projective};
+use ark_ec::{pairing::Pairing, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{Field, One, UniformRand, Zero};
+use ark_std::test_rng;
+use csv::Writer;
+use frida::{
+    dealer::Dealer,
+    reconstruct::{reconstruct, Faults},
+    session::Session,
+    share::verify_my_share,
+    SoundnessRegime,
+};
+use std::{mem::size_of, time::Instant};
+use util::memory::{reset_peak_rss, TrackingAllocator};
+
+#[global_allocator]
+static ALLOC: TrackingAllocator = TrackingAllocator;
+
+// (nodes, threshold, repetition) to deal, verify, transcript and package
+// sizes, and reconstruct.
+type Scheme = fn(usize, usize, usize) -> [usize; 5];
+
+// Fewer than `threshold` of `nodes` nodes learn nothing in every scheme.
+// Feldman and KZG hand each node one evaluation of a polynomial of degree
+// below threshold, so any threshold reconstruct; FRIDA two evaluations of a
+// polynomial sized to hide the proof's openings too, so its quorum of
+// reconstructing nodes may be larger.
+fn main() {
+    let mut wtr = Writer::from_path("vss.csv").unwrap();
+    wtr.write_record([
+        "scheme",
+        "nodes",
+        "threshold",
+        "deal_time",
+        "node_verify_time",
+        "transcript_size",
+        "package_size",
+        "reconstruct_time",
+        "peak_heap",
+    ])
+    .unwrap();
+    let repetition = 3;
+    for log_nodes in 6..=10 {
+        let nodes = 1 << log_nodes;
+        let threshold = nodes / 2;
+        let schemes: [(&str, Scheme); 3] =
+            [("frida", frida_vss), ("feldman", feldman), ("kzg", kzg)];
+        for (scheme, run) in schemes {
+            TrackingAllocator::reset();
+            reset_peak_rss();
+            let result = run(nodes, threshold, repetition);
+            let mut record = vec![scheme.to_string(), nodes.to_string(), threshold.to_string()];
+            record.extend(result.map(|x| x.to_string()));
+            record.push(TrackingAllocator::peak().to_string());
+            wtr.write_record(record).unwrap();
+        }
+    }
+}
+
+fn elapsed<T, F: FnMut() -> T>(repetition: usize, mut f: F) -> (usize, T) {
+    let start = Instant::now();
+    for _ in 1..repetition {
+        f();
+    }
+    let out = f();
+    (start.elapsed().as_micros() as usize / repetition, out)
+}
+
+fn evaluate(coeff: &[Fr], x: Fr) -> Fr {
+    coeff.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c)
+}
+
+// f(0) from `points` on a polynomial of degree below `points.len()`.
+fn lagrange_at_zero(points: &[(Fr, Fr)]) -> Fr {
+    points
+        .iter()
+        .map(|&(xi, yi)| {
+            let (num, den) = points
+                .iter()
+                .filter(|(xj, _)| *xj != xi)
+                .fold((Fr::one(), Fr::one()), |(num, den), &(xj, _)| {
+                    (num * xj, den * (xj - xi))
+                });
+            yi * num * den.inverse().unwrap()
+        })
+        .sum()
+}
+
+fn frida_vss(nodes: usize, threshold: usize, repetition: usize) -> [usize; 5] {
+    let mut rng = test_rng();
+    let dealer = |query_num| Dealer {
+        context: b"bench".to_vec(),
+        session: Session::Dkg(0),
+        query_num,
+        regime: SoundnessRegime::Proven,
+    };
+    // As many queries as the sharing can hide without a larger quorum.
+    let quorum = |q| dealer(q).sharing(1, nodes, threshold).map(|x| x.quorum());
+    let least = quorum(1).unwrap();
+    let query_num = (1..)
+        .take_while(|&q| quorum(q) == Ok(least))
+        .last()
+        .unwrap();
+    let dealer = dealer(query_num);
+    let keys = (0..nodes as u64).collect::<Vec<_>>();
+    let secret = [Fr::rand(&mut rng)];
+
+    let (deal_time, dealing) = elapsed(repetition, || {
+        dealer.deal(&secret, &keys, threshold, &mut rng).unwrap()
+    });
+    let transcript = &dealing.transcript;
+    let expected = dealer.sharing(1, nodes, threshold).unwrap();
+    let (verify_time, _) = elapsed(repetition, || {
+        verify_my_share(&dealing.packages[0], transcript, &expected, threshold, 0).unwrap()
+    });
+    let transcript_size = transcript.proof.to_bytes().len() + 2 * 32;
+    let package_size = dealing.packages[0].to_bytes().len();
+
+    let quorum = transcript.sharing.quorum();
+    let shares = dealing.packages[..quorum]
+        .iter()
+        .map(|x| verify_my_share(x, transcript, &expected, threshold, x.index).unwrap())
+        .collect::<Vec<_>>();
+    let (reconstruct_time, recovered) = elapsed(repetition, || {
+        reconstruct(&shares, quorum, 1, Faults::Reject).unwrap()
+    });
+    assert_eq!(recovered.secret, secret);
+
+    [
+        deal_time,
+        verify_time,
+        transcript_size,
+        package_size,
+        reconstruct_time,
+    ]
+}
+
+fn feldman(nodes: usize, threshold: usize, repetition: usize) -> [usize; 5] {
+    let mut rng = test_rng();
+    let coeff = (0..threshold)
+        .map(|_| Fr::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let g = G1Projective::generator();
+
+    let (deal_time, (commitments, shares)) = elapsed(repetition, || {
+        let commitments =
+            G1Projective::normalize_batch(&coeff.iter().map(|a| g * *a).collect::<Vec<_>>());
+        let shares = (1..=nodes as u64)
+            .map(|i| evaluate(&coeff, Fr::from(i)))
+            .collect::<Vec<_>>();
+        (commitments, shares)
+    });
+
+    // g^{f(i)} against the product of the commitments raised to i^j.
+    let (verify_time, _) = elapsed(repetition, || {
+        let x = Fr::from(1u64);
+        let powers = (0..threshold)
+            .scan(Fr::one(), |p, _| {
+                let current = *p;
+                *p *= x;
+                Some(current)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            G1Projective::msm(&commitments, &powers).unwrap(),
+            g * shares[0]
+        );
+    });
+    let transcript_size = threshold * size_of::<G1Affine>();
+    let package_size = size_of::<Fr>();
+
+    let points = (1..=threshold as u64)
+        .zip(&shares)
+        .map(|(i, y)| (Fr::from(i), *y))
+        .collect::<Vec<_>>();
+    let (reconstruct_time, secret) = elapsed(repetition, || lagrange_at_zero(&points));
+    assert_eq!(secret, coeff[0]);
+
+    [
+        deal_time,
+        verify_time,
+        transcript_size,
+        package_size,
+        reconstruct_time,
+    ]
+}
+
+// Univariate KZG with one witness per node, each computed separately, so the
+// dealer does `nodes` multi-scalar multiplications of `threshold` points.
+fn kzg(nodes: usize, threshold: usize, repetition: usize) -> [usize; 5] {
+    let mut rng = test_rng();
+    let tau = Fr::rand(&mut rng);
+    let g = G1Projective::generator();
+    let h = G2Projective::generator();
+    let powers = G1Projective::normalize_batch(
+        &(0..threshold)
+            .scan(Fr::one(), |p, _| {
+                let current = *p;
+                *p *= tau;
+                Some(g * current)
+            })
+            .collect::<Vec<_>>(),
+    );
+    let h_tau = h * tau;
+    let coeff = (0..threshold)
+        .map(|_| Fr::rand(&mut rng))
+        .collect::<Vec<_>>();
+
+    let (deal_time, (commitment, packages)) = elapsed(repetition, || {
+        let commitment = G1Projective::msm(&powers, &coeff).unwrap();
+        let packages = (1..=nodes as u64)
+            .map(|i| {
+                let x = Fr::from(i);
+                // (f - f(x)) / (X - x) by synthetic division.
+                let mut quotient = vec![Fr::zero(); threshold - 1];
+                let mut carry = Fr::zero();
+                for k in (1..threshold).rev() {
+                    carry = coeff[k] + carry * x;
+                    quotient[k - 1] = carry;
+                }
+                let witness = G1Projective::msm(&powers[..threshold - 1], &quotient).unwrap();
+                (evaluate(&coeff, x), witness.into_affine())
+            })
+            .collect::<Vec<_>>();
+        (commitment, packages)
+    });
+
+    let (verify_time, _) = elapsed(repetition, || {
+        let (y, witness) = packages[0];
+        let x = Fr::from(1u64);
+        assert_eq!(
+            Bn254::pairing((commitment - g * y).into_affine(), h.into_affine()),
+            Bn254::pairing(witness, (h_tau - h * x).into_affine())
+        );
+    });
+    let transcript_size = size_of::<G1Affine>();
+    let package_size = size_of::<Fr>() + size_of::<G1Affine>();
+
+    let points = (1..=threshold as u64)
+        .zip(&packages)
+        .map(|(i, (y, _))| (Fr::from(i), *y))
+        .collect::<Vec<_>>();
+    let (reconstruct_time, secret) = elapsed(repetition, || lagrange_at_zero(&points));
+    assert_eq!(secret, coeff[0]);
+
+    [
+        deal_time,
+        verify_time,
+        transcript_size,
+        package_size,
+        reconstruct_time,
+    ]
+}
@@ -5,6 +5,7 @@ use ark_ff::Field;
 use util::{
     merkle_tree::{Blake16, MerkleRoot, MerkleTreeProver},
     mul_group::Radix2Group,
+    vrs::VrsScheme,
 };
 
 use crate::poly::{MultilinearPoly, UniPolyEvals, UniVarPoly};
@@ -58,6 +59,7 @@ pub struct VeriRsProver {
     step: usize,
 }
 
+#[derive(Clone)]
 pub struct Symbol {
     poly: MultilinearPoly,
     replica: UniVarPoly,
@@ -218,6 +220,15 @@ impl VeriRsVerifier {
     }
 
     pub fn verify(&self, symbol: Symbol) -> bool {
+        self.check(symbol, None)
+    }
+
+    // Also requires the symbol's path to lead to the committed `root`.
+    pub fn verify_against(&self, root: &[u8; 16], symbol: Symbol) -> bool {
+        self.check(symbol, Some(root))
+    }
+
+    fn check(&self, symbol: Symbol, expected_root: Option<&[u8; 16]>) -> bool {
         let Symbol {
             poly,
             replica,
@@ -232,19 +243,24 @@ impl VeriRsVerifier {
             replica.serialize(),
             self.symbol_number,
         );
+        if expected_root.is_some_and(|x| *x != root) {
+            return false;
+        }
         let first_challenge = <Fr as Field>::from_random_bytes(&root).unwrap();
         let mut x = replica.eval(&first_challenge);
         let mut eval_point = vec![];
 
         for ((poly, paths), ((&inner, &outer), &leave_number)) in
-            proofs.into_iter().zip(merkle_paths.into_iter()).zip(
+            proofs.into_iter().zip(merkle_paths).zip(
                 self.inner_location
                     .iter()
                     .zip(self.outer_location.iter())
                     .zip(self.leaves_number.iter()),
             )
         {
-            assert_eq!(x, poly.n_th_eval(inner));
+            if x != poly.n_th_eval(inner) {
+                return false;
+            }
             let root: [u8; 16] =
                 MerkleRoot::<Blake16>::get_root(paths, outer, poly.serialize(), leave_number);
             let challenge = <Fr as Field>::from_random_bytes(&root).unwrap();
@@ -256,7 +272,9 @@ impl VeriRsVerifier {
             );
         }
 
-        assert_eq!(x, final_poly.eval(&self.final_point));
+        if x != final_poly.eval(&self.final_point) {
+            return false;
+        }
         let x = final_poly.eval(&19260817.into());
         eval_point.append(
             &mut iter::successors(Some(Fr::from(19260817)), |&x| Some(x * x))
@@ -268,8 +286,67 @@ impl VeriRsVerifier {
                 .take(replica.len().ilog2() as usize)
                 .collect::<Vec<_>>(),
         );
-        assert_eq!(x, poly.eval(&eval_point));
-        true
+        x == poly.eval(&eval_point)
+    }
+}
+
+// Share i is replica i, a column across every encoded row, carrying its own
+// proof; any 2^log_row_length distinct replicas recover the rows.
+#[derive(Clone)]
+pub struct VeriRsShare {
+    pub index: usize,
+    pub symbol: Symbol,
+}
+
+impl VrsScheme for VeriRsProver {
+    type Commitment = [u8; 16];
+    type State = Proofs;
+    type Proof = ();
+    type Share = VeriRsShare;
+
+    fn commit(&self, data: &[Fr]) -> ([u8; 16], Proofs) {
+        let proofs = self.prove(data.to_vec(), self.encode(data.to_vec()));
+        (proofs.first_tree.commit(), proofs)
+    }
+
+    fn disperse(&self, proofs: &Proofs) -> ((), Vec<VeriRsShare>) {
+        let shares = (0..proofs.replicas.len())
+            .map(|i| self.sample(proofs, i))
+            .collect();
+        ((), shares)
+    }
+
+    fn sample(&self, proofs: &Proofs, index: usize) -> VeriRsShare {
+        VeriRsShare {
+            index,
+            symbol: proofs.n_th_replica(index),
+        }
+    }
+
+    fn verify(&self, commitment: &[u8; 16], _: &(), share: &VeriRsShare) -> bool {
+        share.index < 1 << self.log_symbol_number
+            && VeriRsVerifier::setup(
+                share.index,
+                self.step,
+                self.log_symbol_number,
+                self.code_rate,
+            )
+            .verify_against(commitment, share.symbol.clone())
+    }
+
+    fn reconstruct(&self, shares: &[VeriRsShare]) -> Option<Vec<Fr>> {
+        let mut data = vec![];
+        for row in 0..self.layer_number {
+            let evals = shares
+                .iter()
+                .map(|x| Some((x.index, *x.symbol.replica.coeff().get(row)?)))
+                .collect::<Option<Vec<_>>>()?;
+            data.extend(
+                self.fft_group
+                    .interpolate(&evals, 1 << self.log_row_length)?,
+            );
+        }
+        Some(data)
     }
 }
 
@@ -279,6 +356,8 @@ mod tests {
     use ark_ff::UniformRand;
     use rand::thread_rng;
 
+    use util::vrs::{roundtrip, VrsScheme};
+
     use super::{VeriRsProver, VeriRsVerifier};
 
     #[test]
@@ -303,4 +382,19 @@ mod tests {
         );
         assert!(verifier.verify(symbol));
     }
+
+    #[test]
+    fn vrs_roundtrip() {
+        let mut rng = thread_rng();
+        let prover = VeriRsProver::setup(8, 2, 1, 2);
+        let data = (0..(1 << 8))
+            .map(|_| <Fr as UniformRand>::rand(&mut rng))
+            .collect::<Vec<_>>();
+        assert!(roundtrip(&prover, &data, 1 << 6));
+
+        let (commitment, proofs) = prover.commit(&data);
+        let share = prover.sample(&proofs, 5);
+        assert!(!prover.verify(&[0u8; 16], &(), &share));
+        assert!(prover.verify(&commitment, &(), &share));
+    }
 }
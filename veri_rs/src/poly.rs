@@ -47,9 +47,9 @@ impl UniVarPoly {
 
     pub fn serialize(&self) -> Vec<u8> {
         let mut bytes = vec![];
-        self.0.iter().for_each(|x| {
-            <Fr as CanonicalSerialize>::serialize_compressed(&x, &mut bytes).unwrap()
-        });
+        self.0
+            .iter()
+            .for_each(|x| <Fr as CanonicalSerialize>::serialize_compressed(x, &mut bytes).unwrap());
         bytes
     }
 
@@ -57,8 +57,16 @@ impl UniVarPoly {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn coeff(&self) -> &[Fr] {
+        &self.0
+    }
+
     pub fn eval(&self, point: &Fr) -> Fr {
-        let mut res = self.0.last().unwrap().clone();
+        let mut res = *self.0.last().unwrap();
         for i in self.0.iter().rev().skip(1) {
             res *= point;
             res += i;
@@ -82,11 +90,15 @@ impl UniPolyEvals {
         self.evals.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.evals.is_empty()
+    }
+
     pub fn serialize(&self) -> Vec<u8> {
         let mut bytes = vec![];
-        self.evals.iter().for_each(|x| {
-            <Fr as CanonicalSerialize>::serialize_compressed(&x, &mut bytes).unwrap()
-        });
+        self.evals
+            .iter()
+            .for_each(|x| <Fr as CanonicalSerialize>::serialize_compressed(x, &mut bytes).unwrap());
         bytes
     }
 